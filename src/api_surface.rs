@@ -0,0 +1,1000 @@
+use crate::parser::{format_generics, format_signature, is_test_item};
+use crate::protocol::{ApiChange, ApiChangeKind, ApiDiffResponse, ApiDiffSummary, ApiItem, ApiItemKind, ApiSurfaceResponse, ChangeLevel};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// ソースの読み込み元（作業ツリーか git のリビジョン）
+pub enum SourceTree {
+    WorkingTree(PathBuf),
+    Revision { crate_dir: PathBuf, revision: String },
+}
+
+impl SourceTree {
+    /// revision が指定されていればその時点の git のツリー、なければ作業ツリーを読む
+    pub fn new(crate_dir: &Path, revision: Option<&str>) -> Result<Self> {
+        let Some(revision) = revision else {
+            return Ok(Self::WorkingTree(crate_dir.to_path_buf()));
+        };
+
+        let output = Command::new("git")
+            .arg("-C").arg(crate_dir)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{revision}^{{commit}}"))
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            anyhow::bail!("Unknown git revision: {revision}");
+        }
+
+        Ok(Self::Revision { crate_dir: crate_dir.to_path_buf(), revision: revision.to_string() })
+    }
+
+    /// クレートのディレクトリ以下の .rs ファイル（git の追跡対象と、作業ツリーなら無視されていない未追跡ファイル）
+    pub fn rust_files(&self) -> Result<Vec<PathBuf>> {
+        let (crate_dir, args) = match self {
+            Self::WorkingTree(crate_dir) => (crate_dir, vec!["ls-files", "--cached", "--others", "--exclude-standard"]),
+            Self::Revision { crate_dir, revision } => (crate_dir, vec!["ls-tree", "-r", "--name-only", revision.as_str()]),
+        };
+        // どちらも -C で指定したディレクトリ以下を、そこからの相対パスで返す
+        let output = Command::new("git")
+            .arg("-C").arg(crate_dir)
+            .args(args)
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            anyhow::bail!("git failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let mut files: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout).lines()
+            .filter(|line| line.ends_with(".rs"))
+            .map(PathBuf::from)
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// クレートのディレクトリからの相対パスでファイルを読む
+    pub fn read(&self, relative: &Path) -> Option<String> {
+        match self {
+            Self::WorkingTree(crate_dir) => std::fs::read_to_string(crate_dir.join(relative)).ok(),
+            Self::Revision { crate_dir, revision } => {
+                // "<rev>:./path" は -C で指定したディレクトリからの相対パスになる
+                let output = Command::new("git")
+                    .arg("-C").arg(crate_dir)
+                    .arg("show")
+                    .arg(format!("{revision}:./{}", relative.to_string_lossy()))
+                    .output()
+                    .ok()?;
+                output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+            }
+        }
+    }
+
+    pub fn revision(&self) -> Option<&str> {
+        match self {
+            Self::WorkingTree(_) => None,
+            Self::Revision { revision, .. } => Some(revision),
+        }
+    }
+}
+
+/// 公開 API のアイテムと、比較用の引数名を除いたシグネチャ
+pub struct ExportedItem {
+    pub item: ApiItem,
+    shape: String,
+}
+
+/// クレートの公開 API
+pub struct ApiSurface {
+    pub root_file: String,
+    pub items: Vec<ExportedItem>,
+}
+
+/// 可視性で絞り込む前のアイテムの情報
+#[derive(Clone)]
+struct Entry {
+    kind: ApiItemKind,
+    signature: String,
+    shape: String,
+    file_path: String,
+    line: usize,
+    non_exhaustive: bool,
+    required: bool,
+}
+
+impl Entry {
+    fn new(kind: ApiItemKind, signature: String, file_path: &str, line: usize) -> Self {
+        Entry {
+            kind,
+            shape: signature.clone(),
+            signature,
+            file_path: file_path.to_string(),
+            line,
+            non_exhaustive: false,
+            required: false,
+        }
+    }
+
+    fn export(&self, path: String) -> ExportedItem {
+        ExportedItem {
+            item: ApiItem {
+                path,
+                kind: self.kind,
+                signature: self.signature.clone(),
+                file_path: self.file_path.clone(),
+                line: self.line,
+                non_exhaustive: self.non_exhaustive,
+                required: self.required,
+            },
+            shape: self.shape.clone(),
+        }
+    }
+}
+
+/// モジュールで定義されたアイテム（フィールド・バリアント・メソッドなどのメンバーを含む）
+struct Definition {
+    name: String,
+    is_pub: bool,
+    entry: Entry,
+    members: Members,
+}
+
+/// pub use の1項目（name が None ならグロブ）
+struct ReExport {
+    path: Vec<String>,
+    name: Option<String>,
+    file_path: String,
+    line: usize,
+}
+
+#[derive(Default)]
+struct Module {
+    file_path: String,
+    line: usize,
+    items: Vec<Definition>,
+    children: Vec<(String, bool)>,  // (子モジュール名, pub か)
+    reexports: Vec<ReExport>,
+}
+
+/// メンバー名と情報（フィールド・バリアント・メソッドなど）
+type Members = Vec<(String, Entry)>;
+
+/// クレート全体のモジュール（キーは "crate::a::b"）と、型に後から結び付ける impl
+#[derive(Default)]
+struct CrateModules {
+    modules: BTreeMap<String, Module>,
+    inherent_impls: Vec<(String, String, Members)>,  // (モジュール, 型名, pub メソッド・定数)
+    trait_impls: Vec<(String, Entry)>,               // (型名, impl)
+}
+
+/// クレートのルート（src/lib.rs、なければ src/main.rs）から到達できる公開 API を取り出す
+pub fn extract(source: &SourceTree) -> Result<ApiSurface> {
+    let (root_file, content) = ["src/lib.rs", "src/main.rs"].into_iter()
+        .find_map(|file| source.read(Path::new(file)).map(|content| (file, content)))
+        .with_context(|| match source.revision() {
+            Some(revision) => format!("No src/lib.rs or src/main.rs at revision {revision}"),
+            None => "No src/lib.rs or src/main.rs in crate directory".to_string(),
+        })?;
+    let syntax_tree = syn::parse_file(&content)
+        .with_context(|| format!("Failed to parse {root_file}"))?;
+
+    let mut crate_modules = CrateModules::default();
+    let mut collector = ModuleCollector { source, crate_modules: &mut crate_modules };
+    collector.collect_module("crate".to_string(), root_file, Path::new("src"), 0, &syntax_tree.items);
+    crate_modules.attach_inherent_impls();
+
+    let mut items = Vec::new();
+    crate_modules.export_module("crate", "crate", &mut items, &mut HashSet::new());
+
+    // 公開されている型への trait 実装
+    let exported_types: HashSet<&str> = items.iter()
+        .filter(|e| matches!(e.item.kind, ApiItemKind::Struct | ApiItemKind::Enum | ApiItemKind::TypeAlias))
+        .filter_map(|e| e.item.path.rsplit("::").next())
+        .collect();
+    let impls: Vec<ExportedItem> = crate_modules.trait_impls.iter()
+        .filter(|(type_name, _)| exported_types.contains(type_name.as_str()))
+        .map(|(_, entry)| entry.export(entry.signature.clone()))
+        .collect();
+    items.extend(impls);
+
+    let mut seen = HashSet::new();
+    items.retain(|e| seen.insert((e.item.path.clone(), e.item.kind)));
+    items.sort_by(|a, b| a.item.path.cmp(&b.item.path).then(a.item.kind.cmp(&b.item.kind)));
+
+    Ok(ApiSurface { root_file: root_file.to_string(), items })
+}
+
+/// 作業ツリーのクレートで公開 API として到達できるアイテムの定義位置（ファイルごとの行）
+///
+/// クレートのルートから pub mod と pub use を辿るので、非公開モジュールの pub アイテムは含まない。
+pub fn exported_lines(crate_dir: &Path) -> Result<HashMap<PathBuf, HashSet<usize>>> {
+    let surface = extract(&SourceTree::WorkingTree(crate_dir.to_path_buf()))?;
+    let mut lines: HashMap<PathBuf, HashSet<usize>> = HashMap::new();
+    for exported in surface.items {
+        lines.entry(PathBuf::from(exported.item.file_path)).or_default().insert(exported.item.line);
+    }
+    Ok(lines)
+}
+
+/// api_surface のレスポンスを作る
+pub fn api_surface(crate_dir: &Path, revision: Option<&str>) -> Result<ApiSurfaceResponse> {
+    let source = SourceTree::new(crate_dir, revision)?;
+    let surface = extract(&source)?;
+    Ok(ApiSurfaceResponse {
+        root_file: surface.root_file,
+        revision: revision.map(|r| r.to_string()),
+        items: surface.items.into_iter().map(|e| e.item).collect(),
+    })
+}
+
+/// 2つのリビジョン（to が None なら作業ツリー）の公開 API を比較する
+pub fn api_diff(crate_dir: &Path, from: &str, to: Option<&str>) -> Result<ApiDiffResponse> {
+    let old = extract(&SourceTree::new(crate_dir, Some(from))?)?;
+    let new = extract(&SourceTree::new(crate_dir, to)?)?;
+    let changes = diff_surfaces(&old.items, &new.items);
+
+    let mut summary = ApiDiffSummary::default();
+    for change in &changes {
+        match change.level {
+            ChangeLevel::Breaking => summary.breaking += 1,
+            ChangeLevel::Additive => summary.additive += 1,
+            ChangeLevel::Patch => summary.patch += 1,
+        }
+    }
+
+    Ok(ApiDiffResponse {
+        from: from.to_string(),
+        to: to.map(|t| t.to_string()),
+        level: changes.iter().map(|c| c.level).max().unwrap_or(ChangeLevel::Patch),
+        changes,
+        summary,
+    })
+}
+
+/// 公開 API の差分を破壊的・追加・パッチに分類する（影響の大きい順）
+pub fn diff_surfaces(old: &[ExportedItem], new: &[ExportedItem]) -> Vec<ApiChange> {
+    let old_map: BTreeMap<(&str, ApiItemKind), &ExportedItem> = old.iter()
+        .map(|e| ((e.item.path.as_str(), e.item.kind), e))
+        .collect();
+    let new_map: BTreeMap<(&str, ApiItemKind), &ExportedItem> = new.iter()
+        .map(|e| ((e.item.path.as_str(), e.item.kind), e))
+        .collect();
+    let old_paths: HashSet<&str> = old_map.keys().map(|(path, _)| *path).collect();
+    let new_paths: HashSet<&str> = new_map.keys().map(|(path, _)| *path).collect();
+
+    let mut changes = Vec::new();
+
+    // 親ごと削除・追加されたメンバーは親の変更として扱う
+    for (key, old_item) in &old_map {
+        if new_map.contains_key(key) || parent_path(key.0).is_some_and(|p| old_paths.contains(p) && !new_paths.contains(p)) {
+            continue;
+        }
+        changes.push(change(old_item, ApiChangeKind::Removed, ChangeLevel::Breaking, "removed from the public API".to_string()));
+    }
+
+    for (key, new_item) in &new_map {
+        if old_map.contains_key(key) || parent_path(key.0).is_some_and(|p| new_paths.contains(p) && !old_paths.contains(p)) {
+            continue;
+        }
+        let old_parent = parent_path(key.0)
+            .and_then(|p| old.iter().find(|e| e.item.path == p && e.item.kind != ApiItemKind::Module));
+        let (level, reason) = classify_addition(&new_item.item, old_parent.map(|e| &e.item));
+        changes.push(change(new_item, ApiChangeKind::Added, level, reason.to_string()));
+    }
+
+    for (key, new_item) in &new_map {
+        let Some(old_item) = old_map.get(key) else {
+            continue;
+        };
+        let mut reasons: Vec<(ChangeLevel, &str)> = Vec::new();
+
+        let (old, new) = (&old_item.item, &new_item.item);
+        if old.signature != new.signature {
+            reasons.push(if old_item.shape == new_item.shape {
+                (ChangeLevel::Patch, "parameter names changed")
+            } else if new.signature.strip_prefix("const ") == Some(old.signature.as_str()) {
+                (ChangeLevel::Additive, "now a const fn")
+            } else if old.signature.strip_prefix("const ") == Some(new.signature.as_str()) {
+                (ChangeLevel::Breaking, "no longer a const fn")
+            } else {
+                (ChangeLevel::Breaking, "signature changed")
+            });
+        }
+        match (old.non_exhaustive, new.non_exhaustive) {
+            (false, true) => reasons.push((ChangeLevel::Breaking, "can no longer be constructed or matched exhaustively outside the crate")),
+            (true, false) => reasons.push((ChangeLevel::Additive, "can now be constructed or matched exhaustively outside the crate")),
+            _ => {}
+        }
+        match (old.required, new.required) {
+            (false, true) => reasons.push((ChangeLevel::Breaking, "default implementation removed")),
+            (true, false) => reasons.push((ChangeLevel::Additive, "default implementation added")),
+            _ => {}
+        }
+
+        if let Some(level) = reasons.iter().map(|(level, _)| *level).max() {
+            let reason = reasons.iter().map(|(_, r)| *r).collect::<Vec<_>>().join(", ");
+            let mut changed = change(new_item, ApiChangeKind::Changed, level, reason);
+            changed.old_signature = Some(old.signature.clone());
+            changes.push(changed);
+        }
+    }
+
+    changes.sort_by(|a, b| b.level.cmp(&a.level).then(a.path.cmp(&b.path)));
+    changes
+}
+
+/// 追加されたアイテムが既存の利用側を壊すか（網羅的な列挙型・構造体リテラル・trait の実装）
+fn classify_addition(item: &ApiItem, old_parent: Option<&ApiItem>) -> (ChangeLevel, &'static str) {
+    let Some(parent) = old_parent else {
+        return (ChangeLevel::Additive, "new public item");
+    };
+
+    match item.kind {
+        ApiItemKind::Variant if !parent.non_exhaustive => (ChangeLevel::Breaking, "new variant in an exhaustive enum"),
+        ApiItemKind::Field if !parent.non_exhaustive => (ChangeLevel::Breaking, "new field in a struct that can be built with a struct literal"),
+        ApiItemKind::TraitMethod | ApiItemKind::AssocType | ApiItemKind::Const
+            if item.required && parent.kind == ApiItemKind::Trait => (ChangeLevel::Breaking, "new trait item without a default"),
+        _ => (ChangeLevel::Additive, "new public item"),
+    }
+}
+
+fn change(exported: &ExportedItem, change: ApiChangeKind, level: ChangeLevel, reason: String) -> ApiChange {
+    let signature = Some(exported.item.signature.clone());
+    let removed = change == ApiChangeKind::Removed;
+    ApiChange {
+        path: exported.item.path.clone(),
+        kind: exported.item.kind,
+        change,
+        level,
+        old_signature: if removed { signature.clone() } else { None },
+        new_signature: if removed { None } else { signature },
+        reason,
+    }
+}
+
+/// "crate::a::Foo::bar" → "crate::a::Foo"
+fn parent_path(path: &str) -> Option<&str> {
+    path.rsplit_once("::").map(|(parent, _)| parent)
+}
+
+struct ModuleCollector<'a> {
+    source: &'a SourceTree,
+    crate_modules: &'a mut CrateModules,
+}
+
+impl ModuleCollector<'_> {
+    /// module_dir は子モジュールのファイルを探すディレクトリ
+    fn collect_module(&mut self, module_path: String, file_path: &str, module_dir: &Path, line: usize, items: &[syn::Item]) {
+        let mut module = Module { file_path: file_path.to_string(), line, ..Default::default() };
+
+        for item in &expand_item_macros(items) {
+            if is_test_item(item_attrs(item), file_path) {
+                continue;
+            }
+            match item {
+                syn::Item::Mod(item_mod) => {
+                    let name = item_mod.ident.to_string();
+                    let child_path = format!("{module_path}::{name}");
+                    let child_line = item_mod.ident.span().start().line;
+                    module.children.push((name.clone(), is_pub(&item_mod.vis)));
+
+                    if let Some((_, child_items)) = &item_mod.content {
+                        self.collect_module(child_path, file_path, &module_dir.join(&name), child_line, child_items);
+                    } else if let Some((child_file, content)) = self.find_module_file(file_path, module_dir, &name, &item_mod.attrs)
+                        && let Ok(syntax_tree) = syn::parse_file(&content) {
+                        let child_dir = if child_file.ends_with("mod.rs") || path_attribute(&item_mod.attrs).is_some() {
+                            child_file.parent().unwrap_or(Path::new("")).to_path_buf()
+                        } else {
+                            child_file.with_extension("")
+                        };
+                        let child_file = child_file.to_string_lossy().to_string();
+                        self.collect_module(child_path, &child_file, &child_dir, child_line, &syntax_tree.items);
+                    }
+                }
+                syn::Item::Use(item_use) if is_pub(&item_use.vis) => {
+                    let line = item_use.use_token.span.start().line;
+                    let mut flattened = Vec::new();
+                    flatten_use_tree(&item_use.tree, &mut Vec::new(), &mut flattened);
+                    module.reexports.extend(flattened.into_iter().map(|(path, name)| ReExport {
+                        path,
+                        name,
+                        file_path: file_path.to_string(),
+                        line,
+                    }));
+                }
+                syn::Item::Impl(item_impl) => self.collect_impl(&module_path, file_path, item_impl),
+                syn::Item::Macro(item_macro) => {
+                    // #[macro_export] されたマクロはクレートのルートに公開される
+                    if let Some(ident) = &item_macro.ident
+                        && item_macro.attrs.iter().any(|a| a.path().is_ident("macro_export")) {
+                        let entry = Entry::new(ApiItemKind::Macro, format!("macro_rules! {ident}"), file_path, ident.span().start().line);
+                        let definition = Definition { name: format!("{ident}!"), is_pub: true, entry, members: Vec::new() };
+                        self.crate_modules.modules.entry("crate".to_string()).or_default().items.push(definition);
+                    }
+                }
+                other => {
+                    if let Some(definition) = self.definition(file_path, other) {
+                        module.items.push(definition);
+                    }
+                }
+            }
+        }
+
+        // ルートより先にマクロが登録されている場合があるので、既存のアイテムを残す
+        let existing = self.crate_modules.modules.remove(&module_path);
+        if let Some(existing) = existing {
+            module.items.extend(existing.items);
+        }
+        self.crate_modules.modules.insert(module_path, module);
+    }
+
+    /// `mod name;` のファイル（#[path] 属性、name.rs、name/mod.rs の順）
+    fn find_module_file(&self, file_path: &str, module_dir: &Path, name: &str, attrs: &[syn::Attribute]) -> Option<(PathBuf, String)> {
+        let candidates = match path_attribute(attrs) {
+            Some(path) => vec![Path::new(file_path).parent().unwrap_or(Path::new("")).join(path)],
+            None => vec![module_dir.join(format!("{name}.rs")), module_dir.join(name).join("mod.rs")],
+        };
+        candidates.into_iter().find_map(|file| self.source.read(&file).map(|content| (file, content)))
+    }
+
+    fn definition(&self, file_path: &str, item: &syn::Item) -> Option<Definition> {
+        let generics_of = |generics: &syn::Generics| format_generics(generics).unwrap_or_default();
+
+        let (ident, vis, entry, members) = match item {
+            syn::Item::Fn(item_fn) => {
+                let mut entry = Entry::new(ApiItemKind::Function, fn_signature(&item_fn.sig), file_path, line_of(&item_fn.sig.ident));
+                entry.shape = fn_shape(&item_fn.sig);
+                (&item_fn.sig.ident, &item_fn.vis, entry, Vec::new())
+            }
+            syn::Item::Struct(item_struct) => {
+                let signature = format!("struct {}{}{}", item_struct.ident, generics_of(&item_struct.generics), where_clause(&item_struct.generics));
+                let mut entry = Entry::new(ApiItemKind::Struct, signature, file_path, line_of(&item_struct.ident));
+                entry.non_exhaustive = has_non_exhaustive(&item_struct.attrs)
+                    || item_struct.fields.iter().any(|field| !is_pub(&field.vis));
+
+                let members = item_struct.fields.iter().enumerate()
+                    .filter(|(_, field)| is_pub(&field.vis))
+                    .map(|(index, field)| {
+                        let name = field.ident.as_ref().map(|i| i.to_string()).unwrap_or_else(|| index.to_string());
+                        let ty = &field.ty;
+                        let line = field.ident.as_ref().map(line_of).unwrap_or(entry.line);
+                        let field_entry = Entry::new(ApiItemKind::Field, format!("{name} : {}", quote::quote!(#ty)), file_path, line);
+                        (name, field_entry)
+                    })
+                    .collect();
+                (&item_struct.ident, &item_struct.vis, entry, members)
+            }
+            syn::Item::Enum(item_enum) => {
+                let signature = format!("enum {}{}{}", item_enum.ident, generics_of(&item_enum.generics), where_clause(&item_enum.generics));
+                let mut entry = Entry::new(ApiItemKind::Enum, signature, file_path, line_of(&item_enum.ident));
+                entry.non_exhaustive = has_non_exhaustive(&item_enum.attrs);
+
+                let members = item_enum.variants.iter()
+                    .map(|variant| {
+                        let fields = &variant.fields;
+                        let mut signature = format!("{}{}", variant.ident, quote::quote!(#fields));
+                        if let Some((_, discriminant)) = &variant.discriminant {
+                            signature.push_str(&format!(" = {}", quote::quote!(#discriminant)));
+                        }
+                        (variant.ident.to_string(), Entry::new(ApiItemKind::Variant, signature, file_path, line_of(&variant.ident)))
+                    })
+                    .collect();
+                (&item_enum.ident, &item_enum.vis, entry, members)
+            }
+            syn::Item::Trait(item_trait) => {
+                let unsafety = if item_trait.unsafety.is_some() { "unsafe " } else { "" };
+                let supertraits = &item_trait.supertraits;
+                let bounds = if supertraits.is_empty() { String::new() } else { format!(" : {}", quote::quote!(#supertraits)) };
+                let signature = format!("{unsafety}trait {}{}{bounds}{}", item_trait.ident, generics_of(&item_trait.generics), where_clause(&item_trait.generics));
+                let entry = Entry::new(ApiItemKind::Trait, signature, file_path, line_of(&item_trait.ident));
+
+                let members = item_trait.items.iter()
+                    .filter_map(|trait_item| {
+                        let (name, member) = match trait_item {
+                            syn::TraitItem::Fn(method) => {
+                                let mut member = Entry::new(ApiItemKind::TraitMethod, fn_signature(&method.sig), file_path, line_of(&method.sig.ident));
+                                member.shape = fn_shape(&method.sig);
+                                member.required = method.default.is_none();
+                                (&method.sig.ident, member)
+                            }
+                            syn::TraitItem::Type(assoc_type) => {
+                                let bounds = &assoc_type.bounds;
+                                let bounds = if bounds.is_empty() { String::new() } else { format!(" : {}", quote::quote!(#bounds)) };
+                                let signature = format!("type {}{}{bounds}", assoc_type.ident, generics_of(&assoc_type.generics));
+                                let mut member = Entry::new(ApiItemKind::AssocType, signature, file_path, line_of(&assoc_type.ident));
+                                member.required = assoc_type.default.is_none();
+                                (&assoc_type.ident, member)
+                            }
+                            syn::TraitItem::Const(assoc_const) => {
+                                let ty = &assoc_const.ty;
+                                let mut member = Entry::new(ApiItemKind::Const, format!("const {} : {}", assoc_const.ident, quote::quote!(#ty)), file_path, line_of(&assoc_const.ident));
+                                member.required = assoc_const.default.is_none();
+                                (&assoc_const.ident, member)
+                            }
+                            _ => return None,
+                        };
+                        Some((name.to_string(), member))
+                    })
+                    .collect();
+                (&item_trait.ident, &item_trait.vis, entry, members)
+            }
+            syn::Item::Type(item_type) => {
+                let ty = &item_type.ty;
+                let signature = format!("type {}{} = {}", item_type.ident, generics_of(&item_type.generics), quote::quote!(#ty));
+                (&item_type.ident, &item_type.vis, Entry::new(ApiItemKind::TypeAlias, signature, file_path, line_of(&item_type.ident)), Vec::new())
+            }
+            syn::Item::Const(item_const) => {
+                let ty = &item_const.ty;
+                let signature = format!("const {} : {}", item_const.ident, quote::quote!(#ty));
+                (&item_const.ident, &item_const.vis, Entry::new(ApiItemKind::Const, signature, file_path, line_of(&item_const.ident)), Vec::new())
+            }
+            syn::Item::Static(item_static) => {
+                let ty = &item_static.ty;
+                let mutability = if matches!(item_static.mutability, syn::StaticMutability::Mut(_)) { "mut " } else { "" };
+                let signature = format!("static {mutability}{} : {}", item_static.ident, quote::quote!(#ty));
+                (&item_static.ident, &item_static.vis, Entry::new(ApiItemKind::Static, signature, file_path, line_of(&item_static.ident)), Vec::new())
+            }
+            _ => return None,
+        };
+
+        Some(Definition { name: ident.to_string(), is_pub: is_pub(vis), entry, members })
+    }
+
+    fn collect_impl(&mut self, module_path: &str, file_path: &str, item_impl: &syn::ItemImpl) {
+        let type_name = crate::parser::type_name(&item_impl.self_ty);
+        let line = item_impl.impl_token.span.start().line;
+
+        if let Some((negative, trait_path, _)) = &item_impl.trait_ {
+            let self_ty = &item_impl.self_ty;
+            let signature = format!(
+                "impl{} {}{} for {}{}",
+                format_generics(&item_impl.generics).unwrap_or_default(),
+                if negative.is_some() { "!" } else { "" },
+                quote::quote!(#trait_path),
+                quote::quote!(#self_ty),
+                where_clause(&item_impl.generics),
+            );
+            self.crate_modules.trait_impls.push((type_name, Entry::new(ApiItemKind::Impl, signature, file_path, line)));
+            return;
+        }
+
+        let members: Members = item_impl.items.iter()
+            .filter_map(|impl_item| match impl_item {
+                syn::ImplItem::Fn(method) if is_pub(&method.vis) => {
+                    let mut entry = Entry::new(ApiItemKind::Method, fn_signature(&method.sig), file_path, line_of(&method.sig.ident));
+                    entry.shape = fn_shape(&method.sig);
+                    Some((method.sig.ident.to_string(), entry))
+                }
+                syn::ImplItem::Const(assoc_const) if is_pub(&assoc_const.vis) => {
+                    let ty = &assoc_const.ty;
+                    let signature = format!("const {} : {}", assoc_const.ident, quote::quote!(#ty));
+                    Some((assoc_const.ident.to_string(), Entry::new(ApiItemKind::Const, signature, file_path, line_of(&assoc_const.ident))))
+                }
+                _ => None,
+            })
+            .collect();
+        if !members.is_empty() {
+            self.crate_modules.inherent_impls.push((module_path.to_string(), type_name, members));
+        }
+    }
+}
+
+impl CrateModules {
+    /// 固有 impl のメソッドを型の定義に結び付ける（同じモジュールの型を優先）
+    fn attach_inherent_impls(&mut self) {
+        for (module_path, type_name, members) in std::mem::take(&mut self.inherent_impls) {
+            let is_type = |d: &Definition| d.name == type_name
+                && matches!(d.entry.kind, ApiItemKind::Struct | ApiItemKind::Enum | ApiItemKind::TypeAlias);
+            let same_module = self.modules.get(&module_path).is_some_and(|m| m.items.iter().any(is_type));
+
+            let target = self.modules.iter_mut()
+                .filter(|(path, _)| !same_module || **path == module_path)
+                .find_map(|(_, module)| module.items.iter_mut().find(|d| is_type(d)));
+            if let Some(definition) = target {
+                definition.members.extend(members);
+            }
+        }
+    }
+
+    /// module_path のモジュールの公開アイテムを export_path 以下に公開されたものとして追加する
+    fn export_module(&self, module_path: &str, export_path: &str, out: &mut Vec<ExportedItem>, visited: &mut HashSet<(String, String)>) {
+        // グロブの循環（pub use super::*）で無限に辿らないようにする
+        if !visited.insert((module_path.to_string(), export_path.to_string())) {
+            return;
+        }
+        let Some(module) = self.modules.get(module_path) else {
+            return;
+        };
+
+        for definition in module.items.iter().filter(|d| d.is_pub) {
+            export_definition(definition, &format!("{export_path}::{}", definition.name), out);
+        }
+
+        for (name, _) in module.children.iter().filter(|(_, is_pub)| *is_pub) {
+            self.export_child_module(&format!("{module_path}::{name}"), &format!("{export_path}::{name}"), name, out, visited);
+        }
+
+        for reexport in &module.reexports {
+            self.export_reexport(module_path, export_path, reexport, out, visited);
+        }
+    }
+
+    fn export_child_module(&self, module_path: &str, export_path: &str, name: &str, out: &mut Vec<ExportedItem>, visited: &mut HashSet<(String, String)>) {
+        let Some(module) = self.modules.get(module_path) else {
+            return;
+        };
+        let entry = Entry::new(ApiItemKind::Module, format!("mod {name}"), &module.file_path, module.line);
+        out.push(entry.export(export_path.to_string()));
+        self.export_module(module_path, export_path, out, visited);
+    }
+
+    fn export_reexport(&self, module_path: &str, export_path: &str, reexport: &ReExport, out: &mut Vec<ExportedItem>, visited: &mut HashSet<(String, String)>) {
+        let Some(name) = &reexport.name else {
+            // pub use a::*;
+            match self.resolve_module(module_path, &reexport.path) {
+                Some(target) => self.export_module(&target, export_path, out, visited),
+                None => out.push(external_reexport(reexport, format!("{export_path}::*"))),
+            }
+            return;
+        };
+
+        let (target_name, parent) = reexport.path.split_last().expect("use path is never empty");
+        // use a::b::{self} は a::b 自体
+        let (target_name, parent) = if target_name == "self" {
+            match parent.split_last() {
+                Some((module_name, rest)) => (module_name, rest),
+                None => return,
+            }
+        } else {
+            (target_name, parent)
+        };
+
+        let alias_path = format!("{export_path}::{name}");
+        let Some(parent_module) = self.resolve_module(module_path, parent) else {
+            out.push(external_reexport(reexport, alias_path));
+            return;
+        };
+
+        let mut found = false;
+        if let Some(module) = self.modules.get(&parent_module) {
+            for definition in module.items.iter().filter(|d| d.name == *target_name) {
+                export_definition(definition, &alias_path, out);
+                found = true;
+            }
+        }
+        let target_module = format!("{parent_module}::{target_name}");
+        if self.modules.contains_key(&target_module) {
+            self.export_child_module(&target_module, &alias_path, name, out, visited);
+            found = true;
+        }
+        if !found {
+            out.push(external_reexport(reexport, alias_path));
+        }
+    }
+
+    /// use のパス（モジュール部分）をクレート内のモジュールに解決する。クレート外なら None
+    fn resolve_module(&self, current: &str, segments: &[String]) -> Option<String> {
+        let mut module = current.to_string();
+        for (i, segment) in segments.iter().enumerate() {
+            match segment.as_str() {
+                "crate" if i == 0 => module = "crate".to_string(),
+                "self" => {}
+                "super" => module = parent_path(&module)?.to_string(),
+                name => {
+                    let child = format!("{module}::{name}");
+                    if !self.modules.contains_key(&child) {
+                        return None;
+                    }
+                    module = child;
+                }
+            }
+        }
+        Some(module)
+    }
+}
+
+/// cfg_rt! { ... } のようにアイテムを包むマクロの中身を、マクロのあった位置のアイテムとして展開する（macro_rules! は除く）
+fn expand_item_macros(items: &[syn::Item]) -> Vec<syn::Item> {
+    let mut expanded = Vec::with_capacity(items.len());
+    for item in items {
+        if let syn::Item::Macro(item_macro) = item
+            && item_macro.ident.is_none()
+            && let Ok(inner) = syn::parse2::<syn::File>(item_macro.mac.tokens.clone()) {
+            expanded.extend(expand_item_macros(&inner.items));
+        } else {
+            expanded.push(item.clone());
+        }
+    }
+    expanded
+}
+
+fn export_definition(definition: &Definition, path: &str, out: &mut Vec<ExportedItem>) {
+    out.push(definition.entry.export(path.to_string()));
+    for (name, member) in &definition.members {
+        out.push(member.export(format!("{path}::{name}")));
+    }
+}
+
+/// クレート外のアイテムの再エクスポート
+fn external_reexport(reexport: &ReExport, path: String) -> ExportedItem {
+    let mut signature = format!("pub use {}", reexport.path.join("::"));
+    match &reexport.name {
+        Some(name) if reexport.path.last() != Some(name) => signature.push_str(&format!(" as {name}")),
+        Some(_) => {}
+        None => signature.push_str("::*"),
+    }
+    Entry::new(ApiItemKind::ReExport, signature, &reexport.file_path, reexport.line).export(path)
+}
+
+/// use ツリーを (パス, 公開名) に展開する（公開名が None ならグロブ）
+pub fn flatten_use_tree(tree: &syn::UseTree, prefix: &mut Vec<String>, out: &mut Vec<(Vec<String>, Option<String>)>) {
+    match tree {
+        syn::UseTree::Path(use_path) => {
+            prefix.push(use_path.ident.to_string());
+            flatten_use_tree(&use_path.tree, prefix, out);
+            prefix.pop();
+        }
+        syn::UseTree::Name(use_name) => {
+            let ident = use_name.ident.to_string();
+            let name = if ident == "self" { prefix.last().cloned() } else { Some(ident.clone()) };
+            if let Some(name) = name {
+                out.push(([prefix.as_slice(), &[ident]].concat(), Some(name)));
+            }
+        }
+        syn::UseTree::Rename(use_rename) => {
+            let path = [prefix.as_slice(), &[use_rename.ident.to_string()]].concat();
+            out.push((path, Some(use_rename.rename.to_string())));
+        }
+        syn::UseTree::Glob(_) => out.push((prefix.clone(), None)),
+        syn::UseTree::Group(group) => {
+            for item in &group.items {
+                flatten_use_tree(item, prefix, out);
+            }
+        }
+    }
+}
+
+/// format_signature に const・unsafe・extern・ジェネリクス・where 句を加えたシグネチャ
+fn fn_signature(sig: &syn::Signature) -> String {
+    let signature = format_signature(sig);
+    let (asyncness, rest) = signature.split_once("fn ").unwrap_or(("", &signature));
+    let (name, rest) = rest.split_once('(').unwrap_or((rest, ""));
+
+    let constness = if sig.constness.is_some() { "const " } else { "" };
+    let unsafety = if sig.unsafety.is_some() { "unsafe " } else { "" };
+    let abi = sig.abi.as_ref().map(|abi| format!("{} ", quote::quote!(#abi))).unwrap_or_default();
+    let generics = format_generics(&sig.generics).unwrap_or_default();
+
+    format!("{constness}{asyncness}{unsafety}{abi}fn {name}{generics}({rest}{}", where_clause(&sig.generics))
+}
+
+/// 引数名を `_` に置き換えたシグネチャ（引数名の変更だけなら API は変わらない）
+fn fn_shape(sig: &syn::Signature) -> String {
+    let mut sig = sig.clone();
+    for input in sig.inputs.iter_mut() {
+        if let syn::FnArg::Typed(pat_type) = input {
+            *pat_type.pat = syn::parse_quote!(_);
+        }
+    }
+    quote::quote!(#sig).to_string()
+}
+
+fn where_clause(generics: &syn::Generics) -> String {
+    generics.where_clause.as_ref()
+        .map(|where_clause| format!(" {}", quote::quote!(#where_clause)))
+        .unwrap_or_default()
+}
+
+fn is_pub(vis: &syn::Visibility) -> bool {
+    matches!(vis, syn::Visibility::Public(_))
+}
+
+fn has_non_exhaustive(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("non_exhaustive"))
+}
+
+/// #[path = "..."] 属性の値
+pub fn path_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident("path"))
+        .find_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(path), .. }), .. }) => Some(path.value()),
+            _ => None,
+        })
+}
+
+fn line_of(ident: &syn::Ident) -> usize {
+    ident.span().start().line
+}
+
+pub fn item_attrs(item: &syn::Item) -> &[syn::Attribute] {
+    match item {
+        syn::Item::Const(i) => &i.attrs,
+        syn::Item::Enum(i) => &i.attrs,
+        syn::Item::Fn(i) => &i.attrs,
+        syn::Item::Impl(i) => &i.attrs,
+        syn::Item::Macro(i) => &i.attrs,
+        syn::Item::Mod(i) => &i.attrs,
+        syn::Item::Static(i) => &i.attrs,
+        syn::Item::Struct(i) => &i.attrs,
+        syn::Item::Trait(i) => &i.attrs,
+        syn::Item::Type(i) => &i.attrs,
+        syn::Item::Use(i) => &i.attrs,
+        _ => &[],
+    }
+}
+
+pub fn kind_label(kind: ApiItemKind) -> &'static str {
+    match kind {
+        ApiItemKind::Module => "mod",
+        ApiItemKind::Function => "fn",
+        ApiItemKind::Struct => "struct",
+        ApiItemKind::Field => "field",
+        ApiItemKind::Enum => "enum",
+        ApiItemKind::Variant => "variant",
+        ApiItemKind::Trait => "trait",
+        ApiItemKind::TraitMethod => "trait fn",
+        ApiItemKind::AssocType => "assoc type",
+        ApiItemKind::Method => "method",
+        ApiItemKind::Const => "const",
+        ApiItemKind::Static => "static",
+        ApiItemKind::TypeAlias => "type",
+        ApiItemKind::Macro => "macro",
+        ApiItemKind::Impl => "impl",
+        ApiItemKind::ReExport => "re-export",
+    }
+}
+
+/// 公開 API の一覧をテキストに整形（CLI 用）
+pub fn format_surface(report: &ApiSurfaceResponse) -> String {
+    let mut result = format!(
+        "Public API of {} ({}): {} item(s)\n\n",
+        report.root_file,
+        report.revision.as_deref().unwrap_or("working tree"),
+        report.items.len(),
+    );
+    for item in &report.items {
+        let path = if item.kind == ApiItemKind::Impl { "" } else { item.path.as_str() };
+        result.push_str(&format!("  {:<10} {:<40} {}\n", kind_label(item.kind), path, item.signature));
+    }
+    result
+}
+
+/// 公開 API の差分をテキストに整形（CLI 用）
+pub fn format_diff(report: &ApiDiffResponse) -> String {
+    let bump = match report.level {
+        ChangeLevel::Breaking => "major",
+        ChangeLevel::Additive => "minor",
+        ChangeLevel::Patch => "patch",
+    };
+    let mut result = format!(
+        "API diff {}..{}: {} breaking, {} additive, {} patch → {bump} version bump\n",
+        report.from,
+        report.to.as_deref().unwrap_or("working tree"),
+        report.summary.breaking, report.summary.additive, report.summary.patch,
+    );
+
+    for change in &report.changes {
+        let marker = match change.change {
+            ApiChangeKind::Added => "+",
+            ApiChangeKind::Removed => "-",
+            ApiChangeKind::Changed => "~",
+        };
+        result.push_str(&format!(
+            "\n{marker} [{:?}] {} {}: {}\n",
+            change.level, kind_label(change.kind), change.path, change.reason,
+        ));
+        if let Some(old) = &change.old_signature {
+            result.push_str(&format!("    - {old}\n"));
+        }
+        if let Some(new) = &change.new_signature {
+            result.push_str(&format!("    + {new}\n"));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn surface(files: &[(&str, &str)]) -> ApiSurface {
+        let dir = TempDir::new().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        extract(&SourceTree::WorkingTree(dir.path().to_path_buf())).unwrap()
+    }
+
+    #[test]
+    fn test_api_surface_and_diff() {
+        let old = surface(&[
+            ("src/lib.rs", r#"
+mod inner;
+pub mod shapes;
+pub use inner::{helper, Config as Settings};
+
+pub fn area(width: u32, height: u32) -> u32 { width * height }
+pub fn removed() {}
+fn private() {}
+
+pub enum Mode { Fast, Slow }
+
+pub trait Render {
+    fn render(&self) -> String;
+}
+
+#[cfg(test)]
+mod tests {
+    pub fn test_only() {}
+}
+"#),
+            ("src/inner.rs", "pub fn helper() {}\npub struct Config { pub verbose: bool }\n"),
+            ("src/shapes/mod.rs", "pub struct Circle { pub radius: f64, id: u32 }\nimpl Circle {\n    pub fn new(radius: f64) -> Self { Circle { radius, id: 0 } }\n    fn id(&self) -> u32 { self.id }\n}\n"),
+        ]);
+
+        let paths: Vec<(&str, ApiItemKind)> = old.items.iter().map(|e| (e.item.path.as_str(), e.item.kind)).collect();
+        assert_eq!(paths, vec![
+            ("crate::Mode", ApiItemKind::Enum),
+            ("crate::Mode::Fast", ApiItemKind::Variant),
+            ("crate::Mode::Slow", ApiItemKind::Variant),
+            ("crate::Render", ApiItemKind::Trait),
+            ("crate::Render::render", ApiItemKind::TraitMethod),
+            ("crate::Settings", ApiItemKind::Struct),
+            ("crate::Settings::verbose", ApiItemKind::Field),
+            ("crate::area", ApiItemKind::Function),
+            ("crate::helper", ApiItemKind::Function),
+            ("crate::removed", ApiItemKind::Function),
+            ("crate::shapes", ApiItemKind::Module),
+            ("crate::shapes::Circle", ApiItemKind::Struct),
+            ("crate::shapes::Circle::new", ApiItemKind::Method),
+            ("crate::shapes::Circle::radius", ApiItemKind::Field),
+        ]);
+        let area = old.items.iter().find(|e| e.item.path == "crate::area").unwrap();
+        assert_eq!(area.item.signature, "fn area(width : u32, height : u32) -> u32");
+        assert!(old.items.iter().find(|e| e.item.path == "crate::shapes::Circle").unwrap().item.non_exhaustive);
+
+        let new = surface(&[
+            ("src/lib.rs", r#"
+mod inner;
+pub mod shapes;
+pub use inner::{helper, Config as Settings};
+
+pub fn area(w: u32, h: u32) -> u32 { w * h }
+pub const fn added() -> u32 { 1 }
+
+pub enum Mode { Fast, Slow, Auto }
+
+pub trait Render {
+    fn render(&self) -> String;
+    fn name(&self) -> String { String::new() }
+}
+"#),
+            ("src/inner.rs", "pub fn helper(verbose: bool) {}\npub struct Config { pub verbose: bool }\n"),
+            ("src/shapes/mod.rs", "pub struct Circle { pub radius: f64, pub label: String, id: u32 }\n"),
+        ]);
+
+        let changes = diff_surfaces(&old.items, &new.items);
+        let found: Vec<(&str, ApiChangeKind, ChangeLevel)> = changes.iter()
+            .map(|c| (c.path.as_str(), c.change, c.level))
+            .collect();
+        assert_eq!(found, vec![
+            ("crate::Mode::Auto", ApiChangeKind::Added, ChangeLevel::Breaking),
+            ("crate::helper", ApiChangeKind::Changed, ChangeLevel::Breaking),
+            ("crate::removed", ApiChangeKind::Removed, ChangeLevel::Breaking),
+            ("crate::shapes::Circle::new", ApiChangeKind::Removed, ChangeLevel::Breaking),
+            ("crate::Render::name", ApiChangeKind::Added, ChangeLevel::Additive),
+            ("crate::added", ApiChangeKind::Added, ChangeLevel::Additive),
+            ("crate::shapes::Circle::label", ApiChangeKind::Added, ChangeLevel::Additive),
+            ("crate::area", ApiChangeKind::Changed, ChangeLevel::Patch),
+        ]);
+    }
+}
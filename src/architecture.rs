@@ -0,0 +1,211 @@
+use crate::protocol::{ArchitectureReport, ArchitectureRuleKind, ArchitectureViolation, ModuleGraphResponse, ModuleReferenceKind};
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::path::Path;
+
+/// プロジェクトのルートに置くルールファイルの既定の名前
+pub const DEFAULT_CONFIG_FILE: &str = "architecture.json";
+
+/// レイヤーのルール（architecture.json）
+///
+/// ```json
+/// { "rules": [
+///     { "module": "protocol", "must_not_depend_on": ["server"] },
+///     { "module": "parser", "may_only_depend_on": ["protocol"], "reason": "パーサーは単体で使えるようにする" }
+/// ] }
+/// ```
+///
+/// モジュールは `crate::` を省いたパスで書き、そのモジュールと子孫のモジュールに当てはまる。
+#[derive(Debug, Deserialize)]
+pub struct ArchitectureConfig {
+    pub rules: Vec<LayerRule>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LayerRule {
+    pub module: String,
+    #[serde(default)]
+    pub must_not_depend_on: Vec<String>,
+    #[serde(default)]
+    pub may_only_depend_on: Option<Vec<String>>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl ArchitectureConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read architecture rules: {}", path.display()))?;
+        let config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid architecture rules: {}", path.display()))?;
+        for rule in &config.rules {
+            if rule.must_not_depend_on.is_empty() && rule.may_only_depend_on.is_none() {
+                bail!("Rule for `{}` has neither must_not_depend_on nor may_only_depend_on", rule.module);
+            }
+        }
+        Ok(config)
+    }
+
+    fn patterns(&self) -> impl Iterator<Item = &String> {
+        self.rules.iter().flat_map(|rule| {
+            std::iter::once(&rule.module)
+                .chain(&rule.must_not_depend_on)
+                .chain(rule.may_only_depend_on.iter().flatten())
+        })
+    }
+}
+
+/// クレートのルートからの相対パス（"crate::parser::ast" → "parser::ast"、ルートは ""）
+fn relative(module: &str) -> &str {
+    module.split_once("::").map(|(_, rest)| rest).unwrap_or("")
+}
+
+/// module がパターンのモジュールか、その子孫か
+fn matches(pattern: &str, module: &str) -> bool {
+    let pattern = pattern.strip_prefix("crate").map(|rest| rest.trim_start_matches("::")).unwrap_or(pattern);
+    let module = relative(module);
+    if pattern.is_empty() {
+        return module.is_empty();
+    }
+    module == pattern || module.starts_with(&format!("{pattern}::"))
+}
+
+/// モジュールの依存をルールに照らして、違反する参照を列挙する
+pub fn check(config: &ArchitectureConfig, config_path: &str, graph: &ModuleGraphResponse) -> ArchitectureReport {
+    let mut violations = Vec::new();
+
+    for rule in &config.rules {
+        for dependency in graph.dependencies.iter().filter(|d| matches(&rule.module, &d.from)) {
+            // ルールの対象のモジュールの中での依存は対象外
+            if matches(&rule.module, &dependency.to) {
+                continue;
+            }
+            let mut broken = Vec::new();
+            if let Some(pattern) = rule.must_not_depend_on.iter().find(|p| matches(p, &dependency.to)) {
+                broken.push((ArchitectureRuleKind::MustNotDependOn, format!("{} must not depend on {}", rule.module, pattern)));
+            }
+            if let Some(allowed) = &rule.may_only_depend_on
+                && !allowed.iter().any(|p| matches(p, &dependency.to)) {
+                broken.push((ArchitectureRuleKind::MayOnlyDependOn, format!("{} may only depend on {}", rule.module, allowed.join(", "))));
+            }
+
+            for (kind, description) in broken {
+                violations.extend(dependency.references.iter().map(|reference| ArchitectureViolation {
+                    rule: description.clone(),
+                    kind,
+                    reason: rule.reason.clone(),
+                    from: dependency.from.clone(),
+                    to: dependency.to.clone(),
+                    file_path: reference.file_path.clone(),
+                    line: reference.line,
+                    column: reference.column,
+                    path: reference.path.clone(),
+                    reference_kind: reference.kind,
+                }));
+            }
+        }
+    }
+    violations.sort_by(|a, b| (&a.file_path, a.line, a.column, &a.rule).cmp(&(&b.file_path, b.line, b.column, &b.rule)));
+
+    let mut unmatched_patterns: Vec<String> = config.patterns()
+        .filter(|pattern| !graph.modules.iter().any(|m| matches(pattern, &m.path)))
+        .cloned()
+        .collect();
+    unmatched_patterns.sort();
+    unmatched_patterns.dedup();
+
+    ArchitectureReport {
+        config_path: config_path.to_string(),
+        rules: config.rules.len(),
+        dependencies_checked: graph.dependencies.len(),
+        unmatched_patterns,
+        violations,
+    }
+}
+
+fn reference_label(kind: ModuleReferenceKind) -> &'static str {
+    match kind {
+        ModuleReferenceKind::Use => "use",
+        ModuleReferenceKind::Path => "path",
+        ModuleReferenceKind::Call => "call",
+    }
+}
+
+/// 違反をルールごとにまとめてテキストに整形（位置は file:line:column）
+pub fn format_report(report: &ArchitectureReport) -> String {
+    let mut result = format!("🏛️  Architecture check ({}): {} rules, {} module dependencies\n",
+        report.config_path, report.rules, report.dependencies_checked);
+    for pattern in &report.unmatched_patterns {
+        result.push_str(&format!("⚠️  Pattern `{}` matches no module\n", pattern));
+    }
+
+    if report.violations.is_empty() {
+        result.push_str("\n✅ No architecture violations\n");
+        return result;
+    }
+    result.push_str(&format!("\n❌ {} violations\n", report.violations.len()));
+
+    let mut rules: Vec<&str> = Vec::new();
+    for violation in &report.violations {
+        if !rules.contains(&violation.rule.as_str()) {
+            rules.push(&violation.rule);
+        }
+    }
+    for rule in rules {
+        result.push_str(&format!("\n{}\n", rule));
+        let violations: Vec<_> = report.violations.iter().filter(|v| v.rule == rule).collect();
+        if let Some(reason) = violations.first().and_then(|v| v.reason.as_deref()) {
+            result.push_str(&format!("  reason: {}\n", reason));
+        }
+        for v in violations {
+            result.push_str(&format!("  {}:{}:{}  {} → {}  {} {}\n",
+                v.file_path, v.line, v.column + 1, v.from, v.to, reference_label(v.reference_kind), v.path));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_architecture() {
+        let dir = TempDir::new().unwrap();
+        let files = [
+            ("src/main.rs", "mod protocol;\nmod parser;\nmod server;\n\nfn main() {\n    server::run();\n}\n"),
+            ("src/protocol.rs", "use crate::server::Port;\n\npub struct Request;\n"),
+            ("src/parser.rs", "use crate::protocol::Request;\n\npub fn parse() -> Request {\n    listen();\n    Request\n}\n"),
+            ("src/server.rs", "pub struct Port;\n\npub fn run() {\n    crate::parser::parse();\n}\n\npub fn listen() {}\n"),
+        ];
+        let mut parser = RustParser::new();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            parser.parse_file(&path).unwrap();
+        }
+
+        let config: ArchitectureConfig = serde_json::from_str(r#"{ "rules": [
+            { "module": "protocol", "must_not_depend_on": ["server"] },
+            { "module": "crate::parser", "may_only_depend_on": ["protocol"], "reason": "standalone parser" },
+            { "module": "server", "must_not_depend_on": ["missing"] }
+        ] }"#).unwrap();
+        let graph = crate::module_graph::build_with_calls(&parser, dir.path()).unwrap();
+        let report = check(&config, DEFAULT_CONFIG_FILE, &graph);
+
+        let violations: Vec<(&str, &str, usize, usize, ModuleReferenceKind)> = report.violations.iter()
+            .map(|v| (v.rule.as_str(), v.to.as_str(), v.line, v.column, v.reference_kind))
+            .collect();
+        // server → parser は対象のルールがないので違反ではない
+        assert_eq!(violations, vec![
+            ("crate::parser may only depend on protocol", "crate::server", 4, 4, ModuleReferenceKind::Call),
+            ("protocol must not depend on server", "crate::server", 1, 19, ModuleReferenceKind::Use),
+        ]);
+        assert_eq!(report.violations[0].reason.as_deref(), Some("standalone parser"));
+        assert_eq!(report.unmatched_patterns, vec!["missing"]);
+    }
+}
@@ -0,0 +1,622 @@
+use crate::parser::{AsyncSiteInfo, RustParser};
+use crate::protocol::{AsyncHazard, AsyncHazardKind, AsyncHazardSummary, AsyncHazardsParams, AsyncHazardsResponse, SymbolType};
+use proc_macro2::Span;
+use std::collections::{HashMap, VecDeque};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// 関数の識別子（名前, ファイル, 定義行）。CallInfo の caller と対応する
+type FunctionKey = (String, String, usize);
+
+/// 引数のクロージャを別スレッドで実行する関数（中のブロッキング呼び出しは問題ない）
+const OFFLOADING_FUNCTIONS: &[&str] = &["spawn_blocking", "block_in_place"];
+
+/// 同期的にブロックするメソッド（tokio の blocking_* と、Future を同期的に待つ block_on）
+const BLOCKING_METHODS: &[&str] = &["blocking_lock", "blocking_read", "blocking_write", "blocking_recv", "blocking_send", "block_on"];
+
+/// ロックのガードを返すメソッド（引数なし）
+const GUARD_METHODS: &[&str] = &["lock", "read", "write", "blocking_lock", "try_lock", "lock_owned", "read_owned", "write_owned"];
+
+/// 関数本体で見つかった危険箇所の候補（呼び出し経路は build_report で求める）
+pub struct BodyHazard {
+    pub kind: AsyncHazardKind,
+    pub name: String,
+    pub start: Span,
+    pub end: Span,
+    pub in_async_block: bool,  // 同期関数の中の async ブロック・async クロージャ
+}
+
+/// ファイル先頭の use からパスの別名を集める（`use std::fs;` → "fs" => "std::fs"）
+pub fn collect_imports(items: &[syn::Item]) -> HashMap<String, String> {
+    let mut imports = HashMap::new();
+    for item in items {
+        if let syn::Item::Use(item_use) = item {
+            collect_use_tree(&item_use.tree, &mut Vec::new(), &mut imports);
+        }
+    }
+    imports
+}
+
+fn collect_use_tree(tree: &syn::UseTree, prefix: &mut Vec<String>, imports: &mut HashMap<String, String>) {
+    match tree {
+        syn::UseTree::Path(use_path) => {
+            prefix.push(use_path.ident.to_string());
+            collect_use_tree(&use_path.tree, prefix, imports);
+            prefix.pop();
+        }
+        syn::UseTree::Name(use_name) if use_name.ident == "self" => {
+            if let Some(last) = prefix.last() {
+                imports.insert(last.clone(), prefix.join("::"));
+            }
+        }
+        syn::UseTree::Name(use_name) => {
+            let name = use_name.ident.to_string();
+            imports.insert(name.clone(), [prefix.as_slice(), &[name]].concat().join("::"));
+        }
+        syn::UseTree::Rename(use_rename) => {
+            let path = [prefix.as_slice(), &[use_rename.ident.to_string()]].concat().join("::");
+            imports.insert(use_rename.rename.to_string(), path);
+        }
+        syn::UseTree::Glob(_) => {}
+        syn::UseTree::Group(group) => {
+            for item in &group.items {
+                collect_use_tree(item, prefix, imports);
+            }
+        }
+    }
+}
+
+/// 関数本体からブロッキング呼び出し・.await をまたぐガード・捨てられた呼び出しを探す
+///
+/// 捨てられた呼び出しは呼び出し先が async fn かどうかに関わらず記録し、build_report で絞り込む。
+pub fn scan_body(block: &syn::Block, is_async: bool, imports: &HashMap<String, String>) -> Vec<BodyHazard> {
+    let mut visitor = HazardVisitor {
+        imports,
+        is_async_fn: is_async,
+        async_block_depth: 0,
+        offloaded_depth: 0,
+        hazards: Vec::new(),
+    };
+    visitor.visit_block(block);
+    visitor.hazards
+}
+
+struct HazardVisitor<'a> {
+    imports: &'a HashMap<String, String>,
+    is_async_fn: bool,
+    async_block_depth: usize,
+    offloaded_depth: usize,  // spawn_blocking などの引数の中
+    hazards: Vec<BodyHazard>,
+}
+
+impl HazardVisitor<'_> {
+    fn add(&mut self, kind: AsyncHazardKind, name: String, start: Span, end: Span) {
+        if kind == AsyncHazardKind::BlockingCall && self.offloaded_depth > 0 {
+            return;
+        }
+        self.hazards.push(BodyHazard { kind, name, start, end, in_async_block: self.async_block_depth > 0 });
+    }
+
+    fn in_async(&self) -> bool {
+        self.is_async_fn || self.async_block_depth > 0
+    }
+
+    /// use の別名を展開したパス（`fs::read` → "std::fs::read"）
+    fn resolve_path(&self, path: &syn::Path) -> String {
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        match segments.split_first() {
+            Some((first, rest)) => match self.imports.get(first) {
+                Some(full) => [full.clone()].into_iter().chain(rest.iter().cloned()).collect::<Vec<_>>().join("::"),
+                None => segments.join("::"),
+            },
+            None => String::new(),
+        }
+    }
+
+    fn with_offloaded(&mut self, f: impl FnOnce(&mut Self)) {
+        self.offloaded_depth += 1;
+        f(self);
+        self.offloaded_depth -= 1;
+    }
+
+    fn with_async_block(&mut self, f: impl FnOnce(&mut Self)) {
+        self.async_block_depth += 1;
+        f(self);
+        self.async_block_depth -= 1;
+    }
+
+    /// メソッド呼び出し（awaited は直後に .await が続くか）
+    fn visit_method_call(&mut self, method_call: &syn::ExprMethodCall, awaited: bool) {
+        let method = method_call.method.to_string();
+        if OFFLOADING_FUNCTIONS.contains(&method.as_str()) {
+            self.visit_expr(&method_call.receiver);
+            self.with_offloaded(|this| {
+                for arg in &method_call.args {
+                    this.visit_expr(arg);
+                }
+            });
+            return;
+        }
+
+        if !awaited {
+            let blocking = if method == "lock" && method_call.args.is_empty() {
+                // tokio の Mutex::lock は .await するので、待たない lock() は同期ロック
+                Some(".lock()".to_string())
+            } else if BLOCKING_METHODS.contains(&method.as_str()) {
+                Some(format!(".{method}()"))
+            } else if matches!(method.as_str(), "output" | "status" | "wait" | "wait_with_output")
+                && self.is_std_command(&method_call.receiver) {
+                Some(format!("std::process::Command::{method}"))
+            } else {
+                None
+            };
+            if let Some(name) = blocking {
+                self.add(AsyncHazardKind::BlockingCall, name, method_call.method.span(), method_call.span());
+            }
+        }
+
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    /// メソッドチェーンの根が std::process::Command::new か
+    fn is_std_command(&self, expr: &syn::Expr) -> bool {
+        match expr {
+            syn::Expr::MethodCall(method_call) => self.is_std_command(&method_call.receiver),
+            syn::Expr::Reference(reference) => self.is_std_command(&reference.expr),
+            syn::Expr::Call(call) => match &*call.func {
+                syn::Expr::Path(path_expr) => self.resolve_path(&path_expr.path).starts_with("std::process::Command::"),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// `let guard = m.lock().unwrap();` の後、drop される前に .await があれば記録
+    fn check_guards(&mut self, block: &syn::Block) {
+        for (i, stmt) in block.stmts.iter().enumerate() {
+            let syn::Stmt::Local(local) = stmt else {
+                continue;
+            };
+            let Some(guard) = guard_binding(local) else {
+                continue;
+            };
+            for later in &block.stmts[i + 1..] {
+                if is_drop_of(later, &guard) {
+                    break;
+                }
+                let mut finder = AwaitFinder::default();
+                finder.visit_stmt(later);
+                if let Some(await_span) = finder.found {
+                    self.add(AsyncHazardKind::GuardAcrossAwait, guard, local.let_token.span, await_span);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// 文として捨てられた呼び出し（`foo();` / `let _ = foo();`）
+    fn check_discarded_call(&mut self, expr: &syn::Expr) {
+        let name = match expr {
+            syn::Expr::Call(call) => match &*call.func {
+                syn::Expr::Path(path_expr) => path_expr.path.segments.last().map(|s| s.ident.to_string()),
+                _ => None,
+            },
+            syn::Expr::MethodCall(method_call) => Some(method_call.method.to_string()),
+            _ => None,
+        };
+        if let Some(name) = name {
+            self.add(AsyncHazardKind::UnawaitedFuture, name, expr.span(), expr.span());
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for HazardVisitor<'_> {
+    // ネストしたアイテムは別の関数として収集される
+    fn visit_item(&mut self, _item: &'ast syn::Item) {}
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        if self.in_async() {
+            self.check_guards(block);
+        }
+        visit::visit_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast syn::Stmt) {
+        match stmt {
+            syn::Stmt::Expr(expr, Some(_)) => self.check_discarded_call(expr),
+            syn::Stmt::Local(local) if matches!(local.pat, syn::Pat::Wild(_)) => {
+                if let Some(init) = &local.init {
+                    self.check_discarded_call(&init.expr);
+                }
+            }
+            _ => {}
+        }
+        visit::visit_stmt(self, stmt);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path_expr) = &*call.func {
+            let path = self.resolve_path(&path_expr.path);
+            let last = path.rsplit("::").next().unwrap_or_default();
+
+            if OFFLOADING_FUNCTIONS.contains(&last) || path.ends_with("thread::spawn") {
+                self.with_offloaded(|this| {
+                    for arg in &call.args {
+                        this.visit_expr(arg);
+                    }
+                });
+                return;
+            }
+            if is_blocking_path(&path) || last == "block_on" {
+                self.add(AsyncHazardKind::BlockingCall, path, call.func.span(), call.span());
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        self.visit_method_call(method_call, false);
+    }
+
+    fn visit_expr_await(&mut self, await_expr: &'ast syn::ExprAwait) {
+        match &*await_expr.base {
+            syn::Expr::MethodCall(method_call) => self.visit_method_call(method_call, true),
+            base => self.visit_expr(base),
+        }
+    }
+
+    fn visit_expr_async(&mut self, async_expr: &'ast syn::ExprAsync) {
+        self.with_async_block(|this| this.visit_block(&async_expr.block));
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        if closure.asyncness.is_some() {
+            self.with_async_block(|this| this.visit_expr(&closure.body));
+        } else {
+            visit::visit_expr_closure(self, closure);
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Ok(args) = mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// 最初の .await を探す（async ブロック・クロージャ・アイテムの中は別の実行単位なので除く）
+#[derive(Default)]
+struct AwaitFinder {
+    found: Option<Span>,
+}
+
+impl<'ast> Visit<'ast> for AwaitFinder {
+    fn visit_item(&mut self, _item: &'ast syn::Item) {}
+    fn visit_expr_async(&mut self, _async_expr: &'ast syn::ExprAsync) {}
+    fn visit_expr_closure(&mut self, _closure: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_await(&mut self, await_expr: &'ast syn::ExprAwait) {
+        if self.found.is_none() {
+            self.found = Some(await_expr.span());
+        }
+    }
+}
+
+/// std::fs・std::thread::sleep・TcpStream::connect・`*::blocking::*` のブロッキング API か
+fn is_blocking_path(path: &str) -> bool {
+    (path.starts_with("std::fs::") && path != "std::fs::OpenOptions::new")
+        || path == "std::thread::sleep"
+        || path == "std::net::TcpStream::connect"
+        || path.contains("::blocking::")
+}
+
+/// 同期ロックのガードを束縛する let なら変数名
+///
+/// `.await` したロック（tokio::sync::Mutex など）のガードは .await をまたいで持ってよいので対象外。
+fn guard_binding(local: &syn::Local) -> Option<String> {
+    let ident = match &local.pat {
+        syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+        syn::Pat::Type(pat_type) => match &*pat_type.pat {
+            syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    // .unwrap() / .expect() / ? を外して lock() などを探す
+    let mut expr = &*local.init.as_ref()?.expr;
+    loop {
+        expr = match expr {
+            syn::Expr::MethodCall(method_call) if matches!(method_call.method.to_string().as_str(), "unwrap" | "expect") => &method_call.receiver,
+            syn::Expr::Try(try_expr) => &try_expr.expr,
+            syn::Expr::Await(_) => return None,
+            _ => break,
+        };
+    }
+
+    match expr {
+        syn::Expr::MethodCall(method_call)
+            if method_call.args.is_empty() && GUARD_METHODS.contains(&method_call.method.to_string().as_str()) => Some(ident.to_string()),
+        _ => None,
+    }
+}
+
+/// `drop(guard);` か
+fn is_drop_of(stmt: &syn::Stmt, guard: &str) -> bool {
+    let syn::Stmt::Expr(syn::Expr::Call(call), _) = stmt else {
+        return false;
+    };
+    let is_drop = matches!(&*call.func, syn::Expr::Path(p) if p.path.is_ident("drop"));
+    is_drop && call.args.iter().any(|arg| matches!(arg, syn::Expr::Path(p) if p.path.is_ident(guard)))
+}
+
+/// 危険箇所を async fn ごとにまとめ、同期関数を経由するブロッキング呼び出しは呼び出し経路を求める
+pub fn build_report(parser: &RustParser, params: &AsyncHazardsParams) -> AsyncHazardsResponse {
+    let include_tests = params.include_tests.unwrap_or(false);
+
+    let functions: HashMap<FunctionKey, _> = parser.get_all_symbols().values()
+        .flatten()
+        .filter(|s| s.symbol_type == SymbolType::Function && s.crate_name.is_none())
+        .filter(|s| include_tests || !s.is_test)
+        .map(|s| ((s.name.clone(), s.file_path.clone(), s.line), s))
+        .collect();
+    let is_async = |key: &FunctionKey| functions[key].signature.starts_with("async ");
+
+    // 同名の関数がすべて async fn である名前（名前だけで呼び出し先を決めるため）
+    let mut async_names: HashMap<&str, bool> = HashMap::new();
+    for (key, symbol) in &functions {
+        *async_names.entry(symbol.name.as_str()).or_insert(true) &= is_async(key);
+    }
+
+    let mut sites_by_function: HashMap<FunctionKey, Vec<&AsyncSiteInfo>> = HashMap::new();
+    for site in parser.get_async_sites() {
+        let key = (site.function.clone(), site.file_path.clone(), site.function_line);
+        if functions.contains_key(&key) {
+            sites_by_function.entry(key).or_default().push(site);
+        }
+    }
+
+    let mut hazards = Vec::new();
+    let mut push_hazard = |function: &FunctionKey, site: &AsyncSiteInfo, call_chain: Vec<String>| {
+        let symbol = functions[function];
+        hazards.push(AsyncHazard {
+            kind: site.kind,
+            function: symbol.name.clone(),
+            function_file: symbol.file_path.clone(),
+            function_line: symbol.line,
+            scope: symbol.scope.clone(),
+            name: site.name.clone(),
+            file_path: site.file_path.clone(),
+            line: site.line,
+            column: site.column,
+            end_line: site.end_line,
+            end_column: site.end_column,
+            context: site.context.clone(),
+            call_chain,
+        });
+    };
+
+    // 非同期の文脈（async fn・async ブロック）にある危険箇所
+    let mut blocking_chains: HashMap<FunctionKey, Vec<FunctionKey>> = HashMap::new();
+    let mut queue: VecDeque<FunctionKey> = VecDeque::new();
+    for (key, sites) in &sites_by_function {
+        for site in sites {
+            let in_async = is_async(key) || site.in_async_block;
+            match site.kind {
+                AsyncHazardKind::BlockingCall if in_async => push_hazard(key, site, vec![key.0.clone()]),
+                AsyncHazardKind::BlockingCall => {
+                    if !blocking_chains.contains_key(key) {
+                        blocking_chains.insert(key.clone(), vec![key.clone()]);
+                        queue.push_back(key.clone());
+                    }
+                }
+                AsyncHazardKind::GuardAcrossAwait => push_hazard(key, site, vec![key.0.clone()]),
+                AsyncHazardKind::UnawaitedFuture => {
+                    if async_names.get(site.name.as_str()).copied().unwrap_or(false) {
+                        push_hazard(key, site, vec![key.0.clone(), site.name.clone()]);
+                    }
+                }
+            }
+        }
+    }
+
+    // 同期関数のブロッキング呼び出しを呼び出し元へ幅優先で広げ、async fn に着いたら止める（最短の経路を記録）
+    // メソッド呼び出しはレシーバの型が分からず、`.collect()` などを同名の関数と取り違えるのでたどらない
+    while let Some(key) = queue.pop_front() {
+        let chain = blocking_chains[&key].clone();
+        for call in parser.get_calls_to_function(&key.0).into_iter().filter(|call| !call.method_call) {
+            let caller = (call.caller.clone(), call.caller_file.clone(), call.caller_line);
+            if !functions.contains_key(&caller) || blocking_chains.contains_key(&caller) {
+                continue;
+            }
+            let mut caller_chain = vec![caller.clone()];
+            caller_chain.extend(chain.iter().cloned());
+            blocking_chains.insert(caller.clone(), caller_chain);
+            if !is_async(&caller) {
+                queue.push_back(caller);
+            }
+        }
+    }
+    for (key, chain) in &blocking_chains {
+        if !is_async(key) {
+            continue;
+        }
+        let terminal = chain.last().expect("chain always contains the blocking function");
+        let names: Vec<String> = chain.iter().map(|k| k.0.clone()).collect();
+        for site in &sites_by_function[terminal] {
+            if site.kind == AsyncHazardKind::BlockingCall && !site.in_async_block {
+                push_hazard(key, site, names.clone());
+            }
+        }
+    }
+
+    hazards.retain(|h| params.file_path.as_deref().is_none_or(|path| h.function_file == path));
+    hazards.retain(|h| params.kind.is_none_or(|kind| h.kind == kind));
+    hazards.sort_by(|a, b| {
+        a.function_file.cmp(&b.function_file)
+            .then(a.function_line.cmp(&b.function_line))
+            .then(a.file_path.cmp(&b.file_path))
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+
+    let summary = AsyncHazardSummary {
+        async_functions: functions.iter()
+            .filter(|(key, symbol)| is_async(key) && params.file_path.as_deref().is_none_or(|path| symbol.file_path == path))
+            .count(),
+        blocking_calls: hazards.iter().filter(|h| h.kind == AsyncHazardKind::BlockingCall).count(),
+        guards_across_await: hazards.iter().filter(|h| h.kind == AsyncHazardKind::GuardAcrossAwait).count(),
+        unawaited_futures: hazards.iter().filter(|h| h.kind == AsyncHazardKind::UnawaitedFuture).count(),
+    };
+
+    AsyncHazardsResponse { hazards, summary }
+}
+
+/// レポートを関数ごとのテキストに整形（CLI 用）
+pub fn format_text_report(report: &AsyncHazardsResponse) -> String {
+    let summary = &report.summary;
+    let mut result = format!(
+        "Async hazards: {} blocking call(s), {} guard(s) held across .await, {} unawaited future(s) in {} async fn(s)\n",
+        summary.blocking_calls, summary.guards_across_await, summary.unawaited_futures, summary.async_functions,
+    );
+
+    let mut current_function: Option<(&str, usize)> = None;
+    for hazard in &report.hazards {
+        if current_function != Some((hazard.function_file.as_str(), hazard.function_line)) {
+            let name = match &hazard.scope {
+                Some(scope) => format!("{scope}::{}", hazard.function),
+                None => hazard.function.clone(),
+            };
+            result.push_str(&format!("\n{}:{} {}\n", hazard.function_file, hazard.function_line, name));
+            current_function = Some((&hazard.function_file, hazard.function_line));
+        }
+
+        let description = match hazard.kind {
+            AsyncHazardKind::BlockingCall => format!("blocking call {}", hazard.name),
+            AsyncHazardKind::GuardAcrossAwait => format!("guard `{}` held across .await (line {})", hazard.name, hazard.end_line),
+            AsyncHazardKind::UnawaitedFuture => format!("future of async fn {} is never awaited", hazard.name),
+        };
+        result.push_str(&format!(
+            "  {}:{}:{} {}\n        {}\n",
+            hazard.file_path, hazard.line, hazard.column, description, hazard.context,
+        ));
+        if hazard.call_chain.len() > 1 {
+            result.push_str(&format!("        via {}\n", hazard.call_chain.join(" → ")));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_async_hazards() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/server.rs", r#"
+use std::fs;
+use std::sync::Mutex;
+
+fn load(path: &str) -> String {
+    read_config(path)
+}
+
+fn read_config(path: &str) -> String {
+    fs::read_to_string(path).unwrap()
+}
+
+async fn handle(state: &Mutex<u32>, path: &str) {
+    let config = load(path);
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    let guard = state.lock().unwrap();
+    notify().await;
+    drop(guard);
+    notify();
+    let _ = tokio::task::spawn_blocking(move || std::fs::read("x")).await;
+}
+
+async fn notify() {}
+
+async fn short_guard(state: &tokio::sync::Mutex<u32>) {
+    let guard = state.lock().await;
+    drop(guard);
+    notify().await;
+}
+
+fn spawn_worker() {
+    tokio::spawn(async move {
+        std::fs::remove_file("x").ok();
+    });
+}
+"#).unwrap();
+
+        let params = AsyncHazardsParams { file_path: None, kind: None, include_tests: None };
+        let report = build_report(&parser, &params);
+        let found: Vec<(&str, AsyncHazardKind, &str, usize, Vec<&str>)> = report.hazards.iter()
+            .map(|h| (h.function.as_str(), h.kind, h.name.as_str(), h.line, h.call_chain.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(found, vec![
+            ("handle", AsyncHazardKind::BlockingCall, "std::fs::read_to_string", 10, vec!["handle", "load", "read_config"]),
+            ("handle", AsyncHazardKind::BlockingCall, "std::thread::sleep", 15, vec!["handle"]),
+            ("handle", AsyncHazardKind::GuardAcrossAwait, "guard", 16, vec!["handle"]),
+            ("handle", AsyncHazardKind::BlockingCall, ".lock()", 16, vec!["handle"]),
+            ("handle", AsyncHazardKind::UnawaitedFuture, "notify", 19, vec!["handle", "notify"]),
+            ("spawn_worker", AsyncHazardKind::BlockingCall, "std::fs::remove_file", 33, vec!["spawn_worker"]),
+        ]);
+        assert_eq!(report.hazards[2].end_line, 17);
+        assert_eq!((report.summary.async_functions, report.summary.blocking_calls), (3, 4));
+
+        let guards = build_report(&parser, &AsyncHazardsParams { kind: Some(AsyncHazardKind::GuardAcrossAwait), ..params });
+        assert_eq!(guards.hazards.len(), 1);
+    }
+
+    #[test]
+    fn test_blocking_call_not_propagated_through_method_calls() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/lib.rs", r#"
+use std::fs;
+
+fn collect(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap()
+}
+
+async fn gather(items: Vec<u32>) -> Vec<u32> {
+    items.into_iter().collect()
+}
+
+async fn direct() -> Vec<u8> {
+    collect("x")
+}
+"#).unwrap();
+
+        let report = build_report(&parser, &AsyncHazardsParams { file_path: None, kind: None, include_tests: None });
+        // イテレータの .collect() は同名の同期関数 collect とは別物
+        let found: Vec<(&str, Vec<&str>)> = report.hazards.iter()
+            .map(|h| (h.function.as_str(), h.call_chain.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(found, vec![("direct", vec!["direct", "collect"])]);
+    }
+
+    #[test]
+    fn test_async_lock_guard_across_await() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/server.rs", r#"
+async fn notify() {}
+
+async fn handle(state: &tokio::sync::Mutex<u32>) {
+    let guard = state.lock().await;
+    notify().await;
+    drop(guard);
+}
+"#).unwrap();
+
+        // .await したロックのガードは .await をまたいで持ってよい
+        let report = build_report(&parser, &AsyncHazardsParams { file_path: None, kind: None, include_tests: None });
+        assert!(report.hazards.is_empty());
+    }
+}
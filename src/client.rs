@@ -1,4 +1,4 @@
-use crate::protocol::{self, ServerRequest, ServerResponse, FindDefinitionParams, FindUsagesParams, SymbolType};
+use crate::protocol::{self, ServerRequest, ServerResponse, FindDefinitionParams, FindUsagesParams, SearchSymbolsParams, GetDiagnosticsParams, FindLocalReferencesParams, FindTestsForParams, ListTestsParams, TestKind, UnsafeReportParams, PanicReportParams, GetMetricsParams, FindDeadCodeParams, ApiSurfaceParams, ApiDiffParams, AsyncHazardsParams, GetErrorMapParams, FindErrorOriginsParams, FindSwallowedErrorsParams, FindImplementationsParams, GetImplMatrixParams, JsonSchemaParams, GetSourceParams, ExplainSymbolParams, RenameSymbolParams, StructuralSearchParams, SemanticDiffParams, SymbolHistoryParams, GetChurnParams, ImpactAnalysisParams, SuggestImportsParams, SymbolType};
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -60,10 +60,6 @@ impl CodeIntelClient {
         self.send_request_internal(&request.method, request.params).await
     }
 
-    /// シンボル定義を検索（互換性のための旧API）
-    pub async fn find_definition(&self, function_name: &str) -> Result<Value> {
-        self.find_definition_with_type(function_name, Some(SymbolType::Function)).await
-    }
     
     /// シンボル定義を検索（型指定付き）
     pub async fn find_definition_with_type(&self, symbol_name: &str, symbol_type: Option<SymbolType>) -> Result<Value> {
@@ -99,6 +95,238 @@ impl CodeIntelClient {
         response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
     }
 
+    /// シンボルをあいまい検索
+    pub async fn search_symbols(&self, query: &str, symbol_type: Option<SymbolType>, limit: Option<usize>) -> Result<Value> {
+        let params = serde_json::to_value(SearchSymbolsParams {
+            query: query.to_string(),
+            symbol_type,
+            limit,
+        })?;
+
+        let response = self.send_request_internal(protocol::methods::SEARCH_SYMBOLS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// パースエラーの診断情報を取得
+    /// 指定位置のローカル変数・パラメータの参照を検索
+    pub async fn find_local_references(&self, file_path: &str, line: usize, column: usize) -> Result<Value> {
+        let params = serde_json::to_value(FindLocalReferencesParams {
+            file_path: file_path.to_string(),
+            line,
+            column,
+        })?;
+
+        let response = self.send_request_internal(protocol::methods::FIND_LOCAL_REFERENCES, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// 指定シンボルを推移的に呼び出しているテストを検索
+    pub async fn find_tests_for(&self, symbol_name: &str, max_depth: Option<usize>) -> Result<Value> {
+        let params = serde_json::to_value(FindTestsForParams {
+            symbol_name: symbol_name.to_string(),
+            max_depth,
+        })?;
+
+        let response = self.send_request_internal(protocol::methods::FIND_TESTS_FOR, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// インデックス済みのテスト一覧を取得
+    pub async fn list_tests(&self, kind: Option<TestKind>, file_path: Option<&str>) -> Result<Value> {
+        let params = serde_json::to_value(ListTestsParams {
+            kind,
+            file_path: file_path.map(|p| p.to_string()),
+        })?;
+
+        let response = self.send_request_internal(protocol::methods::LIST_TESTS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// unsafe なコードの一覧と SAFETY コメントの有無を取得
+    pub async fn unsafe_report(&self, params: UnsafeReportParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::UNSAFE_REPORT, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// パニックしうる箇所と、呼び出し先経由でパニックしうる関数を取得
+    pub async fn panic_report(&self, params: PanicReportParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::PANIC_REPORT, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// 関数ごとの規模・複雑さの指標を取得
+    pub async fn get_metrics(&self, params: GetMetricsParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::GET_METRICS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// 根から到達できない関数・型・モジュールを取得
+    pub async fn find_dead_code(&self, params: FindDeadCodeParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::FIND_DEAD_CODE, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// クレートの公開 API を取得
+    pub async fn api_surface(&self, params: ApiSurfaceParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::API_SURFACE, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// 2つの git リビジョン間の公開 API の差分を取得
+    pub async fn api_diff(&self, params: ApiDiffParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::API_DIFF, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// async fn から到達できるブロッキング呼び出しなどの危険箇所を取得
+    pub async fn async_hazards(&self, params: AsyncHazardsParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::ASYNC_HAZARDS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// Result・Option を返す関数と ? による伝播を取得
+    pub async fn get_error_map(&self, params: GetErrorMapParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::GET_ERROR_MAP, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// エラー型が生まれる箇所を取得
+    pub async fn find_error_origins(&self, params: FindErrorOriginsParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::FIND_ERROR_ORIGINS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// エラーを握りつぶしている呼び出し元を取得
+    pub async fn find_swallowed_errors(&self, params: FindSwallowedErrorsParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::FIND_SWALLOWED_ERRORS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// トレイトを実装している型（derive を含む）を取得
+    pub async fn find_implementations(&self, params: FindImplementationsParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::FIND_IMPLEMENTATIONS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// 型ごとの derive・impl の表を取得
+    pub async fn get_impl_matrix(&self, params: GetImplMatrixParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::GET_IMPL_MATRIX, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// serde の型の JSON Schema を取得
+    pub async fn get_json_schema(&self, params: JsonSchemaParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::GET_JSON_SCHEMA, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// シンボルのソーステキストを取得
+    pub async fn get_source(&self, params: GetSourceParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::GET_SOURCE, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// シンボルの要約（シグネチャ・ドキュメント・関連シンボル）を取得
+    pub async fn explain_symbol(&self, params: ExplainSymbolParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::EXPLAIN_SYMBOL, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// シンボルのリネームに必要な書き換えを取得（apply なら書き換えて再インデックス）
+    pub async fn rename_symbol(&self, params: RenameSymbolParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::RENAME_SYMBOL, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// 構文パターンで式を検索（rewrite があれば置換結果も、apply なら書き込みまで）
+    pub async fn structural_search(&self, params: StructuralSearchParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::STRUCTURAL_SEARCH, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// 2つのリビジョン間のシンボル・呼び出し関係の差分を取得
+    pub async fn semantic_diff(&self, params: SemanticDiffParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::SEMANTIC_DIFF, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// シンボルの行範囲に触れたコミットの履歴を取得
+    pub async fn symbol_history(&self, params: SymbolHistoryParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::SYMBOL_HISTORY, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// シンボルごとの変更回数・最終変更日を取得
+    pub async fn get_churn(&self, params: GetChurnParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::GET_CHURN, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// diff から影響を受ける関数・公開 API・テストを取得
+    pub async fn impact_analysis(&self, params: ImpactAnalysisParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::IMPACT_ANALYSIS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    /// ファイルから名前を使うための use のパスを取得
+    pub async fn suggest_imports(&self, params: SuggestImportsParams) -> Result<Value> {
+        let params = serde_json::to_value(params)?;
+
+        let response = self.send_request_internal(protocol::methods::SUGGEST_IMPORTS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
+    pub async fn get_diagnostics(&self, file_path: Option<&str>) -> Result<Value> {
+        let params = serde_json::to_value(GetDiagnosticsParams {
+            file_path: file_path.map(|p| p.to_string()),
+        })?;
+
+        let response = self.send_request_internal(protocol::methods::GET_DIAGNOSTICS, params).await?;
+        response.result.ok_or_else(|| anyhow::anyhow!("No result in response"))
+    }
+
     /// サーバーが起動しているかチェック
     pub async fn is_server_running(&self) -> bool {
         (self.health_check().await).is_ok()
@@ -0,0 +1,645 @@
+use crate::parser::{RustParser, is_test_item, is_test_path};
+use crate::protocol::{Confidence, DeadCodeSummary, DeadItem, DeadItemKind, FindDeadCodeParams, FindDeadCodeResponse};
+use proc_macro2::{TokenStream, TokenTree};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// コンパイラ・derive ヘルパーの属性（これ以外の属性はマクロがアイテムを登録している可能性がある）
+const KNOWN_ATTRIBUTES: &[&str] = &[
+    "doc", "derive", "allow", "warn", "deny", "forbid", "expect", "cfg", "cfg_attr",
+    "inline", "cold", "must_use", "deprecated", "track_caller", "non_exhaustive", "repr",
+    "serde", "error", "default", "no_mangle", "export_name", "unsafe", "bench", "rustfmt", "clippy",
+];
+
+/// 到達可能性を調べるアイテム（関数・型）
+struct Node {
+    kind: DeadItemKind,
+    name: String,
+    file_path: String,
+    line: usize,
+    end_line: usize,
+    scope: Option<String>,
+    module: String,  // 所属モジュールのキー（ファイルパス、インラインモジュールは "<ファイル>::a::b"）
+    visibility: String,
+    root: bool,
+    is_test: bool,
+    macro_attribute: Option<String>,
+    references: HashSet<String>,  // 本体・シグネチャ・フィールドから参照している名前
+}
+
+/// モジュール（ファイル・インラインモジュール）
+struct ModuleNode {
+    key: String,
+    name: String,
+    file_path: String,
+    line: usize,
+    end_line: usize,
+    scope: Option<String>,
+    visibility: String,
+    is_test: bool,
+}
+
+/// プロジェクト全体の収集結果
+#[derive(Default)]
+struct Analysis {
+    nodes: Vec<Node>,
+    modules: Vec<ModuleNode>,
+    root_references: HashSet<String>,  // アイテムの外（const の初期化式・pub use など）からの参照
+    macro_mentions: HashSet<String>,   // macro_rules! の本体に現れる名前
+    string_mentions: HashSet<String>,  // 文字列リテラルに現れる名前
+}
+
+/// main・テスト・trait 実装・エクスポートされた関数・ライブラリの pub アイテムから到達できないコードを探す
+///
+/// 名前ベースで到達可能性を求めるため、同名のアイテムはまとめて生きているとみなす（見逃しはあっても誤検出は少ない）。
+pub fn find_dead_code<'a>(parser: &RustParser, files: impl IntoIterator<Item = &'a Path>, params: &FindDeadCodeParams) -> FindDeadCodeResponse {
+    let mut analysis = Analysis::default();
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let Ok(syntax_tree) = syn::parse_file(&content) else {
+            continue;
+        };
+        collect_file(parser, &file.to_string_lossy(), &syntax_tree, &mut analysis);
+    }
+
+    let alive = reachable_nodes(&analysis);
+    build_response(&analysis, &alive, params)
+}
+
+fn collect_file(parser: &RustParser, file_path: &str, syntax_tree: &syn::File, analysis: &mut Analysis) {
+    let path = Path::new(file_path);
+    let is_test_file = is_test_path(file_path);
+    let name = match path.file_stem().and_then(|s| s.to_str()) {
+        Some("mod") => path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()),
+        stem => stem.map(|s| s.to_string()),
+    }.unwrap_or_default();
+
+    if !is_crate_root(path) {
+        analysis.modules.push(ModuleNode {
+            key: file_path.to_string(),
+            name,
+            file_path: file_path.to_string(),
+            line: 1,
+            end_line: syntax_tree.span().end().line.max(1),
+            scope: None,
+            visibility: "private".to_string(),
+            is_test: is_test_file,
+        });
+    }
+
+    let mut collector = ReachabilityCollector {
+        parser,
+        file_path,
+        is_library_file: is_library_file(path),
+        scope: Vec::new(),
+        module: file_path.to_string(),
+        owners: Vec::new(),
+        test_depth: usize::from(is_test_file),
+        analysis,
+    };
+    collector.visit_file(syntax_tree);
+}
+
+/// クレートのルートファイル（モジュールとしては報告しない）
+fn is_crate_root(path: &Path) -> bool {
+    let in_target_dir = path.components()
+        .any(|c| matches!(c.as_os_str().to_str(), Some("bin" | "tests" | "examples" | "benches")));
+    let file_name = path.file_name().and_then(|n| n.to_str());
+    in_target_dir || matches!(file_name, Some("main.rs" | "lib.rs" | "build.rs"))
+}
+
+/// ライブラリクレートのファイルか（同じ src/ に lib.rs があり、バイナリのファイルではない）
+fn is_library_file(path: &Path) -> bool {
+    let Some(src_dir) = path.ancestors().find(|dir| dir.file_name().is_some_and(|n| n == "src")) else {
+        return false;
+    };
+    let is_binary = path.file_name().is_some_and(|n| n == "main.rs")
+        || path.strip_prefix(src_dir).is_ok_and(|rest| rest.starts_with("bin"));
+    !is_binary && src_dir.join("lib.rs").is_file()
+}
+
+/// 根から名前を辿って生きているアイテムを求める
+fn reachable_nodes(analysis: &Analysis) -> Vec<bool> {
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, node) in analysis.nodes.iter().enumerate() {
+        by_name.entry(node.name.as_str()).or_default().push(i);
+    }
+
+    let mut alive = vec![false; analysis.nodes.len()];
+    let mut queue: VecDeque<&str> = analysis.root_references.iter().map(String::as_str).collect();
+    for (i, node) in analysis.nodes.iter().enumerate() {
+        if node.root {
+            alive[i] = true;
+            queue.extend(node.references.iter().map(String::as_str));
+        }
+    }
+
+    let mut reached: HashSet<&str> = HashSet::new();
+    while let Some(name) = queue.pop_front() {
+        if !reached.insert(name) {
+            continue;
+        }
+        for &i in by_name.get(name).into_iter().flatten() {
+            if !alive[i] {
+                alive[i] = true;
+                queue.extend(analysis.nodes[i].references.iter().map(String::as_str));
+            }
+        }
+    }
+
+    alive
+}
+
+fn build_response(analysis: &Analysis, alive: &[bool], params: &FindDeadCodeParams) -> FindDeadCodeResponse {
+    let include_tests = params.include_tests.unwrap_or(false);
+    let min_confidence = params.min_confidence.unwrap_or(Confidence::Low);
+    let included = |file_path: &str, is_test: bool, confidence: Confidence| {
+        params.file_path.as_deref().is_none_or(|path| file_path == path)
+            && (include_tests || !is_test)
+            && confidence >= min_confidence
+    };
+
+    let mut items = Vec::new();
+    for (node, _) in analysis.nodes.iter().zip(alive).filter(|(_, alive)| !**alive) {
+        let (confidence, reasons) = node_confidence(analysis, node);
+        if included(&node.file_path, node.is_test, confidence) {
+            items.push(DeadItem {
+                name: node.name.clone(),
+                kind: node.kind,
+                file_path: node.file_path.clone(),
+                line: node.line,
+                end_line: node.end_line,
+                scope: node.scope.clone(),
+                visibility: node.visibility.clone(),
+                confidence,
+                reasons,
+            });
+        }
+    }
+
+    // 中のアイテムがすべて到達不能なモジュール（外側の到達不能なモジュールに含まれるものは除く）
+    let dead_module_keys: HashSet<&str> = analysis.modules.iter()
+        .filter(|module| {
+            let mut contained = analysis.nodes.iter().zip(alive)
+                .filter(|(node, _)| is_within_module(&node.module, &module.key))
+                .peekable();
+            contained.peek().is_some() && contained.all(|(_, alive)| !*alive)
+        })
+        .map(|module| module.key.as_str())
+        .collect();
+    for module in &analysis.modules {
+        if !dead_module_keys.contains(module.key.as_str())
+            || dead_module_keys.iter().any(|key| *key != module.key && is_within_module(&module.key, key)) {
+            continue;
+        }
+        let confidence = analysis.nodes.iter()
+            .filter(|node| is_within_module(&node.module, &module.key))
+            .map(|node| node_confidence(analysis, node).0)
+            .min()
+            .unwrap_or(Confidence::High);
+        if included(&module.file_path, module.is_test, confidence) {
+            items.push(DeadItem {
+                name: module.name.clone(),
+                kind: DeadItemKind::Module,
+                file_path: module.file_path.clone(),
+                line: module.line,
+                end_line: module.end_line,
+                scope: module.scope.clone(),
+                visibility: module.visibility.clone(),
+                confidence,
+                reasons: vec!["no item in this module is reachable".to_string()],
+            });
+        }
+    }
+    items.sort_by(|a, b| {
+        a.file_path.cmp(&b.file_path)
+            .then(a.line.cmp(&b.line))
+            .then((b.kind == DeadItemKind::Module).cmp(&(a.kind == DeadItemKind::Module)))
+    });
+
+    let summary = DeadCodeSummary {
+        roots: analysis.nodes.iter().filter(|node| node.root).count(),
+        analyzed_items: analysis.nodes.len(),
+        dead_functions: items.iter().filter(|i| i.kind == DeadItemKind::Function).count(),
+        dead_types: items.iter().filter(|i| !matches!(i.kind, DeadItemKind::Function | DeadItemKind::Module)).count(),
+        dead_modules: items.iter().filter(|i| i.kind == DeadItemKind::Module).count(),
+    };
+
+    FindDeadCodeResponse { items, summary }
+}
+
+fn is_within_module(module: &str, ancestor: &str) -> bool {
+    module == ancestor || module.strip_prefix(ancestor).is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// 到達不能と判定した確からしさ（マクロ・文字列・属性マクロ・pub による見逃しの可能性で下げる）
+fn node_confidence(analysis: &Analysis, node: &Node) -> (Confidence, Vec<String>) {
+    let mut reasons = vec!["not reachable from main, tests, trait impls, exported functions or public library API".to_string()];
+    let confidence = if analysis.macro_mentions.contains(&node.name) {
+        reasons.push("name appears in a macro_rules! body".to_string());
+        Confidence::Low
+    } else if analysis.string_mentions.contains(&node.name) {
+        reasons.push("name appears in a string literal".to_string());
+        Confidence::Low
+    } else if let Some(attribute) = &node.macro_attribute {
+        reasons.push(format!("attribute #[{attribute}] may register it"));
+        Confidence::Medium
+    } else if node.visibility == "pub" {
+        reasons.push("pub item may be used from another crate in the workspace".to_string());
+        Confidence::Medium
+    } else {
+        Confidence::High
+    };
+    (confidence, reasons)
+}
+
+/// トークン列の識別子（include_strings なら文字列リテラル中の識別子も）を集める
+fn collect_token_idents(tokens: TokenStream, include_strings: bool, out: &mut HashSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Group(group) => collect_token_idents(group.stream(), include_strings, out),
+            TokenTree::Ident(ident) => {
+                out.insert(ident.to_string());
+            }
+            TokenTree::Literal(literal) if include_strings => {
+                if let Ok(lit) = syn::parse2::<syn::LitStr>(TokenTree::Literal(literal).into()) {
+                    out.extend(string_words(&lit.value()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 文字列中の識別子らしい単語
+fn string_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_'))
+        .map(str::to_string)
+}
+
+/// ファイル内のアイテムと、アイテムごとの参照名を集める
+struct ReachabilityCollector<'a> {
+    parser: &'a RustParser,
+    file_path: &'a str,
+    is_library_file: bool,
+    scope: Vec<String>,
+    module: String,
+    owners: Vec<usize>,  // 参照を記録する先のアイテム（空ならルートからの参照）
+    test_depth: usize,
+    analysis: &'a mut Analysis,
+}
+
+impl ReachabilityCollector<'_> {
+    fn add_reference(&mut self, name: String) {
+        match self.owners.last() {
+            Some(&owner) => self.analysis.nodes[owner].references.insert(name),
+            None => self.analysis.root_references.insert(name),
+        };
+    }
+
+    fn add_node(&mut self, kind: DeadItemKind, ident: &syn::Ident, item_span: proc_macro2::Span, visibility: String, attrs: &[syn::Attribute], root: bool) -> usize {
+        let is_test = self.test_depth > 0 || crate::test_map::test_attribute(attrs).is_some();
+        self.analysis.nodes.push(Node {
+            kind,
+            name: ident.to_string(),
+            file_path: self.file_path.to_string(),
+            line: ident.span().start().line,
+            end_line: item_span.end().line,
+            scope: (!self.scope.is_empty()).then(|| self.scope.join("::")),
+            module: self.module.clone(),
+            root: root || (self.is_library_file && visibility == "pub"),
+            visibility,
+            is_test,
+            macro_attribute: macro_attribute(attrs),
+            references: HashSet::new(),
+        });
+        self.analysis.nodes.len() - 1
+    }
+
+    /// owner を参照の記録先、name をスコープとして f を実行
+    fn with_owner(&mut self, owner: usize, f: impl FnOnce(&mut Self)) {
+        self.scope.push(self.analysis.nodes[owner].name.clone());
+        self.owners.push(owner);
+        f(self);
+        self.owners.pop();
+        self.scope.pop();
+    }
+
+    /// impl_of は impl ブロック内のメソッドの場合に (Self 型, trait 実装か)
+    fn visit_fn(&mut self, sig: &syn::Signature, vis: String, attrs: &[syn::Attribute], item_span: proc_macro2::Span, block: &syn::Block, impl_of: Option<(&str, bool)>) {
+        let is_main = self.scope.is_empty() && sig.ident == "main";
+        let is_test = crate::test_map::test_attribute(attrs).is_some() || attrs.iter().any(|a| a.path().is_ident("bench"));
+        let is_trait_impl = impl_of.is_some_and(|(_, is_trait_impl)| is_trait_impl);
+        let root = is_trait_impl || is_main || is_test || is_exported(attrs) || sig.abi.is_some();
+
+        let owner = self.add_node(DeadItemKind::Function, &sig.ident, item_span, vis, attrs, root);
+        // 固有メソッドが使われていれば Self 型も使われている。trait 実装のメソッドは常に根なので、
+        // Self 型への参照を記録すると型が使われていなくても生きていることになってしまう
+        if let Some((self_type, false)) = impl_of {
+            self.analysis.nodes[owner].references.insert(self_type.to_string());
+        }
+        self.with_owner(owner, |this| {
+            for attr in attrs {
+                this.visit_attribute(attr);
+            }
+            this.visit_signature(sig);
+            this.visit_block(block);
+        });
+    }
+}
+
+/// #[no_mangle] / #[export_name] / #[unsafe(no_mangle)] が付いているか
+fn is_exported(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let mut names = HashSet::new();
+        collect_token_idents(quote::quote!(#attr), false, &mut names);
+        names.contains("no_mangle") || names.contains("export_name")
+    })
+}
+
+/// 既知の属性・テスト属性以外の属性（属性マクロ）の名前
+fn macro_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter()
+        .filter(|attr| crate::test_map::test_attribute(std::slice::from_ref(*attr)).is_none())
+        .map(|attr| attr.path())
+        .find(|path| !path.segments.first().is_some_and(|segment| KNOWN_ATTRIBUTES.contains(&segment.ident.to_string().as_str())))
+        .map(|path| path.segments.iter().map(|s| s.ident.to_string()).collect::<Vec<_>>().join("::"))
+}
+
+impl<'ast> Visit<'ast> for ReachabilityCollector<'_> {
+    fn visit_item_fn(&mut self, item_fn: &'ast syn::ItemFn) {
+        let vis = self.parser.format_visibility(&item_fn.vis);
+        self.visit_fn(&item_fn.sig, vis, &item_fn.attrs, item_fn.span(), &item_fn.block, None);
+    }
+
+    fn visit_item_struct(&mut self, item_struct: &'ast syn::ItemStruct) {
+        let vis = self.parser.format_visibility(&item_struct.vis);
+        let owner = self.add_node(DeadItemKind::Struct, &item_struct.ident, item_struct.span(), vis, &item_struct.attrs, false);
+        self.with_owner(owner, |this| visit::visit_item_struct(this, item_struct));
+    }
+
+    fn visit_item_enum(&mut self, item_enum: &'ast syn::ItemEnum) {
+        let vis = self.parser.format_visibility(&item_enum.vis);
+        let owner = self.add_node(DeadItemKind::Enum, &item_enum.ident, item_enum.span(), vis, &item_enum.attrs, false);
+        self.with_owner(owner, |this| visit::visit_item_enum(this, item_enum));
+    }
+
+    fn visit_item_type(&mut self, item_type: &'ast syn::ItemType) {
+        let vis = self.parser.format_visibility(&item_type.vis);
+        let owner = self.add_node(DeadItemKind::TypeAlias, &item_type.ident, item_type.span(), vis, &item_type.attrs, false);
+        self.with_owner(owner, |this| visit::visit_item_type(this, item_type));
+    }
+
+    fn visit_item_trait(&mut self, item_trait: &'ast syn::ItemTrait) {
+        // trait のメソッドは trait と一体として扱う
+        let vis = self.parser.format_visibility(&item_trait.vis);
+        let owner = self.add_node(DeadItemKind::Trait, &item_trait.ident, item_trait.span(), vis, &item_trait.attrs, false);
+        self.with_owner(owner, |this| visit::visit_item_trait(this, item_trait));
+    }
+
+    fn visit_item_impl(&mut self, item_impl: &'ast syn::ItemImpl) {
+        // trait 実装のメソッドは trait 経由（動的ディスパッチ含む）で呼ばれうるので根とする
+        let is_trait_impl = item_impl.trait_.is_some();
+        if let Some((_, trait_path, _)) = &item_impl.trait_
+            && let Some(segment) = trait_path.segments.last() {
+            self.add_reference(segment.ident.to_string());
+        }
+        let self_type = crate::parser::type_name(&item_impl.self_ty);
+        if is_test_item(&item_impl.attrs, self.file_path) {
+            self.test_depth += 1;
+        }
+
+        self.scope.push(self_type.clone());
+        for item in &item_impl.items {
+            match item {
+                syn::ImplItem::Fn(item_fn) => {
+                    let vis = self.parser.format_visibility(&item_fn.vis);
+                    self.visit_fn(&item_fn.sig, vis, &item_fn.attrs, item_fn.span(), &item_fn.block, Some((&self_type, is_trait_impl)));
+                }
+                other => self.visit_impl_item(other),
+            }
+        }
+        self.scope.pop();
+
+        if is_test_item(&item_impl.attrs, self.file_path) {
+            self.test_depth -= 1;
+        }
+    }
+
+    fn visit_item_mod(&mut self, item_mod: &'ast syn::ItemMod) {
+        let Some((_, items)) = &item_mod.content else {
+            return;
+        };
+        let is_test = is_test_item(&item_mod.attrs, self.file_path);
+        let name = item_mod.ident.to_string();
+        let inner_module = format!("{}::{}", self.module, name);
+        let outer_module = std::mem::replace(&mut self.module, inner_module);
+        self.analysis.modules.push(ModuleNode {
+            key: self.module.clone(),
+            name: name.clone(),
+            file_path: self.file_path.to_string(),
+            line: item_mod.ident.span().start().line,
+            end_line: item_mod.span().end().line,
+            scope: (!self.scope.is_empty()).then(|| self.scope.join("::")),
+            visibility: self.parser.format_visibility(&item_mod.vis),
+            is_test: is_test || self.test_depth > 0,
+        });
+
+        self.scope.push(name);
+        if is_test {
+            self.test_depth += 1;
+        }
+        for item in items {
+            self.visit_item(item);
+        }
+        if is_test {
+            self.test_depth -= 1;
+        }
+        self.scope.pop();
+        self.module = outer_module;
+    }
+
+    fn visit_item_use(&mut self, item_use: &'ast syn::ItemUse) {
+        // use は参照とみなさない（ライブラリの pub use は再エクスポートなので根とする）
+        if self.is_library_file && matches!(item_use.vis, syn::Visibility::Public(_)) {
+            let mut names = HashSet::new();
+            collect_token_idents(quote::quote!(#item_use), false, &mut names);
+            self.analysis.root_references.extend(names);
+        }
+    }
+
+    fn visit_item_macro(&mut self, item_macro: &'ast syn::ItemMacro) {
+        if item_macro.ident.is_some() {
+            // macro_rules! の本体は使われるか分からないので、確からしさを下げるだけにする
+            collect_token_idents(item_macro.mac.tokens.clone(), false, &mut self.analysis.macro_mentions);
+        } else if let Ok(inner) = syn::parse2::<syn::File>(item_macro.mac.tokens.clone()) {
+            for item in &inner.items {
+                self.visit_item(item);
+            }
+        } else {
+            self.visit_macro(&item_macro.mac);
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let mut names = HashSet::new();
+        collect_token_idents(mac.tokens.clone(), false, &mut names);
+        for name in names {
+            self.add_reference(name);
+        }
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast syn::Attribute) {
+        // #[serde(default = "default_port")] のように文字列で関数を指定する属性
+        if attr.path().is_ident("doc") {
+            return;
+        }
+        let mut names = HashSet::new();
+        collect_token_idents(quote::quote!(#attr), true, &mut names);
+        for name in names {
+            self.add_reference(name);
+        }
+    }
+
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        for segment in &path.segments {
+            self.add_reference(segment.ident.to_string());
+        }
+        visit::visit_path(self, path);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        self.add_reference(method_call.method.to_string());
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_lit_str(&mut self, lit: &'ast syn::LitStr) {
+        self.analysis.string_mentions.extend(string_words(&lit.value()));
+    }
+}
+
+/// テキスト形式のレポート（CLI 用）
+pub fn format_text_report(report: &FindDeadCodeResponse) -> String {
+    let summary = &report.summary;
+    let mut result = format!(
+        "Dead code: {} function(s), {} type(s), {} module(s) unreachable ({} roots, {} items analyzed)\n",
+        summary.dead_functions, summary.dead_types, summary.dead_modules, summary.roots, summary.analyzed_items,
+    );
+
+    let mut current_file: Option<&str> = None;
+    for item in &report.items {
+        if current_file != Some(item.file_path.as_str()) {
+            result.push_str(&format!("\n{}\n", item.file_path));
+            current_file = Some(&item.file_path);
+        }
+        let kind = match item.kind {
+            DeadItemKind::Function => "fn",
+            DeadItemKind::Struct => "struct",
+            DeadItemKind::Enum => "enum",
+            DeadItemKind::Trait => "trait",
+            DeadItemKind::TypeAlias => "type",
+            DeadItemKind::Module => "mod",
+        };
+        let name = match &item.scope {
+            Some(scope) => format!("{scope}::{}", item.name),
+            None => item.name.clone(),
+        };
+        result.push_str(&format!("  {:>5}  {:<6} {:<40} {:?}\n", item.line, kind, name, item.confidence));
+        for reason in item.reasons.iter().skip(1) {
+            result.push_str(&format!("         ↳ {reason}\n"));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_dead_code() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), r#"
+mod unused;
+
+fn main() {
+    let config = Config::load();
+    println!("{}", helper(config.port));
+}
+
+struct Config { port: u16 }
+
+impl Config {
+    fn load() -> Self { Config { port: default_port() } }
+    fn save(&self) {}
+}
+
+impl std::fmt::Display for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{}", format_port(self.port)) }
+}
+
+struct Unused;
+
+impl std::fmt::Display for Unused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "unused") }
+}
+
+fn default_port() -> u16 { 8080 }
+fn format_port(port: u16) -> String { port.to_string() }
+fn helper(x: u16) -> u16 { x }
+fn orphan() { orphan_callee(); }
+fn orphan_callee() {}
+fn named_in_string() {}
+
+macro_rules! call_it { () => { from_macro() } }
+fn from_macro() {}
+
+#[cfg(test)]
+mod tests {
+    fn test_helper() -> u16 { super::helper(1) }
+
+    #[test]
+    fn it_works() { assert_eq!(test_helper(), 1); }
+}
+
+const NAME: &str = "named_in_string";
+"#).unwrap();
+        fs::write(dir.path().join("src/unused.rs"), "pub struct Gone;\n\nfn gone() -> Gone { Gone }\n").unwrap();
+
+        let parser = RustParser::new();
+        let files = [dir.path().join("src/main.rs"), dir.path().join("src/unused.rs")];
+        let params = FindDeadCodeParams { file_path: None, min_confidence: None, include_tests: None };
+        let report = find_dead_code(&parser, files.iter().map(|p| p.as_path()), &params);
+
+        let found: Vec<(&str, DeadItemKind, Confidence)> = report.items.iter()
+            .map(|i| (i.name.as_str(), i.kind, i.confidence))
+            .collect();
+        assert_eq!(found, vec![
+            ("save", DeadItemKind::Function, Confidence::High),
+            ("Unused", DeadItemKind::Struct, Confidence::High),
+            ("orphan", DeadItemKind::Function, Confidence::High),
+            ("orphan_callee", DeadItemKind::Function, Confidence::High),
+            ("named_in_string", DeadItemKind::Function, Confidence::Low),
+            ("from_macro", DeadItemKind::Function, Confidence::Low),
+            ("unused", DeadItemKind::Module, Confidence::Medium),
+            ("Gone", DeadItemKind::Struct, Confidence::Medium),
+            ("gone", DeadItemKind::Function, Confidence::High),
+        ]);
+        assert_eq!(report.summary.dead_modules, 1);
+
+        let high_only = find_dead_code(&parser, files.iter().map(|p| p.as_path()), &FindDeadCodeParams {
+            min_confidence: Some(Confidence::High),
+            ..params
+        });
+        assert_eq!(high_only.items.len(), 5);
+    }
+}
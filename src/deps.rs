@@ -0,0 +1,236 @@
+use crate::parser::{RustParser, SymbolInfo};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// 依存クレートのソースでこれより大きいファイルはスキップ（生成コードなど）
+pub const MAX_DEPENDENCY_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Cargo.lock に記録されたパッケージ
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,  // None はワークスペース内（パス依存）
+}
+
+impl LockedPackage {
+    /// コード上で使われるクレート名（ハイフンはアンダースコアになる）
+    pub fn crate_name(&self) -> String {
+        self.name.replace('-', "_")
+    }
+
+    /// crates.io 等のレジストリ由来か
+    pub fn is_registry(&self) -> bool {
+        self.source.as_deref()
+            .is_some_and(|s| s.starts_with("registry+") || s.starts_with("sparse+"))
+    }
+}
+
+/// プロジェクトディレクトリから親方向に Cargo.lock を探す（ワークスペース対応）
+pub fn find_cargo_lock(project_path: &Path) -> Option<PathBuf> {
+    let start = project_path.canonicalize().ok()?;
+    start.ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())
+}
+
+/// Cargo.lock の [[package]] エントリを読み取る
+pub fn parse_cargo_lock(content: &str) -> Vec<LockedPackage> {
+    let mut packages = Vec::new();
+    let mut current: Option<(Option<String>, Option<String>, Option<String>)> = None;
+
+    let mut flush = |current: &mut Option<(Option<String>, Option<String>, Option<String>)>| {
+        if let Some((Some(name), Some(version), source)) = current.take() {
+            packages.push(LockedPackage { name, version, source });
+        }
+    };
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            flush(&mut current);
+            if line == "[[package]]" {
+                current = Some((None, None, None));
+            }
+            continue;
+        }
+
+        let Some((name, version, source)) = current.as_mut() else {
+            continue;
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim() {
+            "name" => *name = Some(value),
+            "version" => *version = Some(value),
+            "source" => *source = Some(value),
+            _ => {}
+        }
+    }
+    flush(&mut current);
+
+    packages
+}
+
+/// パッケージのソースディレクトリをローカルから探す（ネットワークアクセスなし）
+///
+/// `vendor/` ディレクトリを優先し、なければ `$CARGO_HOME/registry/src` を探す。
+pub fn locate_package_source(package: &LockedPackage, workspace_root: &Path) -> Option<PathBuf> {
+    if !package.is_registry() {
+        return None;
+    }
+
+    // cargo vendor は重複バージョンのみ "<name>-<version>" で配置する
+    let vendor_dir = workspace_root.join("vendor");
+    let vendor_candidates = [
+        vendor_dir.join(format!("{}-{}", package.name, package.version)),
+        vendor_dir.join(&package.name),
+    ];
+    for candidate in vendor_candidates {
+        if candidate.join("Cargo.toml").is_file() && manifest_version_matches(&candidate, &package.version) {
+            return Some(candidate);
+        }
+    }
+
+    let registry_src = cargo_home()?.join("registry").join("src");
+    let dir_name = format!("{}-{}", package.name, package.version);
+    std::fs::read_dir(registry_src).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(&dir_name))
+        .find(|path| path.join("Cargo.toml").is_file())
+}
+
+/// 依存クレートの公開 API を読み取った結果
+///
+/// インデックスのロックを持たずに作り、最後に `CodeIndexer::add_dependency_scan` でまとめて登録する。
+pub struct DependencyScan {
+    pub crates: Vec<(String, String)>,  // 読み取った依存クレート (名前, バージョン)
+    pub symbols: Vec<SymbolInfo>,
+}
+
+/// Cargo.lock の依存クレートのうち indexed にないもののソースをローカルから探し、公開 API を読み取る
+///
+/// `vendor/` と `$CARGO_HOME/registry/src` のみを参照し、ダウンロードは行わない。
+/// 見つからないクレートやルートを読めないクレートはスキップする。
+pub fn scan_dependencies(project_path: &Path, indexed: &[(String, String)]) -> Result<DependencyScan> {
+    let mut scan = DependencyScan { crates: Vec::new(), symbols: Vec::new() };
+    let Some(lock_path) = find_cargo_lock(project_path) else {
+        debug!("No Cargo.lock found for {}, skipping dependency indexing", project_path.display());
+        return Ok(scan);
+    };
+    let workspace_root = lock_path.parent().unwrap_or(project_path).to_path_buf();
+
+    let content = std::fs::read_to_string(&lock_path)
+        .with_context(|| format!("Failed to read {}", lock_path.display()))?;
+
+    let parser = RustParser::new();
+    for package in parse_cargo_lock(&content) {
+        if indexed.iter().chain(&scan.crates).any(|(name, version)| *name == package.name && *version == package.version) {
+            continue;
+        }
+        let Some(source_dir) = locate_package_source(&package, &workspace_root) else {
+            if package.is_registry() {
+                debug!("Sources not available locally: {} {}", package.name, package.version);
+            }
+            continue;
+        };
+
+        debug!("Indexing dependency {} {} from {}", package.name, package.version, source_dir.display());
+        match public_symbols(&parser, &source_dir, &package.crate_name()) {
+            Ok(symbols) => scan.symbols.extend(symbols),
+            Err(e) => debug!("Failed to read public API of {} {}: {}", package.name, package.version, e),
+        }
+        scan.crates.push((package.name, package.version));
+    }
+
+    Ok(scan)
+}
+
+/// クレートのルートから pub mod / pub use で辿れるアイテムだけをシンボルにする
+fn public_symbols(parser: &RustParser, source_dir: &Path, crate_name: &str) -> Result<Vec<SymbolInfo>> {
+    let mut symbols = Vec::new();
+    for (relative, lines) in crate::api_surface::exported_lines(source_dir)? {
+        let path = source_dir.join(relative);
+        let too_large = std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_DEPENDENCY_FILE_SIZE);
+        if too_large {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(file_symbols) = parser.external_symbols(&path.to_string_lossy(), &content, crate_name, &lines) {
+            symbols.extend(file_symbols);
+        }
+    }
+    Ok(symbols)
+}
+
+/// CARGO_HOME（未設定時は ~/.cargo）
+fn cargo_home() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("CARGO_HOME") {
+        return Some(PathBuf::from(home));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo"))
+}
+
+/// vendor 配下の Cargo.toml のバージョンが一致するか
+fn manifest_version_matches(package_dir: &Path, version: &str) -> bool {
+    let Ok(manifest) = std::fs::read_to_string(package_dir.join("Cargo.toml")) else {
+        return false;
+    };
+    manifest.lines()
+        .filter_map(|line| line.trim().strip_prefix("version"))
+        .filter_map(|rest| rest.trim().strip_prefix('='))
+        .any(|value| value.trim().trim_matches('"') == version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const LOCK: &str = r#"
+version = 4
+
+[[package]]
+name = "my_app"
+version = "0.1.0"
+dependencies = [
+ "tiny-dep",
+]
+
+[[package]]
+name = "tiny-dep"
+version = "1.2.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "abc"
+"#;
+
+    #[test]
+    fn test_parse_cargo_lock() {
+        let packages = parse_cargo_lock(LOCK);
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "my_app");
+        assert!(!packages[0].is_registry());
+        assert_eq!(packages[1].crate_name(), "tiny_dep");
+        assert_eq!(packages[1].version, "1.2.3");
+        assert!(packages[1].is_registry());
+    }
+
+    #[test]
+    fn test_locate_vendored_package() {
+        let dir = tempdir().unwrap();
+        let package_dir = dir.path().join("vendor").join("tiny-dep");
+        fs::create_dir_all(package_dir.join("src")).unwrap();
+        fs::write(package_dir.join("Cargo.toml"), "[package]\nname = \"tiny-dep\"\nversion = \"1.2.3\"\n").unwrap();
+
+        let packages = parse_cargo_lock(LOCK);
+        assert_eq!(locate_package_source(&packages[1], dir.path()), Some(package_dir));
+        assert_eq!(locate_package_source(&packages[0], dir.path()), None);
+    }
+}
@@ -0,0 +1,679 @@
+use crate::parser::{ErrorSignatureInfo, ErrorSiteInfo, RustParser};
+use crate::protocol::{
+    ErrorFunction, ErrorReturnKind, ErrorSite, ErrorSiteKind, FindErrorOriginsParams, FindErrorOriginsResponse,
+    FindSwallowedErrorsParams, FindSwallowedErrorsResponse, GetErrorMapParams, GetErrorMapResponse, SymbolType,
+};
+use proc_macro2::Span;
+use std::collections::HashMap;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// 結果が Result であることが名前だけで分かるクレート外のメソッド・関数
+const FALLIBLE_FUNCTIONS: &[&str] = &[
+    "parse", "from_str", "from_value", "from_slice", "from_reader", "try_from", "try_into",
+    "send", "write_all", "flush", "sync_all",
+];
+
+/// 戻り値の型から分かる失敗の種類
+pub struct ReturnType {
+    pub kind: ErrorReturnKind,
+    pub ok_type: String,
+    pub error_type: Option<String>,
+}
+
+/// 関数本体で見つかったエラーの箇所（エラー型の解決は build 系の関数で行う）
+pub struct BodySite {
+    pub kind: ErrorSiteKind,
+    pub start: Span,
+    pub end: Span,
+    pub callee: Option<String>,
+    pub explicit_error: Option<String>,
+}
+
+/// 型をスペースなしの文字列にする（`std :: io :: Error` → "std::io::Error"）
+fn type_string(ty: &syn::Type) -> String {
+    let mut result = String::new();
+    let tokens = quote::quote!(#ty).to_string();
+    for word in tokens.split(' ') {
+        let needs_space = result.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ',')
+            && word.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '\'');
+        if needs_space {
+            result.push(' ');
+        }
+        result.push_str(word);
+    }
+    result
+}
+
+/// use の別名を先頭のセグメントに展開したパス（`io::Error` → "std::io::Error"）
+fn resolve_segments(segments: &[String], imports: &HashMap<String, String>) -> String {
+    match segments.split_first() {
+        Some((first, rest)) => match imports.get(first) {
+            Some(full) => [full.clone()].into_iter().chain(rest.iter().cloned()).collect::<Vec<_>>().join("::"),
+            None => segments.join("::"),
+        },
+        None => String::new(),
+    }
+}
+
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments.iter().map(|s| s.ident.to_string()).collect()
+}
+
+/// エラー型を正規化（単純なパスなら use の別名を展開する）
+fn resolve_type(ty: &syn::Type, imports: &HashMap<String, String>) -> String {
+    match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none()
+            && type_path.path.segments.iter().all(|s| s.arguments.is_none()) => {
+            resolve_segments(&path_segments(&type_path.path), imports)
+        }
+        other => type_string(other),
+    }
+}
+
+fn generic_types(segment: &syn::PathSegment) -> Vec<&syn::Type> {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// ファイル内の `type Result<T> = std::result::Result<T, E>;` から別名 -> エラー型を集める
+pub fn collect_result_aliases(items: &[syn::Item], imports: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut aliases = HashMap::new();
+    for item in items {
+        if let syn::Item::Type(item_type) = item
+            && let Some(ret) = return_type_of(&item_type.ty, imports, &HashMap::new())
+            && let Some(error_type) = ret.error_type {
+            aliases.insert(item_type.ident.to_string(), error_type);
+        }
+    }
+    aliases
+}
+
+/// 関数の戻り値が Result・Option ならその型を返す
+pub fn return_type(output: &syn::ReturnType, imports: &HashMap<String, String>, aliases: &HashMap<String, String>) -> Option<ReturnType> {
+    match output {
+        syn::ReturnType::Type(_, ty) => return_type_of(ty, imports, aliases),
+        syn::ReturnType::Default => None,
+    }
+}
+
+fn return_type_of(ty: &syn::Type, imports: &HashMap<String, String>, aliases: &HashMap<String, String>) -> Option<ReturnType> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segments = path_segments(&type_path.path);
+    let last = type_path.path.segments.last()?;
+    let args = generic_types(last);
+    let ok_type = args.first().map(|ty| type_string(ty)).unwrap_or_else(|| "()".to_string());
+
+    // 別名の展開後のパスで判定する（`use anyhow::Result;` → "anyhow::Result"）
+    let resolved = resolve_segments(&segments, imports);
+    let resolved_last = resolved.rsplit("::").next().unwrap_or_default();
+
+    if resolved_last == "Option" && args.len() == 1 {
+        return Some(ReturnType { kind: ErrorReturnKind::Option, ok_type, error_type: None });
+    }
+    if resolved_last != "Result" && !(segments.len() == 1 && aliases.contains_key(&segments[0])) {
+        return None;
+    }
+
+    let error_type = match args.get(1) {
+        Some(error) => Some(resolve_type(error, imports)),
+        // 同じファイルの `type Result<T> = ...` を優先する
+        None if segments.len() == 1 && aliases.contains_key(&segments[0]) => aliases.get(&segments[0]).cloned(),
+        // `anyhow::Result<T>` / `io::Result<T>` → そのモジュールの Error
+        None => resolved.rsplit_once("::")
+            .filter(|(module, _)| *module != "std::result" && *module != "core::result")
+            .map(|(module, _)| format!("{module}::Error")),
+    };
+
+    Some(ReturnType { kind: ErrorReturnKind::Result, ok_type, error_type })
+}
+
+/// 関数本体から ? ・Err(...) の生成・結果の握りつぶしを探す
+///
+/// クロージャ・async ブロックの中の ? と Err(...) は囲んでいる関数のエラーにならないので記録しない。
+pub fn scan_body(block: &syn::Block, imports: &HashMap<String, String>) -> Vec<BodySite> {
+    let mut visitor = ErrorVisitor { imports, closure_depth: 0, sites: Vec::new() };
+    visitor.visit_block(block);
+    visitor.sites
+}
+
+struct ErrorVisitor<'a> {
+    imports: &'a HashMap<String, String>,
+    closure_depth: usize,  // クロージャ・async ブロックの中
+    sites: Vec<BodySite>,
+}
+
+impl ErrorVisitor<'_> {
+    fn add(&mut self, kind: ErrorSiteKind, span: Span, source: Source) {
+        self.sites.push(BodySite { kind, start: span, end: span, callee: source.callee, explicit_error: source.explicit_error });
+    }
+
+    fn with_closure(&mut self, f: impl FnOnce(&mut Self)) {
+        self.closure_depth += 1;
+        f(self);
+        self.closure_depth -= 1;
+    }
+
+    /// map_err・context などのアダプタをたどって、結果を作った呼び出しと変換後のエラー型を求める
+    fn analyze_source(&self, mut expr: &syn::Expr) -> Source {
+        let mut explicit_error = None;
+        loop {
+            match expr {
+                syn::Expr::Await(await_expr) => expr = &await_expr.base,
+                syn::Expr::Paren(paren) => expr = &paren.expr,
+                syn::Expr::MethodCall(method_call) => {
+                    let method = method_call.method.to_string();
+                    let converted = match method.as_str() {
+                        "map_err" | "ok_or" | "ok_or_else" => Some(method_call.args.first().and_then(|arg| self.error_type_of(arg))),
+                        "context" | "with_context" => Some(Some("anyhow::Error".to_string())),
+                        _ => None,
+                    };
+                    match converted {
+                        Some(error_type) => {
+                            // 最も外側のアダプタが ? に渡るエラー型を決める
+                            if explicit_error.is_none() {
+                                explicit_error = Some(error_type);
+                            }
+                            expr = &method_call.receiver;
+                        }
+                        None => return Source { callee: Some(method), explicit_error: explicit_error.flatten() },
+                    }
+                }
+                syn::Expr::Call(call) => {
+                    let callee = match &*call.func {
+                        syn::Expr::Path(path) => Some(resolve_segments(&path_segments(&path.path), self.imports)),
+                        _ => None,
+                    };
+                    return Source { callee, explicit_error: explicit_error.flatten() };
+                }
+                _ => return Source { callee: None, explicit_error: explicit_error.flatten() },
+            }
+        }
+    }
+
+    /// エラー値の式から型を求める（`AppError::Io` / `AppError::new(..)` / `anyhow!(..)` / クロージャの本体）
+    fn error_type_of(&self, expr: &syn::Expr) -> Option<String> {
+        match expr {
+            syn::Expr::Path(path) if path.path.segments.len() > 1 => {
+                let segments = path_segments(&path.path);
+                Some(resolve_segments(&segments[..segments.len() - 1], self.imports))
+            }
+            syn::Expr::Call(call) => self.error_type_of(&call.func),
+            syn::Expr::Struct(expr_struct) => Some(resolve_segments(&path_segments(&expr_struct.path), self.imports)),
+            syn::Expr::Closure(closure) => self.error_type_of(&closure.body),
+            syn::Expr::Block(block) => match block.block.stmts.last() {
+                Some(syn::Stmt::Expr(expr, None)) => self.error_type_of(expr),
+                _ => None,
+            },
+            syn::Expr::Macro(mac) => mac.mac.path.segments.last()
+                .filter(|s| s.ident == "anyhow" || s.ident == "format_err")
+                .map(|_| "anyhow::Error".to_string()),
+            syn::Expr::Paren(paren) => self.error_type_of(&paren.expr),
+            _ => None,
+        }
+    }
+}
+
+/// 結果を作った呼び出しと、アダプタで変換された後のエラー型
+struct Source {
+    callee: Option<String>,
+    explicit_error: Option<String>,
+}
+
+impl<'ast> Visit<'ast> for ErrorVisitor<'_> {
+    // ネストした関数は別の関数として収集される
+    fn visit_item(&mut self, _item: &'ast syn::Item) {}
+
+    fn visit_expr_try(&mut self, try_expr: &'ast syn::ExprTry) {
+        if self.closure_depth == 0 {
+            let source = self.analyze_source(&try_expr.expr);
+            self.add(ErrorSiteKind::Try, try_expr.span(), source);
+        }
+        // `foo().ok()?` は None を伝播しているので握りつぶしではない
+        match &*try_expr.expr {
+            syn::Expr::MethodCall(method_call) if method_call.method == "ok" && method_call.args.is_empty() => {
+                self.visit_expr(&method_call.receiver);
+            }
+            inner => self.visit_expr(inner),
+        }
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        let is_err = matches!(&*call.func, syn::Expr::Path(p) if p.path.is_ident("Err"));
+        // `Err(e)` のように受け取ったエラーをそのまま返すのは生成ではない
+        let passes_through = call.args.first().is_some_and(|arg| matches!(arg, syn::Expr::Path(p) if p.path.get_ident().is_some()));
+        if is_err && self.closure_depth == 0 && call.args.len() == 1 && !passes_through {
+            let explicit_error = call.args.first().and_then(|arg| self.error_type_of(arg));
+            self.add(ErrorSiteKind::ErrConstruct, call.span(), Source { callee: None, explicit_error });
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        let kind = match method_call.method.to_string().as_str() {
+            "ok" if method_call.args.is_empty() => Some(ErrorSiteKind::Ok),
+            "unwrap_or_default" => Some(ErrorSiteKind::UnwrapOrDefault),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            let source = self.analyze_source(&method_call.receiver);
+            self.add(kind, method_call.span(), Source { explicit_error: None, ..source });
+        }
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        if matches!(local.pat, syn::Pat::Wild(_))
+            && let Some(init) = &local.init {
+            let source = self.analyze_source(&init.expr);
+            if source.callee.is_some() {
+                self.add(ErrorSiteKind::LetUnderscore, local.span(), Source { explicit_error: None, ..source });
+            }
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        self.with_closure(|this| visit::visit_expr_closure(this, closure));
+    }
+
+    fn visit_expr_async(&mut self, async_expr: &'ast syn::ExprAsync) {
+        self.with_closure(|this| visit::visit_expr_async(this, async_expr));
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // anyhow の bail! / ensure! は anyhow::Error を生成して返す
+        let name = mac.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+        if (name == "bail" || name == "ensure") && self.closure_depth == 0 {
+            self.add(ErrorSiteKind::ErrConstruct, mac.span(), Source { callee: None, explicit_error: Some("anyhow::Error".to_string()) });
+        }
+    }
+}
+
+/// エラー型がクエリに一致するか（"::" を含むクエリは末尾一致、含まなければ最後のセグメントで比較）
+fn type_matches(actual: &str, query: &str) -> bool {
+    if query.contains("::") {
+        actual == query || actual.ends_with(&format!("::{query}"))
+    } else {
+        actual.rsplit("::").next() == Some(query)
+    }
+}
+
+/// クレート外のエラー型が名前から分かる呼び出し
+fn external_error_type(callee: &str) -> Option<String> {
+    (callee.starts_with("std::fs::") || callee.starts_with("std::io::")).then(|| "std::io::Error".to_string())
+}
+
+/// インデックス済みのシグネチャ・箇所を引くための表
+struct ErrorIndex<'a> {
+    parser: &'a RustParser,
+    signatures: HashMap<(String, String, usize), &'a ErrorSignatureInfo>,
+    by_name: HashMap<&'a str, Vec<&'a ErrorSignatureInfo>>,
+    included: HashMap<(String, String, usize), Option<String>>,  // 対象の関数 -> スコープ
+}
+
+impl<'a> ErrorIndex<'a> {
+    fn new(parser: &'a RustParser, include_tests: bool) -> Self {
+        let included: HashMap<_, _> = parser.get_all_symbols().values()
+            .flatten()
+            .filter(|s| s.symbol_type == SymbolType::Function && s.crate_name.is_none())
+            .filter(|s| include_tests || !s.is_test)
+            .map(|s| ((s.name.clone(), s.file_path.clone(), s.line), s.scope.clone()))
+            .collect();
+
+        let mut signatures = HashMap::new();
+        let mut by_name: HashMap<&str, Vec<&ErrorSignatureInfo>> = HashMap::new();
+        for signature in parser.get_error_signatures() {
+            signatures.insert((signature.name.clone(), signature.file_path.clone(), signature.line), signature);
+            by_name.entry(signature.name.as_str()).or_default().push(signature);
+        }
+
+        Self { parser, signatures, by_name, included }
+    }
+
+    fn includes(&self, site: &ErrorSiteInfo) -> bool {
+        self.included.contains_key(&(site.function.clone(), site.file_path.clone(), site.function_line))
+    }
+
+    /// プロジェクト内の関数を名前で引く（`Self::foo` / `crate::a::foo` も最後のセグメントで引く）
+    fn project_callee(&self, callee: &str) -> Option<&Vec<&'a ErrorSignatureInfo>> {
+        let segments: Vec<&str> = callee.split("::").collect();
+        let local = segments.len() == 1 || matches!(segments[0], "Self" | "self" | "crate" | "super");
+        if local { self.by_name.get(segments[segments.len() - 1]) } else { None }
+    }
+
+    /// 呼び出し先の結果が Result だと分かるか
+    fn is_fallible_callee(&self, callee: &str) -> bool {
+        let name = callee.rsplit("::").next().unwrap_or(callee);
+        match self.project_callee(callee) {
+            Some(signatures) => signatures.iter().any(|s| s.return_kind == ErrorReturnKind::Result),
+            None => external_error_type(callee).is_some() || FALLIBLE_FUNCTIONS.contains(&name),
+        }
+    }
+
+    /// 呼び出し先のエラー型（同名の関数で一致する場合のみ）
+    fn callee_error_type(&self, callee: &str) -> Option<String> {
+        match self.project_callee(callee) {
+            Some(signatures) => {
+                let first = signatures.first()?.error_type.clone()?;
+                signatures.iter().all(|s| s.error_type.as_deref() == Some(first.as_str())).then_some(first)
+            }
+            None => external_error_type(callee),
+        }
+    }
+
+    fn to_site(&self, site: &ErrorSiteInfo) -> ErrorSite {
+        let to_type = self.signatures.get(&(site.function.clone(), site.file_path.clone(), site.function_line))
+            .and_then(|signature| signature.error_type.clone());
+        let from_type = match site.kind {
+            ErrorSiteKind::ErrConstruct => site.explicit_error.clone().or_else(|| to_type.clone()),
+            _ => site.explicit_error.clone().or_else(|| site.callee.as_deref().and_then(|c| self.callee_error_type(c))),
+        };
+        ErrorSite {
+            kind: site.kind,
+            function: site.function.clone(),
+            file_path: site.file_path.clone(),
+            line: site.line,
+            column: site.column,
+            end_line: site.end_line,
+            end_column: site.end_column,
+            context: site.context.clone(),
+            callee: site.callee.clone(),
+            from_type,
+            to_type,
+        }
+    }
+
+    fn to_function(&self, signature: &ErrorSignatureInfo, propagates: Vec<ErrorSite>) -> ErrorFunction {
+        let key = (signature.name.clone(), signature.file_path.clone(), signature.line);
+        ErrorFunction {
+            name: signature.name.clone(),
+            file_path: signature.file_path.clone(),
+            line: signature.line,
+            scope: self.included.get(&key).cloned().flatten(),
+            return_kind: signature.return_kind,
+            ok_type: signature.ok_type.clone(),
+            error_type: signature.error_type.clone(),
+            propagates,
+        }
+    }
+
+    fn included_signatures(&self) -> impl Iterator<Item = &'a ErrorSignatureInfo> + '_ {
+        self.parser.get_error_signatures().iter()
+            .filter(|s| self.included.contains_key(&(s.name.clone(), s.file_path.clone(), s.line)))
+    }
+}
+
+fn sort_sites(sites: &mut [ErrorSite]) {
+    sites.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)).then(a.column.cmp(&b.column)));
+}
+
+fn sort_functions(functions: &mut [ErrorFunction]) {
+    functions.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+}
+
+/// Result・Option を返す関数と、それぞれの ? による伝播の一覧
+pub fn get_error_map(parser: &RustParser, params: &GetErrorMapParams) -> GetErrorMapResponse {
+    let index = ErrorIndex::new(parser, params.include_tests.unwrap_or(false));
+
+    let mut propagates: HashMap<(String, String, usize), Vec<ErrorSite>> = HashMap::new();
+    for site in parser.get_error_sites() {
+        if site.kind == ErrorSiteKind::Try {
+            let key = (site.function.clone(), site.file_path.clone(), site.function_line);
+            propagates.entry(key).or_default().push(index.to_site(site));
+        }
+    }
+
+    let mut functions: Vec<ErrorFunction> = index.included_signatures()
+        .filter(|s| params.file_path.as_deref().is_none_or(|path| s.file_path == path))
+        .filter(|s| params.function.as_deref().is_none_or(|name| s.name == name))
+        .map(|s| {
+            let mut sites = propagates.remove(&(s.name.clone(), s.file_path.clone(), s.line)).unwrap_or_default();
+            sort_sites(&mut sites);
+            index.to_function(s, sites)
+        })
+        .collect();
+    sort_functions(&mut functions);
+
+    GetErrorMapResponse { functions }
+}
+
+/// エラー型が生まれる箇所（Err(...) の生成・From による変換・クレート外の呼び出し）
+pub fn find_error_origins(parser: &RustParser, params: &FindErrorOriginsParams) -> FindErrorOriginsResponse {
+    let index = ErrorIndex::new(parser, params.include_tests.unwrap_or(false));
+    let query = params.error_type.as_str();
+    let matches = |ty: &Option<String>| ty.as_deref().is_some_and(|ty| type_matches(ty, query));
+
+    let mut origins: Vec<ErrorSite> = parser.get_error_sites().iter()
+        .filter(|site| index.includes(site))
+        .filter(|site| matches!(site.kind, ErrorSiteKind::Try | ErrorSiteKind::ErrConstruct))
+        .map(|site| (site, index.to_site(site)))
+        .filter(|(site, resolved)| match resolved.kind {
+            ErrorSiteKind::ErrConstruct => matches(&resolved.from_type),
+            _ => {
+                // 別のエラー型から ? で変換される
+                let converted = matches(&resolved.to_type) && resolved.from_type.is_some() && !matches(&resolved.from_type);
+                // クレート外の呼び出しから ? で入ってくる
+                let external = matches(&resolved.from_type)
+                    && site.explicit_error.is_none()
+                    && site.callee.as_deref().is_some_and(|c| index.project_callee(c).is_none());
+                converted || external
+            }
+        })
+        .map(|(_, resolved)| resolved)
+        .collect();
+    sort_sites(&mut origins);
+
+    let mut returning_functions: Vec<ErrorFunction> = index.included_signatures()
+        .filter(|s| matches(&s.error_type))
+        .map(|s| index.to_function(s, Vec::new()))
+        .collect();
+    sort_functions(&mut returning_functions);
+
+    FindErrorOriginsResponse { error_type: params.error_type.clone(), origins, returning_functions }
+}
+
+/// 呼び出し元でエラーを捨てている箇所（let _ = ・.ok()・.unwrap_or_default()）
+pub fn find_swallowed_errors(parser: &RustParser, params: &FindSwallowedErrorsParams) -> FindSwallowedErrorsResponse {
+    let index = ErrorIndex::new(parser, params.include_tests.unwrap_or(false));
+
+    let mut sites: Vec<ErrorSite> = parser.get_error_sites().iter()
+        .filter(|site| index.includes(site))
+        .filter(|site| params.file_path.as_deref().is_none_or(|path| site.file_path == path))
+        .filter(|site| match site.kind {
+            ErrorSiteKind::Ok => true,
+            // Option の unwrap_or_default や Result 以外の let _ = は除く
+            ErrorSiteKind::LetUnderscore | ErrorSiteKind::UnwrapOrDefault => {
+                site.callee.as_deref().is_some_and(|c| index.is_fallible_callee(c))
+            }
+            ErrorSiteKind::Try | ErrorSiteKind::ErrConstruct => false,
+        })
+        .filter(|site| params.function.as_deref().is_none_or(|name| {
+            site.callee.as_deref().is_some_and(|c| c.rsplit("::").next() == Some(name))
+        }))
+        .map(|site| index.to_site(site))
+        .collect();
+    sort_sites(&mut sites);
+
+    FindSwallowedErrorsResponse { sites }
+}
+
+fn describe_site(site: &ErrorSite) -> String {
+    let callee = site.callee.as_deref().unwrap_or("an expression");
+    let from = site.from_type.as_deref().unwrap_or("unknown");
+    match site.kind {
+        ErrorSiteKind::Try => match (&site.from_type, &site.to_type) {
+            (Some(from), Some(to)) if from != to => format!("? converts {from} → {to} from {callee}"),
+            _ => format!("? propagates {from} from {callee}"),
+        },
+        ErrorSiteKind::ErrConstruct => format!("constructs {from}"),
+        ErrorSiteKind::LetUnderscore => format!("let _ = discards result of {callee}"),
+        ErrorSiteKind::Ok => format!(".ok() discards error of {callee}"),
+        ErrorSiteKind::UnwrapOrDefault => format!(".unwrap_or_default() discards error of {callee}"),
+    }
+}
+
+fn format_sites(result: &mut String, sites: &[ErrorSite], indent: &str) {
+    for site in sites {
+        result.push_str(&format!(
+            "{indent}{}:{}:{} {}\n{indent}      {}\n",
+            site.file_path, site.line, site.column, describe_site(site), site.context,
+        ));
+    }
+}
+
+fn function_label(function: &ErrorFunction) -> String {
+    let name = match &function.scope {
+        Some(scope) => format!("{scope}::{}", function.name),
+        None => function.name.clone(),
+    };
+    match function.return_kind {
+        ErrorReturnKind::Result => format!("{name} -> Result<{}, {}>", function.ok_type, function.error_type.as_deref().unwrap_or("?")),
+        ErrorReturnKind::Option => format!("{name} -> Option<{}>", function.ok_type),
+    }
+}
+
+/// get_error_map の結果をテキストに整形（CLI 用）
+pub fn format_error_map(report: &GetErrorMapResponse) -> String {
+    let propagations: usize = report.functions.iter().map(|f| f.propagates.len()).sum();
+    let mut result = format!("Error map: {} fallible function(s), {} ? site(s)\n", report.functions.len(), propagations);
+    for function in &report.functions {
+        result.push_str(&format!("\n{}:{} {}\n", function.file_path, function.line, function_label(function)));
+        format_sites(&mut result, &function.propagates, "  ");
+    }
+    result
+}
+
+/// find_error_origins の結果をテキストに整形（CLI 用）
+pub fn format_error_origins(report: &FindErrorOriginsResponse) -> String {
+    let mut result = format!(
+        "Error origins of {}: {} site(s), returned by {} function(s)\n",
+        report.error_type, report.origins.len(), report.returning_functions.len(),
+    );
+    if !report.origins.is_empty() {
+        result.push_str("\nOrigins:\n");
+        format_sites(&mut result, &report.origins, "  ");
+    }
+    if !report.returning_functions.is_empty() {
+        result.push_str("\nReturned by:\n");
+        for function in &report.returning_functions {
+            result.push_str(&format!("  {}:{} {}\n", function.file_path, function.line, function_label(function)));
+        }
+    }
+    result
+}
+
+/// find_swallowed_errors の結果をテキストに整形（CLI 用）
+pub fn format_swallowed_errors(report: &FindSwallowedErrorsResponse) -> String {
+    let mut result = format!("Swallowed errors: {} site(s)\n", report.sites.len());
+    let mut current_function: Option<(&str, &str)> = None;
+    for site in &report.sites {
+        if current_function != Some((site.file_path.as_str(), site.function.as_str())) {
+            result.push_str(&format!("\n{} in {}\n", site.file_path, site.function));
+            current_function = Some((&site.file_path, &site.function));
+        }
+        format_sites(&mut result, std::slice::from_ref(site), "  ");
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_flow() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/config.rs", r#"
+use std::fs;
+use std::io;
+
+pub enum ConfigError {
+    Io(io::Error),
+    Missing(String),
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self { ConfigError::Io(e) }
+}
+
+type Result<T> = std::result::Result<T, ConfigError>;
+
+fn read_raw(path: &str) -> io::Result<String> {
+    let text = fs::read_to_string(path)?;
+    Ok(text)
+}
+
+fn load(path: &str) -> Result<String> {
+    let text = read_raw(path)?;
+    if text.is_empty() {
+        return Err(ConfigError::Missing(path.to_string()));
+    }
+    Ok(text)
+}
+
+fn port(text: &str) -> Option<u16> {
+    let line = text.lines().next()?;
+    line.parse().ok()
+}
+
+fn run() -> anyhow::Result<()> {
+    let text = load("a").map_err(|_| anyhow::anyhow!("load failed"))?;
+    let _ = load("b");
+    let _ = port(&text);
+    let size: usize = text.parse().unwrap_or_default();
+    if size == 0 {
+        anyhow::bail!("empty");
+    }
+    Ok(())
+}
+"#).unwrap();
+
+        let map = get_error_map(&parser, &GetErrorMapParams { file_path: None, function: None, include_tests: None });
+        let functions: Vec<(&str, ErrorReturnKind, Option<&str>)> = map.functions.iter()
+            .map(|f| (f.name.as_str(), f.return_kind, f.error_type.as_deref()))
+            .collect();
+        assert_eq!(functions, vec![
+            ("read_raw", ErrorReturnKind::Result, Some("std::io::Error")),
+            ("load", ErrorReturnKind::Result, Some("ConfigError")),
+            ("port", ErrorReturnKind::Option, None),
+            ("run", ErrorReturnKind::Result, Some("anyhow::Error")),
+        ]);
+        let load_try = &map.functions[1].propagates[0];
+        assert_eq!((load_try.callee.as_deref(), load_try.from_type.as_deref(), load_try.to_type.as_deref()),
+            (Some("read_raw"), Some("std::io::Error"), Some("ConfigError")));
+        let run_try = &map.functions[3].propagates[0];
+        assert_eq!((run_try.callee.as_deref(), run_try.from_type.as_deref()), (Some("load"), Some("anyhow::Error")));
+
+        let origins = find_error_origins(&parser, &FindErrorOriginsParams { error_type: "io::Error".to_string(), include_tests: None });
+        let lines: Vec<(ErrorSiteKind, usize)> = origins.origins.iter().map(|s| (s.kind, s.line)).collect();
+        assert_eq!(lines, vec![(ErrorSiteKind::Try, 17)]);
+        assert_eq!(origins.returning_functions.len(), 1);
+
+        let config = find_error_origins(&parser, &FindErrorOriginsParams { error_type: "ConfigError".to_string(), include_tests: None });
+        let lines: Vec<(ErrorSiteKind, usize)> = config.origins.iter().map(|s| (s.kind, s.line)).collect();
+        assert_eq!(lines, vec![(ErrorSiteKind::Try, 22), (ErrorSiteKind::ErrConstruct, 24)]);
+
+        let swallowed = find_swallowed_errors(&parser, &FindSwallowedErrorsParams { function: None, file_path: None, include_tests: None });
+        let sites: Vec<(ErrorSiteKind, usize, Option<&str>)> = swallowed.sites.iter()
+            .map(|s| (s.kind, s.line, s.callee.as_deref()))
+            .collect();
+        assert_eq!(sites, vec![
+            (ErrorSiteKind::Ok, 31, Some("parse")),
+            (ErrorSiteKind::LetUnderscore, 36, Some("load")),
+            (ErrorSiteKind::UnwrapOrDefault, 38, Some("parse")),
+        ]);
+
+        let load_only = find_swallowed_errors(&parser, &FindSwallowedErrorsParams { function: Some("load".to_string()), file_path: None, include_tests: None });
+        assert_eq!(load_only.sites.len(), 1);
+    }
+}
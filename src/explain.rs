@@ -0,0 +1,375 @@
+use crate::parser::{RustParser, SymbolInfo, doc_comment};
+use crate::protocol::{
+    ExplainSymbolParams, ExplainSymbolResponse, ImplementedTrait, RelatedNames, SymbolExplanation, SymbolType,
+};
+use proc_macro2::LineColumn;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use syn::visit::{self, Visit};
+
+/// 呼び出し元・呼び出し先・テストの名前を返す件数のデフォルト
+pub const DEFAULT_EXPLAIN_LIMIT: usize = 5;
+
+/// 構文木から分かるシンボルの周辺情報
+#[derive(Default)]
+struct SymbolContext {
+    docs: Option<String>,
+    modules: Vec<String>,  // ファイル内のインラインモジュール
+    owner: Option<String>,
+}
+
+/// シンボルごとに定義・ドキュメント・関連シンボルをまとめる（ファイルは読み直す）
+pub fn explain_symbols(parser: &RustParser, symbols: &[&SymbolInfo], params: &ExplainSymbolParams) -> ExplainSymbolResponse {
+    let limit = params.limit.unwrap_or(DEFAULT_EXPLAIN_LIMIT);
+    let mut files: HashMap<&str, Option<syn::File>> = HashMap::new();
+
+    // 呼び出し先はインデックス済みの関数のみ数える
+    let function_names: HashSet<&str> = parser.get_all_symbols().values()
+        .flatten()
+        .filter(|s| s.symbol_type == SymbolType::Function && s.crate_name.is_none())
+        .map(|s| s.name.as_str())
+        .collect();
+
+    let symbols = symbols.iter()
+        .map(|symbol| {
+            let syntax_tree = files.entry(symbol.file_path.as_str())
+                .or_insert_with(|| {
+                    let content = std::fs::read_to_string(&symbol.file_path).ok()?;
+                    syn::parse_file(&content).ok()
+                })
+                .as_ref();
+            let context = syntax_tree
+                .and_then(|tree| find_context(tree, symbol.line, symbol.column))
+                .unwrap_or_default();
+            explain(parser, symbol, context, &function_names, limit)
+        })
+        .collect();
+
+    ExplainSymbolResponse { symbols }
+}
+
+fn explain(parser: &RustParser, symbol: &SymbolInfo, context: SymbolContext, function_names: &HashSet<&str>, limit: usize) -> SymbolExplanation {
+    let mut module_path = file_module_path(&symbol.file_path, symbol.crate_name.as_deref());
+    for module in &context.modules {
+        module_path.push_str(&format!("::{module}"));
+    }
+
+    let mut implemented_traits = Vec::new();
+    let mut implementors = RelatedNames::default();
+    let mut callers = RelatedNames::default();
+    let mut callees = RelatedNames::default();
+    let mut tests = RelatedNames::default();
+
+    match symbol.symbol_type {
+        SymbolType::Struct | SymbolType::Enum => {
+            // 同名の型が複数あれば同じファイルの実装を優先する（impl_matrix と同じ）
+            let impls: Vec<_> = parser.get_impls().iter().filter(|info| info.type_name == symbol.name).collect();
+            let same_file = impls.iter().any(|info| info.file_path == symbol.file_path);
+            let mut traits: HashMap<&str, crate::protocol::ImplSource> = HashMap::new();
+            for info in impls.into_iter().filter(|info| !same_file || info.file_path == symbol.file_path) {
+                traits.entry(info.trait_name.as_str())
+                    .and_modify(|source| *source = (*source).min(info.source))
+                    .or_insert(info.source);
+            }
+            implemented_traits = traits.into_iter()
+                .map(|(trait_name, source)| ImplementedTrait { trait_name: trait_name.to_string(), source })
+                .collect();
+            implemented_traits.sort_by(|a, b| a.trait_name.cmp(&b.trait_name));
+        }
+        SymbolType::Trait => {
+            let counts = parser.get_impls().iter()
+                .filter(|info| info.trait_name == symbol.name && !info.is_test)
+                .map(|info| info.type_name.as_str());
+            implementors = rank(counts, limit);
+        }
+        SymbolType::Function => {
+            let calls = parser.get_call_graph();
+            callers = rank(calls.iter().filter(|c| c.callee == symbol.name).map(|c| c.caller.as_str()), limit);
+            callees = rank(
+                calls.iter()
+                    .filter(|c| c.caller == symbol.name && c.caller_file == symbol.file_path && c.caller_line == symbol.line)
+                    .filter(|c| function_names.contains(c.callee.as_str()))
+                    .map(|c| c.callee.as_str()),
+                limit,
+            );
+
+            let matches = crate::test_map::find_tests_for(parser, &symbol.name, crate::test_map::DEFAULT_TEST_SEARCH_DEPTH);
+            tests = RelatedNames {
+                count: matches.len(),
+                top: matches.iter().take(limit).map(|m| m.test.name.clone()).collect(),
+            };
+        }
+    }
+
+    SymbolExplanation {
+        name: symbol.name.clone(),
+        symbol_type: symbol.symbol_type.clone(),
+        signature: symbol.signature.clone(),
+        visibility: symbol.visibility.clone(),
+        docs: context.docs,
+        module_path,
+        owner: context.owner,
+        file_path: symbol.file_path.clone(),
+        line: symbol.line,
+        end_line: symbol.end_line,
+        crate_name: symbol.crate_name.clone(),
+        implemented_traits,
+        implementors,
+        callers,
+        callees,
+        tests,
+    }
+}
+
+/// 名前の出現回数を数え、多い順に上位 limit 件を返す
+fn rank<'a>(names: impl Iterator<Item = &'a str>, limit: usize) -> RelatedNames {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut ranked: Vec<(&str, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    RelatedNames {
+        count: ranked.len(),
+        top: ranked.into_iter().take(limit).map(|(name, _)| name.to_string()).collect(),
+    }
+}
+
+/// ファイルパスからモジュールパスを推定（src/ 以下のディレクトリ構成に従う）
+fn file_module_path(file_path: &str, crate_name: Option<&str>) -> String {
+    let root = crate_name.map(|name| name.replace('-', "_")).unwrap_or_else(|| "crate".to_string());
+    let components: Vec<String> = Path::new(file_path).components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let Some(src) = components.iter().rposition(|c| c == "src") else {
+        return root;
+    };
+
+    let mut segments = vec![root];
+    let rest = &components[src + 1..];
+    for (index, component) in rest.iter().enumerate() {
+        let name = component.strip_suffix(".rs").unwrap_or(component);
+        // src/lib.rs・src/main.rs はクレートルート、mod.rs はディレクトリのモジュール自身
+        let is_root = index == 0 && matches!(name, "lib" | "main");
+        if !is_root && name != "mod" {
+            segments.push(name.to_string());
+        }
+    }
+    segments.join("::")
+}
+
+/// 識別子の位置が (line, column) のアイテムを探し、ドキュメントと囲んでいるモジュール・impl を求める
+fn find_context(syntax_tree: &syn::File, line: usize, column: usize) -> Option<SymbolContext> {
+    let mut finder = ContextFinder {
+        target: LineColumn { line, column },
+        modules: Vec::new(),
+        owner: None,
+        found: None,
+    };
+    finder.visit_file(syntax_tree);
+    finder.found
+}
+
+struct ContextFinder {
+    target: LineColumn,
+    modules: Vec<String>,
+    owner: Option<String>,
+    found: Option<SymbolContext>,
+}
+
+impl ContextFinder {
+    fn check(&mut self, ident: &syn::Ident, attrs: &[syn::Attribute]) {
+        if self.found.is_none() && ident.span().start() == self.target {
+            self.found = Some(SymbolContext {
+                docs: doc_comment(attrs),
+                modules: self.modules.clone(),
+                owner: self.owner.clone(),
+            });
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ContextFinder {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        self.modules.push(item.ident.to_string());
+        visit::visit_item_mod(self, item);
+        self.modules.pop();
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let self_type = crate::parser::type_name(&item.self_ty);
+        let owner = match &item.trait_ {
+            Some((_, path, _)) => format!("impl {} for {}", crate::trait_impls::path_string(path), self_type),
+            None => format!("impl {self_type}"),
+        };
+        let outer = self.owner.replace(owner);
+        visit::visit_item_impl(self, item);
+        self.owner = outer;
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.check(&item.sig.ident, &item.attrs);
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.check(&item.sig.ident, &item.attrs);
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        self.check(&item.sig.ident, &item.attrs);
+        visit::visit_trait_item_fn(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.check(&item.ident, &item.attrs);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.check(&item.ident, &item.attrs);
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.check(&item.ident, &item.attrs);
+        let outer = self.owner.replace(format!("trait {}", item.ident));
+        visit::visit_item_trait(self, item);
+        self.owner = outer;
+    }
+}
+
+fn related_line(label: &str, related: &RelatedNames) -> String {
+    let more = if related.count > related.top.len() { ", ..." } else { "" };
+    format!("  {label}: {} ({}{})\n", related.count, related.top.join(", "), more)
+}
+
+/// explain_symbol の結果をテキストに整形（CLI・MCP 用）
+pub fn format_explanations(response: &ExplainSymbolResponse) -> String {
+    let mut result = String::new();
+    for symbol in &response.symbols {
+        let kind = format!("{:?}", symbol.symbol_type).to_lowercase();
+        result.push_str(&format!("{} {} ({}:{}-{})\n", kind, symbol.name, symbol.file_path, symbol.line, symbol.end_line));
+        result.push_str(&format!("  {}\n", symbol.signature));
+        result.push_str(&format!("  module: {}\n", symbol.module_path));
+        if let Some(owner) = &symbol.owner {
+            result.push_str(&format!("  owner: {owner}\n"));
+        }
+        result.push_str(&format!("  visibility: {}\n", if symbol.visibility.is_empty() { "private" } else { &symbol.visibility }));
+        if let Some(docs) = &symbol.docs {
+            for (index, line) in docs.lines().enumerate() {
+                let label = if index == 0 { "docs: " } else { "      " };
+                result.push_str(format!("  {label}{line}").trim_end());
+                result.push('\n');
+            }
+        }
+
+        match symbol.symbol_type {
+            SymbolType::Struct | SymbolType::Enum => {
+                let traits: Vec<String> = symbol.implemented_traits.iter()
+                    .map(|t| format!("{} ({})", t.trait_name, crate::trait_impls::source_label(t.source)))
+                    .collect();
+                result.push_str(&format!("  traits: {}\n", if traits.is_empty() { "-".to_string() } else { traits.join(", ") }));
+            }
+            SymbolType::Trait => result.push_str(&related_line("implementors", &symbol.implementors)),
+            SymbolType::Function => {
+                result.push_str(&related_line("callers", &symbol.callers));
+                result.push_str(&related_line("callees", &symbol.callees));
+                result.push_str(&related_line("tests", &symbol.tests));
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ImplSource;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_explain_symbol() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("src/config.rs");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, r#"use std::fmt;
+
+/// 設定
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub name: String,
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl Config {
+    /// 表示名を返す
+    ///
+    /// 空なら "default"
+    pub fn label(&self) -> String {
+        normalize(&self.name)
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.to_string()
+}
+
+pub mod nested {
+    pub trait Named {
+        fn name(&self) -> String;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_label() {
+        let config = super::Config { name: String::new() };
+        assert_eq!(config.label(), "");
+    }
+}
+"#).unwrap();
+        let mut parser = RustParser::new();
+        parser.parse_file(&path).unwrap();
+
+        let params = ExplainSymbolParams { symbol_name: String::new(), symbol_type: None, file_path: None, line: None, limit: None };
+        let explain = |name: &str| {
+            let symbols = parser.find_symbol(name, None).unwrap();
+            explain_symbols(&parser, &symbols, &params).symbols.remove(0)
+        };
+
+        let config = explain("Config");
+        assert_eq!(config.module_path, "crate::config");
+        assert_eq!(config.docs.as_deref(), Some("設定"));
+        assert_eq!(config.owner, None);
+        let traits: Vec<(&str, ImplSource)> = config.implemented_traits.iter().map(|t| (t.trait_name.as_str(), t.source)).collect();
+        assert_eq!(traits, vec![("Clone", ImplSource::Derive), ("Debug", ImplSource::Derive), ("Display", ImplSource::Manual)]);
+
+        let label = explain("label");
+        assert_eq!(label.owner.as_deref(), Some("impl Config"));
+        assert_eq!(label.docs.as_deref(), Some("表示名を返す\n\n空なら \"default\""));
+        assert_eq!((label.callers.count, label.callers.top.clone()), (2, vec!["fmt".to_string(), "test_label".to_string()]));
+        assert_eq!(label.callees.top, vec!["normalize"]);
+        assert_eq!(label.tests.top, vec!["test_label"]);
+
+        let fmt = explain("fmt");
+        assert_eq!(fmt.owner.as_deref(), Some("impl fmt::Display for Config"));
+        assert_eq!(fmt.callees.top, vec!["label"]);
+
+        let named = explain("Named");
+        assert_eq!(named.module_path, "crate::config::nested");
+        let name = explain("name");
+        assert_eq!(name.owner.as_deref(), Some("trait Named"));
+
+        assert_eq!(file_module_path("src/main.rs", None), "crate");
+        assert_eq!(file_module_path("/deps/tokio-util/src/sync/mod.rs", Some("tokio-util")), "tokio_util::sync");
+    }
+}
@@ -0,0 +1,478 @@
+use crate::parser::{RustParser, SymbolInfo, qualified_name};
+use crate::protocol::{ChurnSortKey, CommitInfo, GetChurnParams, GetChurnResponse, GitStats, SymbolChurn, SymbolHistory, SymbolHistoryParams, SymbolHistoryResponse};
+use anyhow::{Context, Result};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+pub const DEFAULT_CHURN_LIMIT: usize = 50;
+
+/// unified diff のハンク（`@@ -old_start,old_count +new_start,new_count @@`）
+#[derive(Debug, Clone, Copy)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+}
+
+impl Hunk {
+    /// ハンクの見出し行を読む（見出し行でなければ None）
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.strip_prefix("@@ ")?.split_whitespace();
+        let (old_start, old_count) = parse_range(parts.next()?.strip_prefix('-')?)?;
+        let (new_start, new_count) = parse_range(parts.next()?.strip_prefix('+')?)?;
+        Some(Self { old_start, old_count, new_start, new_count })
+    }
+
+    /// 新しい側でこのハンクより後ろにある行か（件数 0 のハンクは new_start の行の直後にある）
+    fn is_before(&self, line: usize) -> bool {
+        self.new_start + self.new_count.max(1) - 1 < line
+    }
+
+    fn contains(&self, line: usize) -> bool {
+        self.new_count > 0 && self.new_start <= line && line < self.new_start + self.new_count
+    }
+
+    /// 新しい側の行範囲 [start, end] を変更したか
+    fn touches(&self, start: usize, end: usize) -> bool {
+        if self.new_count == 0 {
+            // 行の削除は範囲の内側で起きたときだけ数える
+            start <= self.new_start && self.new_start < end
+        } else {
+            self.new_start <= end && start < self.new_start + self.new_count
+        }
+    }
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// 変更後の行範囲を変更前の行番号に戻す（範囲がなくなれば None ＝ この変更で追加された）
+fn map_to_old(hunks: &[Hunk], start: usize, end: usize) -> Option<(usize, usize)> {
+    let map = |line: usize, is_start: bool| {
+        let mut delta = 0isize;
+        for hunk in hunks {
+            if hunk.contains(line) {
+                // 書き換えられた行は、変更前のハンクの先頭・末尾に寄せる
+                // 件数 0 のハンク（行の追加のみ）は old_start の行の直後に挿入されている
+                return match (is_start, hunk.old_count) {
+                    (true, 0) => hunk.old_start + 1,
+                    (true, _) => hunk.old_start,
+                    (false, 0) => hunk.old_start,
+                    (false, count) => hunk.old_start + count - 1,
+                };
+            }
+            if !hunk.is_before(line) {
+                break;
+            }
+            delta += hunk.old_count as isize - hunk.new_count as isize;
+        }
+        line.saturating_add_signed(delta)
+    };
+    let (old_start, old_end) = (map(start, true), map(end, false));
+    (old_start <= old_end).then_some((old_start, old_end))
+}
+
+/// ファイルのコミット履歴（新しい順）と作業ツリーの未コミットの変更
+///
+/// リネームは追わない。マージをまたぐ履歴は日付順に並べて行番号をたどるので近似になる。
+#[derive(Default)]
+pub struct FileHistory {
+    uncommitted: Vec<Hunk>,
+    commits: Vec<(CommitInfo, Vec<Hunk>)>,
+}
+
+/// 複数ファイルの履歴を、リポジトリごとに1回の git log でまとめて読む
+///
+/// 戻り値はキーを渡したパスとする履歴と、git のリポジトリの外にある・読めないなどで履歴を得られなかったファイル。
+pub fn load_histories<'a>(file_paths: impl IntoIterator<Item = &'a str>) -> Result<(HashMap<&'a str, FileHistory>, Vec<&'a str>)> {
+    let mut skipped = Vec::new();
+    let mut toplevels: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+    // リポジトリのルート -> (ルートからの相対パス -> 渡したパス)
+    let mut repositories: BTreeMap<PathBuf, HashMap<String, &'a str>> = BTreeMap::new();
+    let mut seen = HashSet::new();
+    for file_path in file_paths {
+        if !seen.insert(file_path) {
+            continue;
+        }
+        let Ok(canonical) = Path::new(file_path).canonicalize() else {
+            skipped.push(file_path);
+            continue;
+        };
+        let dir = canonical.parent().unwrap_or(Path::new("/")).to_path_buf();
+        let toplevel = toplevels.entry(dir.clone())
+            .or_insert_with(|| git(&dir, ["rev-parse", "--show-toplevel"]).ok()
+                .and_then(|output| PathBuf::from(output.trim()).canonicalize().ok()));
+        let relative = toplevel.as_ref()
+            .and_then(|root| Some((root, canonical.strip_prefix(root).ok()?.to_str()?)));
+        match relative {
+            Some((root, relative)) => {
+                repositories.entry(root.clone()).or_default().insert(relative.replace('\\', "/"), file_path);
+            }
+            None => skipped.push(file_path),
+        }
+    }
+
+    let mut histories = HashMap::new();
+    for (root, files) in repositories {
+        let pathspecs: Vec<&str> = files.keys().map(|relative| relative.as_str()).collect();
+        let mut repository_histories: HashMap<&str, FileHistory> = HashMap::new();
+
+        let log = git(&root, [
+            "--literal-pathspecs", "-c", "core.quotePath=false", "log", "--no-color", "--no-ext-diff", "--no-renames", "-p", "-U0",
+            "--format=%x1e%H%x1f%an%x1f%at%x1f%s", "--",
+        ].into_iter().chain(pathspecs.iter().copied()))?;
+        for record in log.split('\x1e') {
+            let (header, diff) = record.split_once('\n').unwrap_or((record, ""));
+            let mut fields = header.split('\x1f');
+            let (Some(hash), Some(author), Some(Ok(timestamp))) = (fields.next(), fields.next(), fields.next().map(str::parse)) else {
+                continue;
+            };
+            let info = CommitInfo {
+                hash: hash.to_string(),
+                author: author.to_string(),
+                timestamp,
+                summary: fields.next().unwrap_or_default().to_string(),
+            };
+            for (relative, hunks) in parse_file_hunks(diff) {
+                if files.contains_key(relative) {
+                    repository_histories.entry(relative).or_default().commits.push((info.clone(), hunks));
+                }
+            }
+        }
+
+        // コミットがまだないリポジトリでは HEAD がないので、未コミットの変更はなしとする
+        let uncommitted = git(&root, [
+            "--literal-pathspecs", "-c", "core.quotePath=false", "diff", "--no-color", "--no-ext-diff", "-U0", "HEAD", "--",
+        ].into_iter().chain(pathspecs.iter().copied())).unwrap_or_default();
+        for (relative, hunks) in parse_file_hunks(&uncommitted) {
+            if files.contains_key(relative) {
+                repository_histories.entry(relative).or_default().uncommitted = hunks;
+            }
+        }
+
+        // 履歴のない（未追跡・未コミットの）ファイルも空の履歴として返す
+        for (relative, file_path) in &files {
+            histories.insert(*file_path, repository_histories.remove(relative.as_str()).unwrap_or_default());
+        }
+    }
+
+    Ok((histories, skipped))
+}
+
+impl FileHistory {
+    /// 作業ツリーの行範囲 [start, end] に触れたコミット（新しい順）と、未コミットの変更があるか
+    pub fn trace(&self, start: usize, end: usize) -> (Vec<&CommitInfo>, bool) {
+        let uncommitted = self.uncommitted.iter().any(|hunk| hunk.touches(start, end));
+        let Some(mut range) = map_to_old(&self.uncommitted, start, end) else {
+            return (Vec::new(), uncommitted);
+        };
+
+        let mut touched = Vec::new();
+        for (info, hunks) in &self.commits {
+            if hunks.iter().any(|hunk| hunk.touches(range.0, range.1)) {
+                touched.push(info);
+            }
+            match map_to_old(hunks, range.0, range.1) {
+                Some(old) => range = old,
+                None => break,
+            }
+        }
+        (touched, uncommitted)
+    }
+}
+
+/// 複数ファイルの diff をファイル（リポジトリのルートからの相対パス）ごとのハンクに分ける
+fn parse_file_hunks(diff: &str) -> Vec<(&str, Vec<Hunk>)> {
+    let mut files: Vec<(&str, Vec<Hunk>)> = Vec::new();
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            files.push(("", Vec::new()));
+        } else if let Some(path) = line.strip_prefix("--- a/").or_else(|| line.strip_prefix("+++ b/")) {
+            // 削除されたファイルは +++ が /dev/null なので、--- の側のパスを使う。
+            // ハンクの後ろの行は、内容が "-- a/" などで始まる削除・追加の行
+            if let Some(file) = files.last_mut()
+                && file.1.is_empty() {
+                // 空白を含むパスの後ろには git がタブを付ける
+                file.0 = path.trim_end_matches('\t');
+            }
+        } else if let Some(hunk) = Hunk::parse(line)
+            && let Some(file) = files.last_mut() {
+            file.1.push(hunk);
+        }
+    }
+    files
+}
+
+/// dir で git を実行して標準出力を返す（失敗したら標準エラーの内容をエラーにする）
+pub fn git<S: AsRef<OsStr>>(dir: &Path, args: impl IntoIterator<Item = S>) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C").arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("git failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn git_stats(commits: &[&CommitInfo], uncommitted: bool, now: i64) -> Option<GitStats> {
+    let last = commits.first()?;
+    let first = commits.last()?;
+    let days = |timestamp: i64| ((now - timestamp).max(0) / 86_400) as u64;
+    Some(GitStats {
+        last_commit: last.hash.clone(),
+        last_author: last.author.clone(),
+        last_summary: last.summary.clone(),
+        last_modified: last.timestamp,
+        last_modified_days: days(last.timestamp),
+        age_days: days(first.timestamp),
+        churn: commits.len(),
+        authors: commits.iter().map(|c| c.author.as_str()).collect::<HashSet<_>>().len(),
+        uncommitted,
+    })
+}
+
+/// シンボルごとに行範囲に触れたコミットの履歴を求める
+pub fn symbol_history(symbols: &[SymbolInfo], params: &SymbolHistoryParams) -> Result<SymbolHistoryResponse> {
+    let limit = params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    let now = chrono::Utc::now().timestamp();
+
+    let (histories, skipped) = load_histories(symbols.iter().map(|symbol| symbol.file_path.as_str()))?;
+    if let Some(file_path) = skipped.first() {
+        anyhow::bail!("No git history available for {file_path}");
+    }
+    let mut result = Vec::new();
+    for symbol in symbols {
+        let (commits, uncommitted) = histories[symbol.file_path.as_str()].trace(symbol.line, symbol.end_line);
+        result.push(SymbolHistory {
+            name: symbol.name.clone(),
+            symbol_type: symbol.symbol_type.clone(),
+            file_path: symbol.file_path.clone(),
+            line: symbol.line,
+            end_line: symbol.end_line,
+            scope: symbol.scope.clone(),
+            stats: git_stats(&commits, uncommitted, now),
+            commits: commits.into_iter().take(limit).cloned().collect(),
+        });
+    }
+    Ok(SymbolHistoryResponse { symbols: result })
+}
+
+/// get_churn の対象になるプロジェクト内のシンボル（git を実行する前にインデックスのロックを外せるよう複製する）
+pub fn churn_symbols<'a>(parser: &RustParser, files: impl Iterator<Item = &'a Path>, params: &GetChurnParams) -> Vec<SymbolInfo> {
+    let include_tests = params.include_tests.unwrap_or(false);
+    let files: HashSet<String> = files
+        .map(|path| path.to_string_lossy().to_string())
+        .filter(|path| params.file_path.as_deref().is_none_or(|file| path == file))
+        .collect();
+
+    parser.get_all_symbols().values()
+        .flatten()
+        .filter(|symbol| symbol.crate_name.is_none() && (include_tests || !symbol.is_test) && files.contains(&symbol.file_path))
+        .cloned()
+        .collect()
+}
+
+/// シンボルを git の履歴から求めた変更頻度・鮮度で並べる
+///
+/// 履歴はリポジトリごとに1回の git log でまとめて読む。履歴を得られないファイルは飛ばして skipped_files で返す。
+pub fn churn_report(symbols: &[SymbolInfo], params: &GetChurnParams) -> Result<GetChurnResponse> {
+    let (histories, skipped) = load_histories(symbols.iter().map(|symbol| symbol.file_path.as_str()))?;
+    let mut skipped_files: Vec<String> = skipped.into_iter().map(|file_path| file_path.to_string()).collect();
+    skipped_files.sort();
+
+    let now = chrono::Utc::now().timestamp();
+    let mut churn = Vec::new();
+    for symbol in symbols {
+        let Some(history) = histories.get(symbol.file_path.as_str()) else {
+            continue;
+        };
+        let (commits, uncommitted) = history.trace(symbol.line, symbol.end_line);
+        let Some(stats) = git_stats(&commits, uncommitted, now) else {
+            continue;
+        };
+        churn.push(SymbolChurn {
+            name: symbol.name.clone(),
+            symbol_type: symbol.symbol_type.clone(),
+            file_path: symbol.file_path.clone(),
+            line: symbol.line,
+            end_line: symbol.end_line,
+            scope: symbol.scope.clone(),
+            stats,
+        });
+    }
+    let mut symbols = churn;
+
+    // 同順位は定義位置の順
+    symbols.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+    match params.sort_by.unwrap_or(ChurnSortKey::Churn) {
+        ChurnSortKey::Churn => symbols.sort_by_key(|s| Reverse((s.stats.churn, s.stats.last_modified))),
+        ChurnSortKey::Recent => symbols.sort_by_key(|s| Reverse(s.stats.last_modified)),
+        ChurnSortKey::Oldest => symbols.sort_by_key(|s| s.stats.last_modified),
+    }
+    let total_symbols = symbols.len();
+    symbols.truncate(params.limit.unwrap_or(DEFAULT_CHURN_LIMIT));
+    Ok(GetChurnResponse { symbols, total_symbols, skipped_files })
+}
+
+/// 並び替えキーの名前を解釈（CLI 用）
+pub fn parse_sort_key(name: &str) -> Option<ChurnSortKey> {
+    match name {
+        "churn" => Some(ChurnSortKey::Churn),
+        "recent" => Some(ChurnSortKey::Recent),
+        "oldest" | "stale" => Some(ChurnSortKey::Oldest),
+        _ => None,
+    }
+}
+
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(8)]
+}
+
+fn format_date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// symbol_history の結果をテキストに整形（CLI・MCP 用）
+pub fn format_history(response: &SymbolHistoryResponse) -> String {
+    let mut result = String::new();
+    for symbol in &response.symbols {
+        let name = qualified_name(symbol.scope.as_deref(), &symbol.name);
+        result.push_str(&format!("{name} ({}:{}-{})\n", symbol.file_path, symbol.line, symbol.end_line));
+        let Some(stats) = &symbol.stats else {
+            result.push_str("  not committed yet\n\n");
+            continue;
+        };
+        result.push_str(&format!(
+            "  churn {}, {} author(s), last modified {} day(s) ago, first committed {} day(s) ago{}\n",
+            stats.churn, stats.authors, stats.last_modified_days, stats.age_days,
+            if stats.uncommitted { ", has uncommitted changes" } else { "" },
+        ));
+        for commit in &symbol.commits {
+            result.push_str(&format!("  {} {} {:<16} {}\n", short_hash(&commit.hash), format_date(commit.timestamp), commit.author, commit.summary));
+        }
+        if symbol.commits.len() < stats.churn {
+            result.push_str(&format!("  ... {} older commit(s)\n", stats.churn - symbol.commits.len()));
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// get_churn の結果を表形式のテキストに整形（CLI・MCP 用）
+pub fn format_churn(report: &GetChurnResponse) -> String {
+    let mut result = format!(
+        "{:<40} {:>5} {:>7} {:>9} {:>8}  {:<8} {:<16} {}\n",
+        "symbol", "churn", "authors", "modified", "age", "commit", "author", "location",
+    );
+    for s in &report.symbols {
+        let modified = format!("{}d{}", s.stats.last_modified_days, if s.stats.uncommitted { "*" } else { "" });
+        result.push_str(&format!(
+            "{:<40} {:>5} {:>7} {:>9} {:>7}d  {:<8} {:<16} {}:{}\n",
+            qualified_name(s.scope.as_deref(), &s.name), s.stats.churn, s.stats.authors, modified, s.stats.age_days,
+            short_hash(&s.stats.last_commit), s.stats.last_author, s.file_path, s.line,
+        ));
+    }
+    result.push_str(&format!("\n{} of {} symbol(s) (* = uncommitted changes)\n", report.symbols.len(), report.total_symbols));
+    if !report.skipped_files.is_empty() {
+        result.push_str(&format!("skipped {} file(s) without git history: {}\n", report.skipped_files.len(), report.skipped_files.join(", ")));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit(dir: &Path, message: &str) {
+        for args in [vec!["add", "."], vec!["commit", "-q", "-m", message]] {
+            let status = Command::new("git").arg("-C").arg(dir)
+                .args(["-c", "user.name=alice", "-c", "user.email=alice@example.com"])
+                .args(&args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+    }
+
+    #[test]
+    fn test_symbol_history() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("lib.rs");
+        Command::new("git").arg("-C").arg(dir.path()).args(["init", "-q"]).status().unwrap();
+
+        fs::write(&file, "pub fn stable() -> i32 {\n    1\n}\n\npub fn hot() -> i32 {\n    1\n}\n").unwrap();
+        commit(dir.path(), "add functions");
+        fs::write(&file, "pub fn stable() -> i32 {\n    1\n}\n\npub fn hot() -> i32 {\n    2\n}\n").unwrap();
+        commit(dir.path(), "tune hot");
+        // 上に行を足しても、下のシンボルの履歴は行番号をずらして追える
+        fs::write(&file, "pub fn added() {}\n\npub fn stable() -> i32 {\n    1\n}\n\npub fn hot() -> i32 {\n    let x = 3;\n    x\n}\n").unwrap();
+        commit(dir.path(), "add another and tune hot again");
+        fs::write(&file, "pub fn added() {}\n\npub fn stable() -> i32 {\n    1\n}\n\npub fn hot() -> i32 {\n    let x = 4;\n    x\n}\n").unwrap();
+
+        let mut parser = RustParser::new();
+        parser.parse_file(&file).unwrap();
+        let files = [file.clone()];
+        let params = GetChurnParams { file_path: None, sort_by: None, limit: None, include_tests: None };
+        let symbols = churn_symbols(&parser, files.iter().map(|f| f.as_path()), &params);
+        let report = churn_report(&symbols, &params).unwrap();
+
+        let churn: Vec<(&str, usize, bool)> = report.symbols.iter().map(|s| (s.name.as_str(), s.stats.churn, s.stats.uncommitted)).collect();
+        assert_eq!(churn, vec![("hot", 3, true), ("added", 1, false), ("stable", 1, false)]);
+        assert_eq!(report.symbols[0].stats.last_summary, "add another and tune hot again");
+        assert_eq!(report.symbols[0].stats.authors, 1);
+
+        let hot: Vec<SymbolInfo> = parser.find_symbol("hot", None).unwrap().into_iter().cloned().collect();
+        let params = SymbolHistoryParams { symbol_name: "hot".to_string(), symbol_type: None, file_path: None, line: None, limit: Some(2) };
+        let history = symbol_history(&hot, &params).unwrap();
+        let summaries: Vec<&str> = history.symbols[0].commits.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["add another and tune hot again", "tune hot"]);
+        assert!(format_history(&history).contains("... 1 older commit(s)"));
+    }
+
+    #[test]
+    fn test_churn_report_skips_files_without_history() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        Command::new("git").arg("-C").arg(dir.path()).args(["init", "-q"]).status().unwrap();
+
+        let first = dir.path().join("first.rs");
+        let second = dir.path().join("second.rs");
+        let untracked = outside.path().join("untracked.rs");
+        fs::write(&first, "pub fn first() {}\n").unwrap();
+        fs::write(&second, "pub fn second() {}\n").unwrap();
+        commit(dir.path(), "add files");
+        fs::write(&second, "pub fn second() -> i32 {\n    1\n}\n").unwrap();
+        commit(dir.path(), "change second");
+        fs::write(&untracked, "pub fn outside() {}\n").unwrap();
+
+        let mut parser = RustParser::new();
+        let files = [first, second, untracked.clone()];
+        for file in &files {
+            parser.parse_file(file).unwrap();
+        }
+        let params = GetChurnParams { file_path: None, sort_by: None, limit: None, include_tests: None };
+        let symbols = churn_symbols(&parser, files.iter().map(|f| f.as_path()), &params);
+        let report = churn_report(&symbols, &params).unwrap();
+
+        // 1回の git log から、ファイルごとの履歴に分けて数える
+        let churn: Vec<(&str, usize)> = report.symbols.iter().map(|s| (s.name.as_str(), s.stats.churn)).collect();
+        assert_eq!(churn, vec![("second", 2), ("first", 1)]);
+        assert_eq!(report.skipped_files, vec![untracked.to_string_lossy().to_string()]);
+        assert!(format_churn(&report).contains("skipped 1 file(s) without git history"));
+    }
+}
@@ -7,15 +7,6 @@ pub struct CallGraphGenerator {
     indexer: CodeIndexer,
 }
 
-#[derive(Debug, Clone)]
-pub struct GraphNode {
-    pub name: String,
-    pub file_path: String,
-    pub line: usize,
-    pub children: Vec<String>,
-    pub parents: Vec<String>,
-}
-
 impl CallGraphGenerator {
     pub fn new() -> Self {
         Self {
@@ -182,12 +173,11 @@ impl CallGraphGenerator {
     }
 
     fn get_function_info(&self, function_name: &str) -> String {
-        if let Some(symbols) = self.indexer.find_definition(function_name, None) {
-            if let Some(symbol) = symbols.first() {
-                return format!(" @ {}:{}", 
-                    symbol.file_path.split('/').last().unwrap_or(&symbol.file_path),
-                    symbol.line);
-            }
+        if let Some(symbols) = self.indexer.find_definition(function_name, None)
+            && let Some(symbol) = symbols.first() {
+            return format!(" @ {}:{}", 
+                symbol.file_path.split('/').next_back().unwrap_or(&symbol.file_path),
+                symbol.line);
         }
         String::new()
     }
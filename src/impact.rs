@@ -0,0 +1,365 @@
+use crate::git_history::{Hunk, git};
+use crate::parser::{RustParser, SymbolInfo, TestInfo, qualified_name};
+use crate::protocol::{ImpactAnalysisResponse, ImpactedSymbol, ImpactedTest, SymbolType};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+pub const DEFAULT_IMPACT_DEPTH: usize = 5;
+
+/// diff に含まれる1ファイル分の変更（行番号は変更後のファイルのもの）
+#[derive(Debug, Default)]
+struct FileChange {
+    path: String,
+    lines: Vec<usize>,      // 追加・書き換えた行
+    deletions: Vec<usize>,  // この行の直前で行が削除された
+}
+
+/// unified diff を読み、ファイルごとの変更行を取り出す（コンテキスト行の数によらない）
+fn parse_diff(diff: &str) -> Vec<FileChange> {
+    let mut files: Vec<FileChange> = Vec::new();
+    let mut new_line = 0;
+    let (mut old_left, mut new_left) = (0, 0);  // ハンクの残りの行数
+
+    for line in diff.lines() {
+        if old_left == 0 && new_left == 0 {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                // "+++ /dev/null"（削除されたファイル）は最後に取り除く
+                let path = path.split('\t').next().unwrap_or(path).trim();
+                let path = path.strip_prefix("b/").unwrap_or(path);
+                files.push(FileChange { path: path.to_string(), ..Default::default() });
+            } else if let Some(hunk) = Hunk::parse(line) {
+                // 件数 0 のハンク（削除のみ）の開始行は、削除位置の直前の行
+                new_line = if hunk.new_count == 0 { hunk.new_start + 1 } else { hunk.new_start };
+                (old_left, new_left) = (hunk.old_count, hunk.new_count);
+            }
+            continue;
+        }
+
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        match line.chars().next() {
+            Some('+') => {
+                file.lines.push(new_line);
+                new_line += 1;
+                new_left = new_left.saturating_sub(1);
+            }
+            Some('-') => {
+                file.deletions.push(new_line);
+                old_left = old_left.saturating_sub(1);
+            }
+            Some('\\') => {}  // "\ No newline at end of file"
+            _ => {
+                new_line += 1;
+                old_left = old_left.saturating_sub(1);
+                new_left = new_left.saturating_sub(1);
+            }
+        }
+    }
+
+    files.retain(|file| file.path != "/dev/null");
+    files
+}
+
+/// 作業ツリーと base（デフォルトは HEAD）の差分を git から取得
+///
+/// base は `--output=...` のようなオプションとして解釈されないよう、先にコミットへ解決してから渡す。
+pub fn git_diff(dir: &Path, base: Option<&str>) -> Result<String> {
+    let base = base.unwrap_or("HEAD");
+    if base.starts_with('-') {
+        anyhow::bail!("Unknown git revision: {base}");
+    }
+    let commit = git(dir, ["rev-parse", "--verify", "--quiet", &format!("{base}^{{commit}}")])
+        .with_context(|| format!("Unknown git revision: {base}"))?;
+    git(dir, ["diff", "--no-color", "--no-ext-diff", "-U0", commit.trim(), "--"])
+}
+
+fn impacted(symbol: &SymbolInfo, distance: usize, via: Option<String>) -> ImpactedSymbol {
+    ImpactedSymbol {
+        name: symbol.name.clone(),
+        symbol_type: symbol.symbol_type.clone(),
+        file_path: symbol.file_path.clone(),
+        line: symbol.line,
+        scope: symbol.scope.clone(),
+        visibility: symbol.visibility.clone(),
+        distance,
+        via,
+    }
+}
+
+/// `let name = |..|` の名前付きクロージャ（呼び出し元・変更箇所としては囲んでいる関数として扱う）
+fn is_closure(symbol: &SymbolInfo) -> bool {
+    symbol.symbol_type == SymbolType::Function && symbol.signature.starts_with("let ")
+}
+
+/// シグネチャに識別子として含まれるか
+fn mentions(signature: &str, name: &str) -> bool {
+    signature.split(|c: char| !c.is_alphanumeric() && c != '_').any(|word| word == name)
+}
+
+/// diff の変更行を囲むシンボルを求め、そこから呼び出し元を推移的にたどって影響範囲を求める
+///
+/// 呼び出しの対応は名前だけで取っているコールグラフに従うので、同名の関数があると多めに出る。
+/// 型・トレイトの変更は、シグネチャにその名前が出てくる関数を距離 1 として扱う。
+pub fn analyze<'a>(parser: &RustParser, files: impl Iterator<Item = &'a Path>, diff: &str, max_depth: usize) -> ImpactAnalysisResponse {
+    let indexed: Vec<&Path> = files.collect();
+    let symbols: Vec<&SymbolInfo> = parser.get_all_symbols().values()
+        .flatten()
+        .filter(|s| s.crate_name.is_none())
+        .collect();
+
+    // 変更行ごとに、それを含むいちばん内側のシンボル
+    let mut changed_files = Vec::new();
+    let mut unmatched_files = Vec::new();
+    let mut changed: Vec<&SymbolInfo> = Vec::new();
+    for change in parse_diff(diff) {
+        let Some(file) = indexed.iter().find(|file| file.ends_with(&change.path)) else {
+            unmatched_files.push(change.path);
+            continue;
+        };
+        let file = file.to_string_lossy();
+        changed_files.push(change.path);
+
+        let in_file: Vec<&SymbolInfo> = symbols.iter().copied().filter(|s| s.file_path == file && !is_closure(s)).collect();
+        let innermost = |matches: &dyn Fn(&SymbolInfo) -> bool| {
+            in_file.iter().copied()
+                .filter(|s| matches(s))
+                .min_by_key(|s| s.end_line - s.line)
+        };
+        let touched = change.lines.iter()
+            .filter_map(|&line| innermost(&|s| s.line <= line && line <= s.end_line))
+            .chain(change.deletions.iter().filter_map(|&line| innermost(&|s| s.line < line && line <= s.end_line)));
+        for symbol in touched {
+            if !changed.iter().any(|c| std::ptr::eq(*c, symbol)) {
+                changed.push(symbol);
+            }
+        }
+    }
+
+    let functions: HashMap<(&str, usize), &SymbolInfo> = symbols.iter()
+        .filter(|s| s.symbol_type == SymbolType::Function && !is_closure(s))
+        .map(|s| ((s.file_path.as_str(), s.line), *s))
+        .collect();
+    // クロージャの中からの呼び出しは、クロージャを囲んでいる関数からの呼び出しとする
+    let closure_owners: HashMap<(&str, usize), &SymbolInfo> = symbols.iter()
+        .filter(|s| is_closure(s))
+        .filter_map(|closure| {
+            let owner = functions.values()
+                .filter(|f| f.file_path == closure.file_path && f.line <= closure.line && closure.end_line <= f.end_line)
+                .min_by_key(|f| f.end_line - f.line)?;
+            Some(((closure.file_path.as_str(), closure.line), *owner))
+        })
+        .collect();
+    let tests: HashMap<(&str, usize, &str), &TestInfo> = parser.get_tests().iter()
+        .map(|t| ((t.file_path.as_str(), t.line, t.name.as_str()), t))
+        .collect();
+
+    let mut visited: HashSet<(&str, usize)> = changed.iter().map(|s| (s.file_path.as_str(), s.line)).collect();
+    let mut queue: VecDeque<(&SymbolInfo, usize)> = VecDeque::new();
+    let mut reached: Vec<ImpactedSymbol> = Vec::new();
+    let mut reached_tests: Vec<ImpactedTest> = Vec::new();
+
+    for symbol in &changed {
+        if symbol.symbol_type == SymbolType::Function {
+            queue.push_back((symbol, 0));
+            if let Some(test) = tests.get(&(symbol.file_path.as_str(), symbol.line, symbol.name.as_str())) {
+                reached_tests.push(test_entry(test, 0, None));
+            }
+            continue;
+        }
+        if max_depth == 0 {
+            continue;
+        }
+        for function in functions.values() {
+            if mentions(&function.signature, &symbol.name) && visited.insert((function.file_path.as_str(), function.line)) {
+                reached.push(impacted(function, 1, Some(symbol.name.clone())));
+                queue.push_back((function, 1));
+            }
+        }
+    }
+
+    while let Some((symbol, distance)) = queue.pop_front() {
+        if distance >= max_depth {
+            continue;
+        }
+        let via = qualified_name(symbol.scope.as_deref(), &symbol.name);
+        for call in parser.get_calls_to_function(&symbol.name) {
+            let key = (call.caller_file.as_str(), call.caller_line);
+            if let Some(test) = tests.get(&(call.caller_file.as_str(), call.caller_line, call.caller.as_str())) {
+                if !reached_tests.iter().any(|t| t.file_path == test.file_path && t.line == test.line && t.name == test.name) {
+                    reached_tests.push(test_entry(test, distance + 1, Some(via.clone())));
+                }
+                continue;
+            }
+            let Some(caller) = functions.get(&key).or_else(|| closure_owners.get(&key)) else {
+                continue;
+            };
+            let key = (caller.file_path.as_str(), caller.line);
+            if visited.insert(key) {
+                reached.push(impacted(caller, distance + 1, Some(via.clone())));
+                queue.push_back((caller, distance + 1));
+            }
+        }
+    }
+
+    let by_distance = |a: &ImpactedSymbol, b: &ImpactedSymbol| {
+        (a.distance, &a.file_path, a.line).cmp(&(b.distance, &b.file_path, b.line))
+    };
+    let mut changed: Vec<ImpactedSymbol> = changed.iter().map(|s| impacted(s, 0, None)).collect();
+    changed.sort_by(by_distance);
+    let is_test = |symbol: &ImpactedSymbol| functions.get(&(symbol.file_path.as_str(), symbol.line)).is_some_and(|s| s.is_test);
+    let mut functions_out: Vec<ImpactedSymbol> = reached.into_iter().filter(|s| !is_test(s)).collect();
+    functions_out.sort_by(by_distance);
+    let public_apis = changed.iter()
+        .chain(&functions_out)
+        .filter(|s| s.visibility == "pub" && !is_test(s))
+        .cloned()
+        .collect();
+    reached_tests.sort_by(|a, b| (a.distance, &a.file_path, a.line).cmp(&(b.distance, &b.file_path, b.line)));
+
+    ImpactAnalysisResponse {
+        changed_files,
+        unmatched_files,
+        changed,
+        functions: functions_out,
+        public_apis,
+        tests: reached_tests,
+    }
+}
+
+fn test_entry(test: &TestInfo, distance: usize, via: Option<String>) -> ImpactedTest {
+    ImpactedTest {
+        name: test.name.clone(),
+        kind: test.kind,
+        file_path: test.file_path.clone(),
+        line: test.line,
+        distance,
+        via,
+    }
+}
+
+/// impact_analysis の結果をテキストに整形（CLI・MCP 用）
+pub fn format_impact(report: &ImpactAnalysisResponse) -> String {
+    let mut result = format!(
+        "Impact of {} changed file(s): {} changed symbol(s), {} impacted function(s), {} public API(s), {} test(s)\n",
+        report.changed_files.len(), report.changed.len(), report.functions.len(), report.public_apis.len(), report.tests.len(),
+    );
+    if !report.unmatched_files.is_empty() {
+        result.push_str(&format!("Not indexed: {}\n", report.unmatched_files.join(", ")));
+    }
+
+    let name = |s: &ImpactedSymbol| qualified_name(s.scope.as_deref(), &s.name);
+    let via = |via: &Option<String>| via.as_ref().map(|v| format!(" via {v}")).unwrap_or_default();
+    for (label, symbols) in [("changed", &report.changed), ("impacted functions", &report.functions), ("public APIs", &report.public_apis)] {
+        if symbols.is_empty() {
+            continue;
+        }
+        result.push_str(&format!("\n[{label}]\n"));
+        for s in symbols {
+            let kind = format!("{:?}", s.symbol_type).to_lowercase();
+            result.push_str(&format!("  {} {kind} {} ({}:{}){}\n", s.distance, name(s), s.file_path, s.line, via(&s.via)));
+        }
+    }
+    if !report.tests.is_empty() {
+        result.push_str("\n[tests]\n");
+        for t in &report.tests {
+            let kind = format!("{:?}", t.kind).to_lowercase();
+            result.push_str(&format!("  {} {kind} {} ({}:{}){}\n", t.distance, t.name, t.file_path, t.line, via(&t.via)));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_git_diff_rejects_options_as_base() {
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("written");
+        let base = format!("--output={}", output.display());
+
+        let error = git_diff(dir.path(), Some(&base)).unwrap_err();
+        assert!(error.to_string().contains("Unknown git revision"));
+        assert!(!output.exists());
+        assert!(git_diff(dir.path(), Some("no-such-revision")).is_err());
+    }
+
+    #[test]
+    fn test_impact_analysis() {
+        let mut parser = RustParser::new();
+        parser.parse_source("/work/src/lib.rs", r#"pub struct Config {
+    pub name: String,
+}
+
+fn parse(input: &str) -> usize {
+    input.len()
+}
+
+pub fn load(input: &str) -> usize {
+    parse(input) + 1
+}
+
+pub fn describe(config: &Config) -> String {
+    config.name.clone()
+}
+
+fn unrelated() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        assert_eq!(load("ab"), 3);
+    }
+}
+"#).unwrap();
+
+        // parse の本体と Config のフィールドを書き換え、unrelated の直前の行を消した diff
+        let diff = r#"diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -2 +2 @@ pub struct Config {
+-    pub name: &'static str,
++    pub name: String,
+@@ -5,3 +5,3 @@
+ fn parse(input: &str) -> usize {
+-    input.chars().count()
++    input.len()
+ }
+@@ -17,2 +16,0 @@
+-// 削除したコメント
+-
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-old
++new
+"#;
+        let files = [Path::new("/work/src/lib.rs")];
+        let report = analyze(&parser, files.into_iter(), diff, DEFAULT_IMPACT_DEPTH);
+
+        assert_eq!(report.changed_files, vec!["src/lib.rs"]);
+        assert_eq!(report.unmatched_files, vec!["README.md"]);
+        fn names(symbols: &[ImpactedSymbol]) -> Vec<(&str, usize)> {
+            symbols.iter().map(|s| (s.name.as_str(), s.distance)).collect()
+        }
+        assert_eq!(names(&report.changed), vec![("Config", 0), ("parse", 0)]);
+        assert_eq!(names(&report.functions), vec![("load", 1), ("describe", 1)]);
+        assert_eq!(names(&report.public_apis), vec![("Config", 0), ("load", 1), ("describe", 1)]);
+        assert_eq!(report.functions[0].via.as_deref(), Some("parse"));
+
+        let tests: Vec<(&str, usize)> = report.tests.iter().map(|t| (t.name.as_str(), t.distance)).collect();
+        assert_eq!(tests, vec![("test_load", 2)]);
+
+        // 深さを制限するとテストまで届かない
+        let report = analyze(&parser, files.into_iter(), diff, 1);
+        assert!(report.tests.is_empty());
+    }
+}
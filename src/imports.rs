@@ -0,0 +1,549 @@
+use crate::api_surface::{flatten_use_tree, item_attrs, kind_label, path_attribute};
+use crate::parser::{RustParser, is_test_item};
+use crate::protocol::{ApiItemKind, ImportSuggestion, SuggestImportsParams, SuggestImportsResponse};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+
+/// use をたどる深さの上限（グロブの循環対策）
+const MAX_USE_DEPTH: usize = 8;
+
+/// 名前が見える範囲
+#[derive(Debug, Clone, PartialEq)]
+enum Scope {
+    Public,          // pub（クレートの外からも見える）
+    Within(String),  // このモジュールとその子孫
+}
+
+/// モジュール内の名前が指すもの
+enum Binding {
+    Item { kind: ApiItemKind, file_path: String, line: usize },
+    Module(String),
+    Use(Vec<String>),  // pub use などの再エクスポート（対象のパス）
+}
+
+struct Named {
+    name: String,
+    scope: Scope,
+    binding: Binding,
+}
+
+#[derive(Default)]
+struct ModuleNode {
+    names: Vec<Named>,
+    globs: Vec<(Vec<String>, Scope)>,  // pub use a::*
+    imported: HashSet<String>,          // private な use で持ち込んだ名前
+}
+
+/// 名前をたどって見つかった定義
+struct Found<'a> {
+    kind: ApiItemKind,
+    file_path: &'a str,
+    line: usize,
+    scopes: Vec<Scope>,  // 経由した束縛の見える範囲
+    reexport: bool,
+}
+
+/// クレートのルートから辿れるモジュールと、そこで定義・再エクスポートされている名前
+struct CrateTree {
+    modules: BTreeMap<String, ModuleNode>,
+    files: HashMap<PathBuf, String>,  // 正規化したファイルパス -> モジュール
+}
+
+impl CrateTree {
+    fn build(root_file: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(root_file).ok()?;
+        let syntax_tree = syn::parse_file(&content).ok()?;
+        let mut tree = CrateTree { modules: BTreeMap::new(), files: HashMap::new() };
+        let root_dir = root_file.parent().unwrap_or(Path::new(""));
+        tree.collect("crate", root_file, root_dir, &syntax_tree.items);
+        Some(tree)
+    }
+
+    /// module_dir は子モジュールのファイルを探すディレクトリ
+    fn collect(&mut self, module_path: &str, file: &Path, module_dir: &Path, items: &[syn::Item]) {
+        if let Ok(canonical) = file.canonicalize() {
+            self.files.entry(canonical).or_insert_with(|| module_path.to_string());
+        }
+        let file_path = file.to_string_lossy().to_string();
+        let mut node = ModuleNode::default();
+
+        for item in items {
+            if is_test_item(item_attrs(item), &file_path) {
+                continue;
+            }
+            let (ident, vis, kind) = match item {
+                syn::Item::Mod(item_mod) => {
+                    let name = item_mod.ident.to_string();
+                    let child_path = format!("{module_path}::{name}");
+                    node.names.push(Named {
+                        name: name.clone(),
+                        scope: scope_of(module_path, &item_mod.vis),
+                        binding: Binding::Module(child_path.clone()),
+                    });
+
+                    if let Some((_, child_items)) = &item_mod.content {
+                        self.collect(&child_path, file, &module_dir.join(&name), child_items);
+                    } else if let Some(child_file) = find_module_file(file, module_dir, &name, &item_mod.attrs)
+                        && let Ok(content) = std::fs::read_to_string(&child_file)
+                        && let Ok(syntax_tree) = syn::parse_file(&content) {
+                        let child_dir = if child_file.ends_with("mod.rs") || path_attribute(&item_mod.attrs).is_some() {
+                            child_file.parent().unwrap_or(Path::new("")).to_path_buf()
+                        } else {
+                            child_file.with_extension("")
+                        };
+                        self.collect(&child_path, &child_file, &child_dir, &syntax_tree.items);
+                    }
+                    continue;
+                }
+                syn::Item::Use(item_use) => {
+                    let mut flattened = Vec::new();
+                    flatten_use_tree(&item_use.tree, &mut Vec::new(), &mut flattened);
+                    // private な use はそのモジュールの中の別名なので、インポートのパスには使わない
+                    if matches!(item_use.vis, syn::Visibility::Inherited) {
+                        node.imported.extend(flattened.into_iter().filter_map(|(_, name)| name));
+                        continue;
+                    }
+                    let scope = scope_of(module_path, &item_use.vis);
+                    for (path, name) in flattened {
+                        match name {
+                            Some(name) if name != "_" => node.names.push(Named { name, scope: scope.clone(), binding: Binding::Use(path) }),
+                            Some(_) => {}
+                            None => node.globs.push((path, scope.clone())),
+                        }
+                    }
+                    continue;
+                }
+                syn::Item::Macro(item_macro) => {
+                    // #[macro_export] されたマクロはクレートのルートに公開される
+                    if let Some(ident) = &item_macro.ident
+                        && item_macro.attrs.iter().any(|a| a.path().is_ident("macro_export")) {
+                        self.modules.entry("crate".to_string()).or_default().names.push(Named {
+                            name: ident.to_string(),
+                            scope: Scope::Public,
+                            binding: Binding::Item { kind: ApiItemKind::Macro, file_path: file_path.clone(), line: ident.span().start().line },
+                        });
+                    }
+                    continue;
+                }
+                syn::Item::Fn(item) => (&item.sig.ident, &item.vis, ApiItemKind::Function),
+                syn::Item::Struct(item) => (&item.ident, &item.vis, ApiItemKind::Struct),
+                syn::Item::Enum(item) => (&item.ident, &item.vis, ApiItemKind::Enum),
+                syn::Item::Trait(item) => (&item.ident, &item.vis, ApiItemKind::Trait),
+                syn::Item::Type(item) => (&item.ident, &item.vis, ApiItemKind::TypeAlias),
+                syn::Item::Const(item) => (&item.ident, &item.vis, ApiItemKind::Const),
+                syn::Item::Static(item) => (&item.ident, &item.vis, ApiItemKind::Static),
+                _ => continue,
+            };
+            node.names.push(Named {
+                name: ident.to_string(),
+                scope: scope_of(module_path, vis),
+                binding: Binding::Item { kind, file_path: file_path.clone(), line: ident.span().start().line },
+            });
+        }
+
+        // ルートより先にマクロが登録されている場合があるので、既存の名前を残す
+        if let Some(existing) = self.modules.remove(module_path) {
+            node.names.extend(existing.names);
+            node.globs.extend(existing.globs);
+            node.imported.extend(existing.imported);
+        }
+        self.modules.insert(module_path.to_string(), node);
+    }
+
+    /// module の中で name が指す定義（再エクスポートをたどる）
+    fn lookup(&self, module: &str, name: &str, depth: usize) -> Vec<Found<'_>> {
+        let Some(node) = self.modules.get(module) else {
+            return Vec::new();
+        };
+        let mut found = Vec::new();
+
+        for named in node.names.iter().filter(|n| n.name == name) {
+            match &named.binding {
+                Binding::Item { kind, file_path, line } => found.push(Found {
+                    kind: *kind,
+                    file_path,
+                    line: *line,
+                    scopes: vec![named.scope.clone()],
+                    reexport: false,
+                }),
+                Binding::Module(_) => {}
+                Binding::Use(path) if depth < MAX_USE_DEPTH => {
+                    for mut target in self.resolve_use(module, path, depth + 1) {
+                        target.scopes.insert(0, named.scope.clone());
+                        target.reexport = true;
+                        found.push(target);
+                    }
+                }
+                Binding::Use(_) => {}
+            }
+        }
+
+        if depth < MAX_USE_DEPTH {
+            for (path, scope) in &node.globs {
+                let Some(target_module) = self.resolve_module(module, path, depth + 1) else {
+                    continue;
+                };
+                for mut target in self.lookup(&target_module, name, depth + 1) {
+                    target.scopes.insert(0, scope.clone());
+                    target.reexport = true;
+                    found.push(target);
+                }
+            }
+        }
+        found
+    }
+
+    /// use の対象（`a::b::Name`）の定義
+    fn resolve_use(&self, module: &str, path: &[String], depth: usize) -> Vec<Found<'_>> {
+        let Some((name, parent)) = path.split_last() else {
+            return Vec::new();
+        };
+        // use a::b::{self} はモジュールなので対象外。依存クレート名から始まる use も追わない
+        if name == "self" || parent.is_empty() {
+            return Vec::new();
+        }
+        match self.resolve_module(module, parent, depth) {
+            Some(parent_module) => self.lookup(&parent_module, name, depth),
+            None => Vec::new(),
+        }
+    }
+
+    /// module から見たパスが指すモジュール（クレート外なら None）
+    fn resolve_module(&self, module: &str, path: &[String], depth: usize) -> Option<String> {
+        let (first, rest) = path.split_first()?;
+        let mut current = match first.as_str() {
+            "crate" => "crate".to_string(),
+            "self" => module.to_string(),
+            "super" => parent_module(module).to_string(),
+            name => self.child_module(module, name, depth)?,
+        };
+        for segment in rest {
+            current = match segment.as_str() {
+                "super" => parent_module(&current).to_string(),
+                name => self.child_module(&current, name, depth)?,
+            };
+        }
+        Some(current)
+    }
+
+    fn child_module(&self, module: &str, name: &str, depth: usize) -> Option<String> {
+        let node = self.modules.get(module)?;
+        node.names.iter()
+            .filter(|n| n.name == name)
+            .find_map(|n| match &n.binding {
+                Binding::Module(path) => Some(path.clone()),
+                Binding::Use(path) if depth < MAX_USE_DEPTH => self.resolve_module(module, path, depth + 1),
+                _ => None,
+            })
+    }
+
+    /// クレートのルートから name の定義に至るパス（"crate" から始まるセグメント）と、その定義
+    fn paths_to(&self, name: &str) -> Vec<(Vec<String>, Found<'_>)> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![("crate".to_string(), vec!["crate".to_string()], Vec::new())];
+
+        while let Some((module, segments, scopes)) = stack.pop() {
+            if !visited.insert(module.clone()) {
+                continue;
+            }
+            for mut found in self.lookup(&module, name, 0) {
+                let mut path = segments.clone();
+                path.push(name.to_string());
+                found.scopes.splice(0..0, scopes.iter().cloned());
+                result.push((path, found));
+            }
+            let Some(node) = self.modules.get(&module) else {
+                continue;
+            };
+            for named in &node.names {
+                if let Binding::Module(child) = &named.binding {
+                    let mut child_segments = segments.clone();
+                    child_segments.push(named.name.clone());
+                    let mut child_scopes = scopes.clone();
+                    child_scopes.push(named.scope.clone());
+                    stack.push((child.clone(), child_segments, child_scopes));
+                }
+            }
+        }
+        result
+    }
+}
+
+pub fn parent_module(module: &str) -> &str {
+    module.rsplit_once("::").map(|(parent, _)| parent).unwrap_or("crate")
+}
+
+/// 可視性から、名前が見える範囲を求める
+fn scope_of(module: &str, vis: &syn::Visibility) -> Scope {
+    let syn::Visibility::Restricted(restricted) = vis else {
+        return match vis {
+            syn::Visibility::Public(_) => Scope::Public,
+            _ => Scope::Within(module.to_string()),
+        };
+    };
+    let mut scope = module.to_string();
+    for segment in &restricted.path.segments {
+        match segment.ident.to_string().as_str() {
+            "crate" => scope = "crate".to_string(),
+            "self" => {}
+            "super" => scope = parent_module(&scope).to_string(),
+            name => scope = format!("{scope}::{name}"),
+        }
+    }
+    Scope::Within(scope)
+}
+
+fn is_visible_from(scope: &Scope, module: &str) -> bool {
+    match scope {
+        Scope::Public => true,
+        Scope::Within(within) => module == within || module.starts_with(&format!("{within}::")),
+    }
+}
+
+/// `mod name;` のファイル（#[path] 属性、name.rs、name/mod.rs の順）
+pub fn find_module_file(file: &Path, module_dir: &Path, name: &str, attrs: &[syn::Attribute]) -> Option<PathBuf> {
+    let candidates = match path_attribute(attrs) {
+        Some(path) => vec![file.parent().unwrap_or(Path::new("")).join(path)],
+        None => vec![module_dir.join(format!("{name}.rs")), module_dir.join(name).join("mod.rs")],
+    };
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// ファイルを含むクレートのディレクトリ（Cargo.toml のあるディレクトリ）
+fn crate_dir(file: &Path) -> Option<PathBuf> {
+    file.ancestors().skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(|dir| dir.to_path_buf())
+}
+
+/// Cargo.toml の [package] の name（コード上の名前なのでハイフンはアンダースコアにする）
+pub fn package_name(cargo_toml: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in cargo_toml.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package && let Some((key, value)) = line.split_once('=') && key.trim() == "name" {
+            return Some(value.trim().trim_matches('"').replace('-', "_"));
+        }
+    }
+    None
+}
+
+/// Cargo.toml の [dependencies] などにクレートがあるか
+fn declares_dependency(cargo_toml: &str, crate_name: &str) -> bool {
+    let mut in_dependencies = false;
+    for line in cargo_toml.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_end_matches(']');
+            // [dependencies.serde] のような書き方
+            if let Some((section, name)) = header.rsplit_once('.')
+                && section.ends_with("dependencies")
+                && name.trim_matches('"').replace('-', "_") == crate_name {
+                return true;
+            }
+            in_dependencies = header.ends_with("dependencies");
+        } else if in_dependencies && let Some((key, _)) = line.split_once('=') && key.trim().trim_matches('"').replace('-', "_") == crate_name {
+            return true;
+        }
+    }
+    false
+}
+
+/// from_file のモジュールから name を使うための use のパスを、短い公開パスを先頭に並べる
+///
+/// 同じクレートのアイテムは `crate::` から始まるパスで、可視性（pub(crate)・pub(super) など）を満たすものだけを返す。
+/// ライブラリクレートを統合テストやバイナリから使う場合や、別のクレートのアイテムは、すべての段が pub のパスだけを返す。
+pub fn suggest_imports(parser: &RustParser, params: &SuggestImportsParams) -> Result<SuggestImportsResponse> {
+    let from_file = Path::new(&params.from_file);
+    let from_canonical = from_file.canonicalize()
+        .with_context(|| format!("File not found: {}", params.from_file))?;
+    let from_crate = crate_dir(from_file).context("No Cargo.toml found for from_file")?;
+    let from_manifest = std::fs::read_to_string(from_crate.join("Cargo.toml")).unwrap_or_default();
+
+    // from_file を含むクレートのルート（src/lib.rs・src/main.rs、どちらにも含まれなければファイル自身）
+    let lib = CrateTree::build(&from_crate.join("src/lib.rs"));
+    let bin = CrateTree::build(&from_crate.join("src/main.rs"));
+    let (own, module_path, own_is_lib) = match (&lib, &bin) {
+        (Some(tree), _) if tree.files.contains_key(&from_canonical) => (None, tree.files[&from_canonical].clone(), true),
+        (_, Some(tree)) if tree.files.contains_key(&from_canonical) => (None, tree.files[&from_canonical].clone(), false),
+        _ => (CrateTree::build(from_file), "crate".to_string(), false),
+    };
+    let own_tree = match (&own, own_is_lib) {
+        (Some(tree), _) => Some(tree),
+        (None, true) => lib.as_ref(),
+        (None, false) => bin.as_ref(),
+    };
+
+    let mut suggestions = Vec::new();
+    let mut add = |tree: &CrateTree, crate_name: Option<&str>, needs_dependency: bool| {
+        for (segments, found) in tree.paths_to(&params.name) {
+            let visible = match crate_name {
+                None => found.scopes.iter().all(|scope| is_visible_from(scope, &module_path)),
+                Some(_) => found.scopes.iter().all(|scope| *scope == Scope::Public),
+            };
+            if !visible {
+                continue;
+            }
+            let mut segments = segments;
+            if let Some(crate_name) = crate_name {
+                segments[0] = crate_name.to_string();
+            }
+            let path = segments.join("::");
+            if suggestions.iter().any(|s: &ImportSuggestion| s.path == path) {
+                continue;
+            }
+            suggestions.push(ImportSuggestion {
+                use_statement: format!("use {path};"),
+                path,
+                kind: found.kind,
+                file_path: found.file_path.to_string(),
+                line: found.line,
+                crate_name: crate_name.map(|name| name.to_string()),
+                public: found.scopes.iter().all(|scope| *scope == Scope::Public),
+                reexport: found.reexport,
+                needs_dependency,
+            });
+        }
+    };
+
+    let mut already_in_scope = false;
+    if let Some(tree) = own_tree {
+        already_in_scope = tree.modules.get(&module_path).is_some_and(|node| {
+            node.imported.contains(&params.name) || node.names.iter().any(|n| n.name == params.name)
+        });
+        add(tree, None, false);
+    }
+    // 同じパッケージのライブラリを、バイナリ・統合テストからクレート名で使う
+    if !own_is_lib
+        && let Some(tree) = &lib
+        && let Some(crate_name) = package_name(&from_manifest) {
+        add(tree, Some(&crate_name), false);
+    }
+
+    // 同名のシンボルがある別のクレート（ワークスペースのメンバーや依存クレート）
+    let from_crate_canonical = from_crate.canonicalize().ok();
+    let mut other_crates: BTreeMap<PathBuf, String> = BTreeMap::new();
+    for symbol in parser.find_symbol(&params.name, None).unwrap_or_default() {
+        let Some(dir) = crate_dir(Path::new(&symbol.file_path)) else {
+            continue;
+        };
+        let Ok(canonical) = dir.canonicalize() else {
+            continue;
+        };
+        if Some(&canonical) == from_crate_canonical.as_ref() || other_crates.contains_key(&canonical) {
+            continue;
+        }
+        let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).unwrap_or_default();
+        if let Some(crate_name) = symbol.crate_name.clone().map(|name| name.replace('-', "_")).or_else(|| package_name(&manifest)) {
+            other_crates.insert(canonical, crate_name);
+        }
+    }
+    for (dir, crate_name) in &other_crates {
+        if let Some(tree) = CrateTree::build(&dir.join("src/lib.rs")) {
+            add(&tree, Some(crate_name), !declares_dependency(&from_manifest, crate_name));
+        }
+    }
+
+    // 公開パスを先に、その中では短い順
+    suggestions.sort_by(|a, b| {
+        (!a.public, a.path.matches("::").count(), &a.path).cmp(&(!b.public, b.path.matches("::").count(), &b.path))
+    });
+    suggestions.truncate(params.limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT));
+
+    Ok(SuggestImportsResponse {
+        name: params.name.clone(),
+        from_file: params.from_file.clone(),
+        module_path: own_tree.map(|_| module_path),
+        already_in_scope,
+        suggestions,
+    })
+}
+
+/// suggest_imports の結果をテキストに整形（CLI・MCP 用）
+pub fn format_suggestions(response: &SuggestImportsResponse) -> String {
+    let module = response.module_path.as_deref().map(|m| format!(" ({m})")).unwrap_or_default();
+    let mut result = format!("Imports for `{}` in {}{}:\n", response.name, response.from_file, module);
+    if response.already_in_scope {
+        result.push_str("  already in scope\n");
+    }
+    if response.suggestions.is_empty() {
+        result.push_str("  no visible path found\n");
+    }
+    for s in &response.suggestions {
+        let mut notes = vec![kind_label(s.kind).to_string()];
+        if s.public {
+            notes.push("public".to_string());
+        }
+        if s.reexport {
+            notes.push("re-export".to_string());
+        }
+        if s.needs_dependency {
+            notes.push(format!("add `{}` to [dependencies]", s.crate_name.as_deref().unwrap_or_default()));
+        }
+        result.push_str(&format!("  {:<50} // {} ({}:{})\n", s.use_statement, notes.join(", "), s.file_path, s.line));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_suggest_imports() {
+        let dir = TempDir::new().unwrap();
+        let files = [
+            ("Cargo.toml", "[package]\nname = \"my-crate\"\n\n[dependencies]\nserde = \"1\"\n"),
+            ("src/lib.rs", "pub mod model;\nmod util;\n\npub use model::user::User;\n"),
+            ("src/model/mod.rs", "pub mod user;\npub(crate) mod internal;\n"),
+            ("src/model/user.rs", "pub struct User;\n\npub(crate) fn validate() {}\n"),
+            ("src/model/internal.rs", "pub fn helper() {}\n"),
+            ("src/util.rs", "use crate::model::user::User;\n\nfn private_helper() {}\n\npub fn make() -> User { User }\n"),
+            ("tests/it.rs", "#[test]\nfn it_works() {}\n"),
+        ];
+        let mut parser = RustParser::new();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            if path.extension().is_some_and(|ext| ext == "rs") {
+                parser.parse_file(&path).unwrap();
+            }
+        }
+
+        let suggest = |name: &str, from: &str| {
+            let params = SuggestImportsParams {
+                name: name.to_string(),
+                from_file: dir.path().join(from).to_string_lossy().to_string(),
+                limit: None,
+            };
+            suggest_imports(&parser, &params).unwrap()
+        };
+        let paths = |response: &SuggestImportsResponse| response.suggestions.iter().map(|s| s.path.clone()).collect::<Vec<_>>();
+
+        // 再エクスポートによる短い公開パスが先頭
+        let response = suggest("User", "src/util.rs");
+        assert_eq!(response.module_path.as_deref(), Some("crate::util"));
+        assert!(response.already_in_scope);
+        assert_eq!(paths(&response), vec!["crate::User", "crate::model::user::User"]);
+        assert!(response.suggestions[0].reexport && response.suggestions[0].public);
+
+        // pub(crate) はクレート内からだけ
+        assert_eq!(paths(&suggest("validate", "src/util.rs")), vec!["crate::model::user::validate"]);
+        assert_eq!(paths(&suggest("helper", "src/util.rs")), vec!["crate::model::internal::helper"]);
+        assert!(suggest("private_helper", "src/lib.rs").suggestions.is_empty());
+
+        // 統合テストはクレート名から始まる公開パスだけ
+        let response = suggest("User", "tests/it.rs");
+        assert_eq!(paths(&response), vec!["my_crate::User", "my_crate::model::user::User"]);
+        assert!(!response.suggestions[0].needs_dependency);
+        assert!(suggest("validate", "tests/it.rs").suggestions.is_empty());
+        assert!(suggest("helper", "tests/it.rs").suggestions.is_empty());
+    }
+}
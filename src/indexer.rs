@@ -1,4 +1,5 @@
-use crate::parser::{RustParser, SymbolInfo};
+use crate::deps::DependencyScan;
+use crate::parser::{ParseOutcome, RustParser, SymbolInfo};
 use crate::protocol::SymbolType;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -12,6 +13,8 @@ pub struct CodeIndexer {
     indexed_files: HashMap<PathBuf, u64>, // ファイルパス -> 最終更新時刻のハッシュ
     watcher: Option<RecommendedWatcher>,
     watch_tx: Option<mpsc::UnboundedSender<notify::Result<Event>>>,
+    index_dependencies: bool,  // 依存クレートのソースもインデックスするか
+    dependency_crates: Vec<(String, String)>,  // インデックス済みの依存クレート (名前, バージョン)
 }
 
 pub type FileWatchReceiver = mpsc::UnboundedReceiver<notify::Result<Event>>;
@@ -23,9 +26,22 @@ impl CodeIndexer {
             indexed_files: HashMap::new(),
             watcher: None,
             watch_tx: None,
+            index_dependencies: false,
+            dependency_crates: Vec::new(),
         }
     }
 
+    /// 依存クレートのソースもインデックスする
+    pub fn with_dependencies(mut self) -> Self {
+        self.index_dependencies = true;
+        self
+    }
+
+    /// 依存クレートのインデックスが有効か
+    pub fn indexes_dependencies(&self) -> bool {
+        self.index_dependencies
+    }
+
     /// ディレクトリを再帰的にインデックス
     pub fn index_directory<P: AsRef<Path>>(&mut self, dir_path: P) -> Result<()> {
         let dir_path = dir_path.as_ref();
@@ -35,8 +51,8 @@ impl CodeIndexer {
         
         let stats = self.get_stats();
         
-        info!("Indexing completed. Found {} symbols ({} functions, {} structs, {} enums, {} traits) in {} files", 
-              stats.total_symbols, stats.total_functions, stats.total_structs, 
+        info!("Indexing completed. Found {} symbols ({} functions, {} methods, {} structs, {} enums, {} traits) in {} files", 
+              stats.total_symbols, stats.total_functions, stats.total_methods, stats.total_structs, 
               stats.total_enums, stats.total_traits, stats.indexed_files_count);
         
         Ok(())
@@ -53,28 +69,87 @@ impl CodeIndexer {
         debug!("Indexing file: {}", file_path.display());
         
         match self.parser.parse_file(file_path) {
-            Ok(()) => {
-                // ファイルのメタデータを記録
-                if let Ok(metadata) = std::fs::metadata(file_path) {
-                    if let Ok(modified) = metadata.modified() {
-                        let hash = self.compute_time_hash(modified);
-                        self.indexed_files.insert(file_path.to_path_buf(), hash);
-                    }
+            Ok(outcome) => {
+                // ファイルのメタデータを記録（パースエラーがあっても監視対象として扱う）
+                if let Ok(metadata) = std::fs::metadata(file_path)
+                    && let Ok(modified) = metadata.modified() {
+                    let hash = self.compute_time_hash(modified);
+                    self.indexed_files.insert(file_path.to_path_buf(), hash);
+                }
+                match outcome {
+                    ParseOutcome::Parsed => debug!("Successfully indexed: {}", file_path.display()),
+                    ParseOutcome::KeptLastGood => warn!("Parse error in {}, keeping last good symbols", file_path.display()),
+                    ParseOutcome::Salvaged { recovered_items } => warn!("Parse error in {}, salvaged {} top-level items", file_path.display(), recovered_items),
+                    ParseOutcome::Failed => warn!("Failed to parse file {}", file_path.display()),
                 }
-                debug!("Successfully indexed: {}", file_path.display());
             }
             Err(e) => {
-                warn!("Failed to parse file {}: {}", file_path.display(), e);
-                // パースエラーがあっても続行
+                warn!("Failed to read file {}: {}", file_path.display(), e);
+                // 読み込みエラーがあっても続行
             }
         }
         
         Ok(())
     }
 
+    /// インデックス済みの依存クレート (名前, バージョン)
+    pub fn dependency_crates(&self) -> &[(String, String)] {
+        &self.dependency_crates
+    }
+
+    /// `deps::scan_dependencies` で読み取った依存クレートの公開 API を登録し、インデックス済みの依存クレート数を返す
+    pub fn add_dependency_scan(&mut self, scan: DependencyScan) -> usize {
+        self.parser.add_external_symbols(scan.symbols);
+        for package in scan.crates {
+            if !self.dependency_crates.contains(&package) {
+                self.dependency_crates.push(package);
+            }
+        }
+        self.dependency_crates.len()
+    }
+
     /// シンボル定義を検索
+    ///
+    /// `tokio::spawn` のように修飾された名前は、先頭セグメントを依存クレート名として絞り込む。
+    /// `crate::` / `self::` / `super::` で始まる場合はプロジェクト内のシンボルのみを返す。
+    /// 残りの修飾（`CodeIndexer::new` の型名やインラインモジュール名）はスコープと照合する。
+    /// 修飾のない名前では、メソッド以外の定義があればそれだけを返す（`new` で全ての型の `new` を返さない）。
     pub fn find_definition(&self, symbol_name: &str, symbol_type: Option<SymbolType>) -> Option<Vec<&SymbolInfo>> {
-        self.parser.find_symbol(symbol_name, symbol_type)
+        let Some((qualifier, name)) = symbol_name.rsplit_once("::") else {
+            let mut symbols = self.parser.find_symbol(symbol_name, symbol_type)?;
+            if symbols.iter().any(|s| !s.is_method) {
+                symbols.retain(|s| !s.is_method);
+            }
+            return Some(symbols);
+        };
+
+        let mut symbols = self.parser.find_symbol(name, symbol_type)?;
+        let mut segments: Vec<&str> = qualifier.split("::").collect();
+        match segments[0] {
+            "crate" | "self" | "super" => {
+                symbols.retain(|s| s.crate_name.is_none());
+                segments.remove(0);
+            }
+            krate if symbols.iter().any(|s| s.crate_name.as_deref() == Some(krate)) => {
+                symbols.retain(|s| s.crate_name.as_deref() == Some(krate));
+                segments.remove(0);
+            }
+            _ => {}
+        }
+
+        // スコープが一致するものがあればそれに絞る（ファイル単位のモジュール名などは一致しないので無視）
+        if !segments.is_empty() {
+            let rest = segments.join("::");
+            let suffix = format!("::{rest}");
+            let scoped: Vec<&SymbolInfo> = symbols.iter()
+                .filter(|s| s.scope.as_deref().is_some_and(|scope| scope == rest || scope.ends_with(&suffix)))
+                .copied()
+                .collect();
+            if !scoped.is_empty() {
+                symbols = scoped;
+            }
+        }
+        Some(symbols)
     }
 
     /// シンボル使用箇所を検索
@@ -82,6 +157,189 @@ impl CodeIndexer {
         self.parser.find_usages(symbol_name, symbol_type)
     }
 
+    /// シンボル名をあいまい検索（ランキング順）
+    pub fn search_symbols(&self, query: &str, symbol_type: Option<SymbolType>, limit: usize) -> Vec<crate::search::SearchMatch<'_>> {
+        crate::search::search_symbols(self.get_all_symbols(), query, symbol_type.as_ref(), limit)
+    }
+
+    /// 指定シンボルを推移的に呼び出しているテストを検索
+    pub fn find_tests_for(&self, symbol_name: &str, max_depth: usize) -> Vec<crate::test_map::TestMatch<'_>> {
+        crate::test_map::find_tests_for(&self.parser, symbol_name, max_depth)
+    }
+
+    /// インデックス済みのテストを取得
+    pub fn get_tests(&self) -> &Vec<crate::parser::TestInfo> {
+        self.parser.get_tests()
+    }
+
+    /// 指定位置のローカル変数・パラメータの束縛と参照を取得（ファイルをその場で解析）
+    pub fn find_local_references<P: AsRef<Path>>(&self, file_path: P, line: usize, column: usize) -> Result<Option<crate::locals::LocalReferences>> {
+        let file_path = file_path.as_ref();
+        let content = std::fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+        let analysis = crate::locals::LocalAnalysis::analyze(&content)?;
+        Ok(analysis.references_at(line, column))
+    }
+
+    /// パースエラーの診断情報を取得
+    pub fn get_diagnostics(&self, file_path: Option<&str>) -> Vec<&crate::parser::DiagnosticInfo> {
+        self.parser.get_diagnostics(file_path)
+    }
+
+    /// unsafe なコードの一覧を取得
+    pub fn get_unsafe_sites(&self) -> &Vec<crate::parser::UnsafeInfo> {
+        self.parser.get_unsafe_sites()
+    }
+
+    /// 根（main・テスト・trait 実装など）から到達できないコードを探す
+    pub fn find_dead_code(&self, params: &crate::protocol::FindDeadCodeParams) -> crate::protocol::FindDeadCodeResponse {
+        let files = self.indexed_files.keys().map(|path| path.as_path());
+        crate::dead_code::find_dead_code(&self.parser, files, params)
+    }
+
+    /// シンボルのソーステキストを取得（名前の解決は find_definition と同じ）
+    pub fn get_source(&self, params: &crate::protocol::GetSourceParams) -> crate::protocol::GetSourceResponse {
+        let symbols: Vec<&SymbolInfo> = self.find_definition(&params.symbol_name, params.symbol_type.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|s| params.file_path.as_deref().is_none_or(|path| s.file_path == path))
+            .filter(|s| params.line.is_none_or(|line| s.line == line))
+            .collect();
+        crate::source::get_source(&symbols, params)
+    }
+
+    /// シンボルの要約を取得（名前の解決は find_definition と同じ）
+    pub fn explain_symbol(&self, params: &crate::protocol::ExplainSymbolParams) -> crate::protocol::ExplainSymbolResponse {
+        let symbols: Vec<&SymbolInfo> = self.find_definition(&params.symbol_name, params.symbol_type.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|s| params.file_path.as_deref().is_none_or(|path| s.file_path == path))
+            .filter(|s| params.line.is_none_or(|line| s.line == line))
+            .collect();
+        crate::explain::explain_symbols(&self.parser, &symbols, params)
+    }
+
+    /// シンボルをリネームするための書き換えを求め、apply なら書き換えたファイルを再インデックスする
+    pub fn rename_symbol(&mut self, params: &crate::protocol::RenameSymbolParams) -> Result<crate::protocol::RenameSymbolResponse> {
+        let mut response = self.plan_rename_symbol(params)?;
+        if params.apply.unwrap_or(false) {
+            for file in crate::rename::apply_edits(&response.edits)? {
+                self.index_file(&file)?;
+            }
+            response.applied = true;
+        }
+        Ok(response)
+    }
+
+    /// rename_symbol の書き換えを求める（ファイルは書き換えない）
+    ///
+    /// 依存クレートのシンボル・構造体のフィールドや、同名の定義が複数あって特定できない場合はエラーにする。
+    pub fn plan_rename_symbol(&self, params: &crate::protocol::RenameSymbolParams) -> Result<crate::protocol::RenameSymbolResponse> {
+        let candidates: Vec<SymbolInfo> = self.find_definition(&params.symbol_name, params.symbol_type.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|s| params.file_path.as_deref().is_none_or(|path| s.file_path == path))
+            .filter(|s| params.line.is_none_or(|line| s.line == line))
+            .cloned()
+            .collect();
+        // フィールドはシンボルとしてインデックスしていないので、同名の別のシンボルをリネームしないよう断る
+        if let Some((qualifier, member)) = params.symbol_name.rsplit_once("::") {
+            let type_name = qualifier.rsplit("::").next().unwrap_or(qualifier);
+            let suffix = format!("::{type_name}");
+            let is_member = candidates.iter()
+                .any(|s| s.scope.as_deref().is_some_and(|scope| scope == type_name || scope.ends_with(&suffix)));
+            let is_field = !is_member && self.find_definition(qualifier, Some(SymbolType::Struct))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|s| s.crate_name.is_none())
+                .any(|s| crate::rename::has_named_field(s, member));
+            if is_field {
+                anyhow::bail!("'{}' is a struct field; renaming fields is not supported", params.symbol_name);
+            }
+        }
+        let symbol = match candidates.as_slice() {
+            [] => anyhow::bail!("Symbol '{}' not found", params.symbol_name),
+            [symbol] => symbol,
+            _ => anyhow::bail!(
+                "'{}' matches {} definitions; specify file_path or line:\n  {}",
+                params.symbol_name,
+                candidates.len(),
+                candidates.iter().map(|s| format!("{}:{}", s.file_path, s.line)).collect::<Vec<_>>().join("\n  "),
+            ),
+        };
+        if let Some(crate_name) = &symbol.crate_name {
+            anyhow::bail!("'{}' is defined in dependency crate '{}' and cannot be renamed", symbol.name, crate_name);
+        }
+
+        let files: Vec<PathBuf> = self.indexed_files.keys().cloned().collect();
+        crate::rename::plan_rename(&self.parser, symbol, &files, &params.new_name)
+    }
+
+    /// 構文パターンに一致する式を探し、apply なら置換を書き込んで再インデックスする
+    pub fn structural_search(&mut self, params: &crate::protocol::StructuralSearchParams) -> Result<crate::protocol::StructuralSearchResponse> {
+        let mut response = self.structural_matches(params)?;
+
+        if params.apply.unwrap_or(false) {
+            let (updated, skipped) = crate::structural_search::apply_replacements(&response.matches)?;
+            for file in updated {
+                self.index_file(&file)?;
+            }
+            response.applied = true;
+            response.skipped_overlapping = skipped;
+        }
+
+        response.matches.truncate(params.limit.unwrap_or(crate::structural_search::DEFAULT_MATCH_LIMIT));
+        Ok(response)
+    }
+
+    /// structural_search の一致をすべて求める（ファイルは書き換えず、件数も絞らない）
+    pub fn structural_matches(&self, params: &crate::protocol::StructuralSearchParams) -> Result<crate::protocol::StructuralSearchResponse> {
+        if params.apply.unwrap_or(false) && params.rewrite.is_none() {
+            anyhow::bail!("apply requires a rewrite template");
+        }
+        let files = crate::structural_search::target_files(self.indexed_files.keys().map(|path| path.as_path()), params.file_path.as_deref());
+        crate::structural_search::search(&files, params)
+    }
+
+    /// symbol_history の対象のシンボル（名前の解決は find_definition と同じ。git を実行する前にロックを外せるよう複製する）
+    pub fn history_symbols(&self, params: &crate::protocol::SymbolHistoryParams) -> Vec<SymbolInfo> {
+        self.find_definition(&params.symbol_name, params.symbol_type.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|s| s.crate_name.is_none())
+            .filter(|s| params.file_path.as_deref().is_none_or(|path| s.file_path == path))
+            .filter(|s| params.line.is_none_or(|line| s.line == line))
+            .cloned()
+            .collect()
+    }
+
+    /// シンボルの行範囲に触れたコミットの履歴を取得
+    pub fn symbol_history(&self, params: &crate::protocol::SymbolHistoryParams) -> Result<crate::protocol::SymbolHistoryResponse> {
+        crate::git_history::symbol_history(&self.history_symbols(params), params)
+    }
+
+    /// get_churn の対象のシンボル
+    pub fn churn_symbols(&self, params: &crate::protocol::GetChurnParams) -> Vec<SymbolInfo> {
+        let files = self.indexed_files.keys().map(|path| path.as_path());
+        crate::git_history::churn_symbols(&self.parser, files, params)
+    }
+
+    /// プロジェクト内のシンボルを変更回数・最終変更日で並べる
+    pub fn get_churn(&self, params: &crate::protocol::GetChurnParams) -> Result<crate::protocol::GetChurnResponse> {
+        crate::git_history::churn_report(&self.churn_symbols(params), params)
+    }
+
+    /// diff の変更行を含むシンボルと、呼び出し元をたどって影響を受ける関数・公開 API・テストを求める
+    pub fn impact_analysis(&self, diff: &str, max_depth: usize) -> crate::protocol::ImpactAnalysisResponse {
+        let files = self.indexed_files.keys().map(|path| path.as_path());
+        crate::impact::analyze(&self.parser, files, diff, max_depth)
+    }
+
+    /// from_file のモジュールから名前を使うための use のパス（可視性・再エクスポート・クレート境界を考慮）
+    pub fn suggest_imports(&self, params: &crate::protocol::SuggestImportsParams) -> Result<crate::protocol::SuggestImportsResponse> {
+        crate::imports::suggest_imports(&self.parser, params)
+    }
+
     /// パーサーへの参照を取得
     pub fn get_parser(&self) -> &crate::parser::RustParser {
         &self.parser
@@ -97,13 +355,23 @@ impl CodeIndexer {
         let all_symbols = self.parser.get_all_symbols();
         
         let mut total_functions = 0;
+        let mut total_methods = 0;
         let mut total_structs = 0;
         let mut total_enums = 0;
         let mut total_traits = 0;
         
+        let mut external_symbols = 0;
+        
         for symbols in all_symbols.values() {
             for symbol in symbols {
+                // 依存クレートのシンボルは別に数える
+                if symbol.crate_name.is_some() {
+                    external_symbols += 1;
+                    continue;
+                }
                 match symbol.symbol_type {
+                    // impl・トレイトのメソッドは関数とは分けて数える
+                    SymbolType::Function if symbol.is_method => total_methods += 1,
                     SymbolType::Function => total_functions += 1,
                     SymbolType::Struct => total_structs += 1,
                     SymbolType::Enum => total_enums += 1,
@@ -112,18 +380,23 @@ impl CodeIndexer {
             }
         }
         
-        let total_symbols = total_functions + total_structs + total_enums + total_traits;
+        let total_symbols = total_functions + total_methods + total_structs + total_enums + total_traits;
         let unique_symbol_names = all_symbols.len();
         let indexed_files_count = self.indexed_files.len();
 
         IndexStats {
             total_symbols,
             total_functions,
+            total_methods,
             total_structs,
             total_enums,
             total_traits,
             unique_symbol_names,
             indexed_files_count,
+            files_with_errors: self.parser.get_recovery_status().len(),
+            external_symbols,
+            dependency_crates: self.dependency_crates.len(),
+            total_tests: self.parser.get_tests().len(),
             is_watching: self.watcher.is_some(),
         }
     }
@@ -154,7 +427,7 @@ impl CodeIndexer {
 
     /// ファイル監視を停止
     pub fn stop_watching(&mut self) {
-        if let Some(watcher) = self.watcher.take() {
+        if self.watcher.take().is_some() {
             info!("Stopping file watcher");
             // Watcherがdropされると自動的に監視停止
         }
@@ -236,10 +509,13 @@ impl CodeIndexer {
     fn reindex_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<()> {
         let file_path = file_path.as_ref();
         
-        // まず古いデータを削除
-        self.remove_file_from_index(file_path);
+        // ファイルが消えている場合は削除として扱う
+        if !file_path.exists() {
+            self.remove_file_from_index(file_path);
+            return Ok(());
+        }
         
-        // 新しくインデックス
+        // パーサー側で古いデータを置き換える（パースエラー時は最後の正常なシンボルを保持）
         self.index_file(file_path)?;
         
         Ok(())
@@ -269,10 +545,11 @@ impl CodeIndexer {
 
             if path.is_dir() {
                 // サブディレクトリを再帰的に処理（.git などは除外）
-                if let Some(dir_name) = path.file_name() {
-                    if !self.should_skip_directory(dir_name.to_string_lossy().as_ref()) {
-                        self.walk_directory(&path)?;
-                    }
+                // 依存クレートとしてインデックスする場合、vendor/ はプロジェクトのコードとして扱わない
+                if let Some(dir_name) = path.file_name()
+                    && !self.should_skip_directory(dir_name.to_string_lossy().as_ref())
+                    && !(self.index_dependencies && dir_name == "vendor") {
+                    self.walk_directory(&path)?;
                 }
             } else if self.is_rust_file(&path) {
                 self.index_file(&path)?;
@@ -309,19 +586,25 @@ impl CodeIndexer {
 pub struct IndexStats {
     pub total_symbols: usize,
     pub total_functions: usize,
+    pub total_methods: usize,
     pub total_structs: usize,
     pub total_enums: usize,
     pub total_traits: usize,
     pub unique_symbol_names: usize,
     pub indexed_files_count: usize,
+    pub files_with_errors: usize,
+    pub external_symbols: usize,
+    pub dependency_crates: usize,
+    pub total_tests: usize,
     pub is_watching: bool,
 }
 
 impl std::fmt::Display for IndexStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "IndexStats {{ total_symbols: {}, functions: {}, structs: {}, enums: {}, traits: {}, unique_names: {}, files: {}, watching: {} }}", 
-               self.total_symbols, self.total_functions, self.total_structs, self.total_enums, 
-               self.total_traits, self.unique_symbol_names, self.indexed_files_count, self.is_watching)
+        write!(f, "IndexStats {{ total_symbols: {}, functions: {}, methods: {}, structs: {}, enums: {}, traits: {}, unique_names: {}, files: {}, files_with_errors: {}, external_symbols: {}, dependency_crates: {}, tests: {}, watching: {} }}", 
+               self.total_symbols, self.total_functions, self.total_methods, self.total_structs, self.total_enums, 
+               self.total_traits, self.unique_symbol_names, self.indexed_files_count, self.files_with_errors,
+               self.external_symbols, self.dependency_crates, self.total_tests, self.is_watching)
     }
 }
 
@@ -350,6 +633,13 @@ pub fn helper() -> i32 {
 pub fn library_function(x: i32) -> i32 {
     x * 2
 }
+
+pub struct Counter;
+
+impl Counter {
+    pub fn helper(&self) -> i32 { 1 }
+    pub fn reset(&mut self) {}
+}
 "#).unwrap();
 
         let mut indexer = CodeIndexer::new();
@@ -357,6 +647,8 @@ pub fn library_function(x: i32) -> i32 {
 
         let stats = indexer.get_stats();
         assert_eq!(stats.total_functions, 3);
+        assert_eq!(stats.total_methods, 2);
+        assert_eq!(stats.total_symbols, 6);
         assert_eq!(stats.indexed_files_count, 2);
         assert!(!stats.is_watching);
 
@@ -364,5 +656,64 @@ pub fn library_function(x: i32) -> i32 {
         let main_funcs = indexer.find_definition("main", Some(SymbolType::Function)).unwrap();
         assert_eq!(main_funcs.len(), 1);
         assert_eq!(main_funcs[0].name, "main");
+
+        // 修飾のない名前はメソッド以外を優先し、メソッドしかなければメソッドを返す
+        let helpers = indexer.find_definition("helper", None).unwrap();
+        assert_eq!(helpers.len(), 1);
+        assert!(!helpers[0].is_method);
+        assert_eq!(indexer.find_definition("Counter::helper", None).unwrap()[0].scope.as_deref(), Some("Counter"));
+        assert!(indexer.find_definition("reset", None).unwrap()[0].is_method);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_index_dependency_sources() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "pub fn helper() {}\n").unwrap();
+        fs::write(dir.path().join("Cargo.lock"), r#"
+[[package]]
+name = "my_app"
+version = "0.1.0"
+
+[[package]]
+name = "tiny-dep"
+version = "1.2.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#).unwrap();
+
+        let dep_dir = dir.path().join("vendor/tiny-dep");
+        fs::create_dir_all(dep_dir.join("src")).unwrap();
+        fs::write(dep_dir.join("Cargo.toml"), "[package]\nname = \"tiny-dep\"\nversion = \"1.2.3\"\n").unwrap();
+        fs::write(dep_dir.join("src/lib.rs"), "pub fn helper() {}\n\nfn private_helper() {}\n\npub struct Config;\n\ncfg_rt! {\n    pub fn spawn() {}\n}\n\nmod internal;\npub use internal::Reexported;\n").unwrap();
+        fs::write(dep_dir.join("src/internal.rs"), "pub struct Reexported;\n\npub fn hidden() {}\n").unwrap();
+
+        let mut indexer = CodeIndexer::new().with_dependencies();
+        indexer.index_directory(dir.path()).unwrap();
+        assert_eq!(indexer.get_stats().external_symbols, 0);
+        let scan = crate::deps::scan_dependencies(dir.path(), indexer.dependency_crates()).unwrap();
+        assert_eq!(indexer.add_dependency_scan(scan), 1);
+
+        let stats = indexer.get_stats();
+        assert_eq!(stats.total_functions, 1);
+        assert_eq!(stats.external_symbols, 4);
+        assert_eq!(stats.dependency_crates, 1);
+        assert_eq!(stats.indexed_files_count, 1);
+
+        assert_eq!(indexer.find_definition("helper", None).unwrap().len(), 2);
+        assert!(indexer.find_definition("private_helper", None).is_none());
+        // 非公開モジュールの pub アイテムは pub use されたものだけ
+        assert!(indexer.find_definition("hidden", None).is_none());
+        assert_eq!(indexer.find_definition("tiny_dep::Reexported", None).unwrap().len(), 1);
+
+        let external = indexer.find_definition("tiny_dep::helper", None).unwrap();
+        assert_eq!(external.len(), 1);
+        assert_eq!(external[0].crate_name.as_deref(), Some("tiny_dep"));
+
+        let local = indexer.find_definition("crate::helper", None).unwrap();
+        assert_eq!(local.len(), 1);
+        assert!(local[0].crate_name.is_none());
+
+        // マクロに包まれたアイテムも登録される
+        assert_eq!(indexer.find_definition("tiny_dep::spawn", None).unwrap().len(), 1);
+    }
+}
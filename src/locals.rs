@@ -0,0 +1,562 @@
+use crate::protocol::{AccessKind, BindingKind};
+use anyhow::{Context, Result};
+use proc_macro2::Span;
+use std::collections::{HashMap, HashSet};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+
+/// ローカル変数・パラメータの束縛
+#[derive(Debug, Clone)]
+pub struct LocalBindingInfo {
+    pub name: String,
+    pub kind: BindingKind,
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+    pub is_mutable: bool,  // `mut` 付きで宣言されたか
+}
+
+/// 束縛への参照（束縛位置自身も含む）
+#[derive(Debug, Clone)]
+pub struct LocalReferenceInfo {
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+    pub access: AccessKind,
+}
+
+/// 位置指定で解決したローカル変数の参照一覧
+#[derive(Debug, Clone)]
+pub struct LocalReferences {
+    pub binding: LocalBindingInfo,
+    pub references: Vec<LocalReferenceInfo>,
+    pub is_mutated: bool,
+}
+
+/// ファイル内のすべての関数本体についてスコープ解決した結果
+pub struct LocalAnalysis {
+    bindings: Vec<LocalBindingInfo>,
+    references: Vec<(usize, LocalReferenceInfo)>,  // (束縛のインデックス, 参照)
+}
+
+impl LocalAnalysis {
+    /// ソースを解析してローカル変数の束縛と参照を解決
+    pub fn analyze(content: &str) -> Result<Self> {
+        let syntax_tree = syn::parse_file(content).context("Failed to parse file")?;
+        let mut path_names = PathNames::default();
+        path_names.visit_file(&syntax_tree);
+        let mut resolver = LocalResolver {
+            path_names,
+            scopes: Vec::new(),
+            bindings: Vec::new(),
+            references: Vec::new(),
+        };
+        resolver.visit_file(&syntax_tree);
+
+        Ok(Self {
+            bindings: resolver.bindings,
+            references: resolver.references,
+        })
+    }
+
+    /// 指定位置（1ベースの行、0ベースの列）にある束縛または参照から、同じ束縛の参照をすべて返す
+    pub fn references_at(&self, line: usize, column: usize) -> Option<LocalReferences> {
+        let (binding_idx, _) = self.references.iter()
+            .find(|(_, r)| r.line == line && r.column <= column && column < r.end_column)?;
+
+        let references: Vec<LocalReferenceInfo> = self.references.iter()
+            .filter(|(idx, _)| idx == binding_idx)
+            .map(|(_, r)| r.clone())
+            .collect();
+        let binding = self.bindings[*binding_idx].clone();
+        let is_mutated = references.iter().any(|r| match r.access {
+            AccessKind::Write | AccessKind::MutableBorrow => true,
+            // 型情報がないため、mut 束縛のレシーバー使用は変更とみなす
+            AccessKind::MethodReceiver => binding.is_mutable,
+            AccessKind::Binding | AccessKind::Read => false,
+        });
+
+        Some(LocalReferences { binding, references, is_mutated })
+    }
+}
+
+/// パターンに書かれたときに束縛ではなくパスになる名前（定数・static・ユニット構造体・バリアントと、use した名前）
+#[derive(Default)]
+struct PathNames {
+    names: HashSet<String>,
+    has_glob_import: bool,  // use a::*; があると取り込まれる名前が分からない
+}
+
+impl PathNames {
+    /// パターン中の識別子がパスか。ファイル内で分からない名前は、グロブ import があるときだけ大文字始まりをパスとみなす
+    fn is_path(&self, name: &str) -> bool {
+        self.names.contains(name) || name == "None" || (self.has_glob_import && name.starts_with(char::is_uppercase))
+    }
+}
+
+impl<'ast> Visit<'ast> for PathNames {
+    fn visit_item_const(&mut self, item: &'ast syn::ItemConst) {
+        self.names.insert(item.ident.to_string());
+        visit::visit_item_const(self, item);
+    }
+
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        self.names.insert(item.ident.to_string());
+        visit::visit_item_static(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        if matches!(item.fields, syn::Fields::Unit) {
+            self.names.insert(item.ident.to_string());
+        }
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_variant(&mut self, variant: &'ast syn::Variant) {
+        self.names.insert(variant.ident.to_string());
+        visit::visit_variant(self, variant);
+    }
+
+    fn visit_use_name(&mut self, name: &'ast syn::UseName) {
+        self.names.insert(name.ident.to_string());
+    }
+
+    fn visit_use_rename(&mut self, rename: &'ast syn::UseRename) {
+        self.names.insert(rename.rename.to_string());
+    }
+
+    fn visit_use_glob(&mut self, _glob: &'ast syn::UseGlob) {
+        self.has_glob_import = true;
+    }
+}
+
+/// 関数本体を走査し、識別子の使用を最も内側の束縛に結びつける
+struct LocalResolver {
+    path_names: PathNames,
+    scopes: Vec<HashMap<String, usize>>,  // 名前 -> 束縛のインデックス（後の束縛がシャドーイングする）
+    bindings: Vec<LocalBindingInfo>,
+    references: Vec<(usize, LocalReferenceInfo)>,
+}
+
+impl LocalResolver {
+    fn with_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// 関数本体は外側のローカル変数を参照できないので、スコープを切り替えて解析
+    fn with_fn_body(&mut self, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::take(&mut self.scopes);
+        self.with_scope(f);
+        self.scopes = outer;
+    }
+
+    fn bind(&mut self, ident: &syn::Ident, kind: BindingKind, is_mutable: bool) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        let (line, column, end_column) = ident_span(ident.span());
+        let idx = self.bindings.len();
+        let name = ident.to_string();
+        scope.insert(name.clone(), idx);
+        self.bindings.push(LocalBindingInfo { name, kind, line, column, end_column, is_mutable });
+        self.references.push((idx, LocalReferenceInfo { line, column, end_column, access: AccessKind::Binding }));
+    }
+
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+
+    fn add_reference(&mut self, name: &str, span: Span, access: AccessKind) {
+        if let Some(idx) = self.lookup(name) {
+            let (line, column, end_column) = ident_span(span);
+            self.references.push((idx, LocalReferenceInfo { line, column, end_column, access }));
+        }
+    }
+
+    /// パターン中の識別子を束縛として登録
+    fn bind_pattern(&mut self, pat: &syn::Pat, kind: BindingKind) {
+        match pat {
+            syn::Pat::Ident(pat_ident) => {
+                // `None` や定数などのパスパターンは束縛ではない
+                let looks_like_path = self.path_names.is_path(&pat_ident.ident.to_string())
+                    && pat_ident.mutability.is_none()
+                    && pat_ident.by_ref.is_none()
+                    && pat_ident.subpat.is_none();
+                if !looks_like_path {
+                    self.bind(&pat_ident.ident, kind, pat_ident.mutability.is_some());
+                }
+                if let Some((_, subpat)) = &pat_ident.subpat {
+                    self.bind_pattern(subpat, kind);
+                }
+            }
+            syn::Pat::Or(pat_or) => {
+                // 各選択肢は同じ名前を束縛するので、2つ目以降は最初の束縛への参照とする
+                let mut cases = pat_or.cases.iter();
+                if let Some(first) = cases.next() {
+                    self.bind_pattern(first, kind);
+                }
+                for case in cases {
+                    self.reference_pattern(case);
+                }
+            }
+            syn::Pat::Paren(p) => self.bind_pattern(&p.pat, kind),
+            syn::Pat::Reference(p) => self.bind_pattern(&p.pat, kind),
+            syn::Pat::Type(p) => self.bind_pattern(&p.pat, kind),
+            syn::Pat::Slice(p) => p.elems.iter().for_each(|e| self.bind_pattern(e, kind)),
+            syn::Pat::Tuple(p) => p.elems.iter().for_each(|e| self.bind_pattern(e, kind)),
+            syn::Pat::TupleStruct(p) => p.elems.iter().for_each(|e| self.bind_pattern(e, kind)),
+            syn::Pat::Struct(p) => p.fields.iter().for_each(|f| self.bind_pattern(&f.pat, kind)),
+            _ => {}
+        }
+    }
+
+    fn reference_pattern(&mut self, pat: &syn::Pat) {
+        struct PatIdents<'a>(Vec<&'a syn::Ident>);
+        impl<'a> Visit<'a> for PatIdents<'a> {
+            fn visit_pat_ident(&mut self, pat_ident: &'a syn::PatIdent) {
+                self.0.push(&pat_ident.ident);
+                visit::visit_pat_ident(self, pat_ident);
+            }
+        }
+
+        let mut idents = PatIdents(Vec::new());
+        idents.visit_pat(pat);
+        for ident in idents.0 {
+            self.add_reference(&ident.to_string(), ident.span(), AccessKind::Binding);
+        }
+    }
+
+    /// 代入先・可変借用の対象となる式。`x.field = ..` や `x[i] = ..` も x への書き込みとする
+    fn visit_place(&mut self, expr: &syn::Expr, access: AccessKind) {
+        match expr {
+            syn::Expr::Path(path_expr) if path_expr.qself.is_none() && path_expr.path.get_ident().is_some() => {
+                if let Some(ident) = path_expr.path.get_ident() {
+                    self.add_reference(&ident.to_string(), ident.span(), access);
+                }
+            }
+            syn::Expr::Field(field) => self.visit_place(&field.base, access),
+            syn::Expr::Index(index) => {
+                self.visit_place(&index.expr, access);
+                self.visit_expr(&index.index);
+            }
+            syn::Expr::Paren(paren) => self.visit_place(&paren.expr, access),
+            syn::Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Deref(_)) => self.visit_place(&unary.expr, access),
+            other => self.visit_expr(other),
+        }
+    }
+
+    fn bind_fn_inputs<'a>(&mut self, inputs: impl IntoIterator<Item = &'a syn::FnArg>) {
+        for input in inputs {
+            match input {
+                syn::FnArg::Receiver(receiver) => {
+                    let ident = syn::Ident::new("self", receiver.self_token.span);
+                    self.bind(&ident, BindingKind::Parameter, receiver.mutability.is_some());
+                }
+                syn::FnArg::Typed(pat_type) => self.bind_pattern(&pat_type.pat, BindingKind::Parameter),
+            }
+        }
+    }
+
+    /// format! 系のインライン引数（"{name}" / "{name:?}"）を参照として登録
+    fn visit_format_string(&mut self, lit: &syn::LitStr) {
+        // トークンはソースのままの表記なので、r#" などの開きの部分を飛ばして中身だけを走査する
+        let text = lit.token().to_string();
+        let raw = text.starts_with('r');
+        let (Some(open), Some(close)) = (text.find('"'), text.rfind('"')) else {
+            return;
+        };
+        if close <= open {
+            return;
+        }
+        let start = lit.span().start();
+        let (mut line, mut column) = (start.line, start.column + text[..=open].chars().count());
+        let chars: Vec<char> = text[open + 1..close].chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let mut consumed = 1;
+            if !raw && chars[i] == '\\' {
+                // エスケープ（\u{7b} など）の中の括弧は書式の指定ではない
+                consumed = match chars.get(i + 1) {
+                    Some('u') => chars[i..].iter().position(|c| *c == '}').map_or(2, |end| end + 1),
+                    Some(_) => 2,
+                    None => 1,
+                };
+            } else if chars[i] == '{' && chars.get(i + 1) == Some(&'{') {
+                consumed = 2;
+            } else if chars[i] == '{' {
+                let name: String = chars[i + 1..].iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_')
+                    .collect();
+                let terminated = matches!(chars.get(i + 1 + name.chars().count()), Some('}') | Some(':'));
+                if terminated && name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                    let name_start = column + 1;
+                    if let Some(idx) = self.lookup(&name) {
+                        self.references.push((idx, LocalReferenceInfo {
+                            line,
+                            column: name_start,
+                            end_column: name_start + name.chars().count(),
+                            access: AccessKind::Read,
+                        }));
+                    }
+                }
+            }
+
+            for c in &chars[i..(i + consumed).min(chars.len())] {
+                if *c == '\n' {
+                    line += 1;
+                    column = 0;
+                } else {
+                    column += 1;
+                }
+            }
+            i += consumed;
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for LocalResolver {
+    fn visit_item_fn(&mut self, item_fn: &'ast syn::ItemFn) {
+        self.with_fn_body(|this| {
+            this.bind_fn_inputs(&item_fn.sig.inputs);
+            this.visit_block(&item_fn.block);
+        });
+    }
+
+    fn visit_impl_item_fn(&mut self, item_fn: &'ast syn::ImplItemFn) {
+        self.with_fn_body(|this| {
+            this.bind_fn_inputs(&item_fn.sig.inputs);
+            this.visit_block(&item_fn.block);
+        });
+    }
+
+    fn visit_trait_item_fn(&mut self, item_fn: &'ast syn::TraitItemFn) {
+        if let Some(block) = &item_fn.default {
+            self.with_fn_body(|this| {
+                this.bind_fn_inputs(&item_fn.sig.inputs);
+                this.visit_block(block);
+            });
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.with_scope(|this| visit::visit_block(this, block));
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        // 初期化式は束縛前のスコープで解決する（`let x = x + 1;`）
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        self.bind_pattern(&local.pat, BindingKind::Local);
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        self.with_scope(|this| {
+            for input in &closure.inputs {
+                this.bind_pattern(input, BindingKind::Parameter);
+            }
+            this.visit_expr(&closure.body);
+        });
+    }
+
+    fn visit_expr_match(&mut self, expr_match: &'ast syn::ExprMatch) {
+        self.visit_expr(&expr_match.expr);
+        for arm in &expr_match.arms {
+            self.with_scope(|this| {
+                this.bind_pattern(&arm.pat, BindingKind::Pattern);
+                if let Some((_, guard)) = &arm.guard {
+                    this.visit_expr(guard);
+                }
+                this.visit_expr(&arm.body);
+            });
+        }
+    }
+
+    fn visit_expr_if(&mut self, expr_if: &'ast syn::ExprIf) {
+        // `if let` の束縛は then 節でのみ有効
+        self.with_scope(|this| {
+            this.visit_expr(&expr_if.cond);
+            this.visit_block(&expr_if.then_branch);
+        });
+        if let Some((_, else_branch)) = &expr_if.else_branch {
+            self.visit_expr(else_branch);
+        }
+    }
+
+    fn visit_expr_while(&mut self, expr_while: &'ast syn::ExprWhile) {
+        self.with_scope(|this| {
+            this.visit_expr(&expr_while.cond);
+            this.visit_block(&expr_while.body);
+        });
+    }
+
+    fn visit_expr_let(&mut self, expr_let: &'ast syn::ExprLet) {
+        self.visit_expr(&expr_let.expr);
+        self.bind_pattern(&expr_let.pat, BindingKind::Pattern);
+    }
+
+    fn visit_expr_for_loop(&mut self, for_loop: &'ast syn::ExprForLoop) {
+        self.visit_expr(&for_loop.expr);
+        self.with_scope(|this| {
+            this.bind_pattern(&for_loop.pat, BindingKind::Pattern);
+            this.visit_block(&for_loop.body);
+        });
+    }
+
+    fn visit_expr_path(&mut self, path_expr: &'ast syn::ExprPath) {
+        if path_expr.qself.is_none()
+            && let Some(ident) = path_expr.path.get_ident() {
+            self.add_reference(&ident.to_string(), ident.span(), AccessKind::Read);
+        }
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast syn::ExprAssign) {
+        self.visit_expr(&assign.right);
+        self.visit_place(&assign.left, AccessKind::Write);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        let is_compound_assign = matches!(binary.op,
+            syn::BinOp::AddAssign(_) | syn::BinOp::SubAssign(_) | syn::BinOp::MulAssign(_)
+            | syn::BinOp::DivAssign(_) | syn::BinOp::RemAssign(_) | syn::BinOp::BitXorAssign(_)
+            | syn::BinOp::BitAndAssign(_) | syn::BinOp::BitOrAssign(_) | syn::BinOp::ShlAssign(_)
+            | syn::BinOp::ShrAssign(_));
+        if is_compound_assign {
+            self.visit_expr(&binary.right);
+            self.visit_place(&binary.left, AccessKind::Write);
+        } else {
+            visit::visit_expr_binary(self, binary);
+        }
+    }
+
+    fn visit_expr_reference(&mut self, reference: &'ast syn::ExprReference) {
+        if reference.mutability.is_some() {
+            self.visit_place(&reference.expr, AccessKind::MutableBorrow);
+        } else {
+            visit::visit_expr_reference(self, reference);
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        self.visit_place(&method_call.receiver, AccessKind::MethodReceiver);
+        for arg in &method_call.args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // println!(...) / vec![...] など、式のカンマ区切りとして解釈できるマクロのみ解析
+        let Ok(args) = mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) else {
+            return;
+        };
+        for arg in &args {
+            if let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) = arg {
+                self.visit_format_string(lit);
+            } else {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// 識別子のスパン（行、開始列、終了列）
+fn ident_span(span: Span) -> (usize, usize, usize) {
+    let start = span.start();
+    let end = span.end();
+    let end_column = if end.line == start.line { end.column } else { start.column + 1 };
+    (start.line, start.column, end_column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"fn process(items: Vec<i32>, limit: usize) -> usize {
+    let mut total = 0;
+    for item in &items {
+        if let Some(x) = item.checked_mul(2) {
+            total += x as usize;
+        }
+    }
+    let total = total.min(limit);
+    println!("{total} of {}", items.len());
+    total
+}
+"#;
+
+    #[test]
+    fn test_parameter_references() {
+        let analysis = LocalAnalysis::analyze(SOURCE).unwrap();
+
+        // パラメータ `items` の定義位置から検索
+        let refs = analysis.references_at(1, 12).unwrap();
+        assert_eq!(refs.binding.name, "items");
+        assert_eq!(refs.binding.kind, BindingKind::Parameter);
+        let lines: Vec<usize> = refs.references.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![1, 3, 9]);
+        assert!(!refs.is_mutated);
+    }
+
+    #[test]
+    fn test_shadowing_and_mutation() {
+        let analysis = LocalAnalysis::analyze(SOURCE).unwrap();
+
+        // 最初の `total` は += で変更され、シャドーイング後の初期化式で読まれる
+        let first = analysis.references_at(5, 13).unwrap();
+        assert_eq!((first.binding.line, first.binding.column), (2, 12));
+        assert!(first.is_mutated);
+        let accesses: Vec<(usize, AccessKind)> = first.references.iter().map(|r| (r.line, r.access)).collect();
+        assert_eq!(accesses, vec![(2, AccessKind::Binding), (5, AccessKind::Write), (8, AccessKind::MethodReceiver)]);
+
+        // シャドーイングした `total` はフォーマット文字列内と末尾で参照される
+        let second = analysis.references_at(10, 4).unwrap();
+        assert_eq!(second.binding.line, 8);
+        assert!(!second.is_mutated);
+        let positions: Vec<(usize, usize)> = second.references.iter().map(|r| (r.line, r.column)).collect();
+        assert_eq!(positions, vec![(8, 8), (9, 15), (10, 4)]);
+
+        // if let の束縛
+        let x = analysis.references_at(4, 20).unwrap();
+        assert_eq!(x.binding.kind, BindingKind::Pattern);
+        assert_eq!(x.references.len(), 2);
+
+        // ローカル変数以外の位置
+        assert!(analysis.references_at(1, 3).is_none());
+    }
+
+    #[test]
+    fn test_uppercase_bindings_and_raw_format_strings() {
+        let source = r##"const LIMIT: u32 = 3;
+
+fn check(value: Option<u32>) {
+    match value {
+        Some(LIMIT) => {}
+        Some(N) => println!(r#"{N} \u{ab}"#),
+        None => {}
+    }
+    let ab = 1;
+    println!("\u{ab} {ab}");
+}
+"##;
+        let analysis = LocalAnalysis::analyze(source).unwrap();
+
+        // 定数はパスだが、ファイル内で定義されていない大文字の名前は束縛
+        assert!(analysis.references_at(5, 13).is_none());
+        let n = analysis.references_at(6, 13).unwrap();
+        assert_eq!(n.binding.name, "N");
+        // r#" の後ろの {N} の位置
+        let positions: Vec<(usize, usize)> = n.references.iter().map(|r| (r.line, r.column)).collect();
+        assert_eq!(positions, vec![(6, 13), (6, 32)]);
+
+        // エスケープの \u{ab} は参照ではない
+        let ab = analysis.references_at(9, 8).unwrap();
+        let positions: Vec<(usize, usize)> = ab.references.iter().map(|r| (r.line, r.column)).collect();
+        assert_eq!(positions, vec![(9, 8), (10, 22)]);
+    }
+}
@@ -6,6 +6,29 @@ mod client;
 mod mcp_client;
 mod web_ui;
 mod graph;
+mod search;
+mod deps;
+mod locals;
+mod test_map;
+mod unsafe_audit;
+mod panic_audit;
+mod metrics;
+mod dead_code;
+mod api_surface;
+mod async_audit;
+mod error_flow;
+mod trait_impls;
+mod serde_schema;
+mod source;
+mod explain;
+mod rename;
+mod structural_search;
+mod semantic_diff;
+mod git_history;
+mod impact;
+mod imports;
+mod module_graph;
+mod architecture;
 
 use clap::{Parser, Subcommand};
 use server::{CodeIntelServer, DEFAULT_PORT};
@@ -48,6 +71,10 @@ enum Commands {
         /// Open browser automatically when web UI is enabled
         #[arg(long)]
         open: bool,
+        
+        /// Also index public APIs of dependencies found offline in vendor/ or the cargo registry
+        #[arg(long)]
+        index_deps: bool,
     },
     /// Generate call graph visualization
     Graph {
@@ -55,7 +82,7 @@ enum Commands {
         #[arg(default_value = ".")]
         project_path: PathBuf,
         
-        /// Focus on specific function
+        /// Focus on specific function (--kind calls only)
         #[arg(short, long)]
         function: Option<String>,
         
@@ -63,13 +90,415 @@ enum Commands {
         #[arg(short = 'o', long, default_value = "tree")]
         format: String,
         
-        /// Maximum depth for call graph
-        #[arg(short, long, default_value_t = 10)]
-        depth: usize,
+        /// Maximum depth for call graph (--kind calls only, default 10)
+        #[arg(short, long)]
+        depth: Option<usize>,
         
-        /// Show only callers (reverse call graph)
+        /// Show only callers (reverse call graph, --kind calls only)
         #[arg(long)]
         callers: bool,
+
+        /// Graph kind: calls (function call graph) or modules (module dependencies from `use` and qualified paths)
+        #[arg(long, default_value = "calls")]
+        kind: String,
+    },
+    /// Report unsafe code and whether each site has a SAFETY comment
+    UnsafeReport {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Only report unsafe code without a SAFETY comment
+        #[arg(long)]
+        undocumented_only: bool,
+
+        /// Exclude unsafe code in tests
+        #[arg(long)]
+        exclude_tests: bool,
+    },
+    /// Show per-function complexity and size metrics
+    Metrics {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (table, json)
+        #[arg(short = 'o', long, default_value = "table")]
+        format: String,
+
+        /// Metric to sort by, descending (complexity, lines, nesting, params, returns, fan_in, fan_out)
+        #[arg(short, long, default_value = "complexity")]
+        sort: String,
+
+        /// Maximum number of functions to show
+        #[arg(short = 'n', long, default_value_t = metrics::DEFAULT_METRICS_LIMIT)]
+        limit: usize,
+
+        /// Include test functions
+        #[arg(long)]
+        include_tests: bool,
+    },
+    /// Find functions, types and modules unreachable from main, tests, trait impls and public library API
+    DeadCode {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Minimum confidence to report (low, medium, high)
+        #[arg(long, default_value = "low")]
+        min_confidence: String,
+
+        /// Include unreachable test code
+        #[arg(long)]
+        include_tests: bool,
+    },
+    /// Show the crate's public API, or diff it between git revisions
+    Api {
+        /// Crate path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Git revision to read (default: working tree)
+        #[arg(short, long)]
+        revision: Option<String>,
+
+        /// Diff against this base git revision and classify changes as breaking, additive or patch
+        #[arg(long)]
+        diff: Option<String>,
+    },
+    /// Find blocking calls reachable from async fns, lock guards held across .await and unawaited futures
+    AsyncHazards {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Include test code
+        #[arg(long)]
+        include_tests: bool,
+    },
+    /// Show functions returning Result or Option with their error types and ? conversions
+    Errors {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Show where this error type can originate instead of the error map
+        #[arg(long, conflicts_with = "swallowed")]
+        origin: Option<String>,
+
+        /// Show callers that swallow errors with let _ =, .ok() or .unwrap_or_default()
+        #[arg(long)]
+        swallowed: bool,
+
+        /// Limit the map or swallowed errors to this function
+        #[arg(short, long)]
+        function: Option<String>,
+
+        /// Include test code
+        #[arg(long)]
+        include_tests: bool,
+    },
+    /// Show which traits each struct and enum gets through #[derive(...)] and manual impl blocks
+    Impls {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// List the types implementing this trait instead of the matrix
+        #[arg(long = "trait", conflicts_with = "traits")]
+        trait_name: Option<String>,
+
+        /// Traits to use as matrix columns (comma separated, default: every implemented trait)
+        #[arg(long, value_delimiter = ',')]
+        traits: Vec<String>,
+
+        /// Include test code
+        #[arg(long)]
+        include_tests: bool,
+    },
+    /// Generate a JSON Schema for a struct or enum deriving Serialize/Deserialize
+    Schema {
+        /// Name of the struct or enum
+        type_name: String,
+
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text prints the schema, json the full response)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// File defining the type, when several types share the name
+        #[arg(long)]
+        file: Option<String>,
+    },
+    /// Print the source text of a symbol
+    Source {
+        /// Name of the symbol, optionally qualified (e.g. CodeIndexer::new)
+        symbol_name: String,
+
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Only symbols defined in this file
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Only the symbol defined at this line
+        #[arg(long)]
+        line: Option<usize>,
+
+        /// Include doc comments and attributes
+        #[arg(short, long)]
+        attributes: bool,
+
+        /// Number of surrounding lines to include
+        #[arg(short = 'C', long, default_value = "0")]
+        context: usize,
+
+        /// Print only the signature without the body
+        #[arg(short, long)]
+        signature: bool,
+
+        /// Print only the signature when the item is longer than this many lines
+        #[arg(long)]
+        max_lines: Option<usize>,
+    },
+    /// Summarize a symbol: signature, docs, owner, traits, callers, callees and tests
+    Explain {
+        /// Name of the symbol, optionally qualified (e.g. CodeIndexer::new)
+        symbol_name: String,
+
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Only symbols defined in this file
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Only the symbol defined at this line
+        #[arg(long)]
+        line: Option<usize>,
+
+        /// Number of caller, callee and test names to list
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+    /// Rename a symbol across the project (dry run unless --apply)
+    Rename {
+        /// Name of the symbol, optionally qualified (e.g. CodeIndexer::new)
+        symbol_name: String,
+
+        /// New identifier
+        new_name: String,
+
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// File defining the symbol, when several share the name
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Definition line, when several share the name
+        #[arg(long)]
+        line: Option<usize>,
+
+        /// Write the edits to disk
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Structural search and replace over Rust expressions (e.g. '$x.unwrap()')
+    Ssr {
+        /// Rust expression pattern with $metavariables ($x, $x:lit, $x:str, $x:ident, $..xs, $_)
+        pattern: String,
+
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Replacement template using the pattern's metavariables
+        #[arg(short, long)]
+        rewrite: Option<String>,
+
+        /// Only search this file
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Maximum number of matches to print
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+
+        /// Write the rewrites to disk
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Show added, removed, moved and changed symbols and call edges between git revisions
+    Diff {
+        /// Base git revision
+        from: String,
+
+        /// Target git revision (default: working tree)
+        to: Option<String>,
+
+        /// Project path to analyze
+        #[arg(short = 'p', long, default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+    },
+    /// List the commits that touched a symbol, with churn, authors and age
+    History {
+        /// Name of the symbol, optionally qualified (e.g. CodeIndexer::new)
+        symbol_name: String,
+
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Only symbols defined in this file
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Only the symbol defined at this line
+        #[arg(long)]
+        line: Option<usize>,
+
+        /// Maximum number of commits to list per symbol
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+    /// Rank symbols by git churn (commits touching them) or by when they last changed
+    Churn {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (table, json)
+        #[arg(short = 'o', long, default_value = "table")]
+        format: String,
+
+        /// Sort order (churn, recent, oldest)
+        #[arg(short, long, default_value = "churn")]
+        sort: String,
+
+        /// Maximum number of symbols to show
+        #[arg(short = 'n', long, default_value_t = git_history::DEFAULT_CHURN_LIMIT)]
+        limit: usize,
+
+        /// Only symbols in this file
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Include test code
+        #[arg(long)]
+        include_tests: bool,
+    },
+    /// Map a unified diff to changed symbols and report impacted functions, public APIs and tests
+    Impact {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Unified diff file to read ("-" for stdin); defaults to `git diff` against --base
+        #[arg(long)]
+        diff: Option<PathBuf>,
+
+        /// Base git revision when no diff is given
+        #[arg(long, default_value = "HEAD")]
+        base: String,
+
+        /// Maximum number of caller hops to follow
+        #[arg(short, long, default_value_t = impact::DEFAULT_IMPACT_DEPTH)]
+        depth: usize,
+    },
+    /// Suggest valid `use` paths for a name as seen from a file
+    Imports {
+        /// Name to import
+        name: String,
+
+        /// File where the `use` will be written (relative to the project path)
+        #[arg(long = "from")]
+        from_file: PathBuf,
+
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
+
+        /// Maximum number of suggestions
+        #[arg(short = 'n', long, default_value_t = imports::DEFAULT_SUGGESTION_LIMIT)]
+        limit: usize,
+    },
+    /// Check module dependencies against layering rules; exits non-zero on violations
+    CheckArchitecture {
+        /// Project path to analyze
+        #[arg(default_value = ".")]
+        project_path: PathBuf,
+
+        /// Rule file (default: architecture.json in the project path)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(short = 'o', long, default_value = "text")]
+        format: String,
     },
     /// Run as MCP client (for Claude Code integration)
     McpClient {
@@ -101,13 +530,16 @@ async fn main() -> Result<(), anyhow::Error> {
     
 
     match cli.command {
-        Commands::Serve { project_path, port, web_ui, web_port, open } => {
+        Commands::Serve { project_path, port, web_ui, web_port, open, index_deps } => {
             info!("Starting code_intel server for project: {}", project_path.display());
             
+            let server = CodeIntelServer::new(project_path.clone());
+            let server = if index_deps { server.with_dependency_indexing() } else { server };
+            
             if web_ui {
                 // Web UIを有効にして起動
                 let (web_server, log_sender) = WebUIServer::new(port);
-                let server = CodeIntelServer::new(project_path.clone()).with_web_ui(log_sender);
+                let server = server.with_web_ui(log_sender);
                 
                 // Web UIサーバーを別タスクで起動
                 let web_port_clone = web_port;
@@ -164,8 +596,6 @@ async fn main() -> Result<(), anyhow::Error> {
                 }
             } else {
                 // 通常モード
-                let server = CodeIntelServer::new(project_path.clone());
-                
                 // サーバーを別タスクで起動
                 let port_clone = port;
                 let server_task = tokio::spawn(async move {
@@ -194,8 +624,114 @@ async fn main() -> Result<(), anyhow::Error> {
             let mcp_client = McpClient::new(port);
             mcp_client.run_stdio().await
         }
-        Commands::Graph { project_path, function, format, depth, callers } => {
-            generate_call_graph(project_path, function, format, depth, callers).await
+        Commands::Graph { project_path, function, format, depth, callers, kind } => {
+            match kind.as_str() {
+                "calls" => generate_call_graph(project_path, function, format, depth.unwrap_or(10), callers).await,
+                "modules" => {
+                    if function.is_some() || depth.is_some() || callers {
+                        eprintln!("--function, --depth and --callers only apply to --kind calls");
+                        std::process::exit(1);
+                    }
+                    generate_module_graph(project_path, format)
+                }
+                _ => {
+                    eprintln!("Unknown graph kind: {}. Available kinds: calls, modules", kind);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::UnsafeReport { project_path, format, undocumented_only, exclude_tests } => {
+            generate_unsafe_report(project_path, format, undocumented_only, exclude_tests)
+        }
+        Commands::Metrics { project_path, format, sort, limit, include_tests } => {
+            show_metrics(project_path, format, sort, limit, include_tests)
+        }
+        Commands::DeadCode { project_path, format, min_confidence, include_tests } => {
+            show_dead_code(project_path, format, min_confidence, include_tests)
+        }
+        Commands::Api { project_path, format, revision, diff } => {
+            show_api(project_path, format, revision, diff)
+        }
+        Commands::AsyncHazards { project_path, format, include_tests } => {
+            show_async_hazards(project_path, format, include_tests)
+        }
+        Commands::Errors { project_path, format, origin, swallowed, function, include_tests } => {
+            show_errors(project_path, format, origin, swallowed, function, include_tests)
+        }
+        Commands::Impls { project_path, format, trait_name, traits, include_tests } => {
+            show_impls(project_path, format, trait_name, traits, include_tests)
+        }
+        Commands::Schema { type_name, project_path, format, file } => {
+            show_schema(type_name, project_path, format, file)
+        }
+        Commands::Source { symbol_name, project_path, format, file, line, attributes, context, signature, max_lines } => {
+            let params = protocol::GetSourceParams {
+                symbol_name,
+                symbol_type: None,
+                file_path: file,
+                line,
+                include_attributes: Some(attributes),
+                context_lines: Some(context),
+                signature_only: Some(signature),
+                max_lines,
+            };
+            show_source(project_path, format, params)
+        }
+        Commands::Explain { symbol_name, project_path, format, file, line, limit } => {
+            let params = protocol::ExplainSymbolParams {
+                symbol_name,
+                symbol_type: None,
+                file_path: file,
+                line,
+                limit,
+            };
+            show_explanation(project_path, format, params)
+        }
+        Commands::Rename { symbol_name, new_name, project_path, format, file, line, apply } => {
+            let params = protocol::RenameSymbolParams {
+                symbol_name,
+                new_name,
+                symbol_type: None,
+                file_path: file,
+                line,
+                apply: Some(apply),
+            };
+            rename_symbol(project_path, format, params)
+        }
+        Commands::Ssr { pattern, project_path, format, rewrite, file, limit, apply } => {
+            let params = protocol::StructuralSearchParams {
+                pattern,
+                rewrite,
+                file_path: file,
+                limit,
+                apply: Some(apply),
+            };
+            structural_search(project_path, format, params)
+        }
+        Commands::Diff { from, to, project_path, format } => {
+            show_semantic_diff(project_path, format, &from, to.as_deref())
+        }
+        Commands::History { symbol_name, project_path, format, file, line, limit } => {
+            let params = protocol::SymbolHistoryParams {
+                symbol_name,
+                symbol_type: None,
+                file_path: file,
+                line,
+                limit,
+            };
+            show_symbol_history(project_path, format, params)
+        }
+        Commands::Churn { project_path, format, sort, limit, file, include_tests } => {
+            show_churn(project_path, format, sort, limit, file, include_tests)
+        }
+        Commands::Impact { project_path, format, diff, base, depth } => {
+            show_impact(project_path, format, diff, base, depth)
+        }
+        Commands::Imports { name, from_file, project_path, format, limit } => {
+            show_imports(project_path, format, name, from_file, limit)
+        }
+        Commands::CheckArchitecture { project_path, config, format } => {
+            check_architecture(project_path, config, format)
         }
         Commands::Status { port } => {
             check_server_status(port).await
@@ -253,3 +789,510 @@ async fn generate_call_graph(
     
     Ok(())
 }
+
+fn generate_module_graph(project_path: PathBuf, format: String) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "tree" | "mermaid" | "stats" | "json") {
+        eprintln!("Unknown format: {}. Available formats: tree, mermaid, stats, json", format);
+        std::process::exit(1);
+    }
+
+    let graph = module_graph::build(&project_path)?;
+
+    match format.as_str() {
+        "mermaid" => print!("{}", module_graph::format_mermaid(&graph)),
+        "stats" => print!("{}", module_graph::format_stats(&graph)),
+        "json" => println!("{}", serde_json::to_string_pretty(&graph)?),
+        _ => print!("{}", module_graph::format_tree(&graph)),
+    }
+
+    Ok(())
+}
+
+fn generate_unsafe_report(
+    project_path: PathBuf,
+    format: String,
+    undocumented_only: bool,
+    exclude_tests: bool,
+) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let params = protocol::UnsafeReportParams {
+        file_path: None,
+        undocumented_only: Some(undocumented_only),
+        include_tests: Some(!exclude_tests),
+    };
+    let report = unsafe_audit::build_report(indexer.get_unsafe_sites(), &params);
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", unsafe_audit::format_text_report(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_metrics(
+    project_path: PathBuf,
+    format: String,
+    sort: String,
+    limit: usize,
+    include_tests: bool,
+) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "table" | "json") {
+        eprintln!("Unknown format: {}. Available formats: table, json", format);
+        std::process::exit(1);
+    }
+
+    let Some(sort_by) = metrics::parse_sort_key(&sort) else {
+        eprintln!("Unknown sort key: {}. Available keys: complexity, lines, nesting, params, returns, fan_in, fan_out", sort);
+        std::process::exit(1);
+    };
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let params = protocol::GetMetricsParams {
+        file_path: None,
+        function: None,
+        sort_by: Some(sort_by),
+        limit: Some(limit),
+        include_tests: Some(include_tests),
+    };
+    let report = metrics::build_report(indexer.get_parser(), &params);
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", metrics::format_table(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_dead_code(
+    project_path: PathBuf,
+    format: String,
+    min_confidence: String,
+    include_tests: bool,
+) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let min_confidence = match min_confidence.as_str() {
+        "low" => protocol::Confidence::Low,
+        "medium" => protocol::Confidence::Medium,
+        "high" => protocol::Confidence::High,
+        _ => {
+            eprintln!("Unknown confidence: {}. Available levels: low, medium, high", min_confidence);
+            std::process::exit(1);
+        }
+    };
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let params = protocol::FindDeadCodeParams {
+        file_path: None,
+        min_confidence: Some(min_confidence),
+        include_tests: Some(include_tests),
+    };
+    let report = indexer.find_dead_code(&params);
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", dead_code::format_text_report(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_api(
+    project_path: PathBuf,
+    format: String,
+    revision: Option<String>,
+    diff: Option<String>,
+) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let output = match diff {
+        Some(from) => {
+            let report = api_surface::api_diff(&project_path, &from, revision.as_deref())?;
+            match format.as_str() {
+                "json" => serde_json::to_string_pretty(&report)?,
+                _ => api_surface::format_diff(&report),
+            }
+        }
+        None => {
+            let report = api_surface::api_surface(&project_path, revision.as_deref())?;
+            match format.as_str() {
+                "json" => serde_json::to_string_pretty(&report)?,
+                _ => api_surface::format_surface(&report),
+            }
+        }
+    };
+    print!("{output}");
+
+    Ok(())
+}
+
+fn show_async_hazards(project_path: PathBuf, format: String, include_tests: bool) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let params = protocol::AsyncHazardsParams {
+        file_path: None,
+        kind: None,
+        include_tests: Some(include_tests),
+    };
+    let report = async_audit::build_report(indexer.get_parser(), &params);
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", async_audit::format_text_report(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_errors(
+    project_path: PathBuf,
+    format: String,
+    origin: Option<String>,
+    swallowed: bool,
+    function: Option<String>,
+    include_tests: bool,
+) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+    let parser = indexer.get_parser();
+    let json = format == "json";
+
+    let output = if let Some(error_type) = origin {
+        let params = protocol::FindErrorOriginsParams { error_type, include_tests: Some(include_tests) };
+        let report = error_flow::find_error_origins(parser, &params);
+        if json { serde_json::to_string_pretty(&report)? + "\n" } else { error_flow::format_error_origins(&report) }
+    } else if swallowed {
+        let params = protocol::FindSwallowedErrorsParams { function, file_path: None, include_tests: Some(include_tests) };
+        let report = error_flow::find_swallowed_errors(parser, &params);
+        if json { serde_json::to_string_pretty(&report)? + "\n" } else { error_flow::format_swallowed_errors(&report) }
+    } else {
+        let params = protocol::GetErrorMapParams { file_path: None, function, include_tests: Some(include_tests) };
+        let report = error_flow::get_error_map(parser, &params);
+        if json { serde_json::to_string_pretty(&report)? + "\n" } else { error_flow::format_error_map(&report) }
+    };
+    print!("{output}");
+
+    Ok(())
+}
+
+fn show_impls(
+    project_path: PathBuf,
+    format: String,
+    trait_name: Option<String>,
+    traits: Vec<String>,
+    include_tests: bool,
+) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+    let parser = indexer.get_parser();
+    let json = format == "json";
+
+    let output = match trait_name {
+        Some(trait_name) => {
+            let params = protocol::FindImplementationsParams { trait_name, include_tests: Some(include_tests) };
+            let report = trait_impls::find_implementations(parser, &params);
+            if json { serde_json::to_string_pretty(&report)? + "\n" } else { trait_impls::format_implementations(&report) }
+        }
+        None => {
+            let params = protocol::GetImplMatrixParams {
+                file_path: None,
+                traits: (!traits.is_empty()).then_some(traits),
+                include_tests: Some(include_tests),
+            };
+            let report = trait_impls::impl_matrix(parser, &params);
+            if json { serde_json::to_string_pretty(&report)? + "\n" } else { trait_impls::format_impl_matrix(&report) }
+        }
+    };
+    print!("{output}");
+
+    Ok(())
+}
+
+fn show_schema(type_name: String, project_path: PathBuf, format: String, file: Option<String>) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let params = protocol::JsonSchemaParams { type_name, file_path: file };
+    let report = serde_schema::json_schema(indexer.get_parser(), &params)?;
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", serde_schema::format_schema(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_source(project_path: PathBuf, format: String, params: protocol::GetSourceParams) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let response = indexer.get_source(&params);
+    if response.sources.is_empty() {
+        eprintln!("No source found for symbol '{}'", params.symbol_name);
+        std::process::exit(1);
+    }
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&response)?),
+        _ => print!("{}", source::format_sources(&response)),
+    }
+
+    Ok(())
+}
+
+fn show_explanation(project_path: PathBuf, format: String, params: protocol::ExplainSymbolParams) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let response = indexer.explain_symbol(&params);
+    if response.symbols.is_empty() {
+        eprintln!("Symbol '{}' not found", params.symbol_name);
+        std::process::exit(1);
+    }
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&response)?),
+        _ => print!("{}", explain::format_explanations(&response)),
+    }
+
+    Ok(())
+}
+
+fn rename_symbol(project_path: PathBuf, format: String, params: protocol::RenameSymbolParams) -> Result<(), anyhow::Error> {
+    // --apply で書き込んでから形式の誤りに気づくことがないよう、先に確認する
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let response = indexer.rename_symbol(&params)?;
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&response)?),
+        _ => print!("{}", rename::format_rename(&response)),
+    }
+
+    Ok(())
+}
+
+fn structural_search(project_path: PathBuf, format: String, params: protocol::StructuralSearchParams) -> Result<(), anyhow::Error> {
+    // --apply で書き込んでから形式の誤りに気づくことがないよう、先に確認する
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let response = indexer.structural_search(&params)?;
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&response)?),
+        _ => print!("{}", structural_search::format_matches(&response)),
+    }
+
+    Ok(())
+}
+
+fn show_semantic_diff(project_path: PathBuf, format: String, from: &str, to: Option<&str>) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let report = semantic_diff::semantic_diff(&project_path, from, to)?;
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", semantic_diff::format_diff(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_symbol_history(project_path: PathBuf, format: String, params: protocol::SymbolHistoryParams) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let response = indexer.symbol_history(&params)?;
+    if response.symbols.is_empty() {
+        eprintln!("Symbol '{}' not found", params.symbol_name);
+        std::process::exit(1);
+    }
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&response)?),
+        _ => print!("{}", git_history::format_history(&response)),
+    }
+
+    Ok(())
+}
+
+fn show_churn(
+    project_path: PathBuf,
+    format: String,
+    sort: String,
+    limit: usize,
+    file: Option<String>,
+    include_tests: bool,
+) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "table" | "json") {
+        eprintln!("Unknown format: {}. Available formats: table, json", format);
+        std::process::exit(1);
+    }
+
+    let Some(sort_by) = git_history::parse_sort_key(&sort) else {
+        eprintln!("Unknown sort key: {}. Available keys: churn, recent, oldest", sort);
+        std::process::exit(1);
+    };
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let params = protocol::GetChurnParams {
+        file_path: file,
+        sort_by: Some(sort_by),
+        limit: Some(limit),
+        include_tests: Some(include_tests),
+    };
+    let report = indexer.get_churn(&params)?;
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", git_history::format_churn(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_impact(project_path: PathBuf, format: String, diff: Option<PathBuf>, base: String, depth: usize) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let diff = match diff {
+        Some(path) if path.as_os_str() == "-" => std::io::read_to_string(std::io::stdin())?,
+        Some(path) => std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read diff {}: {}", path.display(), e))?,
+        None => impact::git_diff(&project_path, Some(&base))?,
+    };
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let report = indexer.impact_analysis(&diff, depth);
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", impact::format_impact(&report)),
+    }
+
+    Ok(())
+}
+
+fn show_imports(project_path: PathBuf, format: String, name: String, from_file: PathBuf, limit: usize) -> Result<(), anyhow::Error> {
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(1);
+    }
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let params = protocol::SuggestImportsParams {
+        name,
+        from_file: project_path.join(from_file).to_string_lossy().to_string(),
+        limit: Some(limit),
+    };
+    let response = indexer.suggest_imports(&params)?;
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&response)?),
+        _ => print!("{}", imports::format_suggestions(&response)),
+    }
+
+    Ok(())
+}
+
+fn check_architecture(project_path: PathBuf, config: Option<PathBuf>, format: String) -> Result<(), anyhow::Error> {
+    // 違反あり（1）と区別できるよう、引数の誤りは clap の使い方エラーと同じ 2 で終了する
+    if !matches!(format.as_str(), "text" | "json") {
+        eprintln!("Unknown format: {}. Available formats: text, json", format);
+        std::process::exit(2);
+    }
+
+    let config_path = config.unwrap_or_else(|| project_path.join(architecture::DEFAULT_CONFIG_FILE));
+    let config = architecture::ArchitectureConfig::load(&config_path)?;
+
+    let mut indexer = indexer::CodeIndexer::new();
+    indexer.index_directory(&project_path)?;
+
+    let graph = module_graph::build_with_calls(indexer.get_parser(), &project_path)?;
+    let report = architecture::check(&config, &config_path.to_string_lossy(), &graph);
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", architecture::format_report(&report)),
+    }
+
+    // CI で使えるように、違反があれば失敗にする
+    if !report.violations.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -32,14 +32,12 @@ pub struct JsonRpcError {
 
 pub struct McpClient {
     client: CodeIntelClient,
-    port: u16,
 }
 
 impl McpClient {
     pub fn new(port: u16) -> Self {
         Self {
             client: CodeIntelClient::new(port),
-            port,
         }
     }
 
@@ -60,61 +58,53 @@ impl McpClient {
         // 初回のメッセージを待つ（タイムアウトあり）
         let mut first_message = true;
         
-        loop {
-            match reader.next_line().await? {
-                Some(line) => {
-                    if first_message {
-                        // eprintln!("[MCP] Received first message");
-                        first_message = false;
-                    }
-                    let trimmed = line.trim();
-                    
-                    // 終了コマンドチェック
-                    if trimmed == "/quit" || trimmed == "/exit" {
-                        break;
-                    }
-                    
-                    // 空行スキップ
-                    if trimmed.is_empty() {
-                        continue;
-                    }
+        // stdin が閉じられる（EOF）まで処理を続ける
+        while let Some(line) = reader.next_line().await? {
+            if first_message {
+                // eprintln!("[MCP] Received first message");
+                first_message = false;
+            }
+            let trimmed = line.trim();
+            
+            // 終了コマンドチェック
+            if trimmed == "/quit" || trimmed == "/exit" {
+                break;
+            }
+            
+            // 空行スキップ
+            if trimmed.is_empty() {
+                continue;
+            }
 
-                    
-                    match self.handle_mcp_message(trimmed).await {
-                        Ok(Some(response)) => {
-                            // コンパクトなJSON出力（改行や余分なスペースを削除）
-                            let response_str = serde_json::to_string(&response)?;
-                            stdout.write_all(response_str.as_bytes()).await?;
-                            stdout.write_all(b"\n").await?;
-                            stdout.flush().await?;
-                        }
-                        Ok(None) => {
-                            // Notification (応答なし)
-                        }
-                        Err(e) => {
-                            // エラーは無視（MCPプロトコル維持のため）
-                            let error_response = JsonRpcResponse {
-                                jsonrpc: "2.0".to_string(),
-                                result: None,
-                                error: Some(JsonRpcError {
-                                    code: -32603,
-                                    message: format!("Internal error: {e}"),
-                                    data: None,
-                                }),
-                                id: None,
-                            };
-                            
-                            let response_str = serde_json::to_string(&error_response)?;
-                            stdout.write_all(response_str.as_bytes()).await?;
-                            stdout.write_all(b"\n").await?;
-                            stdout.flush().await?;
-                        }
-                    }
+            
+            match self.handle_mcp_message(trimmed).await {
+                Ok(Some(response)) => {
+                    // コンパクトなJSON出力（改行や余分なスペースを削除）
+                    let response_str = serde_json::to_string(&response)?;
+                    stdout.write_all(response_str.as_bytes()).await?;
+                    stdout.write_all(b"\n").await?;
+                    stdout.flush().await?;
+                }
+                Ok(None) => {
+                    // Notification (応答なし)
                 }
-                None => {
-                    // stdin closed (EOF)
-                    // eprintln!("[MCP] stdin closed (EOF), exiting");
-                    break;
+                Err(e) => {
+                    // エラーは無視（MCPプロトコル維持のため）
+                    let error_response = JsonRpcResponse {
+                        jsonrpc: "2.0".to_string(),
+                        result: None,
+                        error: Some(JsonRpcError {
+                            code: -32603,
+                            message: format!("Internal error: {e}"),
+                            data: None,
+                        }),
+                        id: None,
+                    };
+                    
+                    let response_str = serde_json::to_string(&error_response)?;
+                    stdout.write_all(response_str.as_bytes()).await?;
+                    stdout.write_all(b"\n").await?;
+                    stdout.flush().await?;
                 }
             }
         }
@@ -169,89 +159,289 @@ impl McpClient {
         Ok(Some(response))
     }
 
-    async fn handle_initialize(&self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
-        // info!("Initializing MCP client");
-        
-        let capabilities = json!({
-            "tools": {
-                "find_definition": {
-                    "description": "Find symbol definition by name (functions, structs, enums, traits)",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "symbol_name": {
-                                "type": "string",
-                                "description": "Name of the symbol to find"
-                            },
-                            "symbol_type": {
-                                "type": "string",
-                                "description": "Type of symbol to search for (Function, Struct, Enum, Trait). If not specified, searches all types.",
-                                "enum": ["Function", "Struct", "Enum", "Trait"]
-                            }
-                        },
-                        "required": ["symbol_name"]
+    /// 提供するツールの定義一覧（initialize と tools/list で共通）
+    fn tool_definitions() -> Vec<Value> {
+        vec![
+            json!({
+                "name": "find_definition",
+                "description": "Find symbol definition by name (functions, structs, enums, traits). When the server indexes dependencies, qualified names like 'tokio::spawn' resolve to read-only dependency symbols (crate_name is set).",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "symbol_name": {
+                            "type": "string",
+                            "description": "Name of the symbol to find, optionally qualified with a crate name ('serde::Serialize') or 'crate::'"
+                        },
+                        "symbol_type": {
+                            "type": "string",
+                            "description": "Type of symbol to search for (Function, Struct, Enum, Trait). If not specified, searches all types.",
+                            "enum": ["Function", "Struct", "Enum", "Trait"]
+                        }
+                    },
+                    "required": ["symbol_name"]
+                }
+            }),
+            json!({
+                "name": "explain_symbol",
+                "description": "Get a compact hover-style summary of a symbol in one call: signature, doc comments, visibility, module path, owning impl/trait, implemented traits (for types), implementors (for traits), caller and callee counts with the top names, related tests and location. Use this instead of chaining find_definition, find_usages and the call graph.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "symbol_name": {
+                            "type": "string",
+                            "description": "Name of the symbol, optionally qualified ('CodeIndexer::new', 'tokio::spawn')"
+                        },
+                        "symbol_type": {
+                            "type": "string",
+                            "description": "Type of symbol (Function, Struct, Enum, Trait). If not specified, all types.",
+                            "enum": ["Function", "Struct", "Enum", "Trait"]
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "Only symbols defined in this file (optional)"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Only the symbol defined at this line (optional)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "How many caller, callee and test names to list (default: 5)"
+                        }
+                    },
+                    "required": ["symbol_name"]
+                }
+            }),
+            json!({
+                "name": "rename_symbol",
+                "description": "Rename a function, method, struct, enum or trait across the project. Returns every text edit (definition, call sites, use paths and re-exports, struct literals, trait impl methods). Dry run by default; set apply to write the files and re-index. Refuses dependency symbols and renames that would collide with an existing name.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "symbol_name": {
+                            "type": "string",
+                            "description": "Name of the symbol, optionally qualified ('CodeIndexer::new')"
+                        },
+                        "new_name": {
+                            "type": "string",
+                            "description": "New identifier"
+                        },
+                        "symbol_type": {
+                            "type": "string",
+                            "description": "Type of symbol (Function, Struct, Enum, Trait). If not specified, all types.",
+                            "enum": ["Function", "Struct", "Enum", "Trait"]
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "File defining the symbol, when several share the name (optional)"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Definition line, when several share the name (optional)"
+                        },
+                        "apply": {
+                            "type": "boolean",
+                            "description": "Write the edits to disk and re-index (default: false, preview only)"
+                        }
+                    },
+                    "required": ["symbol_name", "new_name"]
+                }
+            }),
+            json!({
+                "name": "structural_search",
+                "description": "Search Rust expressions by syntax instead of text, with optional rewrite. The pattern is a Rust expression where $x is a metavariable matching any expression ($x:lit, $x:str and $x:ident add constraints, $..xs matches zero or more arguments, $_ matches without binding). Examples: '$x.unwrap()', 'HashMap::new()', 'log($level, $msg:str)'. Paths match by suffix. Returns spans and bound metavariables; with rewrite (e.g. '$x.expect(\"reason\")') also the replacement text, and apply writes it to disk.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "pattern": {
+                            "type": "string",
+                            "description": "Rust expression with $metavariables"
+                        },
+                        "rewrite": {
+                            "type": "string",
+                            "description": "Replacement template using the pattern's metavariables (optional)"
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "Only search this file (optional)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of matches to return (default: 100)"
+                        },
+                        "apply": {
+                            "type": "boolean",
+                            "description": "Write the rewrites to disk and re-index (default: false). Nested matches are only rewritten at the outermost one."
+                        }
+                    },
+                    "required": ["pattern"]
+                }
+            }),
+            json!({
+                "name": "semantic_diff",
+                "description": "Symbol-level diff between two git revisions (or a revision and the working tree): added, removed and moved symbols, signature changes separated from body-only changes, and call edges added or removed. Doc comment and line-shift changes are ignored.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "Base git revision (e.g. HEAD~1, a tag or a branch)"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Target git revision (default: working tree)"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Directory relative to the project root (default: project root)"
+                        }
+                    },
+                    "required": ["from"]
+                }
+            }),
+            json!({
+                "name": "symbol_history",
+                "description": "List the git commits that touched a symbol's lines (newest first) with churn, number of authors, days since last change and age. Use it to judge how stable an API or function is before changing it.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "symbol_name": {
+                            "type": "string",
+                            "description": "Name of the symbol, optionally qualified (e.g. CodeIndexer::new)"
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "Only symbols defined in this file (optional)"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Only the symbol defined at this line (optional)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of commits per symbol (default: 20)"
+                        }
+                    },
+                    "required": ["symbol_name"]
+                }
+            }),
+            json!({
+                "name": "get_churn",
+                "description": "Rank project symbols by git churn (number of commits touching their lines), most recently changed, or longest unchanged. Shows last commit, author and age for each symbol.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "sort_by": {
+                            "type": "string",
+                            "enum": ["churn", "recent", "oldest"],
+                            "description": "Sort order (default: churn)"
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "Only symbols in this file (optional)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of symbols (default: 50)"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
                     }
-                },
-                "find_usages": {
-                    "description": "Find all usages of a symbol in the codebase",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "symbol_name": {
-                                "type": "string",
-                                "description": "Name of the symbol to find usages for"
-                            },
-                            "symbol_type": {
-                                "type": "string",
-                                "description": "Type of symbol to search for (Function, Struct, Enum, Trait). If not specified, searches all types.",
-                                "enum": ["Function", "Struct", "Enum", "Trait"]
-                            }
-                        },
-                        "required": ["symbol_name"]
+                }
+            }),
+            json!({
+                "name": "impact_analysis",
+                "description": "Pre-merge review: map a unified diff (or `git diff` against a base revision) to the changed symbols, then walk callers transitively and report impacted functions, public APIs and tests ranked by call distance",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "diff": {
+                            "type": "string",
+                            "description": "Unified diff text (default: git diff of the working tree against base)"
+                        },
+                        "base": {
+                            "type": "string",
+                            "description": "Base git revision when no diff is given (default: HEAD)"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Directory to run git diff in, relative to the project root (default: project root)"
+                        },
+                        "max_depth": {
+                            "type": "integer",
+                            "description": "Maximum number of caller hops to follow (default: 5)"
+                        }
                     }
                 }
-            },
-            "resources": {},
-            "prompts": {}
-        });
-
-        Ok(JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            result: Some(json!({
-                "protocolVersion": "2024-11-05",
-                "capabilities": capabilities,
-                "serverInfo": {
-                    "name": "code-intel-client",
-                    "version": "0.1.0"
+            }),
+            json!({
+                "name": "suggest_imports",
+                "description": "Suggest valid `use` paths for a symbol as seen from a given file, honoring visibility (pub(crate), pub(super), private), `pub use` re-exports and crate boundaries; the shortest public path is ranked first",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Name to import (e.g. 'CodeIndexer')"
+                        },
+                        "from_file": {
+                            "type": "string",
+                            "description": "File where the `use` will be written, relative to the project root"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of suggestions (default: 10)"
+                        }
+                    },
+                    "required": ["name", "from_file"]
                 }
-            })),
-            error: None,
-            id: request.id.clone(),
-        })
-    }
-
-    async fn handle_tools_list(&self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
-        let tools = json!([
-            {
-                "name": "find_definition",
-                "description": "Find symbol definition by name (functions, structs, enums, traits)",
+            }),
+            json!({
+                "name": "get_source",
+                "description": "Get the exact source text of a symbol's item (function, method, struct, enum, trait) without reading the whole file. Can include doc comments, attributes and surrounding lines, or return only the signature for large bodies.",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
                         "symbol_name": {
                             "type": "string",
-                            "description": "Name of the symbol to find"
+                            "description": "Name of the symbol, optionally qualified ('CodeIndexer::new', 'tokio::spawn')"
                         },
                         "symbol_type": {
                             "type": "string",
-                            "description": "Type of symbol to search for (Function, Struct, Enum, Trait). If not specified, searches all types.",
+                            "description": "Type of symbol (Function, Struct, Enum, Trait). If not specified, all types.",
                             "enum": ["Function", "Struct", "Enum", "Trait"]
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "Only symbols defined in this file (optional)"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Only the symbol defined at this line (optional)"
+                        },
+                        "include_attributes": {
+                            "type": "boolean",
+                            "description": "Include doc comments and attributes (default: false)"
+                        },
+                        "context_lines": {
+                            "type": "integer",
+                            "description": "Number of surrounding lines to include (default: 0)"
+                        },
+                        "signature_only": {
+                            "type": "boolean",
+                            "description": "Return only the signature without the body (default: false)"
+                        },
+                        "max_lines": {
+                            "type": "integer",
+                            "description": "Return only the signature when the item is longer than this many lines (optional)"
                         }
                     },
                     "required": ["symbol_name"]
                 }
-            },
-            {
+            }),
+            json!({
                 "name": "find_usages",
                 "description": "Find all usages of a symbol in the codebase",
                 "inputSchema": {
@@ -269,89 +459,525 @@ impl McpClient {
                     },
                     "required": ["symbol_name"]
                 }
-            }
-        ]);
-
-        Ok(JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            result: Some(json!({ "tools": tools })),
-            error: None,
-            id: request.id.clone(),
-        })
-    }
-
-    async fn handle_tools_call(&self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
-        let params = request.params.as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Missing parameters for tools/call"))?;
-
-        let tool_name = params.get("name")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing tool name"))?;
-
-        let default_args = json!({});
-        let arguments = params.get("arguments")
-            .unwrap_or(&default_args);
-
-        match tool_name {
-            "find_definition" => self.handle_find_definition_tool(arguments, &request.id).await,
-            "find_usages" => self.handle_find_usages_tool(arguments, &request.id).await,
-            _ => Ok(JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32601,
-                    message: format!("Unknown tool: {tool_name}"),
-                    data: None,
-                }),
-                id: request.id.clone(),
-            })
-        }
-    }
-
-    async fn handle_find_definition_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
-        // 後方互換性のため、function_nameも受け付ける
-        let symbol_name = arguments.get("symbol_name")
-            .or_else(|| arguments.get("function_name"))
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
-        
-        // symbol_typeパラメータを取得
-        let symbol_type = arguments.get("symbol_type")
-            .and_then(|v| v.as_str())
-            .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok());
-
-        // info!("Finding definition for symbol: {} (type: {:?})", symbol_name, symbol_type);
-
-        // サーバーが起動しているかチェック
-        if !self.client.is_server_running().await {
-            return Ok(JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                result: Some(json!({
-                    "content": [{
-                        "type": "text",
-                        "text": "Error: Code intelligence server is not running. Please start the server with 'code_intel serve' before using this tool."
-                    }]
-                })),
-                error: None,
-                id: request_id.clone(),
-            });
-        }
-
-        // サーバーに問い合わせ
-        let server_result = self.client.find_definition_with_type(symbol_name, symbol_type).await?;
-        
-        // protocol::FindDefinitionResponse をパース
-        let find_response: protocol::FindDefinitionResponse = serde_json::from_value(server_result)?;
-
-        let result = if find_response.definitions.is_empty() {
-            json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("No definition found for symbol '{}'", symbol_name)
-                }]
-            })
-        } else {
-            let definitions_text = serde_json::to_string_pretty(&find_response.definitions)?;
+            }),
+            json!({
+                "name": "search_symbols",
+                "description": "Fuzzy search symbols across the workspace (prefix, substring, camelCase/snake_case word parts, typo tolerant). Results are ranked by match quality, visibility and production vs test code.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "Search query (e.g. 'find_def', 'fd', 'CoIn')"
+                        },
+                        "symbol_type": {
+                            "type": "string",
+                            "description": "Type of symbol to search for (Function, Struct, Enum, Trait). If not specified, searches all types.",
+                            "enum": ["Function", "Struct", "Enum", "Trait"]
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of results (default: 20)"
+                        }
+                    },
+                    "required": ["query"]
+                }
+            }),
+            json!({
+                "name": "get_diagnostics",
+                "description": "Get parse errors of indexed files. Files with errors keep their last good symbols or have the surrounding top-level items salvaged.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit diagnostics to this file (optional)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "find_local_references",
+                "description": "Resolve the local variable or parameter at a file position and list all its references with exact spans, including whether it is written, mutably borrowed or mutated.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Path of the file (absolute or relative to the project root)"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Line number (1-based)"
+                        },
+                        "column": {
+                            "type": "integer",
+                            "description": "Column number (0-based)"
+                        }
+                    },
+                    "required": ["file_path", "line", "column"]
+                }
+            }),
+            json!({
+                "name": "find_tests_for",
+                "description": "Find the tests (#[test], #[tokio::test], integration tests and doctests) that transitively call a function, by walking the reverse call graph. Use before changing a function to know which tests to run.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "symbol_name": {
+                            "type": "string",
+                            "description": "Name of the function to find tests for"
+                        },
+                        "max_depth": {
+                            "type": "integer",
+                            "description": "Maximum number of call edges between a test and the function (default: 10)"
+                        }
+                    },
+                    "required": ["symbol_name"]
+                }
+            }),
+            json!({
+                "name": "unsafe_report",
+                "description": "Inventory of unsafe fn, unsafe impl, unsafe trait and unsafe blocks with their location and enclosing symbol, and whether each is preceded by a // SAFETY: comment",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit the report to this file (optional)"
+                        },
+                        "undocumented_only": {
+                            "type": "boolean",
+                            "description": "Only report unsafe code without a SAFETY comment (default: false)"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include unsafe code in tests (default: true)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "panic_report",
+                "description": "Report potential panic sites (unwrap, expect, panic!, unreachable!, todo!, unimplemented!, assert!, indexing) per function, and which functions may panic transitively through the call graph, with the shortest call path to a panic site",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit the report to functions in this file (optional)"
+                        },
+                        "function": {
+                            "type": "string",
+                            "description": "Limit the report to this function (optional)"
+                        },
+                        "public_only": {
+                            "type": "boolean",
+                            "description": "Only report pub functions (default: false)"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "get_metrics",
+                "description": "Per-function metrics computed from the AST: lines of code, cyclomatic complexity, maximum nesting depth, parameter count, early returns (return and ?), fan-in and fan-out. Sorted descending to find hotspots.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit to functions in this file (optional)"
+                        },
+                        "function": {
+                            "type": "string",
+                            "description": "Limit to this function (optional)"
+                        },
+                        "sort_by": {
+                            "type": "string",
+                            "description": "Metric to sort by, descending (default: complexity)",
+                            "enum": ["complexity", "lines", "nesting", "params", "returns", "fan_in", "fan_out"]
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of functions to return (default: 50)"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test functions (default: false)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "find_dead_code",
+                "description": "Find functions, types and modules unreachable from real roots: main, pub items of library crates, #[test] functions, trait impls and #[no_mangle]/extern functions. Each item has a confidence level (High, Medium, Low) because macros and dynamic dispatch limit the analysis.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit the report to this file (optional)"
+                        },
+                        "min_confidence": {
+                            "type": "string",
+                            "description": "Minimum confidence to report (default: Low, i.e. everything)",
+                            "enum": ["Low", "Medium", "High"]
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include unreachable test code (default: false)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "api_surface",
+                "description": "List the crate's public API: pub items reachable from the crate root, including pub use re-exports, with normalised signatures. Can read any git revision.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "crate_path": {
+                            "type": "string",
+                            "description": "Crate directory relative to the project root (default: project root)"
+                        },
+                        "revision": {
+                            "type": "string",
+                            "description": "Git revision to read (default: working tree)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "api_diff",
+                "description": "Diff the public API between two git revisions and classify each change as Breaking, Additive or Patch (semver major/minor/patch). Useful when reviewing library PRs.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "Base git revision (e.g. a tag, branch or commit)"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Target git revision (default: working tree)"
+                        },
+                        "crate_path": {
+                            "type": "string",
+                            "description": "Crate directory relative to the project root (default: project root)"
+                        }
+                    },
+                    "required": ["from"]
+                }
+            }),
+            json!({
+                "name": "async_hazards",
+                "description": "Find async hazards: blocking calls (std::fs, std::thread::sleep, synchronous locks, blocking_* and block_on) reachable from async fn bodies with the call chain that leads to them, lock guards held across .await, and async fn calls whose future is dropped without being awaited",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit the report to functions in this file (optional)"
+                        },
+                        "kind": {
+                            "type": "string",
+                            "description": "Only report this kind of hazard (optional)",
+                            "enum": ["BlockingCall", "GuardAcrossAwait", "UnawaitedFuture"]
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "get_error_map",
+                "description": "List functions returning Result or Option with their error type, and every ? site with the error type it converts from and to",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit the map to functions in this file (optional)"
+                        },
+                        "function": {
+                            "type": "string",
+                            "description": "Limit the map to functions with this name (optional)"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "find_error_origins",
+                "description": "Find where an error type can originate: Err(...) constructions and bail!, ? conversions into it from other error types, and ? on calls outside the crate that return it",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "error_type": {
+                            "type": "string",
+                            "description": "Error type to trace (e.g. 'AppError' or 'io::Error')"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
+                    },
+                    "required": ["error_type"]
+                }
+            }),
+            json!({
+                "name": "find_swallowed_errors",
+                "description": "Find callers that swallow errors with let _ =, .ok() or .unwrap_or_default()",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "function": {
+                            "type": "string",
+                            "description": "Only report callers swallowing the result of this function (optional)"
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit the report to this file (optional)"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "find_implementations",
+                "description": "Find types implementing a trait, including implementations generated by #[derive(...)] (e.g. 'Clone' lists derived types as well as manual impl blocks)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "trait_name": {
+                            "type": "string",
+                            "description": "Trait name or path (e.g. 'Clone' or 'serde::Serialize')"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
+                    },
+                    "required": ["trait_name"]
+                }
+            }),
+            json!({
+                "name": "impl_matrix",
+                "description": "Show a matrix of structs and enums against the traits they implement, marking whether each comes from #[derive(...)] or a manual impl block",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit the matrix to types in this file (optional)"
+                        },
+                        "traits": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Traits to use as columns (default: every implemented trait)"
+                        },
+                        "include_tests": {
+                            "type": "boolean",
+                            "description": "Include test code (default: false)"
+                        }
+                    }
+                }
+            }),
+            json!({
+                "name": "json_schema",
+                "description": "Generate a JSON Schema (draft 2020-12) for a struct or enum deriving Serialize/Deserialize, honoring serde rename, rename_all, skip, default, flatten and tag attributes and following referenced types across files",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "type_name": {
+                            "type": "string",
+                            "description": "Name of the struct or enum"
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "File defining the type, when several types share the name (optional)"
+                        }
+                    },
+                    "required": ["type_name"]
+                }
+            }),
+            json!({
+                "name": "list_tests",
+                "description": "List indexed tests: unit tests, integration tests under tests/ and doctests",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "kind": {
+                            "type": "string",
+                            "description": "Kind of tests to list. If not specified, lists all kinds.",
+                            "enum": ["Unit", "Integration", "Doctest"]
+                        },
+                        "file_path": {
+                            "type": "string",
+                            "description": "Limit tests to this file (optional)"
+                        }
+                    }
+                }
+            }),
+        ]
+    }
+
+    async fn handle_initialize(&self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
+        // info!("Initializing MCP client");
+        
+        // ツール名をキーにしたマップとして公開
+        let tools: serde_json::Map<String, Value> = Self::tool_definitions()
+            .into_iter()
+            .filter_map(|mut tool| {
+                let name = tool.get("name")?.as_str()?.to_string();
+                tool.as_object_mut()?.remove("name");
+                Some((name, tool))
+            })
+            .collect();
+
+        let capabilities = json!({
+            "tools": tools,
+            "resources": {},
+            "prompts": {}
+        });
+
+        Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: Some(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": capabilities,
+                "serverInfo": {
+                    "name": "code-intel-client",
+                    "version": "0.1.0"
+                }
+            })),
+            error: None,
+            id: request.id.clone(),
+        })
+    }
+
+    async fn handle_tools_list(&self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let tools = Self::tool_definitions();
+
+        Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: Some(json!({ "tools": tools })),
+            error: None,
+            id: request.id.clone(),
+        })
+    }
+
+    async fn handle_tools_call(&self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let params = request.params.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Missing parameters for tools/call"))?;
+
+        let tool_name = params.get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing tool name"))?;
+
+        let default_args = json!({});
+        let arguments = params.get("arguments")
+            .unwrap_or(&default_args);
+
+        match tool_name {
+            "find_definition" => self.handle_find_definition_tool(arguments, &request.id).await,
+            "find_usages" => self.handle_find_usages_tool(arguments, &request.id).await,
+            "get_source" => self.handle_get_source_tool(arguments, &request.id).await,
+            "explain_symbol" => self.handle_explain_symbol_tool(arguments, &request.id).await,
+            "rename_symbol" => self.handle_rename_symbol_tool(arguments, &request.id).await,
+            "structural_search" => self.handle_structural_search_tool(arguments, &request.id).await,
+            "semantic_diff" => self.handle_semantic_diff_tool(arguments, &request.id).await,
+            "symbol_history" => self.handle_symbol_history_tool(arguments, &request.id).await,
+            "get_churn" => self.handle_get_churn_tool(arguments, &request.id).await,
+            "impact_analysis" => self.handle_impact_analysis_tool(arguments, &request.id).await,
+            "suggest_imports" => self.handle_suggest_imports_tool(arguments, &request.id).await,
+            "search_symbols" => self.handle_search_symbols_tool(arguments, &request.id).await,
+            "get_diagnostics" => self.handle_get_diagnostics_tool(arguments, &request.id).await,
+            "find_local_references" => self.handle_find_local_references_tool(arguments, &request.id).await,
+            "find_tests_for" => self.handle_find_tests_for_tool(arguments, &request.id).await,
+            "list_tests" => self.handle_list_tests_tool(arguments, &request.id).await,
+            "unsafe_report" => self.handle_unsafe_report_tool(arguments, &request.id).await,
+            "panic_report" => self.handle_panic_report_tool(arguments, &request.id).await,
+            "get_metrics" => self.handle_get_metrics_tool(arguments, &request.id).await,
+            "find_dead_code" => self.handle_find_dead_code_tool(arguments, &request.id).await,
+            "api_surface" => self.handle_api_surface_tool(arguments, &request.id).await,
+            "api_diff" => self.handle_api_diff_tool(arguments, &request.id).await,
+            "async_hazards" => self.handle_async_hazards_tool(arguments, &request.id).await,
+            "get_error_map" => self.handle_get_error_map_tool(arguments, &request.id).await,
+            "find_error_origins" => self.handle_find_error_origins_tool(arguments, &request.id).await,
+            "find_swallowed_errors" => self.handle_find_swallowed_errors_tool(arguments, &request.id).await,
+            "find_implementations" => self.handle_find_implementations_tool(arguments, &request.id).await,
+            "impl_matrix" => self.handle_impl_matrix_tool(arguments, &request.id).await,
+            "json_schema" => self.handle_json_schema_tool(arguments, &request.id).await,
+            _ => Ok(JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32601,
+                    message: format!("Unknown tool: {tool_name}"),
+                    data: None,
+                }),
+                id: request.id.clone(),
+            })
+        }
+    }
+
+    async fn handle_find_definition_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        // 後方互換性のため、function_nameも受け付ける
+        let symbol_name = arguments.get("symbol_name")
+            .or_else(|| arguments.get("function_name"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
+        
+        // symbol_typeパラメータを取得
+        let symbol_type = arguments.get("symbol_type")
+            .and_then(|v| v.as_str())
+            .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok());
+
+        // info!("Finding definition for symbol: {} (type: {:?})", symbol_name, symbol_type);
+
+        // サーバーが起動しているかチェック
+        if !self.client.is_server_running().await {
+            return Ok(JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: Some(json!({
+                    "content": [{
+                        "type": "text",
+                        "text": "Error: Code intelligence server is not running. Please start the server with 'code_intel serve' before using this tool."
+                    }]
+                })),
+                error: None,
+                id: request_id.clone(),
+            });
+        }
+
+        // サーバーに問い合わせ
+        let server_result = self.client.find_definition_with_type(symbol_name, symbol_type).await?;
+        
+        // protocol::FindDefinitionResponse をパース
+        let find_response: protocol::FindDefinitionResponse = serde_json::from_value(server_result)?;
+
+        let result = if find_response.definitions.is_empty() {
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("No definition found for symbol '{}'", symbol_name)
+                }]
+            })
+        } else {
+            let definitions_text = serde_json::to_string_pretty(&find_response.definitions)?;
             json!({
                 "content": [{
                     "type": "text",
@@ -363,71 +989,692 @@ impl McpClient {
             })
         };
 
-        Ok(JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            result: Some(result),
-            error: None,
-            id: request_id.clone(),
-        })
+        Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: Some(result),
+            error: None,
+            id: request_id.clone(),
+        })
+    }
+
+    async fn handle_find_usages_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let symbol_name = arguments.get("symbol_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
+        
+        // symbol_typeパラメータを取得
+        let symbol_type = arguments.get("symbol_type")
+            .and_then(|v| v.as_str())
+            .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok());
+
+        // サーバーが起動しているかチェック
+        if !self.client.is_server_running().await {
+            return Ok(JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: Some(json!({
+                    "content": [{
+                        "type": "text",
+                        "text": "Error: Code intelligence server is not running. Please start the server with 'code_intel serve' before using this tool."
+                    }]
+                })),
+                error: None,
+                id: request_id.clone(),
+            });
+        }
+
+        // サーバーに問い合わせ
+        let server_result = self.client.find_usages(symbol_name, symbol_type).await?;
+        
+        // protocol::FindUsagesResponse をパース
+        let find_response: protocol::FindUsagesResponse = serde_json::from_value(server_result)?;
+
+        let result = if find_response.usages.is_empty() {
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("No usages found for symbol '{}'", symbol_name)
+                }]
+            })
+        } else {
+            let usages_text = serde_json::to_string_pretty(&find_response.usages)?;
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("Found {} usage(s) for symbol '{}':\n\n{}", 
+                                  find_response.usages.len(), 
+                                  symbol_name,
+                                  usages_text)
+                }]
+            })
+        };
+
+        Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: Some(result),
+            error: None,
+            id: request_id.clone(),
+        })
+    }
+
+    async fn handle_search_symbols_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let query = arguments.get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing query parameter"))?;
+
+        let symbol_type = arguments.get("symbol_type")
+            .and_then(|v| v.as_str())
+            .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok());
+
+        let limit = arguments.get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.search_symbols(query, symbol_type, limit).await?;
+        let search_response: protocol::SearchSymbolsResponse = serde_json::from_value(server_result)?;
+
+        let text = if search_response.results.is_empty() {
+            format!("No symbols matching '{query}'")
+        } else {
+            format!("Found {} symbol(s) matching '{}':\n\n{}",
+                    search_response.results.len(),
+                    query,
+                    serde_json::to_string_pretty(&search_response.results)?)
+        };
+
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_get_diagnostics_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let file_path = arguments.get("file_path")
+            .and_then(|v| v.as_str());
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.get_diagnostics(file_path).await?;
+        let diagnostics_response: protocol::GetDiagnosticsResponse = serde_json::from_value(server_result)?;
+
+        let text = if diagnostics_response.files.is_empty() {
+            "No parse errors".to_string()
+        } else {
+            format!("{} file(s) with parse errors:\n\n{}",
+                    diagnostics_response.files.len(),
+                    serde_json::to_string_pretty(&diagnostics_response.files)?)
+        };
+
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_find_local_references_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let file_path = arguments.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing file_path parameter"))?;
+        let line = arguments.get("line")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow::anyhow!("Missing line parameter"))? as usize;
+        let column = arguments.get("column")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow::anyhow!("Missing column parameter"))? as usize;
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.find_local_references(file_path, line, column).await?;
+        let references_response: protocol::FindLocalReferencesResponse = serde_json::from_value(server_result)?;
+
+        let text = match &references_response.binding {
+            None => format!("No local variable or parameter at {file_path}:{line}:{column}"),
+            Some(binding) => format!("{} '{}' ({} reference(s), mutated: {}):\n\n{}",
+                                     match binding.kind {
+                                         protocol::BindingKind::Parameter => "Parameter",
+                                         protocol::BindingKind::Local => "Local variable",
+                                         protocol::BindingKind::Pattern => "Pattern binding",
+                                     },
+                                     binding.name,
+                                     references_response.references.len(),
+                                     references_response.is_mutated,
+                                     serde_json::to_string_pretty(&references_response.references)?),
+        };
+
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_find_tests_for_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let symbol_name = arguments.get("symbol_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
+        let max_depth = arguments.get("max_depth")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.find_tests_for(symbol_name, max_depth).await?;
+        let tests_response: protocol::FindTestsForResponse = serde_json::from_value(server_result)?;
+
+        let text = if tests_response.tests.is_empty() {
+            format!("No tests found that exercise '{symbol_name}'")
+        } else {
+            format!("Found {} test(s) exercising '{}':\n\n{}",
+                    tests_response.tests.len(),
+                    symbol_name,
+                    serde_json::to_string_pretty(&tests_response.tests)?)
+        };
+
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_list_tests_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let kind = arguments.get("kind")
+            .and_then(|v| v.as_str())
+            .and_then(|s| serde_json::from_value::<protocol::TestKind>(json!(s)).ok());
+        let file_path = arguments.get("file_path")
+            .and_then(|v| v.as_str());
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.list_tests(kind, file_path).await?;
+        let tests_response: protocol::ListTestsResponse = serde_json::from_value(server_result)?;
+
+        let text = format!("{} test(s):\n\n{}",
+                           tests_response.tests.len(),
+                           serde_json::to_string_pretty(&tests_response.tests)?);
+
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_unsafe_report_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::UnsafeReportParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            undocumented_only: arguments.get("undocumented_only").and_then(|v| v.as_bool()),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.unsafe_report(params).await?;
+        let report: protocol::UnsafeReportResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::unsafe_audit::format_text_report(&report)))
     }
 
-    async fn handle_find_usages_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+    async fn handle_panic_report_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::PanicReportParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            function: arguments.get("function").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            public_only: arguments.get("public_only").and_then(|v| v.as_bool()),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.panic_report(params).await?;
+        let report: protocol::PanicReportResponse = serde_json::from_value(server_result)?;
+
+        let text = format!("{} function(s) may panic ({} pub), {} direct panic site(s):\n\n{}",
+                           report.summary.may_panic_functions,
+                           report.summary.public_may_panic,
+                           report.summary.total_sites,
+                           serde_json::to_string_pretty(&report)?);
+
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_get_metrics_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::GetMetricsParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            function: arguments.get("function").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            sort_by: arguments.get("sort_by").and_then(|v| v.as_str()).and_then(crate::metrics::parse_sort_key),
+            limit: arguments.get("limit").and_then(|v| v.as_u64()).map(|v| v as usize),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.get_metrics(params).await?;
+        let report: protocol::GetMetricsResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::metrics::format_table(&report)))
+    }
+
+    async fn handle_find_dead_code_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::FindDeadCodeParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            min_confidence: arguments.get("min_confidence")
+                .and_then(|v| v.as_str())
+                .and_then(|s| serde_json::from_value::<protocol::Confidence>(json!(s)).ok()),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.find_dead_code(params).await?;
+        let report: protocol::FindDeadCodeResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::dead_code::format_text_report(&report)))
+    }
+
+    async fn handle_api_surface_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::ApiSurfaceParams {
+            crate_path: arguments.get("crate_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            revision: arguments.get("revision").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.api_surface(params).await?;
+        let report: protocol::ApiSurfaceResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::api_surface::format_surface(&report)))
+    }
+
+    async fn handle_api_diff_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let from = arguments.get("from")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing from parameter"))?;
+        let params = protocol::ApiDiffParams {
+            crate_path: arguments.get("crate_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            from: from.to_string(),
+            to: arguments.get("to").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.api_diff(params).await?;
+        let report: protocol::ApiDiffResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::api_surface::format_diff(&report)))
+    }
+
+    async fn handle_async_hazards_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::AsyncHazardsParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            kind: arguments.get("kind")
+                .and_then(|v| v.as_str())
+                .and_then(|s| serde_json::from_value::<protocol::AsyncHazardKind>(json!(s)).ok()),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.async_hazards(params).await?;
+        let report: protocol::AsyncHazardsResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::async_audit::format_text_report(&report)))
+    }
+
+    async fn handle_get_error_map_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::GetErrorMapParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            function: arguments.get("function").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.get_error_map(params).await?;
+        let report: protocol::GetErrorMapResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::error_flow::format_error_map(&report)))
+    }
+
+    async fn handle_find_error_origins_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let error_type = arguments.get("error_type")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing error_type parameter"))?;
+        let params = protocol::FindErrorOriginsParams {
+            error_type: error_type.to_string(),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.find_error_origins(params).await?;
+        let report: protocol::FindErrorOriginsResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::error_flow::format_error_origins(&report)))
+    }
+
+    async fn handle_find_swallowed_errors_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::FindSwallowedErrorsParams {
+            function: arguments.get("function").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.find_swallowed_errors(params).await?;
+        let report: protocol::FindSwallowedErrorsResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::error_flow::format_swallowed_errors(&report)))
+    }
+
+    async fn handle_find_implementations_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let trait_name = arguments.get("trait_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing trait_name parameter"))?;
+        let params = protocol::FindImplementationsParams {
+            trait_name: trait_name.to_string(),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.find_implementations(params).await?;
+        let report: protocol::FindImplementationsResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::trait_impls::format_implementations(&report)))
+    }
+
+    async fn handle_impl_matrix_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::GetImplMatrixParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            traits: arguments.get("traits")
+                .and_then(|v| v.as_array())
+                .map(|traits| traits.iter().filter_map(|t| t.as_str()).map(|t| t.to_string()).collect()),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.get_impl_matrix(params).await?;
+        let report: protocol::GetImplMatrixResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::trait_impls::format_impl_matrix(&report)))
+    }
+
+    async fn handle_json_schema_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let type_name = arguments.get("type_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing type_name parameter"))?;
+        let params = protocol::JsonSchemaParams {
+            type_name: type_name.to_string(),
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.get_json_schema(params).await?;
+        let report: protocol::JsonSchemaResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::serde_schema::format_schema(&report)))
+    }
+
+    async fn handle_get_source_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
         let symbol_name = arguments.get("symbol_name")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
-        
-        // symbol_typeパラメータを取得
-        let symbol_type = arguments.get("symbol_type")
+        let params = protocol::GetSourceParams {
+            symbol_name: symbol_name.to_string(),
+            symbol_type: arguments.get("symbol_type")
+                .and_then(|v| v.as_str())
+                .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok()),
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            line: arguments.get("line").and_then(|v| v.as_u64()).map(|n| n as usize),
+            include_attributes: arguments.get("include_attributes").and_then(|v| v.as_bool()),
+            context_lines: arguments.get("context_lines").and_then(|v| v.as_u64()).map(|n| n as usize),
+            signature_only: arguments.get("signature_only").and_then(|v| v.as_bool()),
+            max_lines: arguments.get("max_lines").and_then(|v| v.as_u64()).map(|n| n as usize),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.get_source(params).await?;
+        let response: protocol::GetSourceResponse = serde_json::from_value(server_result)?;
+
+        let text = if response.sources.is_empty() {
+            format!("No source found for symbol '{}'", symbol_name)
+        } else {
+            crate::source::format_sources(&response)
+        };
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_explain_symbol_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let symbol_name = arguments.get("symbol_name")
             .and_then(|v| v.as_str())
-            .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok());
+            .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
+        let params = protocol::ExplainSymbolParams {
+            symbol_name: symbol_name.to_string(),
+            symbol_type: arguments.get("symbol_type")
+                .and_then(|v| v.as_str())
+                .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok()),
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            line: arguments.get("line").and_then(|v| v.as_u64()).map(|n| n as usize),
+            limit: arguments.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize),
+        };
 
-        // サーバーが起動しているかチェック
         if !self.client.is_server_running().await {
-            return Ok(JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                result: Some(json!({
-                    "content": [{
-                        "type": "text",
-                        "text": "Error: Code intelligence server is not running. Please start the server with 'code_intel serve' before using this tool."
-                    }]
-                })),
-                error: None,
-                id: request_id.clone(),
-            });
+            return Ok(Self::server_not_running_response(request_id));
         }
 
-        // サーバーに問い合わせ
-        let server_result = self.client.find_usages(symbol_name, symbol_type).await?;
-        
-        // protocol::FindUsagesResponse をパース
-        let find_response: protocol::FindUsagesResponse = serde_json::from_value(server_result)?;
+        let server_result = self.client.explain_symbol(params).await?;
+        let response: protocol::ExplainSymbolResponse = serde_json::from_value(server_result)?;
 
-        let result = if find_response.usages.is_empty() {
-            json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("No usages found for symbol '{}'", symbol_name)
-                }]
-            })
+        let text = if response.symbols.is_empty() {
+            format!("Symbol '{}' not found", symbol_name)
         } else {
-            let usages_text = serde_json::to_string_pretty(&find_response.usages)?;
-            json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Found {} usage(s) for symbol '{}':\n\n{}", 
-                                  find_response.usages.len(), 
-                                  symbol_name,
-                                  usages_text)
-                }]
-            })
+            crate::explain::format_explanations(&response)
+        };
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_rename_symbol_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let symbol_name = arguments.get("symbol_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
+        let new_name = arguments.get("new_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing new_name parameter"))?;
+        let params = protocol::RenameSymbolParams {
+            symbol_name: symbol_name.to_string(),
+            new_name: new_name.to_string(),
+            symbol_type: arguments.get("symbol_type")
+                .and_then(|v| v.as_str())
+                .and_then(|s| serde_json::from_value::<protocol::SymbolType>(json!(s)).ok()),
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            line: arguments.get("line").and_then(|v| v.as_u64()).map(|n| n as usize),
+            apply: arguments.get("apply").and_then(|v| v.as_bool()),
         };
 
-        Ok(JsonRpcResponse {
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.rename_symbol(params).await?;
+        let response: protocol::RenameSymbolResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::rename::format_rename(&response)))
+    }
+
+    async fn handle_structural_search_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let pattern = arguments.get("pattern")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing pattern parameter"))?;
+        let params = protocol::StructuralSearchParams {
+            pattern: pattern.to_string(),
+            rewrite: arguments.get("rewrite").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            limit: arguments.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize),
+            apply: arguments.get("apply").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.structural_search(params).await?;
+        let response: protocol::StructuralSearchResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::structural_search::format_matches(&response)))
+    }
+
+    async fn handle_semantic_diff_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let from = arguments.get("from")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing from parameter"))?;
+        let params = protocol::SemanticDiffParams {
+            path: arguments.get("path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            from: from.to_string(),
+            to: arguments.get("to").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.semantic_diff(params).await?;
+        let report: protocol::SemanticDiffResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::semantic_diff::format_diff(&report)))
+    }
+
+    async fn handle_symbol_history_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let symbol_name = arguments.get("symbol_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing symbol_name parameter"))?;
+        let params = protocol::SymbolHistoryParams {
+            symbol_name: symbol_name.to_string(),
+            symbol_type: None,
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            line: arguments.get("line").and_then(|v| v.as_u64()).map(|n| n as usize),
+            limit: arguments.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.symbol_history(params).await?;
+        let response: protocol::SymbolHistoryResponse = serde_json::from_value(server_result)?;
+
+        let text = if response.symbols.is_empty() {
+            format!("Symbol '{symbol_name}' not found")
+        } else {
+            crate::git_history::format_history(&response)
+        };
+        Ok(Self::text_response(request_id, text))
+    }
+
+    async fn handle_get_churn_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::GetChurnParams {
+            file_path: arguments.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            sort_by: arguments.get("sort_by").and_then(|v| v.as_str()).and_then(crate::git_history::parse_sort_key),
+            limit: arguments.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize),
+            include_tests: arguments.get("include_tests").and_then(|v| v.as_bool()),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.get_churn(params).await?;
+        let report: protocol::GetChurnResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::git_history::format_churn(&report)))
+    }
+
+    async fn handle_impact_analysis_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let params = protocol::ImpactAnalysisParams {
+            diff: arguments.get("diff").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            base: arguments.get("base").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            path: arguments.get("path").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            max_depth: arguments.get("max_depth").and_then(|v| v.as_u64()).map(|n| n as usize),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.impact_analysis(params).await?;
+        let report: protocol::ImpactAnalysisResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::impact::format_impact(&report)))
+    }
+
+    async fn handle_suggest_imports_tool(&self, arguments: &Value, request_id: &Option<Value>) -> Result<JsonRpcResponse> {
+        let name = arguments.get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'name' parameter"))?;
+        let from_file = arguments.get("from_file")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'from_file' parameter"))?;
+        let params = protocol::SuggestImportsParams {
+            name: name.to_string(),
+            from_file: from_file.to_string(),
+            limit: arguments.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize),
+        };
+
+        if !self.client.is_server_running().await {
+            return Ok(Self::server_not_running_response(request_id));
+        }
+
+        let server_result = self.client.suggest_imports(params).await?;
+        let response: protocol::SuggestImportsResponse = serde_json::from_value(server_result)?;
+
+        Ok(Self::text_response(request_id, crate::imports::format_suggestions(&response)))
+    }
+
+    /// テキスト1件を返すツール応答を生成
+    fn text_response(request_id: &Option<Value>, text: String) -> JsonRpcResponse {
+        JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
-            result: Some(result),
+            result: Some(json!({
+                "content": [{
+                    "type": "text",
+                    "text": text
+                }]
+            })),
             error: None,
             id: request_id.clone(),
-        })
+        }
+    }
+
+    /// サーバー未起動時のツール応答
+    fn server_not_running_response(request_id: &Option<Value>) -> JsonRpcResponse {
+        Self::text_response(
+            request_id,
+            "Error: Code intelligence server is not running. Please start the server with 'code_intel serve' before using this tool.".to_string(),
+        )
     }
 
     async fn handle_resources_list(&self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
@@ -0,0 +1,305 @@
+use crate::parser::RustParser;
+use crate::protocol::{FunctionMetrics, GetMetricsParams, GetMetricsResponse, MetricsSortKey, SymbolType};
+use std::collections::HashSet;
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+
+/// get_metrics で limit 未指定時に返す関数の数
+pub const DEFAULT_METRICS_LIMIT: usize = 50;
+
+/// 関数本体の AST から求める指標
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BodyMetrics {
+    pub cyclomatic_complexity: usize,
+    pub max_nesting: usize,
+    pub early_returns: usize,  // 末尾以外の return と ? 演算子
+}
+
+/// 関数本体を計測（ネストしたアイテムは別の関数として扱うので含めない）
+pub fn measure_block(block: &syn::Block) -> BodyMetrics {
+    let mut visitor = ComplexityVisitor::default();
+    visitor.visit_block(block);
+
+    // 末尾の return は早期リターンではない
+    if let Some(syn::Stmt::Expr(syn::Expr::Return(_), _)) = block.stmts.last() {
+        visitor.early_returns -= 1;
+    }
+    visitor.into_metrics()
+}
+
+/// クロージャ本体を計測
+pub fn measure_expr(expr: &syn::Expr) -> BodyMetrics {
+    let mut visitor = ComplexityVisitor::default();
+    visitor.visit_expr(expr);
+    visitor.into_metrics()
+}
+
+/// 空行とコメントだけの行を除いた行数
+pub fn count_code_lines(lines: &[&str]) -> usize {
+    lines.iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .count()
+}
+
+/// 循環的複雑度: 1 + 分岐（if・while・for・match の追加アーム・ガード・&&・||・?）
+#[derive(Default)]
+struct ComplexityVisitor {
+    branches: usize,
+    depth: usize,
+    max_depth: usize,
+    early_returns: usize,
+}
+
+impl ComplexityVisitor {
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+        f(self);
+        self.depth -= 1;
+    }
+
+    fn into_metrics(self) -> BodyMetrics {
+        BodyMetrics {
+            cyclomatic_complexity: self.branches + 1,
+            max_nesting: self.max_depth,
+            early_returns: self.early_returns,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ComplexityVisitor {
+    fn visit_item(&mut self, _item: &'ast syn::Item) {}
+
+    fn visit_expr_if(&mut self, if_expr: &'ast syn::ExprIf) {
+        self.branches += 1;
+        self.visit_expr(&if_expr.cond);
+        self.nested(|this| this.visit_block(&if_expr.then_branch));
+        if let Some((_, else_branch)) = &if_expr.else_branch {
+            // else if は同じ深さとして扱う
+            match &**else_branch {
+                syn::Expr::If(_) => self.visit_expr(else_branch),
+                other => self.nested(|this| this.visit_expr(other)),
+            }
+        }
+    }
+
+    fn visit_expr_while(&mut self, while_expr: &'ast syn::ExprWhile) {
+        self.branches += 1;
+        self.visit_expr(&while_expr.cond);
+        self.nested(|this| this.visit_block(&while_expr.body));
+    }
+
+    fn visit_expr_for_loop(&mut self, for_loop: &'ast syn::ExprForLoop) {
+        self.branches += 1;
+        self.visit_expr(&for_loop.expr);
+        self.nested(|this| this.visit_block(&for_loop.body));
+    }
+
+    fn visit_expr_loop(&mut self, loop_expr: &'ast syn::ExprLoop) {
+        self.nested(|this| this.visit_block(&loop_expr.body));
+    }
+
+    fn visit_expr_match(&mut self, match_expr: &'ast syn::ExprMatch) {
+        self.branches += match_expr.arms.len().saturating_sub(1);
+        self.branches += match_expr.arms.iter().filter(|arm| arm.guard.is_some()).count();
+        self.visit_expr(&match_expr.expr);
+        self.nested(|this| {
+            for arm in &match_expr.arms {
+                this.visit_arm(arm);
+            }
+        });
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        self.nested(|this| this.visit_expr(&closure.body));
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        if matches!(binary.op, syn::BinOp::And(_) | syn::BinOp::Or(_)) {
+            self.branches += 1;
+        }
+        visit::visit_expr_binary(self, binary);
+    }
+
+    fn visit_expr_try(&mut self, try_expr: &'ast syn::ExprTry) {
+        self.branches += 1;
+        self.early_returns += 1;
+        visit::visit_expr_try(self, try_expr);
+    }
+
+    fn visit_expr_return(&mut self, return_expr: &'ast syn::ExprReturn) {
+        self.early_returns += 1;
+        visit::visit_expr_return(self, return_expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Ok(args) = mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// 関数ごとの指標にファンイン・ファンアウトを加え、指定キーの降順に並べる
+pub fn build_report(parser: &RustParser, params: &GetMetricsParams) -> GetMetricsResponse {
+    let include_tests = params.include_tests.unwrap_or(false);
+    let calls = parser.get_call_graph();
+
+    // ファンアウトはインデックス済みの関数への呼び出しのみ数える
+    let function_names: HashSet<&str> = parser.get_all_symbols().values()
+        .flatten()
+        .filter(|s| s.symbol_type == SymbolType::Function && s.crate_name.is_none())
+        .map(|s| s.name.as_str())
+        .collect();
+
+    let mut functions: Vec<FunctionMetrics> = parser.get_function_metrics().iter()
+        .filter(|m| params.file_path.as_deref().is_none_or(|path| m.file_path == path))
+        .filter(|m| params.function.as_deref().is_none_or(|name| m.name == name))
+        .filter_map(|m| {
+            let symbol = parser.find_symbol(&m.name, Some(SymbolType::Function))?
+                .into_iter()
+                .find(|s| s.file_path == m.file_path && s.line == m.line)?;
+            if symbol.is_test && !include_tests {
+                return None;
+            }
+
+            let fan_in = calls.iter()
+                .filter(|c| c.callee == m.name)
+                .map(|c| (&c.caller, &c.caller_file, c.caller_line))
+                .collect::<HashSet<_>>()
+                .len();
+            let fan_out = calls.iter()
+                .filter(|c| c.caller == m.name && c.caller_file == m.file_path && c.caller_line == m.line)
+                .filter(|c| function_names.contains(c.callee.as_str()))
+                .map(|c| c.callee.as_str())
+                .collect::<HashSet<_>>()
+                .len();
+
+            Some(FunctionMetrics {
+                name: m.name.clone(),
+                file_path: m.file_path.clone(),
+                line: m.line,
+                end_line: symbol.end_line,
+                scope: symbol.scope.clone(),
+                visibility: symbol.visibility.clone(),
+                lines_of_code: m.lines_of_code,
+                cyclomatic_complexity: m.body.cyclomatic_complexity,
+                max_nesting: m.body.max_nesting,
+                params: m.params,
+                early_returns: m.body.early_returns,
+                fan_in,
+                fan_out,
+            })
+        })
+        .collect();
+
+    let sort_by = params.sort_by.unwrap_or(MetricsSortKey::Complexity);
+    functions.sort_by(|a, b| {
+        sort_value(b, sort_by).cmp(&sort_value(a, sort_by))
+            .then(a.file_path.cmp(&b.file_path))
+            .then(a.line.cmp(&b.line))
+    });
+
+    let total_functions = functions.len();
+    functions.truncate(params.limit.unwrap_or(DEFAULT_METRICS_LIMIT));
+
+    GetMetricsResponse { functions, total_functions }
+}
+
+/// CLI・MCP で指定する並び替えキー名（complexity, lines, nesting, params, returns, fan_in, fan_out）
+pub fn parse_sort_key(name: &str) -> Option<MetricsSortKey> {
+    match name {
+        "complexity" | "cc" => Some(MetricsSortKey::Complexity),
+        "lines" | "loc" => Some(MetricsSortKey::Lines),
+        "nesting" => Some(MetricsSortKey::Nesting),
+        "params" => Some(MetricsSortKey::Params),
+        "returns" => Some(MetricsSortKey::EarlyReturns),
+        "fan_in" => Some(MetricsSortKey::FanIn),
+        "fan_out" => Some(MetricsSortKey::FanOut),
+        _ => None,
+    }
+}
+
+fn sort_value(metrics: &FunctionMetrics, key: MetricsSortKey) -> usize {
+    match key {
+        MetricsSortKey::Complexity => metrics.cyclomatic_complexity,
+        MetricsSortKey::Lines => metrics.lines_of_code,
+        MetricsSortKey::Nesting => metrics.max_nesting,
+        MetricsSortKey::Params => metrics.params,
+        MetricsSortKey::EarlyReturns => metrics.early_returns,
+        MetricsSortKey::FanIn => metrics.fan_in,
+        MetricsSortKey::FanOut => metrics.fan_out,
+    }
+}
+
+/// 指標を表形式のテキストに整形（CLI 用）
+pub fn format_table(report: &GetMetricsResponse) -> String {
+    let mut result = format!(
+        "{:<40} {:>5} {:>5} {:>5} {:>6} {:>7} {:>6} {:>7}  {}\n",
+        "function", "loc", "cc", "nest", "params", "returns", "fan_in", "fan_out", "location",
+    );
+    for m in &report.functions {
+        let name = match &m.scope {
+            Some(scope) => format!("{scope}::{}", m.name),
+            None => m.name.clone(),
+        };
+        result.push_str(&format!(
+            "{:<40} {:>5} {:>5} {:>5} {:>6} {:>7} {:>6} {:>7}  {}:{}\n",
+            name, m.lines_of_code, m.cyclomatic_complexity, m.max_nesting,
+            m.params, m.early_returns, m.fan_in, m.fan_out, m.file_path, m.line,
+        ));
+    }
+    result.push_str(&format!("\n{} of {} function(s)\n", report.functions.len(), report.total_functions));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_metrics() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/lib.rs", r#"
+pub fn classify(values: &[i32], limit: i32) -> Result<usize, String> {
+    let first = parse(values)?;
+    if first > limit && limit > 0 {
+        return Err("too large".to_string());
+    } else if first < 0 {
+        for v in values {
+            match v {
+                0 => {}
+                x if *x > 1 => {}
+                _ => {}
+            }
+        }
+    }
+    Ok(first as usize)
+}
+
+fn parse(values: &[i32]) -> Result<i32, String> {
+    values.first().copied().ok_or_else(|| "empty".to_string())
+}
+
+fn caller() {
+    let _ = classify(&[], 1);
+    let _ = parse(&[]);
+}
+"#).unwrap();
+
+        let params = GetMetricsParams { file_path: None, function: None, sort_by: None, limit: None, include_tests: None };
+        let report = build_report(&parser, &params);
+        let classify = &report.functions[0];
+        assert_eq!(classify.name, "classify");
+        // 1 + ? + if + && + else if + for + match(2アーム追加 + ガード1)
+        assert_eq!(classify.cyclomatic_complexity, 9);
+        assert_eq!((classify.max_nesting, classify.params, classify.early_returns), (3, 2, 2));
+        assert_eq!((classify.lines_of_code, classify.fan_in, classify.fan_out), (15, 1, 1));
+
+        let by_fan_in = build_report(&parser, &GetMetricsParams { sort_by: Some(MetricsSortKey::FanIn), limit: Some(1), ..params });
+        assert_eq!(by_fan_in.functions[0].name, "parse");
+        assert_eq!((by_fan_in.functions.len(), by_fan_in.total_functions), (1, 3));
+    }
+}
@@ -0,0 +1,518 @@
+use crate::api_surface::{item_attrs, path_attribute};
+use crate::imports::{find_module_file, package_name, parent_module};
+use crate::parser::{RustParser, is_test_item};
+use crate::protocol::{ModuleDependency, ModuleGraphNode, ModuleGraphResponse, ModuleReference, ModuleReferenceKind, SymbolType};
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use syn::visit::Visit;
+
+/// use の別名をたどる深さの上限
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// 解決前の参照（書かれたパスと位置）
+struct RawReference {
+    segments: Vec<String>,
+    file_path: String,
+    line: usize,
+    column: usize,
+    kind: ModuleReferenceKind,
+}
+
+#[derive(Default)]
+struct Module {
+    file_path: String,
+    line: usize,
+    children: HashMap<String, String>,      // mod 名 -> モジュールのパス
+    aliases: HashMap<String, Vec<String>>,  // use で持ち込んだ名前 -> パス
+    references: Vec<RawReference>,
+}
+
+/// クレートのルートから mod 宣言をたどってモジュールと参照を集める
+struct ModuleCollector {
+    modules: BTreeMap<String, Module>,
+    files: HashMap<PathBuf, String>,        // 正規化したファイルパス -> モジュール
+    extern_roots: HashMap<String, String>,  // 別クレートとして参照できるルート（バイナリから見たライブラリ）
+    calls: Vec<(String, String, ModuleReference)>,  // 呼び出しによる依存（解決済み）
+}
+
+impl ModuleCollector {
+    fn collect_root(&mut self, root: &str, root_file: &Path) {
+        let Ok(content) = std::fs::read_to_string(root_file) else {
+            return;
+        };
+        let Ok(syntax_tree) = syn::parse_file(&content) else {
+            return;
+        };
+        let root_dir = root_file.parent().unwrap_or(Path::new(""));
+        self.collect(root, root_file, root_dir, (root_file.to_string_lossy().to_string(), 1), &syntax_tree.items);
+    }
+
+    /// declared_at は mod 宣言の位置（ルートはルートのファイルの先頭）
+    fn collect(&mut self, module_path: &str, file: &Path, module_dir: &Path, declared_at: (String, usize), items: &[syn::Item]) {
+        if let Ok(canonical) = file.canonicalize() {
+            self.files.entry(canonical).or_insert_with(|| module_path.to_string());
+        }
+        let file_path = file.to_string_lossy().to_string();
+        let mut module = Module { file_path: declared_at.0, line: declared_at.1, ..Default::default() };
+        let mut visitor = PathCollector { file_path: &file_path, references: Vec::new() };
+
+        for item in items {
+            if is_test_item(item_attrs(item), &file_path) {
+                continue;
+            }
+            match item {
+                syn::Item::Mod(item_mod) => {
+                    let name = item_mod.ident.to_string();
+                    let child_path = format!("{module_path}::{name}");
+                    module.children.insert(name.clone(), child_path.clone());
+                    let declared_at = (file_path.clone(), item_mod.ident.span().start().line);
+
+                    if let Some((_, child_items)) = &item_mod.content {
+                        self.collect(&child_path, file, &module_dir.join(&name), declared_at, child_items);
+                    } else if let Some(child_file) = find_module_file(file, module_dir, &name, &item_mod.attrs)
+                        && let Ok(content) = std::fs::read_to_string(&child_file)
+                        && let Ok(syntax_tree) = syn::parse_file(&content) {
+                        let child_dir = if child_file.ends_with("mod.rs") || path_attribute(&item_mod.attrs).is_some() {
+                            child_file.parent().unwrap_or(Path::new("")).to_path_buf()
+                        } else {
+                            child_file.with_extension("")
+                        };
+                        self.collect(&child_path, &child_file, &child_dir, declared_at, &syntax_tree.items);
+                    }
+                }
+                syn::Item::Use(item_use) => {
+                    let mut paths = Vec::new();
+                    use_paths(&item_use.tree, &mut Vec::new(), &mut paths);
+                    for (segments, name, span) in paths {
+                        if let Some(name) = name.filter(|name| name != "_") {
+                            module.aliases.insert(name, segments.clone());
+                        }
+                        visitor.push(segments, span, ModuleReferenceKind::Use);
+                    }
+                }
+                _ => visitor.visit_item(item),
+            }
+        }
+        module.references = visitor.references;
+        self.modules.insert(module_path.to_string(), module);
+    }
+
+    /// module から見たパスが指すモジュールのうち最も深いもの（クレート外なら None）
+    fn resolve(&self, module: &str, segments: &[String], depth: usize) -> Option<String> {
+        let (first, rest) = segments.split_first()?;
+        let mut current = match first.as_str() {
+            "crate" => module.split("::").next().unwrap_or(module).to_string(),
+            "self" => module.to_string(),
+            "super" => parent_module(module).to_string(),
+            name => match self.child(module, name, depth) {
+                Some(child) => child,
+                None => self.extern_roots.get(name)?.clone(),
+            },
+        };
+        for segment in rest {
+            current = match segment.as_str() {
+                "super" => parent_module(&current).to_string(),
+                name => match self.child(&current, name, depth) {
+                    Some(child) => child,
+                    None => break,  // 残りはアイテム・関連関数など
+                },
+            };
+        }
+        Some(current)
+    }
+
+    /// module の中の name（子モジュール、または use で持ち込んだ名前の定義があるモジュール）
+    fn child(&self, module: &str, name: &str, depth: usize) -> Option<String> {
+        let node = self.modules.get(module)?;
+        if let Some(child) = node.children.get(name) {
+            return Some(child.clone());
+        }
+        let alias = node.aliases.get(name)?;
+        if depth >= MAX_ALIAS_DEPTH {
+            return None;
+        }
+        self.resolve(module, alias, depth + 1)
+    }
+
+    fn module_of(&self, file_path: &str) -> Option<&String> {
+        let canonical = Path::new(file_path).canonicalize().ok()?;
+        self.files.get(&canonical)
+    }
+
+    /// コールグラフの呼び出しのうち、呼び出し先の定義が1つのモジュールに決まるものを依存に加える
+    ///
+    /// 呼び出しは名前だけで記録されているので、メソッド呼び出し（`x.push()` など）と、
+    /// 同名の関数が複数のモジュールにある場合は加えない。
+    /// モジュールはファイル単位で求める（同じファイルのインラインモジュールは区別しない）。
+    fn collect_calls(&mut self, parser: &RustParser) {
+        let project_function = |symbol: &&crate::parser::SymbolInfo| {
+            symbol.crate_name.is_none() && !symbol.is_test && !symbol.signature.starts_with("let ")
+        };
+        let mut calls = Vec::new();
+        for call in parser.get_call_graph().iter().filter(|call| !call.method_call) {
+            let is_project_caller = parser.find_symbol(&call.caller, Some(SymbolType::Function))
+                .unwrap_or_default()
+                .iter()
+                .any(|s| s.file_path == call.caller_file && s.line == call.caller_line && project_function(s));
+            let Some(from) = self.module_of(&call.caller_file).filter(|_| is_project_caller) else {
+                continue;
+            };
+            let targets: BTreeSet<&String> = parser.find_symbol(&call.callee, Some(SymbolType::Function))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(project_function)
+                .filter_map(|s| self.module_of(&s.file_path))
+                .collect();
+            if targets.len() != 1 {
+                continue;
+            }
+            let to = targets.into_iter().next().unwrap();
+            if to != from {
+                calls.push((from.clone(), to.clone(), ModuleReference {
+                    file_path: call.caller_file.clone(),
+                    line: call.call_line,
+                    column: call.call_column,
+                    path: format!("{}()", call.callee),
+                    kind: ModuleReferenceKind::Call,
+                }));
+            }
+        }
+        self.calls = calls;
+    }
+
+    fn into_graph(self) -> ModuleGraphResponse {
+        let mut edges: BTreeMap<(String, String), Vec<ModuleReference>> = BTreeMap::new();
+        for (module_path, module) in &self.modules {
+            for reference in &module.references {
+                let Some(target) = self.resolve(module_path, &reference.segments, 0) else {
+                    continue;
+                };
+                if target == *module_path || !self.modules.contains_key(&target) {
+                    continue;
+                }
+                edges.entry((module_path.clone(), target)).or_default().push(ModuleReference {
+                    file_path: reference.file_path.clone(),
+                    line: reference.line,
+                    column: reference.column,
+                    path: reference.segments.join("::"),
+                    kind: reference.kind,
+                });
+            }
+        }
+        for (from, to, reference) in self.calls {
+            edges.entry((from, to)).or_default().push(reference);
+        }
+
+        let modules: Vec<ModuleGraphNode> = self.modules.iter()
+            .map(|(path, module)| ModuleGraphNode { path: path.clone(), file_path: module.file_path.clone(), line: module.line })
+            .collect();
+        let dependencies: Vec<ModuleDependency> = edges.into_iter()
+            .map(|((from, to), mut references)| {
+                references.sort_by(|a, b| (&a.file_path, a.line, a.column).cmp(&(&b.file_path, b.line, b.column)));
+                references.dedup_by(|a, b| (&a.file_path, a.line, a.column) == (&b.file_path, b.line, b.column));
+                ModuleDependency { from, to, references }
+            })
+            .collect();
+        let cycles = strongly_connected(&modules, &dependencies);
+        ModuleGraphResponse { modules, dependencies, cycles }
+    }
+}
+
+/// アイテムの中の修飾パス（2セグメント以上）と、関数の中の use を集める
+struct PathCollector<'a> {
+    file_path: &'a str,
+    references: Vec<RawReference>,
+}
+
+impl PathCollector<'_> {
+    fn push(&mut self, segments: Vec<String>, span: proc_macro2::Span, kind: ModuleReferenceKind) {
+        let start = span.start();
+        self.references.push(RawReference {
+            segments,
+            file_path: self.file_path.to_string(),
+            line: start.line,
+            column: start.column,
+            kind,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for PathCollector<'_> {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        if path.segments.len() >= 2 {
+            let segments = path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+            self.push(segments, path.segments[0].ident.span(), ModuleReferenceKind::Path);
+        }
+        syn::visit::visit_path(self, path);
+    }
+
+    fn visit_item_use(&mut self, item_use: &'ast syn::ItemUse) {
+        let mut paths = Vec::new();
+        use_paths(&item_use.tree, &mut Vec::new(), &mut paths);
+        for (segments, _, span) in paths {
+            self.push(segments, span, ModuleReferenceKind::Use);
+        }
+    }
+}
+
+/// use ツリーを (パス, 持ち込む名前, 末尾の位置) に展開する（名前が None ならグロブ、`{self}` はモジュール自身）
+fn use_paths(tree: &syn::UseTree, prefix: &mut Vec<String>, out: &mut Vec<(Vec<String>, Option<String>, proc_macro2::Span)>) {
+    match tree {
+        syn::UseTree::Path(use_path) => {
+            prefix.push(use_path.ident.to_string());
+            use_paths(&use_path.tree, prefix, out);
+            prefix.pop();
+        }
+        syn::UseTree::Name(use_name) if use_name.ident == "self" => {
+            out.push((prefix.clone(), prefix.last().cloned(), use_name.ident.span()));
+        }
+        syn::UseTree::Name(use_name) => {
+            let name = use_name.ident.to_string();
+            let mut path = prefix.clone();
+            path.push(name.clone());
+            out.push((path, Some(name), use_name.ident.span()));
+        }
+        syn::UseTree::Rename(use_rename) => {
+            let path = [prefix.as_slice(), &[use_rename.ident.to_string()]].concat();
+            out.push((path, Some(use_rename.rename.to_string()), use_rename.ident.span()));
+        }
+        syn::UseTree::Glob(glob) => out.push((prefix.clone(), None, glob.star_token.span)),
+        syn::UseTree::Group(group) => {
+            for item in &group.items {
+                use_paths(item, prefix, out);
+            }
+        }
+    }
+}
+
+/// 2つ以上のモジュールからなる強連結成分（Tarjan のアルゴリズム）
+fn strongly_connected(modules: &[ModuleGraphNode], dependencies: &[ModuleDependency]) -> Vec<Vec<String>> {
+    struct Tarjan<'a> {
+        adjacency: HashMap<&'a str, Vec<&'a str>>,
+        index: HashMap<&'a str, usize>,
+        low_link: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        components: Vec<Vec<String>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, node: &'a str) {
+            let index = self.index.len();
+            self.index.insert(node, index);
+            self.low_link.insert(node, index);
+            self.stack.push(node);
+            self.on_stack.insert(node);
+
+            for next in self.adjacency.get(node).cloned().unwrap_or_default() {
+                if !self.index.contains_key(next) {
+                    self.visit(next);
+                    let low = self.low_link[node].min(self.low_link[next]);
+                    self.low_link.insert(node, low);
+                } else if self.on_stack.contains(next) {
+                    let low = self.low_link[node].min(self.index[next]);
+                    self.low_link.insert(node, low);
+                }
+            }
+
+            if self.low_link[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        adjacency: HashMap::new(),
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        components: Vec::new(),
+    };
+    for dependency in dependencies {
+        tarjan.adjacency.entry(dependency.from.as_str()).or_default().push(dependency.to.as_str());
+    }
+    for module in modules {
+        if !tarjan.index.contains_key(module.path.as_str()) {
+            tarjan.visit(&module.path);
+        }
+    }
+    let mut components = tarjan.components;
+    components.sort();
+    components
+}
+
+/// クレートのモジュール依存グラフを求める（ファイルを読んで mod 宣言をたどるので、インデックスは使わない）
+///
+/// src/lib.rs と src/main.rs の両方がある場合、バイナリのモジュールは `crate::`、
+/// ライブラリのモジュールはパッケージ名から始まるパスになる。
+pub fn build(crate_dir: &Path) -> Result<ModuleGraphResponse> {
+    Ok(collect(crate_dir)?.into_graph())
+}
+
+/// use 宣言・修飾パスに加えて、コールグラフの呼び出しも依存に含める（parser はインデックス済みであること）
+pub fn build_with_calls(parser: &RustParser, crate_dir: &Path) -> Result<ModuleGraphResponse> {
+    let mut collector = collect(crate_dir)?;
+    collector.collect_calls(parser);
+    Ok(collector.into_graph())
+}
+
+fn collect(crate_dir: &Path) -> Result<ModuleCollector> {
+    let lib = crate_dir.join("src/lib.rs");
+    let bin = crate_dir.join("src/main.rs");
+    if !lib.is_file() && !bin.is_file() {
+        bail!("No src/lib.rs or src/main.rs in {}", crate_dir.display());
+    }
+
+    let mut collector = ModuleCollector { modules: BTreeMap::new(), files: HashMap::new(), extern_roots: HashMap::new(), calls: Vec::new() };
+    if lib.is_file() {
+        let root = if bin.is_file() {
+            let manifest = std::fs::read_to_string(crate_dir.join("Cargo.toml")).unwrap_or_default();
+            package_name(&manifest).unwrap_or_else(|| "lib".to_string())
+        } else {
+            "crate".to_string()
+        };
+        collector.extern_roots.insert(root.clone(), root.clone());
+        collector.collect_root(&root, &lib);
+    }
+    if bin.is_file() {
+        collector.collect_root("crate", &bin);
+    }
+    Ok(collector)
+}
+
+fn node_id(path: &str) -> String {
+    // Mermaid用のID生成（英数字のみ）
+    path.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// 依存先をツリー形式で表示（graph --kind modules の tree）
+pub fn format_tree(graph: &ModuleGraphResponse) -> String {
+    let mut result = String::from("📦 Module Dependency Graph\n\n");
+    for module in &graph.modules {
+        result.push_str(&format!("{}\n", module.path));
+        let dependencies: Vec<_> = graph.dependencies.iter().filter(|d| d.from == module.path).collect();
+        for (i, dependency) in dependencies.iter().enumerate() {
+            let branch = if i + 1 == dependencies.len() { "└──" } else { "├──" };
+            result.push_str(&format!("{} {} ({})\n", branch, dependency.to, dependency.references.len()));
+        }
+    }
+    result.push_str(&format_cycles(graph));
+    result
+}
+
+/// 循環の一覧（循環の中の依存と、その最初の参照位置）
+fn format_cycles(graph: &ModuleGraphResponse) -> String {
+    if graph.cycles.is_empty() {
+        return "\n✅ No module cycles\n".to_string();
+    }
+    let mut result = format!("\n🔄 Module Cycles: {}\n", graph.cycles.len());
+    for cycle in &graph.cycles {
+        result.push_str(&format!("\n{}\n", cycle.join(" ⇄ ")));
+        for dependency in graph.dependencies.iter().filter(|d| cycle.contains(&d.from) && cycle.contains(&d.to)) {
+            if let Some(reference) = dependency.references.first() {
+                result.push_str(&format!("  {} → {}  {}:{}:{} {}\n",
+                    dependency.from, dependency.to, reference.file_path, reference.line, reference.column + 1, reference.path));
+            }
+        }
+    }
+    result
+}
+
+/// Mermaid 形式（循環に含まれるモジュールを強調する）
+pub fn format_mermaid(graph: &ModuleGraphResponse) -> String {
+    let mut result = String::from("```mermaid\ngraph TD\n");
+    for module in &graph.modules {
+        result.push_str(&format!("    {}[{}]\n", node_id(&module.path), module.path));
+    }
+    for dependency in &graph.dependencies {
+        result.push_str(&format!("    {} --> {}\n", node_id(&dependency.from), node_id(&dependency.to)));
+    }
+    let in_cycle: Vec<String> = graph.cycles.iter().flatten().map(|path| node_id(path)).collect();
+    if !in_cycle.is_empty() {
+        result.push_str("    classDef cycle fill:#fdd,stroke:#c00\n");
+        result.push_str(&format!("    class {} cycle\n", in_cycle.join(",")));
+    }
+    result.push_str("```\n");
+    result
+}
+
+pub fn format_stats(graph: &ModuleGraphResponse) -> String {
+    let mut dependents: BTreeMap<&str, usize> = BTreeMap::new();
+    for dependency in &graph.dependencies {
+        *dependents.entry(dependency.to.as_str()).or_default() += 1;
+    }
+    let most_depended = dependents.iter()
+        .max_by_key(|(path, count)| (**count, std::cmp::Reverse(**path)))
+        .map(|(path, count)| format!("{path} ({count})"))
+        .unwrap_or_else(|| "-".to_string());
+
+    format!("📊 Module Graph Statistics:\n\
+             ├── Total Modules: {}\n\
+             ├── Total Dependencies: {}\n\
+             ├── Cycles: {}\n\
+             └── Most Depended On: {}\n",
+            graph.modules.len(),
+            graph.dependencies.len(),
+            graph.cycles.len(),
+            most_depended)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_module_graph() {
+        let dir = TempDir::new().unwrap();
+        let files = [
+            ("src/main.rs", "mod a;\nmod b;\nmod c;\n\nfn main() {\n    c::run();\n}\n"),
+            ("src/a.rs", "use crate::b::helper;\n\npub fn entry() {\n    helper();\n}\n"),
+            ("src/b.rs", "pub fn helper() {\n    crate::a::entry();\n}\n\n#[cfg(test)]\nmod tests {\n    use crate::c::run;\n}\n"),
+            ("src/c.rs", "use crate::a;\nuse std::fmt;\n\npub fn run() {\n    a::entry();\n}\n"),
+        ];
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+        }
+
+        let graph = build(dir.path()).unwrap();
+        let modules: Vec<&str> = graph.modules.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(modules, vec!["crate", "crate::a", "crate::b", "crate::c"]);
+
+        let edges: Vec<(&str, &str, usize)> = graph.dependencies.iter()
+            .map(|d| (d.from.as_str(), d.to.as_str(), d.references.len()))
+            .collect();
+        // テストモジュールの use、std の use は含めない。c は use と修飾パスの2つの参照
+        assert_eq!(edges, vec![
+            ("crate", "crate::c", 1),
+            ("crate::a", "crate::b", 1),
+            ("crate::b", "crate::a", 1),
+            ("crate::c", "crate::a", 2),
+        ]);
+
+        let reference = &graph.dependencies[2].references[0];
+        assert_eq!((reference.line, reference.column), (2, 4));
+        assert_eq!(reference.path, "crate::a::entry");
+        assert_eq!(reference.kind, ModuleReferenceKind::Path);
+
+        assert_eq!(graph.cycles, vec![vec!["crate::a".to_string(), "crate::b".to_string()]]);
+    }
+}
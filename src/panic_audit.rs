@@ -0,0 +1,153 @@
+use crate::parser::{PanicSiteInfo, RustParser};
+use crate::protocol::{FunctionPanics, PanicReportParams, PanicReportResponse, PanicSite, PanicSummary, SymbolType};
+use std::collections::{HashMap, VecDeque};
+
+/// 関数の識別子（名前, ファイル, 定義行）。CallInfo の caller と対応する
+type FunctionKey = (String, String, usize);
+
+/// パニック箇所を関数ごとにまとめ、コールグラフを逆に辿ってパニックしうる関数を求める
+pub fn build_report(parser: &RustParser, params: &PanicReportParams) -> PanicReportResponse {
+    let include_tests = params.include_tests.unwrap_or(false);
+
+    // 対象となる関数（依存クレートと、指定がなければテストを除く）
+    let functions: HashMap<FunctionKey, _> = parser.get_all_symbols().values()
+        .flatten()
+        .filter(|s| s.symbol_type == SymbolType::Function && s.crate_name.is_none())
+        .filter(|s| include_tests || !s.is_test)
+        .map(|s| ((s.name.clone(), s.file_path.clone(), s.line), s))
+        .collect();
+
+    let mut direct_sites: HashMap<FunctionKey, Vec<&PanicSiteInfo>> = HashMap::new();
+    for site in parser.get_panic_sites() {
+        let key = (site.function.clone(), site.file_path.clone(), site.function_line);
+        if functions.contains_key(&key) {
+            direct_sites.entry(key).or_default().push(site);
+        }
+    }
+
+    // 直接パニックする関数から呼び出し元へ幅優先で広げる（最短の経路を記録）
+    let mut panic_paths: HashMap<FunctionKey, Vec<String>> = HashMap::new();
+    let mut queue: VecDeque<FunctionKey> = VecDeque::new();
+    for key in direct_sites.keys() {
+        panic_paths.insert(key.clone(), vec![key.0.clone()]);
+        queue.push_back(key.clone());
+    }
+    while let Some(key) = queue.pop_front() {
+        let path = panic_paths[&key].clone();
+        for call in parser.get_calls_to_function(&key.0) {
+            let caller = (call.caller.clone(), call.caller_file.clone(), call.caller_line);
+            if !functions.contains_key(&caller) || panic_paths.contains_key(&caller) {
+                continue;
+            }
+            let mut caller_path = vec![caller.0.clone()];
+            caller_path.extend(path.iter().cloned());
+            panic_paths.insert(caller.clone(), caller_path);
+            queue.push_back(caller);
+        }
+    }
+
+    let public_only = params.public_only.unwrap_or(false);
+    let mut results: Vec<FunctionPanics> = panic_paths.into_iter()
+        .filter_map(|(key, panic_path)| {
+            let symbol = functions[&key];
+            let included = params.file_path.as_deref().is_none_or(|path| symbol.file_path == path)
+                && params.function.as_deref().is_none_or(|name| symbol.name == name)
+                && (!public_only || symbol.visibility == "pub");
+            if !included {
+                return None;
+            }
+
+            let mut sites: Vec<PanicSite> = direct_sites.get(&key)
+                .map(|sites| sites.iter().map(|site| PanicSite::from(*site)).collect())
+                .unwrap_or_default();
+            sites.sort_by(|a, b| a.line.cmp(&b.line).then(a.column.cmp(&b.column)));
+
+            Some(FunctionPanics {
+                name: symbol.name.clone(),
+                file_path: symbol.file_path.clone(),
+                line: symbol.line,
+                visibility: symbol.visibility.clone(),
+                scope: symbol.scope.clone(),
+                sites,
+                panic_path,
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+
+    let mut summary = PanicSummary {
+        may_panic_functions: results.len(),
+        public_may_panic: results.iter().filter(|f| f.visibility == "pub").count(),
+        functions_with_sites: results.iter().filter(|f| !f.sites.is_empty()).count(),
+        ..Default::default()
+    };
+    for site in results.iter().flat_map(|f| &f.sites) {
+        summary.total_sites += 1;
+        *summary.sites_by_kind.entry(site.kind).or_default() += 1;
+    }
+
+    PanicReportResponse { functions: results, summary }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::PanicKind;
+
+    #[test]
+    fn test_panic_report() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/lib.rs", r#"
+pub fn entry(items: &[u8]) -> u8 {
+    middle(items)
+}
+
+fn middle(items: &[u8]) -> u8 {
+    first(items)
+}
+
+fn first(items: &[u8]) -> u8 {
+    let parsed: u8 = "1".parse().unwrap();
+    assert!(parsed > 0);
+    items[0] + parsed
+}
+
+pub fn safe(x: Option<u8>) -> u8 {
+    x.unwrap_or(0)
+}
+
+pub fn later() {
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_first() {
+        assert_eq!(super::first(&[1]), 2);
+    }
+}
+"#).unwrap();
+
+        let params = PanicReportParams { file_path: None, function: None, public_only: None, include_tests: None };
+        let report = build_report(&parser, &params);
+        let found: Vec<(&str, usize, Vec<&str>)> = report.functions.iter()
+            .map(|f| (f.name.as_str(), f.sites.len(), f.panic_path.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(found, vec![
+            ("entry", 0, vec!["entry", "middle", "first"]),
+            ("middle", 0, vec!["middle", "first"]),
+            ("first", 3, vec!["first"]),
+            ("later", 1, vec!["later"]),
+        ]);
+        let kinds: Vec<PanicKind> = report.functions[2].sites.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, vec![PanicKind::Unwrap, PanicKind::Assert, PanicKind::Index]);
+        assert_eq!((report.summary.total_sites, report.summary.public_may_panic), (4, 2));
+
+        let public = build_report(&parser, &PanicReportParams { public_only: Some(true), ..params.clone() });
+        assert_eq!(public.functions.len(), 2);
+
+        let with_tests = build_report(&parser, &PanicReportParams { include_tests: Some(true), ..params });
+        assert!(with_tests.functions.iter().any(|f| f.name == "test_first" && f.sites.len() == 1));
+    }
+}
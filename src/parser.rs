@@ -1,8 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use syn::{File, Item, ItemFn, ItemStruct, ItemEnum, ItemTrait, Signature, Visibility};
+use syn::{File, ItemFn, ItemStruct, ItemEnum, ItemTrait, Signature, Visibility};
+use syn::spanned::Spanned;
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use proc_macro2::Span;
 use anyhow::{Context, Result};
-use crate::protocol::SymbolType;
+use crate::protocol::{AsyncHazardKind, ErrorReturnKind, ErrorSiteKind, ImplSource, PanicKind, ParseRecovery, SymbolType, TestKind, UnsafeKind};
 
 #[derive(Debug, Clone)]
 pub struct SymbolInfo {
@@ -11,9 +15,14 @@ pub struct SymbolInfo {
     pub file_path: String,
     pub line: usize,
     pub column: usize,
+    pub end_line: usize,  // アイテム末尾の行
     pub signature: String,
     pub visibility: String,
     pub generics: Option<String>,
+    pub is_test: bool,  // テストコード（#[test] / #[cfg(test)] / tests/ 配下）かどうか
+    pub crate_name: Option<String>,  // 依存クレートのシンボルならクレート名（None はプロジェクト内）
+    pub scope: Option<String>,  // 囲んでいるモジュール・型・関数（例: "tests", "CodeIndexer::new"）。トップレベルは None
+    pub is_method: bool,  // impl・トレイトの中の関数（メソッド・関連関数）か
 }
 
 #[derive(Debug, Clone)]
@@ -43,12 +52,154 @@ pub struct CallInfo {
     pub callee: String,
     pub call_line: usize,
     pub call_column: usize,
-    pub call_context: String,
+    pub method_call: bool,  // `x.name()` の形（レシーバの型が分からないので、同名の関数とは限らない）
+}
+
+/// テスト（#[test] 関数・統合テスト・doctest）
+#[derive(Debug, Clone)]
+pub struct TestInfo {
+    pub name: String,  // doctest は "doctest_<アイテム名>_<行>"
+    pub kind: TestKind,
+    pub file_path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub scope: Option<String>,
+    pub attribute: Option<String>,  // "test" / "tokio::test" など（doctest は None）
+}
+
+/// unsafe なコード（unsafe fn / unsafe impl / unsafe trait / unsafe ブロック）
+#[derive(Debug, Clone)]
+pub struct UnsafeInfo {
+    pub kind: UnsafeKind,
+    pub file_path: String,
+    pub line: usize,    // unsafe キーワードの位置
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub enclosing_symbol: Option<String>,  // unsafe fn・trait 自身、impl は対象の型、ブロックは囲んでいる関数のパス
+    pub signature: String,                 // "unsafe impl Send for Foo" など（ブロックはその行）
+    pub safety_comment: Option<String>,    // 直前の // SAFETY: コメント
+    pub is_test: bool,
+}
+
+/// パニックしうる箇所（unwrap・expect・panic! 系マクロ・assert!・インデックスアクセス）
+#[derive(Debug, Clone)]
+pub struct PanicSiteInfo {
+    pub kind: PanicKind,
+    pub function: String,  // 囲んでいる関数・クロージャ（CallInfo の caller と同じ）
+    pub function_line: usize,
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub context: String,
+}
+
+/// 非同期処理の危険箇所の候補（UnawaitedFuture は呼び出し先が async fn かを問わず記録）
+#[derive(Debug, Clone)]
+pub struct AsyncSiteInfo {
+    pub kind: AsyncHazardKind,
+    pub name: String,      // "std::fs::read" / ".lock()" / ガードの変数名 / 呼び出した関数名
+    pub function: String,  // 囲んでいる関数（CallInfo の caller と同じ）
+    pub function_line: usize,
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub context: String,
+    pub in_async_block: bool,  // 同期関数の中の async ブロック・async クロージャ
+}
+
+/// トレイトの実装（#[derive(...)] は合成した実装として記録）
+#[derive(Debug, Clone)]
+pub struct ImplInfo {
+    pub trait_name: String,  // パスの最後のセグメント（"Serialize"）
+    pub trait_path: String,  // 書かれたとおりのパス（"serde::Serialize"）
+    pub type_name: String,   // type_name と同じ（`impl<T> Foo<T>` → "Foo"）
+    pub file_path: String,
+    pub line: usize,         // derive 属性または impl ブロックの行
+    pub source: ImplSource,
+    pub is_test: bool,
+}
+
+/// Result・Option を返す関数の戻り値の型
+#[derive(Debug, Clone)]
+pub struct ErrorSignatureInfo {
+    pub name: String,
+    pub file_path: String,
+    pub line: usize,  // SymbolInfo の line と同じ（関数名の位置）
+    pub return_kind: ErrorReturnKind,
+    pub ok_type: String,
+    pub error_type: Option<String>,  // use の別名・type Result<T> の別名を展開したエラー型
+}
+
+/// エラーの伝播（?）・生成（Err(...)）・握りつぶしの箇所
+#[derive(Debug, Clone)]
+pub struct ErrorSiteInfo {
+    pub kind: ErrorSiteKind,
+    pub function: String,  // 囲んでいる関数（CallInfo の caller と同じ）
+    pub function_line: usize,
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub context: String,
+    pub callee: Option<String>,          // 結果を伝播・破棄している呼び出し先
+    pub explicit_error: Option<String>,  // map_err・context・Err(...) などから構文で分かるエラー型
+}
+
+/// 関数・メソッド・名前付きクロージャごとの規模と複雑さ
+#[derive(Debug, Clone)]
+pub struct FunctionMetricsInfo {
+    pub name: String,
+    pub file_path: String,
+    pub line: usize,  // SymbolInfo の line と同じ（関数名の位置）
+    pub lines_of_code: usize,
+    pub params: usize,
+    pub body: crate::metrics::BodyMetrics,
+}
+
+/// パースエラーの情報
+#[derive(Debug, Clone)]
+pub struct DiagnosticInfo {
+    pub file_path: String,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// parse_file の結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseOutcome {
+    /// 正常にパースできた
+    Parsed,
+    /// パースエラーがあり、前回正常にパースできた時点のシンボルを保持した
+    KeptLastGood,
+    /// パースエラーがあり、壊れたアイテム以外のトップレベルアイテムを救出した
+    Salvaged { recovered_items: usize },
+    /// パースエラーがあり、何も救出できなかった
+    Failed,
 }
 
 pub struct RustParser {
     symbols: HashMap<String, Vec<SymbolInfo>>,
     call_graph: Vec<CallInfo>,
+    tests: Vec<TestInfo>,
+    unsafe_sites: Vec<UnsafeInfo>,
+    panic_sites: Vec<PanicSiteInfo>,
+    async_sites: Vec<AsyncSiteInfo>,
+    error_signatures: Vec<ErrorSignatureInfo>,
+    error_sites: Vec<ErrorSiteInfo>,
+    impls: Vec<ImplInfo>,
+    function_metrics: Vec<FunctionMetricsInfo>,
+    diagnostics: Vec<DiagnosticInfo>,
+    recovery: HashMap<String, ParseRecovery>,  // パースエラーのあるファイル -> 復旧方法
+    good_files: HashSet<String>,  // 最後のパースが成功したファイル
 }
 
 impl RustParser {
@@ -56,161 +207,375 @@ impl RustParser {
         Self {
             symbols: HashMap::new(),
             call_graph: Vec::new(),
+            tests: Vec::new(),
+            unsafe_sites: Vec::new(),
+            panic_sites: Vec::new(),
+            async_sites: Vec::new(),
+            error_signatures: Vec::new(),
+            error_sites: Vec::new(),
+            impls: Vec::new(),
+            function_metrics: Vec::new(),
+            diagnostics: Vec::new(),
+            recovery: HashMap::new(),
+            good_files: HashSet::new(),
         }
     }
 
-    pub fn parse_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<()> {
+    /// ファイルを読み込んでパースし、既存のシンボルを置き換える
+    ///
+    /// パースエラー時もErrにはせず、診断情報を記録して ParseOutcome で結果を返す。
+    pub fn parse_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<ParseOutcome> {
         let file_path = file_path.as_ref();
         let content = std::fs::read_to_string(file_path)
             .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
 
-        let syntax_tree = syn::parse_file(&content)
-            .with_context(|| format!("Failed to parse file: {}", file_path.display()))?;
+        self.parse_source(&file_path.to_string_lossy(), &content)
+    }
+
+    /// ソース文字列をパースし、指定ファイルのシンボルを置き換える
+    pub fn parse_source(&mut self, file_path: &str, content: &str) -> Result<ParseOutcome> {
+        let error = match syn::parse_file(content) {
+            Ok(syntax_tree) => {
+                self.remove_file_symbols(file_path);
+                self.extract_symbols(&syntax_tree, file_path, content);
+                self.good_files.insert(file_path.to_string());
+                return Ok(ParseOutcome::Parsed);
+            }
+            Err(error) => error,
+        };
+
+        // 編集途中のファイルなど：前回正常だったシンボルはそのまま残す
+        if self.good_files.contains(file_path) {
+            self.diagnostics.retain(|d| d.file_path != file_path);
+            self.diagnostics.extend(Self::diagnostics_from_error(&error, file_path, 0));
+            self.recovery.insert(file_path.to_string(), ParseRecovery::KeptLastGood);
+            return Ok(ParseOutcome::KeptLastGood);
+        }
+
+        // 正常なバージョンがない場合は、壊れたアイテムを除いて救出を試みる
+        self.remove_file_symbols(file_path);
+        let (sanitized, chunk_diagnostics) = Self::blank_broken_items(content, file_path);
+        let mut diagnostics = Self::diagnostics_from_error(&error, file_path, 0);
+        for diagnostic in chunk_diagnostics {
+            if !diagnostics.iter().any(|d| d.line == diagnostic.line && d.column == diagnostic.column) {
+                diagnostics.push(diagnostic);
+            }
+        }
+        self.diagnostics.extend(diagnostics);
+
+        let outcome = match syn::parse_file(&sanitized) {
+            Ok(syntax_tree) => {
+                self.extract_symbols(&syntax_tree, file_path, &sanitized);
+                ParseOutcome::Salvaged { recovered_items: syntax_tree.items.len() }
+            }
+            Err(_) => ParseOutcome::Failed,
+        };
+        let recovery = match outcome {
+            ParseOutcome::Salvaged { .. } => ParseRecovery::Salvaged,
+            _ => ParseRecovery::Failed,
+        };
+        self.recovery.insert(file_path.to_string(), recovery);
 
-        self.extract_symbols(&syntax_tree, file_path.to_string_lossy().to_string(), &content)?;
-        self.extract_function_calls(&syntax_tree, file_path.to_string_lossy().to_string(), &content)?;
-        Ok(())
+        Ok(outcome)
     }
 
-    fn extract_symbols(&mut self, syntax_tree: &File, file_path: String, content: &str) -> Result<()> {
-        for item in &syntax_tree.items {
-            let symbol_info = match item {
-                Item::Fn(item_fn) => Some(self.extract_function_info(item_fn, &file_path, content)?),
-                Item::Struct(item_struct) => Some(self.extract_struct_info(item_struct, &file_path, content)?),
-                Item::Enum(item_enum) => Some(self.extract_enum_info(item_enum, &file_path, content)?),
-                Item::Trait(item_trait) => Some(self.extract_trait_info(item_trait, &file_path, content)?),
-                _ => None,
-            };
-            
-            if let Some(info) = symbol_info {
-                // シンボル名でグループ化
-                self.symbols
-                    .entry(info.name.clone())
-                    .or_default()
-                    .push(info);
+    /// syn のエラーを診断情報に変換（line_offset はチャンク単位でパースした場合の開始行）
+    fn diagnostics_from_error(error: &syn::Error, file_path: &str, line_offset: usize) -> Vec<DiagnosticInfo> {
+        error.clone().into_iter()
+            .map(|e| {
+                let start = e.span().start();
+                let end = e.span().end();
+                DiagnosticInfo {
+                    file_path: file_path.to_string(),
+                    message: e.to_string(),
+                    line: start.line + line_offset,
+                    column: start.column,
+                    end_line: end.line + line_offset,
+                    end_column: end.column,
+                }
+            })
+            .collect()
+    }
+
+    /// トップレベルアイテムごとにパースを試み、失敗したアイテムを空白で塗りつぶす
+    ///
+    /// 行番号を維持するため改行は残す。rustfmt 準拠の整形（アイテムが行頭から始まる）を前提とする。
+    fn blank_broken_items(content: &str, file_path: &str) -> (String, Vec<DiagnosticInfo>) {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut chunks: Vec<(usize, usize)> = Vec::new();
+        let mut chunk_start = 0;
+        let mut only_attributes = true; // 現在のチャンクが属性・コメントだけか
+
+        for (idx, line) in lines.iter().enumerate() {
+            let starts_item = line.chars().next().is_some_and(|c| !c.is_whitespace())
+                && !line.starts_with(['}', ')', ']']);
+            if !starts_item {
+                continue;
+            }
+
+            let is_attribute = line.starts_with("#[") || line.starts_with("//") || line.starts_with("/*");
+            if idx > chunk_start && !only_attributes {
+                chunks.push((chunk_start, idx));
+                chunk_start = idx;
             }
+            only_attributes = is_attribute;
         }
-        Ok(())
+        chunks.push((chunk_start, lines.len()));
+
+        let mut sanitized_lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        let mut diagnostics = Vec::new();
+
+        for (start, end) in chunks {
+            let chunk = lines[start..end].join("\n");
+            if let Err(error) = syn::parse_file(&chunk) {
+                diagnostics.extend(Self::diagnostics_from_error(&error, file_path, start));
+                for line in &mut sanitized_lines[start..end] {
+                    *line = " ".repeat(line.len());
+                }
+            }
+        }
+
+        (sanitized_lines.join("\n"), diagnostics)
+    }
+
+    /// 記録されているパースエラーを取得（file_path 指定時はそのファイルのみ）
+    pub fn get_diagnostics(&self, file_path: Option<&str>) -> Vec<&DiagnosticInfo> {
+        self.diagnostics.iter()
+            .filter(|d| file_path.is_none_or(|path| d.file_path == path))
+            .collect()
+    }
+
+    /// パースエラーのあるファイルとその復旧方法を取得
+    pub fn get_recovery_status(&self) -> &HashMap<String, ParseRecovery> {
+        &self.recovery
+    }
+
+    /// 依存クレートのソースから、公開 API として到達できる行（exported_lines）のアイテムを読み取り専用シンボルとして取り出す
+    ///
+    /// コールグラフや診断情報は記録しない。パースできないファイルは None を返す。
+    pub fn external_symbols(&self, file_path: &str, content: &str, crate_name: &str, exported_lines: &HashSet<usize>) -> Option<Vec<SymbolInfo>> {
+        let syntax_tree = syn::parse_file(content).ok()?;
+        let collected = self.collect_items(&syntax_tree, file_path, content);
+        Some(collected.symbols.into_iter()
+            .filter(|info| exported_lines.contains(&info.line) && !info.is_test)
+            .map(|mut info| {
+                info.crate_name = Some(crate_name.to_string());
+                info
+            })
+            .collect())
+    }
+
+    /// 依存クレートのシンボルを登録（同じファイルの既存のシンボルは置き換える）
+    pub fn add_external_symbols(&mut self, symbols: Vec<SymbolInfo>) {
+        let files: HashSet<String> = symbols.iter().map(|info| info.file_path.clone()).collect();
+        for file_path in &files {
+            self.remove_file_symbols(file_path);
+        }
+        for info in symbols {
+            self.symbols.entry(info.name.clone()).or_default().push(info);
+        }
+    }
+
+    fn extract_symbols(&mut self, syntax_tree: &File, file_path: &str, content: &str) {
+        let collected = self.collect_items(syntax_tree, file_path, content);
+        for info in collected.symbols {
+            // シンボル名でグループ化
+            self.symbols
+                .entry(info.name.clone())
+                .or_default()
+                .push(info);
+        }
+        self.call_graph.extend(collected.calls);
+        self.tests.extend(collected.tests);
+        self.unsafe_sites.extend(collected.unsafe_sites);
+        self.panic_sites.extend(collected.panic_sites);
+        self.async_sites.extend(collected.async_sites);
+        self.error_signatures.extend(collected.error_signatures);
+        self.error_sites.extend(collected.error_sites);
+        self.impls.extend(collected.impls);
+        self.function_metrics.extend(collected.function_metrics);
     }
 
-    fn extract_function_info(&self, item_fn: &ItemFn, file_path: &str, content: &str) -> Result<SymbolInfo> {
-        let name = item_fn.sig.ident.to_string();
-        let signature = self.format_signature(&item_fn.sig);
+    /// ネストしたアイテム・impl メソッド・名前付きクロージャも含めてシンボルと呼び出し関係を収集
+    fn collect_items(&self, syntax_tree: &File, file_path: &str, content: &str) -> CollectedItems {
+        let mut collector = ItemCollector {
+            parser: self,
+            file_path,
+            lines: content.lines().collect(),
+            scope: Vec::new(),
+            callers: Vec::new(),
+            test_depth: 0,
+            trait_visibility: None,
+            imports: HashMap::new(),
+            result_aliases: HashMap::new(),
+            symbols: Vec::new(),
+            calls: Vec::new(),
+            tests: Vec::new(),
+            unsafe_sites: Vec::new(),
+            panic_sites: Vec::new(),
+            async_sites: Vec::new(),
+            error_signatures: Vec::new(),
+            error_sites: Vec::new(),
+            impls: Vec::new(),
+            function_metrics: Vec::new(),
+        };
+        collector.visit_file(syntax_tree);
+        CollectedItems {
+            symbols: collector.symbols,
+            calls: collector.calls,
+            tests: collector.tests,
+            unsafe_sites: collector.unsafe_sites,
+            panic_sites: collector.panic_sites,
+            async_sites: collector.async_sites,
+            error_signatures: collector.error_signatures,
+            error_sites: collector.error_sites,
+            impls: collector.impls,
+            function_metrics: collector.function_metrics,
+        }
+    }
+
+    fn extract_function_info(&self, item_fn: &ItemFn, file_path: &str) -> SymbolInfo {
         let visibility = self.format_visibility(&item_fn.vis);
-        let generics = self.format_generics(&item_fn.sig.generics);
-        
-        // 関数定義の行番号を見つける
-        let (line, column) = self.find_symbol_location(&name, content, "fn");
+        self.extract_fn_signature_info(&item_fn.sig, visibility, &item_fn.attrs, file_path, item_fn.span())
+    }
 
-        Ok(SymbolInfo {
+    /// 関数・メソッドのシグネチャからシンボル情報を作成
+    fn extract_fn_signature_info(&self, sig: &Signature, visibility: String, attrs: &[syn::Attribute], file_path: &str, item_span: Span) -> SymbolInfo {
+        let name = sig.ident.to_string();
+        let signature = format_signature(sig);
+        let generics = format_generics(&sig.generics);
+        let (line, column) = span_location(sig.ident.span());
+
+        SymbolInfo {
             name,
             symbol_type: SymbolType::Function,
             file_path: file_path.to_string(),
             line,
             column,
+            end_line: item_span.end().line,
             signature,
             visibility,
             generics,
-        })
+            is_test: is_test_item(attrs, file_path),
+            crate_name: None,
+            scope: None,
+            is_method: false,
+        }
     }
 
-    fn extract_struct_info(&self, item_struct: &ItemStruct, file_path: &str, content: &str) -> Result<SymbolInfo> {
+    fn extract_struct_info(&self, item_struct: &ItemStruct, file_path: &str) -> SymbolInfo {
         let name = item_struct.ident.to_string();
         let visibility = self.format_visibility(&item_struct.vis);
-        let generics = self.format_generics(&item_struct.generics);
+        let generics = format_generics(&item_struct.generics);
         
         // struct定義のシグネチャ
         let signature = format!("struct {}{}", name, generics.as_deref().unwrap_or(""));
         
-        let (line, column) = self.find_symbol_location(&name, content, "struct");
+        let (line, column) = span_location(item_struct.ident.span());
 
-        Ok(SymbolInfo {
+        SymbolInfo {
             name,
             symbol_type: SymbolType::Struct,
             file_path: file_path.to_string(),
             line,
             column,
+            end_line: item_struct.span().end().line,
             signature,
             visibility,
             generics,
-        })
+            is_test: is_test_item(&item_struct.attrs, file_path),
+            crate_name: None,
+            scope: None,
+            is_method: false,
+        }
     }
 
-    fn extract_enum_info(&self, item_enum: &ItemEnum, file_path: &str, content: &str) -> Result<SymbolInfo> {
+    fn extract_enum_info(&self, item_enum: &ItemEnum, file_path: &str) -> SymbolInfo {
         let name = item_enum.ident.to_string();
         let visibility = self.format_visibility(&item_enum.vis);
-        let generics = self.format_generics(&item_enum.generics);
+        let generics = format_generics(&item_enum.generics);
         
         // enum定義のシグネチャ
         let signature = format!("enum {}{}", name, generics.as_deref().unwrap_or(""));
         
-        let (line, column) = self.find_symbol_location(&name, content, "enum");
+        let (line, column) = span_location(item_enum.ident.span());
 
-        Ok(SymbolInfo {
+        SymbolInfo {
             name,
             symbol_type: SymbolType::Enum,
             file_path: file_path.to_string(),
             line,
             column,
+            end_line: item_enum.span().end().line,
             signature,
             visibility,
             generics,
-        })
+            is_test: is_test_item(&item_enum.attrs, file_path),
+            crate_name: None,
+            scope: None,
+            is_method: false,
+        }
     }
 
-    fn extract_trait_info(&self, item_trait: &ItemTrait, file_path: &str, content: &str) -> Result<SymbolInfo> {
+    fn extract_trait_info(&self, item_trait: &ItemTrait, file_path: &str) -> SymbolInfo {
         let name = item_trait.ident.to_string();
         let visibility = self.format_visibility(&item_trait.vis);
-        let generics = self.format_generics(&item_trait.generics);
+        let generics = format_generics(&item_trait.generics);
         
         // trait定義のシグネチャ
         let signature = format!("trait {}{}", name, generics.as_deref().unwrap_or(""));
         
-        let (line, column) = self.find_symbol_location(&name, content, "trait");
+        let (line, column) = span_location(item_trait.ident.span());
 
-        Ok(SymbolInfo {
+        SymbolInfo {
             name,
             symbol_type: SymbolType::Trait,
             file_path: file_path.to_string(),
             line,
             column,
+            end_line: item_trait.span().end().line,
             signature,
             visibility,
             generics,
-        })
+            is_test: is_test_item(&item_trait.attrs, file_path),
+            crate_name: None,
+            scope: None,
+            is_method: false,
+        }
     }
 
-    fn format_signature(&self, sig: &Signature) -> String {
-        // 簡易的なシグネチャ文字列生成
-        let mut result = String::new();
-        
-        if sig.asyncness.is_some() {
-            result.push_str("async ");
-        }
-        
-        result.push_str("fn ");
-        result.push_str(&sig.ident.to_string());
-        
-        // パラメータ
-        result.push('(');
-        for (i, input) in sig.inputs.iter().enumerate() {
-            if i > 0 {
-                result.push_str(", ");
-            }
-            result.push_str(&format!("{}", quote::quote!(#input)));
+    /// `let name = |args| ...` 形式の名前付きクロージャ
+    fn extract_closure_info(&self, ident: &syn::Ident, closure: &syn::ExprClosure, file_path: &str) -> SymbolInfo {
+        let name = ident.to_string();
+        let inputs = closure.inputs.iter()
+            .map(|input| quote::quote!(#input).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let capture = if closure.capture.is_some() { "move " } else { "" };
+        let mut signature = format!("let {name} = {capture}|{inputs}|");
+        if let syn::ReturnType::Type(_, ty) = &closure.output {
+            signature.push_str(&format!(" -> {}", quote::quote!(#ty)));
         }
-        result.push(')');
-        
-        // 戻り値
-        if let syn::ReturnType::Type(_, ty) = &sig.output {
-            result.push_str(" -> ");
-            result.push_str(&format!("{}", quote::quote!(#ty)));
+        let (line, column) = span_location(ident.span());
+
+        SymbolInfo {
+            name,
+            symbol_type: SymbolType::Function,
+            file_path: file_path.to_string(),
+            line,
+            column,
+            end_line: closure.span().end().line,
+            signature,
+            visibility: "private".to_string(),
+            generics: None,
+            is_test: false,
+            crate_name: None,
+            scope: None,
+            is_method: false,
         }
-        
-        result
     }
 
-    fn format_visibility(&self, vis: &Visibility) -> String {
+    pub fn format_visibility(&self, vis: &Visibility) -> String {
         match vis {
             Visibility::Public(_) => "pub".to_string(),
             Visibility::Restricted(restricted) if restricted.path.is_ident("crate") => "pub(crate)".to_string(),
@@ -236,180 +601,6 @@ impl RustParser {
         &self.symbols
     }
 
-    /// シンボルの位置を見つける
-    fn find_symbol_location(&self, symbol_name: &str, content: &str, keyword: &str) -> (usize, usize) {
-        let lines: Vec<&str> = content.lines().collect();
-        
-        // 各行を検索して関数定義を見つける
-        for (line_idx, line) in lines.iter().enumerate() {
-            let trimmed = line.trim();
-            
-            // シンボル定義のパターンをチェック
-            // キーワードの後にシンボル名が来るパターンを探す
-            let patterns = vec![
-                format!("{} {}", keyword, symbol_name),
-                format!("pub {} {}", keyword, symbol_name),
-                format!("pub(crate) {} {}", keyword, symbol_name),
-                format!("pub(super) {} {}", keyword, symbol_name),
-                format!("async {} {}", keyword, symbol_name), // async fnの場合
-                format!("pub async {} {}", keyword, symbol_name), // pub async fnの場合
-            ];
-            
-            for pattern in patterns {
-                if trimmed.contains(&pattern) {
-                    // 行番号は1ベース、列番号はシンボル名の開始位置
-                    let col = line.find(symbol_name).unwrap_or(0);
-                    return (line_idx + 1, col);
-                }
-            }
-        }
-        
-        // 見つからない場合のフォールバック
-        (1, 0)
-    }
-
-    /// 関数呼び出し関係を抽出
-    fn extract_function_calls(&mut self, syntax_tree: &File, file_path: String, content: &str) -> Result<()> {
-        for item in &syntax_tree.items {
-            if let Item::Fn(item_fn) = item {
-                let caller_name = item_fn.sig.ident.to_string();
-                let caller_line = self.find_symbol_location(&caller_name, content, "fn").0;
-                
-                // 関数本体の中の関数呼び出しを解析
-                self.extract_calls_from_block(&item_fn.block, &caller_name, &file_path, caller_line, content);
-            }
-        }
-        Ok(())
-    }
-    
-    /// ブロック内の関数呼び出しを抽出
-    fn extract_calls_from_block(&mut self, block: &syn::Block, caller: &str, caller_file: &str, caller_line: usize, content: &str) {
-        for stmt in &block.stmts {
-            self.extract_calls_from_stmt(stmt, caller, caller_file, caller_line, content);
-        }
-    }
-    
-    /// ステートメントから関数呼び出しを抽出
-    fn extract_calls_from_stmt(&mut self, stmt: &syn::Stmt, caller: &str, caller_file: &str, caller_line: usize, content: &str) {
-        match stmt {
-            syn::Stmt::Local(local) => {
-                if let Some(init) = &local.init {
-                    self.extract_calls_from_expr(&init.expr, caller, caller_file, caller_line, content);
-                }
-            }
-            syn::Stmt::Item(_) => {
-                // アイテム内の処理は既に extract_function_calls で処理済み
-            }
-            syn::Stmt::Expr(expr, _) => {
-                self.extract_calls_from_expr(expr, caller, caller_file, caller_line, content);
-            }
-            syn::Stmt::Macro(_) => {
-                // マクロ呼び出しは現在スキップ
-            }
-        }
-    }
-    
-    /// 式から関数呼び出しを抽出
-    fn extract_calls_from_expr(&mut self, expr: &syn::Expr, caller: &str, caller_file: &str, caller_line: usize, content: &str) {
-        match expr {
-            syn::Expr::Call(call_expr) => {
-                // 関数呼び出しを発見
-                if let syn::Expr::Path(path_expr) = &*call_expr.func {
-                    if let Some(ident) = path_expr.path.get_ident() {
-                        let callee = ident.to_string();
-                        
-                        // 関数呼び出しの位置を特定
-                        let (call_line, call_column) = self.find_call_location(&callee, content, caller_line);
-                        let call_context = self.get_line_context(content, call_line);
-                        
-                        self.call_graph.push(CallInfo {
-                            caller: caller.to_string(),
-                            caller_file: caller_file.to_string(),
-                            caller_line,
-                            callee,
-                            call_line,
-                            call_column,
-                            call_context,
-                        });
-                    }
-                }
-                
-                // 引数内の関数呼び出しも再帰的に解析
-                for arg in &call_expr.args {
-                    self.extract_calls_from_expr(arg, caller, caller_file, caller_line, content);
-                }
-            }
-            syn::Expr::MethodCall(method_call) => {
-                // メソッド呼び出し
-                let method_name = method_call.method.to_string();
-                let (call_line, call_column) = self.find_call_location(&method_name, content, caller_line);
-                let call_context = self.get_line_context(content, call_line);
-                
-                self.call_graph.push(CallInfo {
-                    caller: caller.to_string(),
-                    caller_file: caller_file.to_string(),
-                    caller_line,
-                    callee: method_name,
-                    call_line,
-                    call_column,
-                    call_context,
-                });
-                
-                // レシーバーと引数も再帰的に解析
-                self.extract_calls_from_expr(&method_call.receiver, caller, caller_file, caller_line, content);
-                for arg in &method_call.args {
-                    self.extract_calls_from_expr(arg, caller, caller_file, caller_line, content);
-                }
-            }
-            syn::Expr::Block(block_expr) => {
-                self.extract_calls_from_block(&block_expr.block, caller, caller_file, caller_line, content);
-            }
-            syn::Expr::If(if_expr) => {
-                self.extract_calls_from_expr(&if_expr.cond, caller, caller_file, caller_line, content);
-                self.extract_calls_from_block(&if_expr.then_branch, caller, caller_file, caller_line, content);
-                if let Some((_, else_branch)) = &if_expr.else_branch {
-                    self.extract_calls_from_expr(else_branch, caller, caller_file, caller_line, content);
-                }
-            }
-            syn::Expr::Match(match_expr) => {
-                self.extract_calls_from_expr(&match_expr.expr, caller, caller_file, caller_line, content);
-                for arm in &match_expr.arms {
-                    self.extract_calls_from_expr(&arm.body, caller, caller_file, caller_line, content);
-                }
-            }
-            syn::Expr::Binary(binary) => {
-                self.extract_calls_from_expr(&binary.left, caller, caller_file, caller_line, content);
-                self.extract_calls_from_expr(&binary.right, caller, caller_file, caller_line, content);
-            }
-            // 他の式タイプも必要に応じて追加
-            _ => {}
-        }
-    }
-    
-    /// 関数呼び出しの位置を特定
-    fn find_call_location(&self, callee: &str, content: &str, start_line: usize) -> (usize, usize) {
-        let lines: Vec<&str> = content.lines().collect();
-        
-        // caller関数内から検索開始
-        for (line_idx, line) in lines.iter().enumerate().skip(start_line.saturating_sub(1)) {
-            if let Some(col) = line.find(&format!("{}(", callee)) {
-                return (line_idx + 1, col);
-            }
-        }
-        
-        (start_line, 0)
-    }
-    
-    /// 指定行のコンテキストを取得
-    fn get_line_context(&self, content: &str, line: usize) -> String {
-        let lines: Vec<&str> = content.lines().collect();
-        if line > 0 && line <= lines.len() {
-            lines[line - 1].trim().to_string()
-        } else {
-            String::new()
-        }
-    }
-    
     /// コールグラフを取得
     pub fn get_call_graph(&self) -> &Vec<CallInfo> {
         &self.call_graph
@@ -422,6 +613,46 @@ impl RustParser {
             .collect()
     }
     
+    /// インデックス済みのテストを取得
+    pub fn get_tests(&self) -> &Vec<TestInfo> {
+        &self.tests
+    }
+
+    /// unsafe なコードの一覧を取得
+    pub fn get_unsafe_sites(&self) -> &Vec<UnsafeInfo> {
+        &self.unsafe_sites
+    }
+
+    /// パニックしうる箇所の一覧を取得
+    pub fn get_panic_sites(&self) -> &Vec<PanicSiteInfo> {
+        &self.panic_sites
+    }
+
+    /// 非同期処理の危険箇所の候補を取得
+    pub fn get_async_sites(&self) -> &Vec<AsyncSiteInfo> {
+        &self.async_sites
+    }
+
+    /// Result・Option を返す関数の一覧を取得
+    pub fn get_error_signatures(&self) -> &Vec<ErrorSignatureInfo> {
+        &self.error_signatures
+    }
+
+    /// エラーの伝播・生成・握りつぶしの箇所を取得
+    pub fn get_error_sites(&self) -> &Vec<ErrorSiteInfo> {
+        &self.error_sites
+    }
+
+    /// トレイトの実装（derive を含む）を取得
+    pub fn get_impls(&self) -> &Vec<ImplInfo> {
+        &self.impls
+    }
+
+    /// 関数ごとの規模と複雑さを取得
+    pub fn get_function_metrics(&self) -> &Vec<FunctionMetricsInfo> {
+        &self.function_metrics
+    }
+
     /// 特定関数への呼び出しを取得
     pub fn get_calls_to_function(&self, function_name: &str) -> Vec<&CallInfo> {
         self.call_graph.iter()
@@ -451,6 +682,19 @@ impl RustParser {
         
         // コールグラフからも該当ファイルの情報を削除
         self.call_graph.retain(|call| call.caller_file != file_path);
+        self.tests.retain(|test| test.file_path != file_path);
+        self.unsafe_sites.retain(|site| site.file_path != file_path);
+        self.panic_sites.retain(|site| site.file_path != file_path);
+        self.async_sites.retain(|site| site.file_path != file_path);
+        self.error_signatures.retain(|signature| signature.file_path != file_path);
+        self.error_sites.retain(|site| site.file_path != file_path);
+        self.impls.retain(|info| info.file_path != file_path);
+        self.function_metrics.retain(|metrics| metrics.file_path != file_path);
+
+        // パースエラー情報も削除
+        self.diagnostics.retain(|d| d.file_path != file_path);
+        self.recovery.remove(file_path);
+        self.good_files.remove(file_path);
     }
 
     /// 指定シンボルの使用箇所を検索
@@ -458,8 +702,13 @@ impl RustParser {
         let mut usages = Vec::new();
         
         // 全ファイルから使用箇所を検索
-        for (_, symbol_infos) in &self.symbols {
+        for symbol_infos in self.symbols.values() {
             for symbol_info in symbol_infos {
+                // 依存クレートのソースは使用箇所の検索対象外
+                if symbol_info.crate_name.is_some() {
+                    continue;
+                }
+
                 // ファイル内容を読み込んで使用箇所を検索
                 if let Ok(content) = std::fs::read_to_string(&symbol_info.file_path) {
                     let file_usages = self.find_usages_in_content(symbol_name, symbol_type.as_ref(), &content, &symbol_info.file_path);
@@ -502,8 +751,8 @@ impl RustParser {
                     };
                     let after_char = line.chars().nth(absolute_pos + symbol_name.len());
                     
-                    let before_ok = before_char.map_or(true, |c| !c.is_alphanumeric() && c != '_');
-                    let after_ok = after_char.map_or(true, |c| !c.is_alphanumeric() && c != '_');
+                    let before_ok = before_char.is_none_or(|c| !c.is_alphanumeric() && c != '_');
+                    let after_ok = after_char.is_none_or(|c| !c.is_alphanumeric() && c != '_');
                     
                     before_ok && after_ok
                 };
@@ -537,24 +786,21 @@ impl RustParser {
         let trimmed = line.trim();
         
         // 関数呼び出しパターン
-        if let Some(after_symbol) = line.get(pos + symbol_name.len()..) {
-            if after_symbol.trim_start().starts_with('(') {
-                return UsageType::FunctionCall;
-            }
+        if let Some(after_symbol) = line.get(pos + symbol_name.len()..)
+            && after_symbol.trim_start().starts_with('(') {
+            return UsageType::FunctionCall;
         }
         
         // 型注釈やstruct初期化
-        if symbol_type == Some(&SymbolType::Struct) || symbol_type == Some(&SymbolType::Enum) {
-            if trimmed.contains("::") || trimmed.contains('{') {
-                return UsageType::TypeUsage;
-            }
+        if (symbol_type == Some(&SymbolType::Struct) || symbol_type == Some(&SymbolType::Enum))
+            && (trimmed.contains("::") || trimmed.contains('{')) {
+            return UsageType::TypeUsage;
         }
         
         // トレイト使用
-        if symbol_type == Some(&SymbolType::Trait) {
-            if trimmed.contains("impl") || trimmed.contains("for") {
-                return UsageType::TraitUsage;
-            }
+        if symbol_type == Some(&SymbolType::Trait)
+            && (trimmed.contains("impl") || trimmed.contains("for")) {
+            return UsageType::TraitUsage;
         }
         
         // インポート
@@ -605,19 +851,614 @@ impl RustParser {
         
         false
     }
+}
+
+/// スパンの開始位置（1ベースの行、0ベースの列）
+fn span_location(span: Span) -> (usize, usize) {
+    let start = span.start();
+    (start.line, start.column)
+}
+
+/// 関数のシグネチャを文字列にする
+pub fn format_signature(sig: &Signature) -> String {
+    // 簡易的なシグネチャ文字列生成
+    let mut result = String::new();
+    
+    if sig.asyncness.is_some() {
+        result.push_str("async ");
+    }
+    
+    result.push_str("fn ");
+    result.push_str(&sig.ident.to_string());
+    
+    // パラメータ
+    result.push('(');
+    for (i, input) in sig.inputs.iter().enumerate() {
+        if i > 0 {
+            result.push_str(", ");
+        }
+        result.push_str(&format!("{}", quote::quote!(#input)));
+    }
+    result.push(')');
+    
+    // 戻り値
+    if let syn::ReturnType::Type(_, ty) = &sig.output {
+        result.push_str(" -> ");
+        result.push_str(&format!("{}", quote::quote!(#ty)));
+    }
+    
+    result
+}
 
-    /// ジェネリクスパラメータをフォーマット
-    fn format_generics(&self, generics: &syn::Generics) -> Option<String> {
-        if generics.params.is_empty() {
-            return None;
+/// ジェネリクスパラメータをフォーマット
+pub fn format_generics(generics: &syn::Generics) -> Option<String> {
+    if generics.params.is_empty() {
+        return None;
+    }
+    
+    let params = generics.params.iter()
+        .map(|p| quote::quote!(#p).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    
+    Some(format!("<{params}>"))
+}
+
+/// テストコードかどうかを判定（属性とファイルパスから推定）
+pub fn is_test_item(attrs: &[syn::Attribute], file_path: &str) -> bool {
+    let has_test_attr = attrs.iter().any(|attr| {
+        let path = attr.path();
+        if path.segments.last().is_some_and(|seg| seg.ident == "test") {
+            return true;
+        }
+        // #[cfg(test)]
+        path.is_ident("cfg") && attr.parse_args::<syn::Ident>().is_ok_and(|ident| ident == "test")
+    });
+
+    has_test_attr || is_test_path(file_path)
+}
+
+/// 結合テスト（クレートのルート直下の tests/）のファイルか
+///
+/// ルートは Cargo.toml のある一番近い祖先のディレクトリで、プロジェクト自体が tests という名前の
+/// ディレクトリの下にあっても誤判定しない。Cargo.toml が見つからなければパスに tests があるかで判定する。
+pub fn is_test_path(file_path: &str) -> bool {
+    let path = Path::new(file_path);
+    if !path.components().any(|c| c.as_os_str() == "tests") {
+        return false;
+    }
+    match path.ancestors().skip(1).find(|dir| dir.join("Cargo.toml").is_file()) {
+        Some(root) => path.strip_prefix(root).ok()
+            .and_then(|relative| relative.components().next())
+            .is_some_and(|c| c.as_os_str() == "tests"),
+        None => true,
+    }
+}
+
+/// ドキュメントコメント（`///` と `#[doc = "..."]`）の本文（各行の前後の空白を除いて改行でつなぐ）
+pub fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs.iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }), .. }) => {
+                Some(s.value().trim().to_string())
+            }
+            _ => None,
+        })
+        .collect();
+    let text = lines.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// スコープ付きの名前（"Foo::bar"、スコープがなければ名前のみ）
+pub fn qualified_name(scope: Option<&str>, name: &str) -> String {
+    match scope {
+        Some(scope) => format!("{scope}::{name}"),
+        None => name.to_string(),
+    }
+}
+
+/// impl 対象の型名（`impl<T> Foo<T>` → "Foo"）
+pub fn type_name(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        syn::Type::Reference(reference) => type_name(&reference.elem),
+        other => quote::quote!(#other).to_string(),
+    }
+}
+
+/// ItemCollector が集めた結果
+struct CollectedItems {
+    symbols: Vec<SymbolInfo>,
+    calls: Vec<CallInfo>,
+    tests: Vec<TestInfo>,
+    unsafe_sites: Vec<UnsafeInfo>,
+    panic_sites: Vec<PanicSiteInfo>,
+    async_sites: Vec<AsyncSiteInfo>,
+    error_signatures: Vec<ErrorSignatureInfo>,
+    error_sites: Vec<ErrorSiteInfo>,
+    impls: Vec<ImplInfo>,
+    function_metrics: Vec<FunctionMetricsInfo>,
+}
+
+/// ファイル内のアイテムを走査し、スコープを追跡しながらシンボルと呼び出し関係を集める
+struct ItemCollector<'a> {
+    parser: &'a RustParser,
+    file_path: &'a str,
+    lines: Vec<&'a str>,
+    scope: Vec<String>,             // 囲んでいるモジュール・型・関数の名前
+    callers: Vec<(String, usize)>,  // 呼び出し元として扱う関数・クロージャ (名前, 定義行)
+    test_depth: usize,              // #[cfg(test)] などテスト用スコープの深さ
+    trait_visibility: Option<String>,  // trait 定義内のメソッドは trait の可視性を引き継ぐ
+    imports: HashMap<String, String>,  // ファイル先頭の use の別名 -> パス
+    result_aliases: HashMap<String, String>,  // `type Result<T> = ...` の別名 -> エラー型
+    symbols: Vec<SymbolInfo>,
+    calls: Vec<CallInfo>,
+    tests: Vec<TestInfo>,
+    unsafe_sites: Vec<UnsafeInfo>,
+    panic_sites: Vec<PanicSiteInfo>,
+    async_sites: Vec<AsyncSiteInfo>,
+    error_signatures: Vec<ErrorSignatureInfo>,
+    error_sites: Vec<ErrorSiteInfo>,
+    impls: Vec<ImplInfo>,
+    function_metrics: Vec<FunctionMetricsInfo>,
+}
+
+impl ItemCollector<'_> {
+    fn add_symbol(&mut self, mut info: SymbolInfo) {
+        info.scope = self.scope_path();
+        info.is_test |= self.test_depth > 0;
+        self.symbols.push(info);
+    }
+
+    fn add_call(&mut self, callee: String, span: Span, method_call: bool) {
+        // 関数の外（const の初期化式など）の呼び出しは記録しない
+        let Some((caller, caller_line)) = self.callers.last() else {
+            return;
+        };
+        let (call_line, call_column) = span_location(span);
+
+        self.calls.push(CallInfo {
+            caller: caller.clone(),
+            caller_file: self.file_path.to_string(),
+            caller_line: *caller_line,
+            callee,
+            call_line,
+            call_column,
+            method_call,
+        });
+    }
+
+    fn line_context(&self, line: usize) -> String {
+        self.lines.get(line.wrapping_sub(1))
+            .map(|l| l.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    fn scope_path(&self) -> Option<String> {
+        (!self.scope.is_empty()).then(|| self.scope.join("::"))
+    }
+
+    /// #[test] などの属性が付いた関数をテストとして登録
+    fn add_test_fn(&mut self, info: &SymbolInfo, attrs: &[syn::Attribute]) {
+        let Some(attribute) = crate::test_map::test_attribute(attrs) else {
+            return;
+        };
+        let is_integration = is_test_path(self.file_path);
+
+        self.tests.push(TestInfo {
+            name: info.name.clone(),
+            kind: if is_integration { TestKind::Integration } else { TestKind::Unit },
+            file_path: self.file_path.to_string(),
+            line: info.line,
+            end_line: info.end_line,
+            scope: self.scope_path(),
+            attribute: Some(attribute),
+        });
+    }
+
+    /// ドキュメントコメント中のコードブロックを doctest として登録し、その呼び出しも記録
+    fn add_doctests(&mut self, attrs: &[syn::Attribute], owner: &str) {
+        for block in crate::test_map::doc_code_blocks(attrs) {
+            let name = format!("doctest_{owner}_{}", block.line);
+
+            for (callee, call_line, call_column, method_call) in crate::test_map::doctest_calls(&block) {
+                self.calls.push(CallInfo {
+                    caller: name.clone(),
+                    caller_file: self.file_path.to_string(),
+                    caller_line: block.line,
+                    callee,
+                    call_line,
+                    call_column,
+                    method_call,
+                });
+            }
+
+            let mut scope = self.scope.clone();
+            scope.push(owner.to_string());
+            self.tests.push(TestInfo {
+                name,
+                kind: TestKind::Doctest,
+                file_path: self.file_path.to_string(),
+                line: block.line,
+                end_line: block.end_line,
+                scope: Some(scope.join("::")),
+                attribute: None,
+            });
+        }
+    }
+
+    /// unsafe なコードを記録（unsafe_span は unsafe キーワード、item_span は全体）
+    fn add_unsafe(&mut self, kind: UnsafeKind, unsafe_span: Span, item_span: Span, name: Option<&str>, signature: String) {
+        let (line, column) = span_location(unsafe_span);
+        let end = item_span.end();
+        let mut path = self.scope.clone();
+        path.extend(name.map(str::to_string));
+        let enclosing_symbol = (!path.is_empty()).then(|| path.join("::"));
+        let safety_comment = crate::unsafe_audit::find_safety_comment(&self.lines, line, column);
+
+        self.unsafe_sites.push(UnsafeInfo {
+            kind,
+            file_path: self.file_path.to_string(),
+            line,
+            column,
+            end_line: end.line,
+            end_column: end.column,
+            enclosing_symbol,
+            signature,
+            safety_comment,
+            is_test: self.test_depth > 0 || is_test_item(&[], self.file_path),
+        });
+    }
+
+    /// パニックしうる箇所を記録（関数の外は記録しない）
+    fn add_panic_site(&mut self, kind: PanicKind, start: Span, end: Span) {
+        let Some((function, function_line)) = self.callers.last() else {
+            return;
+        };
+        let (line, column) = span_location(start);
+        let end = end.end();
+
+        self.panic_sites.push(PanicSiteInfo {
+            kind,
+            function: function.clone(),
+            function_line: *function_line,
+            file_path: self.file_path.to_string(),
+            line,
+            column,
+            end_line: end.line,
+            end_column: end.column,
+            context: self.line_context(line),
+        });
+    }
+
+    /// 関数本体の非同期処理の危険箇所を記録（info は囲んでいる関数）
+    fn add_async_sites(&mut self, info: &SymbolInfo, sig: &Signature, block: &syn::Block) {
+        for hazard in crate::async_audit::scan_body(block, sig.asyncness.is_some(), &self.imports) {
+            let (line, column) = span_location(hazard.start);
+            let end = hazard.end.end();
+
+            self.async_sites.push(AsyncSiteInfo {
+                kind: hazard.kind,
+                name: hazard.name,
+                function: info.name.clone(),
+                function_line: info.line,
+                file_path: self.file_path.to_string(),
+                line,
+                column,
+                end_line: end.line,
+                end_column: end.column,
+                context: self.line_context(line),
+                in_async_block: hazard.in_async_block,
+            });
+        }
+    }
+
+    /// Result・Option を返す関数と、本体のエラーの伝播・生成・握りつぶしを記録（info は囲んでいる関数）
+    fn add_error_flow(&mut self, info: &SymbolInfo, sig: &Signature, block: &syn::Block) {
+        if let Some(ret) = crate::error_flow::return_type(&sig.output, &self.imports, &self.result_aliases) {
+            self.error_signatures.push(ErrorSignatureInfo {
+                name: info.name.clone(),
+                file_path: self.file_path.to_string(),
+                line: info.line,
+                return_kind: ret.kind,
+                ok_type: ret.ok_type,
+                error_type: ret.error_type,
+            });
+        }
+
+        for site in crate::error_flow::scan_body(block, &self.imports) {
+            let (line, column) = span_location(site.start);
+            let end = site.end.end();
+
+            self.error_sites.push(ErrorSiteInfo {
+                kind: site.kind,
+                function: info.name.clone(),
+                function_line: info.line,
+                file_path: self.file_path.to_string(),
+                line,
+                column,
+                end_line: end.line,
+                end_column: end.column,
+                context: self.line_context(line),
+                callee: site.callee,
+                explicit_error: site.explicit_error,
+            });
+        }
+    }
+
+    /// #[derive(...)] で実装されるトレイトを合成した実装として記録
+    fn add_derives(&mut self, type_name: &str, attrs: &[syn::Attribute]) {
+        let is_test = self.test_depth > 0 || is_test_item(attrs, self.file_path);
+        for (trait_path, span) in crate::trait_impls::derived_traits(attrs) {
+            self.impls.push(ImplInfo {
+                trait_name: trait_path.rsplit("::").next().unwrap_or_default().to_string(),
+                trait_path,
+                type_name: type_name.to_string(),
+                file_path: self.file_path.to_string(),
+                line: span_location(span).0,
+                source: ImplSource::Derive,
+                is_test,
+            });
+        }
+    }
+
+    /// 関数の規模と複雑さを記録
+    fn add_metrics(&mut self, info: &SymbolInfo, params: usize, body: crate::metrics::BodyMetrics) {
+        let start = info.line.saturating_sub(1).min(self.lines.len());
+        let end = info.end_line.clamp(start, self.lines.len());
+
+        self.function_metrics.push(FunctionMetricsInfo {
+            name: info.name.clone(),
+            file_path: self.file_path.to_string(),
+            line: info.line,
+            lines_of_code: crate::metrics::count_code_lines(&self.lines[start..end]),
+            params,
+            body,
+        });
+    }
+
+    /// unsafe fn なら記録
+    fn add_unsafe_fn(&mut self, sig: &Signature, item_span: Span) {
+        if let Some(unsafety) = &sig.unsafety {
+            let signature = format!("unsafe {}", format_signature(sig));
+            self.add_unsafe(UnsafeKind::Function, unsafety.span, item_span, Some(&sig.ident.to_string()), signature);
+        }
+    }
+
+    /// 名前付きスコープの中で f を実行（caller_line があれば呼び出し元としても扱う）
+    fn with_scope(&mut self, name: String, caller_line: Option<usize>, is_test: bool, f: impl FnOnce(&mut Self)) {
+        self.scope.push(name.clone());
+        if let Some(line) = caller_line {
+            self.callers.push((name, line));
+        }
+        if is_test {
+            self.test_depth += 1;
+        }
+
+        f(self);
+
+        if is_test {
+            self.test_depth -= 1;
+        }
+        if caller_line.is_some() {
+            self.callers.pop();
+        }
+        self.scope.pop();
+    }
+}
+
+impl<'ast> Visit<'ast> for ItemCollector<'_> {
+    fn visit_file(&mut self, file: &'ast File) {
+        // クレート・モジュールレベルの //! ドキュメント
+        let owner = Path::new(self.file_path).file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.add_doctests(&file.attrs, &owner);
+        self.imports = crate::async_audit::collect_imports(&file.items);
+        self.result_aliases = crate::error_flow::collect_result_aliases(&file.items, &self.imports);
+        visit::visit_file(self, file);
+    }
+
+    fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+        let info = self.parser.extract_function_info(item_fn, self.file_path);
+        let (name, line, is_test) = (info.name.clone(), info.line, info.is_test);
+        self.add_test_fn(&info, &item_fn.attrs);
+        self.add_doctests(&item_fn.attrs, &name);
+        self.add_unsafe_fn(&item_fn.sig, item_fn.span());
+        self.add_metrics(&info, item_fn.sig.inputs.len(), crate::metrics::measure_block(&item_fn.block));
+        self.add_async_sites(&info, &item_fn.sig, &item_fn.block);
+        self.add_error_flow(&info, &item_fn.sig, &item_fn.block);
+        self.add_symbol(info);
+        self.with_scope(name, Some(line), is_test, |this| this.visit_block(&item_fn.block));
+    }
+
+    fn visit_item_struct(&mut self, item_struct: &'ast ItemStruct) {
+        let info = self.parser.extract_struct_info(item_struct, self.file_path);
+        self.add_doctests(&item_struct.attrs, &info.name);
+        self.add_derives(&info.name, &item_struct.attrs);
+        self.add_symbol(info);
+    }
+
+    fn visit_item_enum(&mut self, item_enum: &'ast ItemEnum) {
+        let info = self.parser.extract_enum_info(item_enum, self.file_path);
+        self.add_doctests(&item_enum.attrs, &info.name);
+        self.add_derives(&info.name, &item_enum.attrs);
+        self.add_symbol(info);
+    }
+
+    fn visit_item_trait(&mut self, item_trait: &'ast ItemTrait) {
+        let info = self.parser.extract_trait_info(item_trait, self.file_path);
+        let (name, is_test) = (info.name.clone(), info.is_test);
+        let outer_visibility = self.trait_visibility.replace(info.visibility.clone());
+        self.add_doctests(&item_trait.attrs, &name);
+        if let Some(unsafety) = &item_trait.unsafety {
+            let signature = format!("unsafe {}", info.signature);
+            self.add_unsafe(UnsafeKind::Trait, unsafety.span, item_trait.span(), Some(&name), signature);
+        }
+        self.add_symbol(info);
+        self.with_scope(name, None, is_test, |this| {
+            for item in &item_trait.items {
+                this.visit_trait_item(item);
+            }
+        });
+        self.trait_visibility = outer_visibility;
+    }
+
+    fn visit_trait_item_fn(&mut self, item_fn: &'ast syn::TraitItemFn) {
+        let visibility = self.trait_visibility.clone().unwrap_or_else(|| "private".to_string());
+        let mut info = self.parser.extract_fn_signature_info(&item_fn.sig, visibility, &item_fn.attrs, self.file_path, item_fn.span());
+        info.is_method = true;
+        let (name, line, is_test) = (info.name.clone(), info.line, info.is_test);
+        self.add_doctests(&item_fn.attrs, &name);
+        self.add_unsafe_fn(&item_fn.sig, item_fn.span());
+        if let Some(block) = &item_fn.default {
+            self.add_metrics(&info, item_fn.sig.inputs.len(), crate::metrics::measure_block(block));
+            self.add_async_sites(&info, &item_fn.sig, block);
+            self.add_error_flow(&info, &item_fn.sig, block);
+        }
+        self.add_symbol(info);
+        if let Some(block) = &item_fn.default {
+            self.with_scope(name, Some(line), is_test, |this| this.visit_block(block));
+        }
+    }
+
+    fn visit_item_impl(&mut self, item_impl: &'ast syn::ItemImpl) {
+        let is_test = is_test_item(&item_impl.attrs, self.file_path);
+        if let Some(unsafety) = &item_impl.unsafety {
+            let self_ty = &item_impl.self_ty;
+            let signature = match &item_impl.trait_ {
+                Some((_, trait_path, _)) => format!("unsafe impl {} for {}", quote::quote!(#trait_path), quote::quote!(#self_ty)),
+                None => format!("unsafe impl {}", quote::quote!(#self_ty)),
+            };
+            self.add_unsafe(UnsafeKind::Impl, unsafety.span, item_impl.span(), Some(&type_name(self_ty)), signature);
+        }
+        // 否定実装（impl !Send for Foo）は実装として扱わない
+        if let Some((None, trait_path, _)) = &item_impl.trait_ {
+            let segments: Vec<String> = trait_path.segments.iter().map(|s| s.ident.to_string()).collect();
+            self.impls.push(ImplInfo {
+                trait_name: segments.last().cloned().unwrap_or_default(),
+                trait_path: segments.join("::"),
+                type_name: type_name(&item_impl.self_ty),
+                file_path: self.file_path.to_string(),
+                line: span_location(item_impl.impl_token.span).0,
+                source: ImplSource::Manual,
+                is_test: self.test_depth > 0 || is_test,
+            });
+        }
+        self.with_scope(type_name(&item_impl.self_ty), None, is_test, |this| {
+            for item in &item_impl.items {
+                this.visit_impl_item(item);
+            }
+        });
+    }
+
+    fn visit_impl_item_fn(&mut self, item_fn: &'ast syn::ImplItemFn) {
+        let visibility = self.parser.format_visibility(&item_fn.vis);
+        let mut info = self.parser.extract_fn_signature_info(&item_fn.sig, visibility, &item_fn.attrs, self.file_path, item_fn.span());
+        info.is_method = true;
+        let (name, line, is_test) = (info.name.clone(), info.line, info.is_test);
+        self.add_test_fn(&info, &item_fn.attrs);
+        self.add_doctests(&item_fn.attrs, &name);
+        self.add_unsafe_fn(&item_fn.sig, item_fn.span());
+        self.add_metrics(&info, item_fn.sig.inputs.len(), crate::metrics::measure_block(&item_fn.block));
+        self.add_async_sites(&info, &item_fn.sig, &item_fn.block);
+        self.add_error_flow(&info, &item_fn.sig, &item_fn.block);
+        self.add_symbol(info);
+        self.with_scope(name, Some(line), is_test, |this| this.visit_block(&item_fn.block));
+    }
+
+    fn visit_item_mod(&mut self, item_mod: &'ast syn::ItemMod) {
+        // `mod foo;` は別ファイルとしてインデックスされる
+        let Some((_, items)) = &item_mod.content else {
+            return;
+        };
+        let is_test = is_test_item(&item_mod.attrs, self.file_path);
+        self.with_scope(item_mod.ident.to_string(), None, is_test, |this| {
+            for item in items {
+                this.visit_item(item);
+            }
+        });
+    }
+
+    fn visit_item_macro(&mut self, item_macro: &'ast syn::ItemMacro) {
+        // cfg_rt! { ... } のようにアイテムを包むマクロは中身もシンボルとして扱う（macro_rules! は除く）
+        if item_macro.ident.is_none()
+            && let Ok(inner) = syn::parse2::<File>(item_macro.mac.tokens.clone()) {
+            for item in &inner.items {
+                self.visit_item(item);
+            }
+        }
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        if let syn::Pat::Ident(pat_ident) = &local.pat
+            && let Some(init) = &local.init
+            && let syn::Expr::Closure(closure) = &*init.expr {
+            let info = self.parser.extract_closure_info(&pat_ident.ident, closure, self.file_path);
+            let (name, line) = (info.name.clone(), info.line);
+            self.add_metrics(&info, closure.inputs.len(), crate::metrics::measure_expr(&closure.body));
+            self.add_symbol(info);
+            self.with_scope(name, Some(line), false, |this| this.visit_expr(&closure.body));
+            return;
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_unsafe(&mut self, unsafe_expr: &'ast syn::ExprUnsafe) {
+        let line = span_location(unsafe_expr.unsafe_token.span).0;
+        let signature = self.line_context(line);
+        self.add_unsafe(UnsafeKind::Block, unsafe_expr.unsafe_token.span, unsafe_expr.span(), None, signature);
+        visit::visit_expr_unsafe(self, unsafe_expr);
+    }
+
+    fn visit_expr_call(&mut self, call_expr: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path_expr) = &*call_expr.func
+            && let Some(ident) = path_expr.path.get_ident() {
+            self.add_call(ident.to_string(), ident.span(), false);
+        }
+        visit::visit_expr_call(self, call_expr);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        self.add_call(method_call.method.to_string(), method_call.method.span(), true);
+        let kind = match method_call.method.to_string().as_str() {
+            "unwrap" => Some(PanicKind::Unwrap),
+            "expect" => Some(PanicKind::Expect),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.add_panic_site(kind, method_call.method.span(), method_call.span());
+        }
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_expr_index(&mut self, index_expr: &'ast syn::ExprIndex) {
+        self.add_panic_site(PanicKind::Index, index_expr.bracket_token.span.open(), index_expr.span());
+        visit::visit_expr_index(self, index_expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let kind = mac.path.segments.last().and_then(|segment| match segment.ident.to_string().as_str() {
+            "panic" => Some(PanicKind::Panic),
+            "unreachable" => Some(PanicKind::Unreachable),
+            "todo" => Some(PanicKind::Todo),
+            "unimplemented" => Some(PanicKind::Unimplemented),
+            "assert" | "assert_eq" | "assert_ne" => Some(PanicKind::Assert),
+            _ => None,
+        });
+        if let Some(kind) = kind {
+            self.add_panic_site(kind, mac.path.span(), mac.span());
+        }
+
+        // assert_eq!(f(x), 1) などの引数内の呼び出しも記録（式のカンマ区切りとして解釈できるもののみ）
+        if let Ok(args) = mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
         }
-        
-        let params = generics.params.iter()
-            .map(|p| quote::quote!(#p).to_string())
-            .collect::<Vec<_>>()
-            .join(", ");
-        
-        Some(format!("<{params}>"))
     }
 }
 
@@ -749,4 +1590,127 @@ pub trait Iterator<Item> {
         assert_eq!(iterator.len(), 1);
         assert_eq!(iterator[0].generics, Some("<Item>".to_string()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_error_keeps_last_good_symbols() {
+        let mut parser = RustParser::new();
+        let outcome = parser.parse_source("lib.rs", "pub fn stable() {}\n\npub fn editing() {}\n").unwrap();
+        assert_eq!(outcome, ParseOutcome::Parsed);
+
+        // 編集途中の壊れた内容
+        let outcome = parser.parse_source("lib.rs", "pub fn stable() {}\n\npub fn editing( {\n").unwrap();
+        assert_eq!(outcome, ParseOutcome::KeptLastGood);
+        assert!(parser.find_symbol("stable", None).is_some());
+        assert!(parser.find_symbol("editing", None).is_some());
+
+        let diagnostics = parser.get_diagnostics(Some("lib.rs"));
+        assert!(!diagnostics.is_empty());
+        assert_eq!(parser.get_recovery_status().get("lib.rs"), Some(&ParseRecovery::KeptLastGood));
+
+        // 修正されたら診断情報が消える
+        parser.parse_source("lib.rs", "pub fn stable() {}\n").unwrap();
+        assert!(parser.get_diagnostics(None).is_empty());
+        assert!(parser.find_symbol("editing", None).is_none());
+    }
+
+    #[test]
+    fn test_parse_error_salvages_items() {
+        let mut parser = RustParser::new();
+        let content = r#"pub struct Before {
+    value: i32,
+}
+
+pub fn broken(a: i32 {
+    a
+}
+
+/// ドキュメント
+#[inline]
+pub fn after() -> i32 {
+    1
+}
+"#;
+        let outcome = parser.parse_source("broken.rs", content).unwrap();
+        assert_eq!(outcome, ParseOutcome::Salvaged { recovered_items: 2 });
+
+        assert!(parser.find_symbol("Before", Some(SymbolType::Struct)).is_some());
+        assert!(parser.find_symbol("broken", None).is_none());
+        let after = parser.find_symbol("after", Some(SymbolType::Function)).unwrap();
+        assert_eq!(after[0].line, 11);
+
+        let diagnostics = parser.get_diagnostics(Some("broken.rs"));
+        assert!(diagnostics.iter().any(|d| d.line == 5));
+    }
+
+    #[test]
+    fn test_nested_items_and_closures() {
+        let mut parser = RustParser::new();
+        let content = r#"pub fn outer() {
+    fn inner() {
+        helper();
+    }
+
+    let double = |x: i32| x * 2;
+    let apply = move |x| compute(x);
+    inner();
+}
+
+mod utils {
+    pub fn helper() {}
+}
+
+struct Counter;
+
+impl Counter {
+    pub fn new() -> Self {
+        helper();
+        Counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn setup() {}
+}
+"#;
+        parser.parse_source("nested.rs", content).unwrap();
+
+        let inner = parser.find_symbol("inner", Some(SymbolType::Function)).unwrap();
+        assert_eq!(inner[0].scope.as_deref(), Some("outer"));
+        assert_eq!((inner[0].line, inner[0].end_line), (2, 4));
+
+        let double = parser.find_symbol("double", None).unwrap();
+        assert_eq!(double[0].scope.as_deref(), Some("outer"));
+        assert_eq!(double[0].signature, "let double = |x : i32|");
+
+        let helper = parser.find_symbol("helper", None).unwrap();
+        assert_eq!(helper[0].scope.as_deref(), Some("utils"));
+
+        let new = parser.find_symbol("new", None).unwrap();
+        assert_eq!(new[0].scope.as_deref(), Some("Counter"));
+
+        let setup = parser.find_symbol("setup", None).unwrap();
+        assert!(setup[0].is_test);
+
+        // 呼び出しは最も内側の関数・クロージャに帰属する
+        let callers: Vec<&str> = parser.get_calls_to_function("helper").iter().map(|c| c.caller.as_str()).collect();
+        assert_eq!(callers, vec!["inner", "new"]);
+        assert_eq!(parser.get_calls_to_function("compute")[0].caller, "apply");
+        assert_eq!(parser.get_calls_to_function("inner")[0].caller, "outer");
+        assert_eq!(parser.get_calls_to_function("inner")[0].call_line, 8);
+    }
+
+    #[test]
+    fn test_is_test_path_relative_to_crate_root() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("tests/proj");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::create_dir_all(project.join("tests")).unwrap();
+        fs::write(project.join("Cargo.toml"), "[package]\nname = \"proj\"\n").unwrap();
+
+        // プロジェクトが tests というディレクトリの下にあっても、src/ はテストではない
+        assert!(!is_test_path(&project.join("src/lib.rs").to_string_lossy()));
+        assert!(is_test_path(&project.join("tests/it.rs").to_string_lossy()));
+        assert!(!is_test_item(&[], &project.join("src/lib.rs").to_string_lossy()));
+    }
+}
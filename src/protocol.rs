@@ -19,10 +19,36 @@ pub struct ServerResponse {
 pub mod methods {
     pub const FIND_DEFINITION: &str = "find_definition";
     pub const FIND_USAGES: &str = "find_usages";
-    pub const LIST_SYMBOLS: &str = "list_symbols";
     pub const GET_STATS: &str = "get_stats";
     pub const HEALTH_CHECK: &str = "health_check";
     pub const CHANGE_PROJECT: &str = "change_project";
+    pub const SEARCH_SYMBOLS: &str = "search_symbols";
+    pub const GET_DIAGNOSTICS: &str = "get_diagnostics";
+    pub const FIND_LOCAL_REFERENCES: &str = "find_local_references";
+    pub const FIND_TESTS_FOR: &str = "find_tests_for";
+    pub const LIST_TESTS: &str = "list_tests";
+    pub const UNSAFE_REPORT: &str = "unsafe_report";
+    pub const PANIC_REPORT: &str = "panic_report";
+    pub const GET_METRICS: &str = "get_metrics";
+    pub const FIND_DEAD_CODE: &str = "find_dead_code";
+    pub const API_SURFACE: &str = "api_surface";
+    pub const API_DIFF: &str = "api_diff";
+    pub const ASYNC_HAZARDS: &str = "async_hazards";
+    pub const GET_ERROR_MAP: &str = "get_error_map";
+    pub const FIND_ERROR_ORIGINS: &str = "find_error_origins";
+    pub const FIND_SWALLOWED_ERRORS: &str = "find_swallowed_errors";
+    pub const FIND_IMPLEMENTATIONS: &str = "find_implementations";
+    pub const GET_IMPL_MATRIX: &str = "get_impl_matrix";
+    pub const GET_JSON_SCHEMA: &str = "get_json_schema";
+    pub const GET_SOURCE: &str = "get_source";
+    pub const EXPLAIN_SYMBOL: &str = "explain_symbol";
+    pub const RENAME_SYMBOL: &str = "rename_symbol";
+    pub const STRUCTURAL_SEARCH: &str = "structural_search";
+    pub const SEMANTIC_DIFF: &str = "semantic_diff";
+    pub const SYMBOL_HISTORY: &str = "symbol_history";
+    pub const GET_CHURN: &str = "get_churn";
+    pub const IMPACT_ANALYSIS: &str = "impact_analysis";
+    pub const SUGGEST_IMPORTS: &str = "suggest_imports";
 }
 
 /// シンボルの種類
@@ -34,6 +60,182 @@ pub enum SymbolType {
     Trait,
 }
 
+/// テストの種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum TestKind {
+    Unit,         // src 内の #[test] / #[tokio::test] など
+    Integration,  // tests/ 配下
+    Doctest,      // ドキュメントコメント中のコード例
+}
+
+/// unsafe なコードの種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum UnsafeKind {
+    Function,  // unsafe fn
+    Impl,      // unsafe impl
+    Trait,     // unsafe trait
+    Block,     // unsafe { ... }
+}
+
+/// パニックしうる箇所の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PanicKind {
+    Unwrap,         // .unwrap()
+    Expect,         // .expect(..)
+    Panic,          // panic!
+    Unreachable,    // unreachable!
+    Todo,           // todo!
+    Unimplemented,  // unimplemented!
+    Assert,         // assert! / assert_eq! / assert_ne!
+    Index,          // a[i] / &s[1..]
+}
+
+/// get_metrics の並び替えキー（降順）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum MetricsSortKey {
+    Complexity,
+    Lines,
+    Nesting,
+    Params,
+    EarlyReturns,
+    FanIn,
+    FanOut,
+}
+
+/// 到達不能と判定したアイテムの種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DeadItemKind {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    TypeAlias,
+    Module,
+}
+
+/// 解析結果の確からしさ（マクロや動的ディスパッチで見逃している可能性がある）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+/// 公開 API を構成するアイテムの種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiItemKind {
+    Module,
+    Function,
+    Struct,
+    Field,        // 構造体の pub フィールド
+    Enum,
+    Variant,
+    Trait,
+    TraitMethod,
+    AssocType,    // trait の関連型
+    Method,       // 固有 impl の pub メソッド
+    Const,
+    Static,
+    TypeAlias,
+    Macro,        // #[macro_export] された macro_rules!
+    Impl,         // 公開型への trait 実装
+    ReExport,     // クレート外のアイテムの pub use
+}
+
+/// 公開 API の変更の影響（semver のメジャー・マイナー・パッチに対応）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeLevel {
+    Patch,
+    Additive,
+    Breaking,
+}
+
+/// 公開 API アイテムの変更の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ApiChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// 非同期処理の危険箇所の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AsyncHazardKind {
+    BlockingCall,      // async fn から到達できるブロッキング呼び出し（std::fs・thread::sleep・同期ロックなど）
+    GuardAcrossAwait,  // ロックのガードを保持したままの .await
+    UnawaitedFuture,   // async fn を呼び出して .await せずに捨てている
+}
+
+/// 失敗を返す関数の戻り値の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ErrorReturnKind {
+    Result,
+    Option,
+}
+
+/// エラーの伝播・生成・握りつぶしの箇所の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ErrorSiteKind {
+    Try,              // ? 演算子
+    ErrConstruct,     // Err(...) / bail! / ensure!
+    LetUnderscore,    // let _ = fallible();
+    Ok,               // fallible().ok()
+    UnwrapOrDefault,  // fallible().unwrap_or_default()
+}
+
+/// トレイト実装の由来
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImplSource {
+    Derive,  // #[derive(...)] による実装
+    Manual,  // impl Trait for Type ブロック
+}
+
+/// リネームで書き換える箇所の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum RenameEditKind {
+    Definition,     // リネームするシンボルの定義
+    TraitMethod,    // トレイトの宣言・impl Trait for X 側のメソッド名
+    Call,           // foo(...) / Type::foo(...)
+    MethodCall,     // x.foo(...)
+    Reference,      // 型・パスとしての参照（関数を値として渡す場合を含む）
+    StructLiteral,  // Foo { .. } の構築・パターン
+    Import,         // use
+    Reexport,       // pub use
+}
+
+/// シンボル単位の変更の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SymbolChangeKind {
+    Removed,
+    Added,
+    SignatureChanged,  // シグネチャ（構造体・列挙型は定義全体）が変わった
+    BodyChanged,       // 本体だけが変わった
+    Moved,             // 内容は同じでファイル・スコープだけが変わった
+}
+
+/// get_churn の並び替えキー
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ChurnSortKey {
+    Churn,   // 変更回数の多い順
+    Recent,  // 最近変更された順
+    Oldest,  // 長く変更されていない順
+}
+
+/// モジュール間の依存の根拠になった書き方
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ModuleReferenceKind {
+    Use,   // use 宣言
+    Path,  // コード中の修飾パス（crate::parser::parse() など）
+    Call,  // コールグラフの呼び出し（呼び出し先の定義が1つのモジュールに決まるもの）
+}
+
+/// アーキテクチャのルールの種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ArchitectureRuleKind {
+    MustNotDependOn,  // 指定したモジュールに依存してはいけない
+    MayOnlyDependOn,  // 指定したモジュールにだけ依存してよい
+}
+
 /// find_definition のパラメータ
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FindDefinitionParams {
@@ -54,9 +256,12 @@ pub struct SymbolDefinition {
     pub file_path: String,
     pub line: usize,
     pub column: usize,
+    pub end_line: usize,
     pub signature: String,
     pub visibility: String,
     pub generics: Option<String>,  // ジェネリクスパラメータ
+    pub crate_name: Option<String>,  // 依存クレートのシンボルならクレート名（読み取り専用）
+    pub scope: Option<String>,  // 囲んでいるモジュール・型・関数（トップレベルは None）
 }
 
 /// get_stats のレスポンス
@@ -64,11 +269,16 @@ pub struct SymbolDefinition {
 pub struct StatsResponse {
     pub total_symbols: usize,
     pub total_functions: usize,
+    pub total_methods: usize,  // impl・トレイトの中の関数（total_functions には含まない）
     pub total_structs: usize,
     pub total_enums: usize,
     pub total_traits: usize,
     pub unique_symbol_names: usize,
     pub indexed_files_count: usize,
+    pub files_with_errors: usize,
+    pub external_symbols: usize,
+    pub dependency_crates: usize,
+    pub total_tests: usize,
 }
 
 /// change_project のパラメータ
@@ -117,6 +327,1022 @@ pub enum UsageType {
     Reference,
 }
 
+/// search_symbols のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSymbolsParams {
+    pub query: String,
+    pub symbol_type: Option<SymbolType>,  // None の場合は全種類を検索
+    pub limit: Option<usize>,  // None の場合はデフォルト件数
+}
+
+/// search_symbols のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSymbolsResponse {
+    pub results: Vec<SymbolSearchResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSearchResult {
+    #[serde(flatten)]
+    pub symbol: SymbolDefinition,
+    pub match_kind: MatchKind,
+    pub score: i64,
+    pub is_test: bool,
+}
+
+/// 検索クエリとシンボル名のマッチ種別（品質の高い順）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    WordPart,
+    Substring,
+    Fuzzy,
+}
+
+/// get_diagnostics のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetDiagnosticsParams {
+    pub file_path: Option<String>,  // None の場合は全ファイル
+}
+
+/// get_diagnostics のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetDiagnosticsResponse {
+    pub files: Vec<FileDiagnostics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiagnostics {
+    pub file_path: String,
+    pub recovery: ParseRecovery,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// パースエラー時にインデックスをどう維持したか
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ParseRecovery {
+    KeptLastGood,  // 前回正常にパースできたシンボルを保持
+    Salvaged,      // 壊れたアイテム以外のトップレベルアイテムを救出
+    Failed,        // 何も救出できなかった
+}
+
+/// find_local_references のパラメータ（エディタのカーソル位置と同じ指定方法）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindLocalReferencesParams {
+    pub file_path: String,
+    pub line: usize,    // 1ベース
+    pub column: usize,  // 0ベース
+}
+
+/// find_local_references のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindLocalReferencesResponse {
+    pub binding: Option<LocalBinding>,  // 指定位置がローカル変数でない場合は None
+    pub references: Vec<LocalReference>,
+    pub is_mutated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalBinding {
+    pub name: String,
+    pub kind: BindingKind,
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+    pub is_mutable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalReference {
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+    pub access: AccessKind,
+}
+
+/// ローカル束縛の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BindingKind {
+    Parameter,  // 関数・クロージャの引数
+    Local,      // let 文
+    Pattern,    // match / if let / for などのパターン
+}
+
+/// ローカル変数へのアクセス種別
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum AccessKind {
+    Binding,         // 束縛位置
+    Read,
+    Write,           // 代入・複合代入（フィールドやインデックス経由を含む）
+    MutableBorrow,   // &mut
+    MethodReceiver,  // メソッド呼び出しのレシーバー（&mut self かは型情報がないため不明）
+}
+
+/// find_tests_for のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindTestsForParams {
+    pub symbol_name: String,
+    pub max_depth: Option<usize>,  // None の場合はデフォルトの深さ
+}
+
+/// find_tests_for のレスポンス（呼び出し段数の少ない順）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindTestsForResponse {
+    pub tests: Vec<TestMatchResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestMatchResult {
+    #[serde(flatten)]
+    pub test: TestDefinition,
+    pub depth: usize,
+    pub call_path: Vec<String>,  // テスト → ... → 対象シンボル
+}
+
+/// list_tests のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTestsParams {
+    pub kind: Option<TestKind>,      // None の場合は全種類
+    pub file_path: Option<String>,   // None の場合は全ファイル
+}
+
+/// list_tests のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTestsResponse {
+    pub tests: Vec<TestDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestDefinition {
+    pub name: String,
+    pub kind: TestKind,
+    pub file_path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub scope: Option<String>,
+    pub attribute: Option<String>,
+}
+
+/// unsafe_report のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsafeReportParams {
+    pub file_path: Option<String>,        // None の場合は全ファイル
+    pub undocumented_only: Option<bool>,  // true の場合は SAFETY コメントのないものだけ
+    pub include_tests: Option<bool>,      // デフォルトは true
+}
+
+/// unsafe_report のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsafeReportResponse {
+    pub sites: Vec<UnsafeSite>,
+    pub summary: UnsafeSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsafeSite {
+    pub kind: UnsafeKind,
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub enclosing_symbol: Option<String>,
+    pub signature: String,
+    pub has_safety_comment: bool,
+    pub safety_comment: Option<String>,
+    pub is_test: bool,
+}
+
+/// unsafe_report の集計（フィルタ適用後）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnsafeSummary {
+    pub total: usize,
+    pub documented: usize,
+    pub undocumented: usize,
+    pub functions: usize,
+    pub impls: usize,
+    pub traits: usize,
+    pub blocks: usize,
+}
+
+/// panic_report のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanicReportParams {
+    pub file_path: Option<String>,      // None の場合は全ファイル
+    pub function: Option<String>,       // 指定した関数のみ
+    pub public_only: Option<bool>,      // true の場合は pub 関数のみ
+    pub include_tests: Option<bool>,    // デフォルトは false（テストの assert! などを除外）
+}
+
+/// panic_report のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanicReportResponse {
+    pub functions: Vec<FunctionPanics>,
+    pub summary: PanicSummary,
+}
+
+/// パニックしうる関数（直接のパニック箇所、または呼び出し先経由）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionPanics {
+    pub name: String,
+    pub file_path: String,
+    pub line: usize,
+    pub visibility: String,
+    pub scope: Option<String>,
+    pub sites: Vec<PanicSite>,     // この関数内の直接のパニック箇所
+    pub panic_path: Vec<String>,   // この関数 → ... → パニック箇所を含む関数（最短経路）
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanicSite {
+    pub kind: PanicKind,
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub context: String,
+}
+
+/// panic_report の集計（フィルタ適用後）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PanicSummary {
+    pub total_sites: usize,
+    pub sites_by_kind: std::collections::BTreeMap<PanicKind, usize>,
+    pub functions_with_sites: usize,   // 直接パニック箇所を含む関数
+    pub may_panic_functions: usize,    // 呼び出し先経由も含めてパニックしうる関数
+    pub public_may_panic: usize,       // そのうち pub 関数
+}
+
+/// get_metrics のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetMetricsParams {
+    pub file_path: Option<String>,         // None の場合は全ファイル
+    pub function: Option<String>,          // 指定した関数のみ
+    pub sort_by: Option<MetricsSortKey>,   // デフォルトは Complexity
+    pub limit: Option<usize>,              // デフォルトは 50
+    pub include_tests: Option<bool>,       // デフォルトは false
+}
+
+/// get_metrics のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetMetricsResponse {
+    pub functions: Vec<FunctionMetrics>,
+    pub total_functions: usize,  // limit 適用前の件数
+}
+
+/// 関数ごとの規模・複雑さの指標
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionMetrics {
+    pub name: String,
+    pub file_path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub scope: Option<String>,
+    pub visibility: String,
+    pub lines_of_code: usize,           // 空行・コメント行を除く
+    pub cyclomatic_complexity: usize,
+    pub max_nesting: usize,
+    pub params: usize,
+    pub early_returns: usize,           // 末尾以外の return と ? 演算子
+    pub fan_in: usize,                  // 呼び出し元の関数の数
+    pub fan_out: usize,                 // 呼び出し先の関数の数（インデックス済みの関数のみ）
+}
+
+/// find_dead_code のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindDeadCodeParams {
+    pub file_path: Option<String>,           // None の場合は全ファイル
+    pub min_confidence: Option<Confidence>,  // デフォルトは Low（すべて）
+    pub include_tests: Option<bool>,         // デフォルトは false
+}
+
+/// find_dead_code のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindDeadCodeResponse {
+    pub items: Vec<DeadItem>,
+    pub summary: DeadCodeSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadItem {
+    pub name: String,
+    pub kind: DeadItemKind,
+    pub file_path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub scope: Option<String>,
+    pub visibility: String,
+    pub confidence: Confidence,
+    pub reasons: Vec<String>,
+}
+
+/// find_dead_code の集計（フィルタ適用後）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadCodeSummary {
+    pub roots: usize,           // main・テスト・trait 実装などの根の数
+    pub analyzed_items: usize,
+    pub dead_functions: usize,
+    pub dead_types: usize,
+    pub dead_modules: usize,
+}
+
+/// api_surface のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSurfaceParams {
+    pub crate_path: Option<String>,  // クレートのディレクトリ（デフォルトはプロジェクトのルート）
+    pub revision: Option<String>,    // git のリビジョン（None の場合は作業ツリー）
+}
+
+/// api_surface のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSurfaceResponse {
+    pub root_file: String,
+    pub revision: Option<String>,
+    pub items: Vec<ApiItem>,
+}
+
+/// クレートのルートから到達できる公開アイテム（path は `crate::a::Item` 形式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiItem {
+    pub path: String,
+    pub kind: ApiItemKind,
+    pub signature: String,     // 空白を正規化したシグネチャ
+    pub file_path: String,
+    pub line: usize,
+    pub non_exhaustive: bool,  // クレート外で構築・網羅的なマッチができない型（#[non_exhaustive] か非公開フィールドあり）
+    pub required: bool,        // 実装側が必ず定義する trait のアイテム（デフォルト実装なし）
+}
+
+/// api_diff のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiDiffParams {
+    pub crate_path: Option<String>,  // クレートのディレクトリ（デフォルトはプロジェクトのルート）
+    pub from: String,                // 比較元の git リビジョン
+    pub to: Option<String>,          // 比較先の git リビジョン（None の場合は作業ツリー）
+}
+
+/// api_diff のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiDiffResponse {
+    pub from: String,
+    pub to: Option<String>,
+    pub level: ChangeLevel,  // 変更全体で必要なバージョンの上げ方
+    pub changes: Vec<ApiChange>,
+    pub summary: ApiDiffSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiChange {
+    pub path: String,
+    pub kind: ApiItemKind,
+    pub change: ApiChangeKind,
+    pub level: ChangeLevel,
+    pub old_signature: Option<String>,
+    pub new_signature: Option<String>,
+    pub reason: String,
+}
+
+/// api_diff の集計
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiDiffSummary {
+    pub breaking: usize,
+    pub additive: usize,
+    pub patch: usize,
+}
+
+/// async_hazards のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsyncHazardsParams {
+    pub file_path: Option<String>,        // None の場合は全ファイル
+    pub kind: Option<AsyncHazardKind>,    // None の場合は全種類
+    pub include_tests: Option<bool>,      // デフォルトは false
+}
+
+/// async_hazards のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsyncHazardsResponse {
+    pub hazards: Vec<AsyncHazard>,
+    pub summary: AsyncHazardSummary,
+}
+
+/// 危険箇所（function は async fn、または async ブロックを含む関数）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsyncHazard {
+    pub kind: AsyncHazardKind,
+    pub function: String,
+    pub function_file: String,
+    pub function_line: usize,
+    pub scope: Option<String>,
+    pub name: String,             // ブロッキング呼び出し・ガードの変数・呼び出した async fn の名前
+    pub file_path: String,        // 危険箇所の位置（ガードはロックから .await まで）
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub context: String,
+    pub call_chain: Vec<String>,  // function から危険箇所を含む関数までの呼び出し経路
+}
+
+/// async_hazards の集計（フィルタ適用後）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AsyncHazardSummary {
+    pub async_functions: usize,
+    pub blocking_calls: usize,
+    pub guards_across_await: usize,
+    pub unawaited_futures: usize,
+}
+
+/// get_error_map のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetErrorMapParams {
+    pub file_path: Option<String>,    // None の場合は全ファイル
+    pub function: Option<String>,     // None の場合は全関数
+    pub include_tests: Option<bool>,  // デフォルトは false
+}
+
+/// get_error_map のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetErrorMapResponse {
+    pub functions: Vec<ErrorFunction>,
+}
+
+/// Result・Option を返す関数と、その中の ? による伝播
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorFunction {
+    pub name: String,
+    pub file_path: String,
+    pub line: usize,
+    pub scope: Option<String>,
+    pub return_kind: ErrorReturnKind,
+    pub ok_type: String,
+    pub error_type: Option<String>,  // Result のエラー型（構文から分からない場合は None）
+    pub propagates: Vec<ErrorSite>,
+}
+
+/// エラーに関わる箇所（from_type・to_type は構文から分かる場合のみ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorSite {
+    pub kind: ErrorSiteKind,
+    pub function: String,
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub context: String,
+    pub callee: Option<String>,     // 結果を伝播・破棄している呼び出し先
+    pub from_type: Option<String>,  // 変換前のエラー型
+    pub to_type: Option<String>,    // 変換後のエラー型（囲んでいる関数のエラー型）
+}
+
+/// find_error_origins のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindErrorOriginsParams {
+    pub error_type: String,           // "io::Error" のようにモジュールを含めても、"AppError" だけでもよい
+    pub include_tests: Option<bool>,  // デフォルトは false
+}
+
+/// find_error_origins のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindErrorOriginsResponse {
+    pub error_type: String,
+    pub origins: Vec<ErrorSite>,                // Err(...) の生成、From による変換、クレート外の呼び出し
+    pub returning_functions: Vec<ErrorFunction>,
+}
+
+/// find_swallowed_errors のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindSwallowedErrorsParams {
+    pub function: Option<String>,     // この関数の結果を握りつぶしている呼び出し元に限る
+    pub file_path: Option<String>,    // None の場合は全ファイル
+    pub include_tests: Option<bool>,  // デフォルトは false
+}
+
+/// find_swallowed_errors のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindSwallowedErrorsResponse {
+    pub sites: Vec<ErrorSite>,
+}
+
+/// find_implementations のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindImplementationsParams {
+    pub trait_name: String,           // "Clone" のように名前だけでも、"serde::Serialize" のようにパス付きでもよい
+    pub include_tests: Option<bool>,  // デフォルトは false
+}
+
+/// find_implementations のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindImplementationsResponse {
+    pub trait_name: String,
+    pub implementations: Vec<Implementation>,
+}
+
+/// トレイトの実装（derive による実装も含む）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Implementation {
+    pub trait_name: String,
+    pub trait_path: String,  // 書かれたとおりのパス（"serde::Serialize" など）
+    pub type_name: String,
+    pub file_path: String,
+    pub line: usize,         // derive 属性または impl ブロックの行
+    pub source: ImplSource,
+}
+
+/// get_impl_matrix のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetImplMatrixParams {
+    pub file_path: Option<String>,     // None の場合は全ファイル
+    pub traits: Option<Vec<String>>,   // 列にするトレイト（None の場合は実装のあるトレイトすべて）
+    pub include_tests: Option<bool>,   // デフォルトは false
+}
+
+/// get_impl_matrix のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetImplMatrixResponse {
+    pub traits: Vec<String>,
+    pub rows: Vec<ImplMatrixRow>,
+}
+
+/// 構造体・列挙型1つ分の行（traits はトレイト名 -> 実装の由来）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImplMatrixRow {
+    pub type_name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: String,
+    pub line: usize,
+    pub scope: Option<String>,
+    pub traits: std::collections::BTreeMap<String, ImplSource>,
+}
+
+/// get_json_schema のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaParams {
+    pub type_name: String,
+    pub file_path: Option<String>,  // 同名の型があるときに絞り込む
+}
+
+/// get_json_schema のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaResponse {
+    pub type_name: String,
+    pub file_path: String,
+    pub line: usize,
+    pub schema: serde_json::Value,  // JSON Schema (draft 2020-12)。参照している型は $defs に入る
+    pub unresolved: Vec<String>,    // 定義が分からず {} にした型
+}
+
+/// get_source のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetSourceParams {
+    pub symbol_name: String,                // find_definition と同じく "CodeIndexer::new" のような修飾も可
+    pub symbol_type: Option<SymbolType>,
+    pub file_path: Option<String>,          // 同名のシンボルを絞り込む
+    pub line: Option<usize>,                // 同名のシンボルを定義行で絞り込む
+    pub include_attributes: Option<bool>,   // ドキュメントコメントと属性を含める（デフォルトは false）
+    pub context_lines: Option<usize>,       // 前後に付ける行数（デフォルトは 0）
+    pub signature_only: Option<bool>,       // 本体を省いてシグネチャだけ返す（デフォルトは false）
+    pub max_lines: Option<usize>,           // 本体がこの行数を超えたらシグネチャだけ返す
+}
+
+/// get_source のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetSourceResponse {
+    pub sources: Vec<SymbolSource>,
+}
+
+/// シンボルのソーステキスト
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSource {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: String,
+    pub scope: Option<String>,
+    pub crate_name: Option<String>,
+    pub start_line: usize,      // text の最初の行（前後の行を含む）
+    pub end_line: usize,        // text の最後の行
+    pub item_lines: usize,      // アイテム全体の行数（シグネチャだけ返したときの判断用）
+    pub signature_only: bool,   // 本体を省いたか（max_lines を超えた場合も true）
+    pub text: String,
+}
+
+/// explain_symbol のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainSymbolParams {
+    pub symbol_name: String,              // find_definition と同じく "CodeIndexer::new" のような修飾も可
+    pub symbol_type: Option<SymbolType>,
+    pub file_path: Option<String>,        // 同名のシンボルを絞り込む
+    pub line: Option<usize>,              // 同名のシンボルを定義行で絞り込む
+    pub limit: Option<usize>,             // 呼び出し元・呼び出し先・テストの名前を何件まで返すか（デフォルトは 5）
+}
+
+/// explain_symbol のレスポンス（同名のシンボルがあれば定義ごとに1件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainSymbolResponse {
+    pub symbols: Vec<SymbolExplanation>,
+}
+
+/// シンボルの要約（ホバー表示相当）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolExplanation {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub signature: String,
+    pub visibility: String,
+    pub docs: Option<String>,
+    pub module_path: String,                    // 例: "crate::indexer"、依存クレートなら "tokio::sync"
+    pub owner: Option<String>,                  // 例: "impl CodeIndexer"、"impl fmt::Display for Kind"、"trait Visit"
+    pub file_path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub crate_name: Option<String>,
+    pub implemented_traits: Vec<ImplementedTrait>,  // 構造体・列挙型が実装しているトレイト
+    pub implementors: RelatedNames,             // トレイトを実装している型
+    pub callers: RelatedNames,                  // 呼び出し元の関数（呼び出し回数の多い順）
+    pub callees: RelatedNames,                  // 呼び出し先のインデックス済み関数（呼び出し回数の多い順）
+    pub tests: RelatedNames,                    // 推移的に呼び出しているテスト（近い順）
+}
+
+/// 関連するシンボルの件数と、上位の名前
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelatedNames {
+    pub count: usize,
+    pub top: Vec<String>,
+}
+
+/// 型が実装しているトレイト
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImplementedTrait {
+    pub trait_name: String,
+    pub source: ImplSource,
+}
+
+/// rename_symbol のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameSymbolParams {
+    pub symbol_name: String,              // find_definition と同じく "CodeIndexer::new" のような修飾も可
+    pub new_name: String,
+    pub symbol_type: Option<SymbolType>,
+    pub file_path: Option<String>,        // 同名のシンボルを絞り込む
+    pub line: Option<usize>,              // 同名のシンボルを定義行で絞り込む
+    pub apply: Option<bool>,              // true ならファイルを書き換えて再インデックスする（デフォルトはプレビューのみ）
+}
+
+/// rename_symbol のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameSymbolResponse {
+    pub symbol_name: String,
+    pub new_name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: String,                // 定義のファイル
+    pub line: usize,                      // 定義の行
+    pub edits: Vec<RenameEdit>,           // ファイル・位置順
+    pub skipped: Vec<SkippedReference>,   // 同名の別シンボルの可能性があり書き換えない箇所
+    pub applied: bool,
+}
+
+/// 識別子1つの書き換え（列は0ベースの文字単位）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameEdit {
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+    pub old_text: String,
+    pub new_text: String,
+    pub kind: RenameEditKind,
+    pub context: String,
+}
+
+/// 書き換えなかった参照候補
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedReference {
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+    pub context: String,
+}
+
+/// structural_search のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuralSearchParams {
+    pub pattern: String,            // Rust の式。$x はメタ変数（$x:lit / $x:str / $x:ident で制約、$..xs は0個以上の引数、$_ は束縛しない）
+    pub rewrite: Option<String>,    // 置換テンプレート（$x に束縛したソースを埋め込む）
+    pub file_path: Option<String>,  // このファイルだけを対象にする
+    pub limit: Option<usize>,       // 返す一致の最大数（デフォルトは 100）
+    pub apply: Option<bool>,        // true なら置換をファイルに書き込んで再インデックスする（rewrite が必要）
+}
+
+/// structural_search のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuralSearchResponse {
+    pub pattern: String,
+    pub total_matches: usize,             // limit で切る前の件数
+    pub matches: Vec<StructuralMatch>,    // ファイル・位置順
+    pub applied: bool,
+    pub skipped_overlapping: usize,       // 外側の一致と重なるため置換しなかった件数
+}
+
+/// パターンに一致した式（列は0ベースの文字単位）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuralMatch {
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub text: String,
+    pub bindings: std::collections::BTreeMap<String, String>,  // メタ変数名 -> 一致したソース
+    pub replacement: Option<String>,
+}
+
+/// semantic_diff のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticDiffParams {
+    pub path: Option<String>,  // 比較するディレクトリ（デフォルトはプロジェクトのルート）
+    pub from: String,          // 比較元の git リビジョン
+    pub to: Option<String>,    // 比較先の git リビジョン（None の場合は作業ツリー）
+}
+
+/// semantic_diff のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticDiffResponse {
+    pub from: String,
+    pub to: Option<String>,
+    pub summary: SemanticDiffSummary,
+    pub symbols: Vec<SymbolChange>,
+    pub calls_added: Vec<CallEdge>,
+    pub calls_removed: Vec<CallEdge>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemanticDiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub signature_changed: usize,
+    pub body_changed: usize,
+    pub moved: usize,  // 移動したシンボル（移動と同時に変更されたものを含む）
+    pub calls_added: usize,
+    pub calls_removed: usize,
+}
+
+/// 変更のあったシンボル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolChange {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub kind: SymbolChangeKind,
+    pub moved: bool,                  // ファイルまたはスコープが変わった
+    pub old: Option<SymbolVersion>,   // Added では None
+    pub new: Option<SymbolVersion>,   // Removed では None
+}
+
+/// 片方のリビジョンでのシンボル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolVersion {
+    pub file_path: String,  // 比較するディレクトリからの相対パス
+    pub line: usize,
+    pub scope: Option<String>,
+    pub signature: String,
+}
+
+/// 呼び出し関係（caller は "Type::method" のようにスコープ付き）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallEdge {
+    pub caller: String,
+    pub callee: String,
+    pub file_path: String,
+    pub line: usize,
+}
+
+/// symbol_history のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolHistoryParams {
+    pub symbol_name: String,               // "CodeIndexer::new" のような修飾名も可
+    pub symbol_type: Option<SymbolType>,
+    pub file_path: Option<String>,         // 同名のシンボルを定義ファイルで絞り込む
+    pub line: Option<usize>,               // 同名のシンボルを定義行で絞り込む
+    pub limit: Option<usize>,              // シンボルごとのコミット数の上限。デフォルトは 20
+}
+
+/// symbol_history のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolHistoryResponse {
+    pub symbols: Vec<SymbolHistory>,
+}
+
+/// シンボルの行範囲に触れたコミットの履歴
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolHistory {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub scope: Option<String>,
+    pub stats: Option<GitStats>,  // コミットされたことがなければ None
+    pub commits: Vec<CommitInfo>, // 新しい順
+}
+
+/// get_churn のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetChurnParams {
+    pub file_path: Option<String>,      // None の場合は全ファイル
+    pub sort_by: Option<ChurnSortKey>,  // デフォルトは Churn
+    pub limit: Option<usize>,           // デフォルトは 50
+    pub include_tests: Option<bool>,    // デフォルトは false
+}
+
+/// get_churn のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetChurnResponse {
+    pub symbols: Vec<SymbolChurn>,
+    pub total_symbols: usize,  // limit 適用前の件数
+    pub skipped_files: Vec<String>,  // git の履歴を得られず飛ばしたファイル
+}
+
+/// シンボルと git の履歴から求めた指標
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolChurn {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub scope: Option<String>,
+    pub stats: GitStats,
+}
+
+/// シンボルの行範囲についての git の履歴の要約
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitStats {
+    pub last_commit: String,
+    pub last_author: String,
+    pub last_summary: String,
+    pub last_modified: i64,         // UNIX 時刻
+    pub last_modified_days: u64,    // 最後に変更されてからの日数
+    pub age_days: u64,              // 最初のコミットからの日数
+    pub churn: usize,               // 行範囲に触れたコミットの数
+    pub authors: usize,
+    pub uncommitted: bool,          // 作業ツリーに未コミットの変更があるか
+}
+
+/// コミット
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author: String,
+    pub timestamp: i64,  // UNIX 時刻（author date）
+    pub summary: String,
+}
+
+/// impact_analysis のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactAnalysisParams {
+    pub diff: Option<String>,       // unified diff。None なら git diff（base と作業ツリー）を使う
+    pub base: Option<String>,       // diff が None のときの比較元リビジョン。デフォルトは HEAD
+    pub path: Option<String>,       // git diff を実行するディレクトリ（プロジェクトルートからの相対パス）
+    pub max_depth: Option<usize>,   // 呼び出し元をたどる深さ。デフォルトは 5
+}
+
+/// impact_analysis のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactAnalysisResponse {
+    pub changed_files: Vec<String>,     // インデックス済みのファイルに対応した diff のファイル
+    pub unmatched_files: Vec<String>,   // 対応するファイルがなかった diff のファイル（削除・.rs 以外など）
+    pub changed: Vec<ImpactedSymbol>,   // 変更行を含むシンボル（distance 0）
+    pub functions: Vec<ImpactedSymbol>, // 変更したシンボルを推移的に使う関数（テストを除く）
+    pub public_apis: Vec<ImpactedSymbol>, // changed と functions のうち pub なもの
+    pub tests: Vec<ImpactedTest>,
+}
+
+/// 変更の影響を受けるシンボル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactedSymbol {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub file_path: String,
+    pub line: usize,
+    pub scope: Option<String>,
+    pub visibility: String,
+    pub distance: usize,       // 変更したシンボルからの呼び出しの段数
+    pub via: Option<String>,   // 変更に近い側でつながっているシンボル
+}
+
+/// 変更の影響を受けるテスト
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactedTest {
+    pub name: String,
+    pub kind: TestKind,
+    pub file_path: String,
+    pub line: usize,
+    pub distance: usize,
+    pub via: Option<String>,
+}
+
+/// suggest_imports のパラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestImportsParams {
+    pub name: String,              // インポートしたい名前（"CodeIndexer" など）
+    pub from_file: String,         // use を書くファイル
+    pub limit: Option<usize>,      // デフォルトは 10
+}
+
+/// suggest_imports のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestImportsResponse {
+    pub name: String,
+    pub from_file: String,
+    pub module_path: Option<String>,  // from_file のモジュール（"crate::indexer" など）
+    pub already_in_scope: bool,       // from_file のモジュールで定義・インポート済み
+    pub suggestions: Vec<ImportSuggestion>,
+}
+
+/// use のパスの候補
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSuggestion {
+    pub path: String,                 // "crate::indexer::CodeIndexer"
+    pub use_statement: String,        // "use crate::indexer::CodeIndexer;"
+    pub kind: ApiItemKind,
+    pub file_path: String,            // 定義の位置
+    pub line: usize,
+    pub crate_name: Option<String>,   // 別のクレートのアイテムならクレート名
+    pub public: bool,                 // パスのすべての段が pub（クレートの外からも使える）
+    pub reexport: bool,               // pub use などの再エクスポートを経由する
+    pub needs_dependency: bool,       // from_file のクレートの Cargo.toml に依存の追加が必要
+}
+
+/// モジュールの依存グラフ（use 宣言と修飾パスから求める）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleGraphResponse {
+    pub modules: Vec<ModuleGraphNode>,
+    pub dependencies: Vec<ModuleDependency>,
+    pub cycles: Vec<Vec<String>>,  // 強連結成分（2つ以上のモジュールが互いに依存している）
+}
+
+/// 依存グラフのモジュール
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleGraphNode {
+    pub path: String,       // "crate::indexer"、ライブラリをバイナリから使う場合は "code_intel::indexer"
+    pub file_path: String,  // mod 宣言のあるファイル（ルートはルートのファイル）
+    pub line: usize,
+}
+
+/// モジュール間の依存（from が to を使う）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleDependency {
+    pub from: String,
+    pub to: String,
+    pub references: Vec<ModuleReference>,
+}
+
+/// 依存の根拠になった参照の位置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleReference {
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub path: String,  // 書かれたパス（"crate::parser::RustParser"）
+    pub kind: ModuleReferenceKind,
+}
+
+/// check-architecture の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureReport {
+    pub config_path: String,
+    pub rules: usize,
+    pub dependencies_checked: usize,
+    pub unmatched_patterns: Vec<String>,  // どのモジュールにも当てはまらないパターン（書き間違いの可能性）
+    pub violations: Vec<ArchitectureViolation>,
+}
+
+/// ルール違反の依存（参照1件ごと）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureViolation {
+    pub rule: String,  // "protocol must not depend on server"
+    pub kind: ArchitectureRuleKind,
+    pub reason: Option<String>,
+    pub from: String,
+    pub to: String,
+    pub file_path: String,
+    pub line: usize,
+    pub column: usize,
+    pub path: String,  // 依存の根拠になった書き方（"crate::server::Port"・"listen()"）
+    pub reference_kind: ModuleReferenceKind,
+}
+
 impl From<crate::parser::SymbolInfo> for SymbolDefinition {
     fn from(symbol_info: crate::parser::SymbolInfo) -> Self {
         Self {
@@ -125,9 +1351,12 @@ impl From<crate::parser::SymbolInfo> for SymbolDefinition {
             file_path: symbol_info.file_path,
             line: symbol_info.line,
             column: symbol_info.column,
+            end_line: symbol_info.end_line,
             signature: symbol_info.signature,
             visibility: symbol_info.visibility,
             generics: symbol_info.generics,
+            crate_name: symbol_info.crate_name,
+            scope: symbol_info.scope,
         }
     }
 }
@@ -156,11 +1385,100 @@ impl From<crate::indexer::IndexStats> for StatsResponse {
         Self {
             total_symbols: stats.total_symbols,
             total_functions: stats.total_functions,
+            total_methods: stats.total_methods,
             total_structs: stats.total_structs,
             total_enums: stats.total_enums,
             total_traits: stats.total_traits,
             unique_symbol_names: stats.unique_symbol_names,
             indexed_files_count: stats.indexed_files_count,
+            files_with_errors: stats.files_with_errors,
+            external_symbols: stats.external_symbols,
+            dependency_crates: stats.dependency_crates,
+            total_tests: stats.total_tests,
+        }
+    }
+}
+
+impl From<&crate::parser::TestInfo> for TestDefinition {
+    fn from(test: &crate::parser::TestInfo) -> Self {
+        Self {
+            name: test.name.clone(),
+            kind: test.kind,
+            file_path: test.file_path.clone(),
+            line: test.line,
+            end_line: test.end_line,
+            scope: test.scope.clone(),
+            attribute: test.attribute.clone(),
+        }
+    }
+}
+
+impl From<&crate::parser::UnsafeInfo> for UnsafeSite {
+    fn from(site: &crate::parser::UnsafeInfo) -> Self {
+        Self {
+            kind: site.kind,
+            file_path: site.file_path.clone(),
+            line: site.line,
+            column: site.column,
+            end_line: site.end_line,
+            end_column: site.end_column,
+            enclosing_symbol: site.enclosing_symbol.clone(),
+            signature: site.signature.clone(),
+            has_safety_comment: site.safety_comment.is_some(),
+            safety_comment: site.safety_comment.clone(),
+            is_test: site.is_test,
+        }
+    }
+}
+
+impl From<&crate::parser::PanicSiteInfo> for PanicSite {
+    fn from(site: &crate::parser::PanicSiteInfo) -> Self {
+        Self {
+            kind: site.kind,
+            file_path: site.file_path.clone(),
+            line: site.line,
+            column: site.column,
+            end_line: site.end_line,
+            end_column: site.end_column,
+            context: site.context.clone(),
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<&crate::parser::DiagnosticInfo> for Diagnostic {
+    fn from(diagnostic: &crate::parser::DiagnosticInfo) -> Self {
+        Self {
+            message: diagnostic.message.clone(),
+            line: diagnostic.line,
+            column: diagnostic.column,
+            end_line: diagnostic.end_line,
+            end_column: diagnostic.end_column,
+        }
+    }
+}
+
+impl From<crate::locals::LocalReferences> for FindLocalReferencesResponse {
+    fn from(local_refs: crate::locals::LocalReferences) -> Self {
+        let binding = local_refs.binding;
+        Self {
+            binding: Some(LocalBinding {
+                name: binding.name,
+                kind: binding.kind,
+                line: binding.line,
+                column: binding.column,
+                end_column: binding.end_column,
+                is_mutable: binding.is_mutable,
+            }),
+            references: local_refs.references
+                .into_iter()
+                .map(|r| LocalReference {
+                    line: r.line,
+                    column: r.column,
+                    end_column: r.end_column,
+                    access: r.access,
+                })
+                .collect(),
+            is_mutated: local_refs.is_mutated,
+        }
+    }
+}
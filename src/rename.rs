@@ -0,0 +1,848 @@
+use crate::parser::{RustParser, SymbolInfo};
+use crate::protocol::{RenameEdit, RenameEditKind, RenameSymbolResponse, SkippedReference, SymbolType};
+use anyhow::{Context, Result, bail};
+use proc_macro2::{LineColumn, Span};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+
+/// リネームするシンボルの種類（定義ファイルの構文木から決める）
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Type,                                // 構造体・列挙型・トレイト
+    Function,                            // 自由関数
+    Method { self_type: String },        // 固有メソッド
+    TraitMethod { trait_name: String },  // トレイトのメソッド（宣言と各 impl をまとめてリネーム）
+}
+
+/// 1ファイル分の収集結果
+#[derive(Default)]
+struct FileSites {
+    edits: Vec<(Span, RenameEditKind)>,
+    method_calls: Vec<(Span, bool)>,  // (位置, 受け手が self で対象の impl 内か)
+    skipped: Vec<(Span, &'static str)>,
+    imports_new_name: bool,           // 新しい名前を use しているか
+}
+
+/// リネームに必要な書き換えを求める（型情報は使わず名前とパスで判定する）
+///
+/// 同名の別シンボルを定義しているファイルの修飾なしの参照や、同名のメソッドが他にある場合の
+/// メソッド呼び出しは書き換えず skipped に入れる。名前の衝突があればエラーを返す。
+pub fn plan_rename(parser: &RustParser, symbol: &SymbolInfo, files: &[PathBuf], new_name: &str) -> Result<RenameSymbolResponse> {
+    if syn::parse_str::<syn::Ident>(new_name).is_err() {
+        bail!("'{}' is not a valid identifier", new_name);
+    }
+    if symbol.name == new_name {
+        bail!("'{}' already has that name", symbol.name);
+    }
+
+    let content = std::fs::read_to_string(&symbol.file_path)
+        .with_context(|| format!("Failed to read {}", symbol.file_path))?;
+    let syntax_tree = syn::parse_file(&content)
+        .with_context(|| format!("Failed to parse {}", symbol.file_path))?;
+    let target = classify(&syntax_tree, symbol)
+        .with_context(|| format!("Definition of '{}' not found at {}:{}", symbol.name, symbol.file_path, symbol.line))?;
+
+    let project_symbols: Vec<&SymbolInfo> = parser.get_all_symbols().values()
+        .flatten()
+        .filter(|s| s.crate_name.is_none())
+        .collect();
+    let type_names: HashSet<&str> = project_symbols.iter()
+        .filter(|s| s.symbol_type != SymbolType::Function)
+        .map(|s| s.name.as_str())
+        .collect();
+    let implementors: HashSet<&str> = match &target {
+        Target::TraitMethod { trait_name } => parser.get_impls().iter()
+            .filter(|info| info.trait_name == *trait_name)
+            .map(|info| info.type_name.as_str())
+            .collect(),
+        _ => HashSet::new(),
+    };
+    // 自由関数・型は、同じ名前空間の別の定義があるファイルでは修飾なしの参照を書き換えない
+    let shadowing_files: HashSet<&str> = project_symbols.iter()
+        .filter(|s| s.name == symbol.name && !is_same_symbol(s, symbol))
+        .filter(|s| is_value(&s.symbol_type) == is_value(&symbol.symbol_type))
+        .filter(|s| s.symbol_type != SymbolType::Function || s.scope == symbol.scope)
+        .map(|s| s.file_path.as_str())
+        .collect();
+
+    let mut sites_by_file: Vec<(String, Vec<String>, FileSites)> = Vec::new();
+    for file in files {
+        let file_path = file.to_string_lossy().to_string();
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        // 名前を含まないファイルは構文解析しない
+        if !content.contains(symbol.name.as_str()) && !content.contains(new_name) {
+            continue;
+        }
+        let Ok(syntax_tree) = syn::parse_file(&content) else {
+            continue;
+        };
+
+        let definition = (file_path == symbol.file_path).then_some(LineColumn { line: symbol.line, column: symbol.column });
+        let mut collector = Collector {
+            name: &symbol.name,
+            new_name,
+            target: &target,
+            definition,
+            type_names: &type_names,
+            implementors: &implementors,
+            shadowed: shadowing_files.contains(file_path.as_str()),
+            current_impl: None,
+            current_trait: None,
+            in_pub_use: false,
+            sites: FileSites::default(),
+        };
+        collector.visit_file(&syntax_tree);
+        let lines = content.lines().map(|l| l.to_string()).collect();
+        sites_by_file.push((file_path, lines, collector.sites));
+    }
+
+    // 書き換える定義の数。同名の関数が他になければメソッド呼び出しもすべて書き換える
+    let renamed_definitions = sites_by_file.iter()
+        .flat_map(|(_, _, sites)| &sites.edits)
+        .filter(|(_, kind)| matches!(kind, RenameEditKind::Definition | RenameEditKind::TraitMethod))
+        .count();
+    let same_name_functions = project_symbols.iter()
+        .filter(|s| s.name == symbol.name && s.symbol_type == SymbolType::Function)
+        .count();
+    let unique_method = same_name_functions <= renamed_definitions;
+
+    let mut edits = Vec::new();
+    let mut skipped = Vec::new();
+    let mut conflicts = Vec::new();
+    for (file_path, lines, mut sites) in sites_by_file {
+        for (span, definite) in std::mem::take(&mut sites.method_calls) {
+            if definite || unique_method {
+                sites.edits.push((span, RenameEditKind::MethodCall));
+            } else {
+                sites.skipped.push((span, "method call may resolve to another method with the same name"));
+            }
+        }
+
+        let context = |line: usize| lines.get(line.wrapping_sub(1)).map(|l| l.trim().to_string()).unwrap_or_default();
+        let mut seen = HashSet::new();
+        for (span, kind) in &sites.edits {
+            let start = span.start();
+            if !seen.insert((start.line, start.column)) {
+                continue;
+            }
+            edits.push(RenameEdit {
+                file_path: file_path.clone(),
+                line: start.line,
+                column: start.column,
+                end_column: start.column + symbol.name.chars().count(),
+                old_text: symbol.name.clone(),
+                new_text: new_name.to_string(),
+                kind: *kind,
+                context: context(start.line),
+            });
+        }
+        for (span, reason) in &sites.skipped {
+            let start = span.start();
+            if seen.contains(&(start.line, start.column)) {
+                continue;
+            }
+            skipped.push(SkippedReference {
+                file_path: file_path.clone(),
+                line: start.line,
+                column: start.column,
+                reason: reason.to_string(),
+                context: context(start.line),
+            });
+        }
+
+        let touches_file = !sites.edits.is_empty();
+        if touches_file && sites.imports_new_name && matches!(target, Target::Type | Target::Function) {
+            conflicts.push(format!("'{}' is already imported in {}", new_name, file_path));
+        }
+    }
+    edits.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)).then(a.column.cmp(&b.column)));
+
+    conflicts.extend(find_conflicts(&project_symbols, symbol, &target, &edits, new_name));
+    if !conflicts.is_empty() {
+        bail!("Renaming '{}' to '{}' would cause a name collision:\n  {}", symbol.name, new_name, conflicts.join("\n  "));
+    }
+
+    Ok(RenameSymbolResponse {
+        symbol_name: symbol.name.clone(),
+        new_name: new_name.to_string(),
+        symbol_type: symbol.symbol_type.clone(),
+        file_path: symbol.file_path.clone(),
+        line: symbol.line,
+        edits,
+        skipped,
+        applied: false,
+    })
+}
+
+fn is_same_symbol(a: &SymbolInfo, b: &SymbolInfo) -> bool {
+    a.file_path == b.file_path && a.line == b.line && a.column == b.column
+}
+
+/// 値の名前空間（関数）か、型の名前空間（構造体・列挙型・トレイト）か
+fn is_value(symbol_type: &SymbolType) -> bool {
+    *symbol_type == SymbolType::Function
+}
+
+/// 書き換え後に同じスコープ・同じ名前空間に同名の定義ができないか
+fn find_conflicts(project_symbols: &[&SymbolInfo], symbol: &SymbolInfo, target: &Target, edits: &[RenameEdit], new_name: &str) -> Vec<String> {
+    let existing: Vec<&&SymbolInfo> = project_symbols.iter()
+        .filter(|s| s.name == new_name && is_value(&s.symbol_type) == is_value(&symbol.symbol_type))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    match target {
+        Target::Type | Target::Function => {
+            // 定義と同じスコープ、または参照しているファイルのトップレベルにある同名の定義
+            let edited_files: HashSet<&str> = edits.iter().map(|e| e.file_path.as_str()).collect();
+            for other in existing {
+                let same_scope = other.file_path == symbol.file_path && other.scope == symbol.scope;
+                let top_level_in_edited = other.scope.is_none() && edited_files.contains(other.file_path.as_str());
+                if same_scope || top_level_in_edited {
+                    conflicts.push(format!("'{}' is already defined at {}:{}", new_name, other.file_path, other.line));
+                }
+            }
+        }
+        Target::Method { .. } | Target::TraitMethod { .. } => {
+            // リネームする各定義（トレイトの宣言と impl）と同じ型・トレイトに同名のメソッドがある
+            let scopes: HashSet<Option<&str>> = edits.iter()
+                .filter(|e| matches!(e.kind, RenameEditKind::Definition | RenameEditKind::TraitMethod))
+                .filter_map(|e| project_symbols.iter().find(|s| s.file_path == e.file_path && s.line == e.line && s.name == symbol.name))
+                .map(|s| s.scope.as_deref())
+                .collect();
+            for other in existing.into_iter().filter(|s| scopes.contains(&s.scope.as_deref())) {
+                conflicts.push(format!(
+                    "'{}' already has a method '{}' at {}:{}",
+                    other.scope.as_deref().unwrap_or_default(), new_name, other.file_path, other.line,
+                ));
+            }
+        }
+    }
+    conflicts
+}
+
+/// 定義の位置から、自由関数・固有メソッド・トレイトメソッド・型のどれかを決める
+fn classify(syntax_tree: &syn::File, symbol: &SymbolInfo) -> Option<Target> {
+    let mut classifier = Classifier {
+        position: LineColumn { line: symbol.line, column: symbol.column },
+        current_impl: None,
+        current_trait: None,
+        found: None,
+    };
+    classifier.visit_file(syntax_tree);
+    classifier.found
+}
+
+/// 構造体の定義に名前付きフィールド field があるか（フィールドはシンボルとしてインデックスしないので定義を読み直す）
+pub fn has_named_field(structure: &SymbolInfo, field: &str) -> bool {
+    let Some(syntax_tree) = std::fs::read_to_string(&structure.file_path).ok()
+        .and_then(|content| syn::parse_file(&content).ok()) else {
+        return false;
+    };
+    let mut finder = FieldFinder { structure, field, found: false };
+    finder.visit_file(&syntax_tree);
+    finder.found
+}
+
+struct FieldFinder<'a> {
+    structure: &'a SymbolInfo,
+    field: &'a str,
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for FieldFinder<'_> {
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        let line = item.ident.span().start().line;
+        if item.ident == self.structure.name && (self.structure.line..=self.structure.end_line).contains(&line) {
+            self.found |= item.fields.iter().any(|f| f.ident.as_ref().is_some_and(|ident| ident == self.field));
+        }
+        visit::visit_item_struct(self, item);
+    }
+}
+
+/// impl ブロックの型とトレイト（トレイトはパスの最後のセグメント）
+#[derive(Clone)]
+struct ImplContext {
+    self_type: String,
+    trait_name: Option<String>,
+}
+
+fn impl_context(item: &syn::ItemImpl) -> ImplContext {
+    ImplContext {
+        self_type: crate::parser::type_name(&item.self_ty),
+        trait_name: item.trait_.as_ref()
+            .and_then(|(_, path, _)| path.segments.last())
+            .map(|segment| segment.ident.to_string()),
+    }
+}
+
+struct Classifier {
+    position: LineColumn,
+    current_impl: Option<ImplContext>,
+    current_trait: Option<String>,
+    found: Option<Target>,
+}
+
+impl Classifier {
+    fn check(&mut self, ident: &syn::Ident, target: impl FnOnce(&Self) -> Target) {
+        if self.found.is_none() && ident.span().start() == self.position {
+            self.found = Some(target(self));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Classifier {
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let outer = self.current_impl.replace(impl_context(item));
+        visit::visit_item_impl(self, item);
+        self.current_impl = outer;
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.check(&item.ident, |_| Target::Type);
+        let outer = self.current_trait.replace(item.ident.to_string());
+        visit::visit_item_trait(self, item);
+        self.current_trait = outer;
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.check(&item.sig.ident, |_| Target::Function);
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.check(&item.sig.ident, |this| match &this.current_impl {
+            Some(ImplContext { trait_name: Some(trait_name), .. }) => Target::TraitMethod { trait_name: trait_name.clone() },
+            Some(ImplContext { self_type, .. }) => Target::Method { self_type: self_type.clone() },
+            None => Target::Function,
+        });
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        self.check(&item.sig.ident, |this| Target::TraitMethod { trait_name: this.current_trait.clone().unwrap_or_default() });
+        visit::visit_trait_item_fn(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.check(&item.ident, |_| Target::Type);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.check(&item.ident, |_| Target::Type);
+    }
+}
+
+/// 1ファイルからリネーム対象の識別子を集める
+struct Collector<'a> {
+    name: &'a str,
+    new_name: &'a str,
+    target: &'a Target,
+    definition: Option<LineColumn>,  // このファイルにある定義の位置
+    type_names: &'a HashSet<&'a str>,
+    implementors: &'a HashSet<&'a str>,
+    shadowed: bool,                  // 同名の別の定義があるファイル
+    current_impl: Option<ImplContext>,
+    current_trait: Option<String>,
+    in_pub_use: bool,
+    sites: FileSites,
+}
+
+impl Collector<'_> {
+    fn is_definition(&self, ident: &syn::Ident) -> bool {
+        self.definition == Some(ident.span().start())
+    }
+
+    /// 関数・メソッドの定義名
+    fn check_fn_definition(&mut self, ident: &syn::Ident) {
+        if ident != self.name {
+            return;
+        }
+        if self.is_definition(ident) {
+            self.sites.edits.push((ident.span(), RenameEditKind::Definition));
+            return;
+        }
+        // 同じトレイトの宣言と impl のメソッドはまとめてリネームする
+        if let Target::TraitMethod { trait_name } = self.target {
+            let in_trait = self.current_trait.as_ref() == Some(trait_name);
+            let in_impl = self.current_impl.as_ref().is_some_and(|c| c.trait_name.as_ref() == Some(trait_name));
+            if in_trait || in_impl {
+                self.sites.edits.push((ident.span(), RenameEditKind::TraitMethod));
+            }
+        }
+    }
+
+    /// self の型（impl 内）またはトレイト（トレイト定義内）が対象のメソッドの持ち主か
+    fn self_is_owner(&self) -> bool {
+        match self.target {
+            Target::Method { self_type } => self.current_impl.as_ref().is_some_and(|c| c.self_type == *self_type),
+            Target::TraitMethod { trait_name } => {
+                self.current_trait.as_ref() == Some(trait_name)
+                    || self.current_impl.as_ref().is_some_and(|c| c.trait_name.as_ref() == Some(trait_name))
+            }
+            _ => false,
+        }
+    }
+
+    fn check_path(&mut self, path: &syn::Path, kind: RenameEditKind) {
+        let segments: Vec<&syn::PathSegment> = path.segments.iter().collect();
+        match self.target {
+            Target::Type => {
+                for (index, segment) in segments.iter().enumerate() {
+                    if segment.ident != self.name {
+                        continue;
+                    }
+                    // Foo { .. } の Foo 以外（Foo::new の Foo など）は参照として扱う
+                    let kind = if index + 1 == segments.len() { kind } else { RenameEditKind::Reference };
+                    if self.shadowed && segments.len() == 1 {
+                        self.sites.skipped.push((segment.ident.span(), "another item with the same name is defined in this file"));
+                    } else {
+                        self.sites.edits.push((segment.ident.span(), kind));
+                    }
+                }
+            }
+            _ => {
+                let Some(last) = segments.last() else {
+                    return;
+                };
+                if last.ident != self.name {
+                    return;
+                }
+                let owner = segments.len().checked_sub(2).map(|i| segments[i].ident.to_string());
+                let matches = match (self.target, owner.as_deref()) {
+                    (Target::Function, None) => {
+                        if self.shadowed {
+                            self.sites.skipped.push((last.ident.span(), "another function with the same name is defined in this file"));
+                            return;
+                        }
+                        true
+                    }
+                    // Type::foo は関連関数なので自由関数ではない
+                    (Target::Function, Some(owner)) => owner != "Self" && !self.type_names.contains(owner),
+                    (Target::Method { self_type }, Some(owner)) => owner == self_type || (owner == "Self" && self.self_is_owner()),
+                    (Target::TraitMethod { trait_name }, Some(owner)) => {
+                        owner == trait_name || self.implementors.contains(owner) || (owner == "Self" && self.self_is_owner())
+                    }
+                    _ => false,
+                };
+                if matches {
+                    self.sites.edits.push((last.ident.span(), kind));
+                }
+            }
+        }
+    }
+
+    fn check_use_ident(&mut self, ident: &syn::Ident) {
+        if ident == self.new_name {
+            self.sites.imports_new_name = true;
+        }
+        if ident == self.name && matches!(self.target, Target::Type | Target::Function) {
+            let kind = if self.in_pub_use { RenameEditKind::Reexport } else { RenameEditKind::Import };
+            self.sites.edits.push((ident.span(), kind));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Collector<'_> {
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let outer = self.current_impl.replace(impl_context(item));
+        visit::visit_item_impl(self, item);
+        self.current_impl = outer;
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        if *self.target == Target::Type && item.ident == self.name && self.is_definition(&item.ident) {
+            self.sites.edits.push((item.ident.span(), RenameEditKind::Definition));
+        }
+        let outer = self.current_trait.replace(item.ident.to_string());
+        visit::visit_item_trait(self, item);
+        self.current_trait = outer;
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        if *self.target == Target::Type && item.ident == self.name && self.is_definition(&item.ident) {
+            self.sites.edits.push((item.ident.span(), RenameEditKind::Definition));
+        }
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        if *self.target == Target::Type && item.ident == self.name && self.is_definition(&item.ident) {
+            self.sites.edits.push((item.ident.span(), RenameEditKind::Definition));
+        }
+        visit::visit_item_enum(self, item);
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if *self.target == Target::Function {
+            self.check_fn_definition(&item.sig.ident);
+        }
+        // 関数本体の中の impl・トレイトは外側と無関係
+        let outer_impl = self.current_impl.take();
+        let outer_trait = self.current_trait.take();
+        visit::visit_item_fn(self, item);
+        self.current_impl = outer_impl;
+        self.current_trait = outer_trait;
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        if matches!(self.target, Target::Method { .. } | Target::TraitMethod { .. }) {
+            self.check_fn_definition(&item.sig.ident);
+        }
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        if matches!(self.target, Target::TraitMethod { .. }) {
+            self.check_fn_definition(&item.sig.ident);
+        }
+        visit::visit_trait_item_fn(self, item);
+    }
+
+    fn visit_item_use(&mut self, item: &'ast syn::ItemUse) {
+        self.in_pub_use = !matches!(item.vis, syn::Visibility::Inherited);
+        visit::visit_item_use(self, item);
+        self.in_pub_use = false;
+    }
+
+    fn visit_use_path(&mut self, use_path: &'ast syn::UsePath) {
+        // use Enum::Variant の Enum
+        if *self.target == Target::Type && use_path.ident == self.name {
+            self.check_use_ident(&use_path.ident);
+        }
+        visit::visit_use_path(self, use_path);
+    }
+
+    fn visit_use_name(&mut self, use_name: &'ast syn::UseName) {
+        self.check_use_ident(&use_name.ident);
+    }
+
+    fn visit_use_rename(&mut self, use_rename: &'ast syn::UseRename) {
+        // use a::foo as bar は元の名前だけ書き換える
+        if use_rename.rename == self.new_name {
+            self.sites.imports_new_name = true;
+        }
+        if use_rename.ident == self.name && matches!(self.target, Target::Type | Target::Function) {
+            let kind = if self.in_pub_use { RenameEditKind::Reexport } else { RenameEditKind::Import };
+            self.sites.edits.push((use_rename.ident.span(), kind));
+        }
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path) = &*call.func {
+            let kind = if *self.target == Target::Type { RenameEditKind::StructLiteral } else { RenameEditKind::Call };
+            self.check_path(&path.path, kind);
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if call.method == self.name && matches!(self.target, Target::Method { .. } | Target::TraitMethod { .. }) {
+            let on_self = matches!(&*call.receiver, syn::Expr::Path(path) if path.path.is_ident("self"));
+            self.sites.method_calls.push((call.method.span(), on_self && self.self_is_owner()));
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_struct(&mut self, expr: &'ast syn::ExprStruct) {
+        self.check_path(&expr.path, RenameEditKind::StructLiteral);
+        visit::visit_expr_struct(self, expr);
+    }
+
+    fn visit_pat_struct(&mut self, pat: &'ast syn::PatStruct) {
+        self.check_path(&pat.path, RenameEditKind::StructLiteral);
+        visit::visit_pat_struct(self, pat);
+    }
+
+    fn visit_pat_tuple_struct(&mut self, pat: &'ast syn::PatTupleStruct) {
+        self.check_path(&pat.path, RenameEditKind::StructLiteral);
+        visit::visit_pat_tuple_struct(self, pat);
+    }
+
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        self.check_path(path, RenameEditKind::Reference);
+        visit::visit_path(self, path);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        self.visit_path(&mac.path);
+        if let Ok(args) = mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// 書き換えをファイルに反映し、書き換えたファイルを返す
+///
+/// すべてのファイルで元のテキストを確かめてから write_files でまとめて置き換える。
+pub fn apply_edits(edits: &[RenameEdit]) -> Result<Vec<PathBuf>> {
+    let mut by_file: BTreeMap<&str, Vec<&RenameEdit>> = BTreeMap::new();
+    for edit in edits {
+        by_file.entry(edit.file_path.as_str()).or_default().push(edit);
+    }
+
+    let mut updated = Vec::new();
+    for (file_path, mut file_edits) in by_file {
+        let original = std::fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read {}", file_path))?;
+        let mut content = original.clone();
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        // 後ろから置き換えて前の位置をずらさない
+        file_edits.sort_by(|a, b| b.line.cmp(&a.line).then(b.column.cmp(&a.column)));
+        for edit in file_edits {
+            let range = line_starts.get(edit.line.wrapping_sub(1))
+                .and_then(|&start| {
+                    let line = &content[start..];
+                    let from = start + line.char_indices().nth(edit.column)?.0;
+                    Some(from..from + edit.old_text.len())
+                })
+                .filter(|range| {
+                    // 識別子の途中（load_config の load など）は一致とみなさない
+                    let next = content[range.end.min(content.len())..].chars().next();
+                    content.get(range.clone()) == Some(edit.old_text.as_str())
+                        && !next.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                });
+            let Some(range) = range else {
+                bail!("{}:{}:{} no longer contains '{}'; re-run the rename", file_path, edit.line, edit.column + 1, edit.old_text);
+            };
+            content.replace_range(range, &edit.new_text);
+        }
+        updated.push(FileUpdate { path: PathBuf::from(file_path), original, content });
+    }
+
+    write_files(&updated)?;
+    Ok(updated.into_iter().map(|update| update.path).collect())
+}
+
+/// write_files で書き換える1ファイル分の内容
+pub struct FileUpdate {
+    pub path: PathBuf,
+    pub original: String,  // 失敗したときに戻す元の内容
+    pub content: String,
+}
+
+/// 複数のファイルをまとめて書き換える（一部だけ書き換わった状態を残さない）
+///
+/// すべての一時ファイルを書いてから rename で置き換え、途中で失敗したら置き換え済みのファイルを
+/// 元の内容に戻す。戻せなかったファイルがあればエラーメッセージに挙げる。
+pub fn write_files(updates: &[FileUpdate]) -> Result<()> {
+    let temp_paths: Vec<PathBuf> = updates.iter().map(|update| temp_path(&update.path)).collect();
+    let remove_temps = |temps: &[PathBuf]| {
+        for temp in temps {
+            let _ = std::fs::remove_file(temp);
+        }
+    };
+
+    for (i, (update, temp)) in updates.iter().zip(&temp_paths).enumerate() {
+        if let Err(e) = std::fs::write(temp, &update.content) {
+            remove_temps(&temp_paths[..=i]);
+            return Err(e).with_context(|| format!("Failed to write {}; no files were changed", temp.display()));
+        }
+    }
+
+    for (i, (update, temp)) in updates.iter().zip(&temp_paths).enumerate() {
+        if let Err(e) = std::fs::rename(temp, &update.path) {
+            remove_temps(&temp_paths[i..]);
+            let not_restored: Vec<String> = updates[..i].iter()
+                .filter(|done| std::fs::write(&done.path, &done.original).is_err())
+                .map(|done| done.path.display().to_string())
+                .collect();
+            if not_restored.is_empty() {
+                return Err(e).with_context(|| format!("Failed to replace {}; no files were changed", update.path.display()));
+            }
+            return Err(e).with_context(|| format!(
+                "Failed to replace {}; these files were changed and could not be restored: {}",
+                update.path.display(),
+                not_restored.join(", "),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.rename.tmp"))
+}
+
+fn edit_kind_label(kind: RenameEditKind) -> &'static str {
+    match kind {
+        RenameEditKind::Definition => "definition",
+        RenameEditKind::TraitMethod => "trait method",
+        RenameEditKind::Call => "call",
+        RenameEditKind::MethodCall => "method call",
+        RenameEditKind::Reference => "reference",
+        RenameEditKind::StructLiteral => "struct literal",
+        RenameEditKind::Import => "use",
+        RenameEditKind::Reexport => "pub use",
+    }
+}
+
+/// rename_symbol の結果をテキストに整形（CLI・MCP 用）
+pub fn format_rename(response: &RenameSymbolResponse) -> String {
+    let files: HashSet<&str> = response.edits.iter().map(|e| e.file_path.as_str()).collect();
+    let mut result = format!(
+        "Rename {} -> {}: {} edit(s) in {} file(s){}\n",
+        response.symbol_name, response.new_name, response.edits.len(), files.len(),
+        if response.applied { " (applied)" } else { " (dry run)" },
+    );
+
+    let mut current_file = "";
+    for edit in &response.edits {
+        if edit.file_path != current_file {
+            current_file = &edit.file_path;
+            result.push_str(&format!("\n{}\n", current_file));
+        }
+        result.push_str(&format!("  {:>5}:{:<3} {:<14} {}\n", edit.line, edit.column + 1, edit_kind_label(edit.kind), edit.context));
+    }
+
+    if !response.skipped.is_empty() {
+        result.push_str(&format!("\nNot renamed ({} possible reference(s), check manually):\n", response.skipped.len()));
+        for skipped in &response.skipped {
+            result.push_str(&format!("  {}:{}:{} {} ({})\n", skipped.file_path, skipped.line, skipped.column + 1, skipped.context, skipped.reason));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rename_symbol() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("lib.rs"), r#"mod config;
+pub use config::{Config, load};
+
+pub trait Source {
+    fn fetch(&self) -> String;
+}
+
+pub fn run() -> String {
+    let config = config::load();
+    let Config { name } = Config::new(&config.name);
+    let _loader: fn() -> Config = load;
+    format!("{}", config.fetch())
+}
+"#).unwrap();
+        fs::write(src.join("config.rs"), r#"use crate::Source;
+
+pub struct Config {
+    pub name: String,
+}
+
+impl Config {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string() }
+    }
+
+    pub fn label(&self) -> String {
+        self.fetch()
+    }
+}
+
+impl Source for Config {
+    fn fetch(&self) -> String {
+        self.name.clone()
+    }
+}
+
+pub fn load() -> Config {
+    Config::new("default")
+}
+
+pub fn reload() -> Config {
+    load()
+}
+"#).unwrap();
+
+        let mut parser = RustParser::new();
+        let files = vec![src.join("lib.rs"), src.join("config.rs")];
+        for file in &files {
+            parser.parse_file(file).unwrap();
+        }
+        let symbol = |name: &str| parser.find_symbol(name, None).unwrap()[0].clone();
+        let summary = |response: &RenameSymbolResponse| -> Vec<(String, usize, RenameEditKind)> {
+            response.edits.iter()
+                .map(|e| (Path::new(&e.file_path).file_name().unwrap().to_string_lossy().to_string(), e.line, e.kind))
+                .collect()
+        };
+
+        let load = plan_rename(&parser, &symbol("load"), &files, "load_config").unwrap();
+        assert_eq!(summary(&load), vec![
+            ("config.rs".to_string(), 23, RenameEditKind::Definition),
+            ("config.rs".to_string(), 28, RenameEditKind::Call),
+            ("lib.rs".to_string(), 2, RenameEditKind::Reexport),
+            ("lib.rs".to_string(), 9, RenameEditKind::Call),
+            ("lib.rs".to_string(), 11, RenameEditKind::Reference),
+        ]);
+
+        let config = plan_rename(&parser, &symbol("Config"), &files, "Settings").unwrap();
+        let kinds: Vec<RenameEditKind> = config.edits.iter().filter(|e| e.file_path.ends_with("lib.rs")).map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![RenameEditKind::Reexport, RenameEditKind::StructLiteral, RenameEditKind::Reference, RenameEditKind::Reference]);
+        assert_eq!(config.edits.len(), 10);
+
+        // トレイトのメソッドは宣言・impl・呼び出しをまとめて書き換える
+        let fetch = plan_rename(&parser, &symbol("fetch"), &files, "retrieve").unwrap();
+        assert_eq!(summary(&fetch), vec![
+            ("config.rs".to_string(), 13, RenameEditKind::MethodCall),
+            ("config.rs".to_string(), 18, RenameEditKind::TraitMethod),
+            ("lib.rs".to_string(), 5, RenameEditKind::Definition),
+            ("lib.rs".to_string(), 12, RenameEditKind::MethodCall),
+        ]);
+
+        let collision = plan_rename(&parser, &symbol("load"), &files, "reload").unwrap_err();
+        assert!(collision.to_string().contains("name collision"), "{collision}");
+        let method_collision = plan_rename(&parser, &symbol("label"), &files, "new").unwrap_err();
+        assert!(method_collision.to_string().contains("already has a method 'new'"), "{method_collision}");
+        assert!(plan_rename(&parser, &symbol("load"), &files, "fn").is_err());
+
+        apply_edits(&load.edits).unwrap();
+        let lib = fs::read_to_string(src.join("lib.rs")).unwrap();
+        assert!(lib.contains("pub use config::{Config, load_config};"));
+        assert!(lib.contains("let _loader: fn() -> Config = load_config;"));
+        assert!(fs::read_to_string(src.join("config.rs")).unwrap().contains("pub fn load_config() -> Config {"));
+        assert!(apply_edits(&load.edits).is_err());
+
+        // フィールドはリネームの対象外なので、呼び出し側で断れるよう定義から判定する
+        assert!(has_named_field(&symbol("Config"), "name"));
+        assert!(!has_named_field(&symbol("Config"), "label"));
+    }
+
+    #[test]
+    fn test_write_files_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.rs");
+        let second = temp_dir.path().join("b.rs");
+        fs::write(&first, "fn a() {}\n").unwrap();
+        // ディレクトリは rename で置き換えられないので、2つ目の置き換えで失敗する
+        fs::create_dir(&second).unwrap();
+
+        let update = |path: &Path, original: &str| FileUpdate {
+            path: path.to_path_buf(),
+            original: original.to_string(),
+            content: "fn renamed() {}\n".to_string(),
+        };
+        let err = write_files(&[update(&first, "fn a() {}\n"), update(&second, "")]).unwrap_err();
+        assert!(err.to_string().contains("no files were changed"), "{err}");
+        assert_eq!(fs::read_to_string(&first).unwrap(), "fn a() {}\n");
+        let leftovers: Vec<_> = fs::read_dir(temp_dir.path()).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".rename.tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+    }
+}
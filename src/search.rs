@@ -0,0 +1,280 @@
+use crate::parser::SymbolInfo;
+use crate::protocol::{MatchKind, SymbolType};
+use std::collections::HashMap;
+
+/// limit 未指定時の最大件数
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// 検索結果（スコアの高い順に並べて返す）
+#[derive(Debug, Clone)]
+pub struct SearchMatch<'a> {
+    pub symbol: &'a SymbolInfo,
+    pub match_kind: MatchKind,
+    pub score: i64,
+}
+
+/// シンボル名をあいまい検索してランキング順に返す
+///
+/// マッチ品質（完全一致 > 前方一致 > 単語単位 > 部分一致 > タイポ許容）を基本スコアとし、
+/// 可視性と本番コードかどうかで補正する（依存クレートのシンボルはプロジェクト内より下げる）。
+pub fn search_symbols<'a>(
+    symbols: &'a HashMap<String, Vec<SymbolInfo>>,
+    query: &str,
+    symbol_type: Option<&SymbolType>,
+    limit: usize,
+) -> Vec<SearchMatch<'a>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+
+    for (name, infos) in symbols {
+        // マッチ判定は名前単位で一度だけ行う
+        let Some((match_kind, base_score)) = match_name(query, name) else {
+            continue;
+        };
+
+        for symbol in infos {
+            if symbol_type.is_some_and(|t| symbol.symbol_type != *t) {
+                continue;
+            }
+
+            matches.push(SearchMatch {
+                symbol,
+                match_kind,
+                score: base_score + visibility_bonus(&symbol.visibility) + origin_bonus(symbol),
+            });
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        b.score.cmp(&a.score)
+            .then(a.match_kind.cmp(&b.match_kind))
+            .then(a.symbol.name.cmp(&b.symbol.name))
+            .then(a.symbol.file_path.cmp(&b.symbol.file_path))
+            .then(a.symbol.line.cmp(&b.symbol.line))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// クエリと名前を照合し、マッチ種別と基本スコアを返す
+fn match_name(query: &str, name: &str) -> Option<(MatchKind, i64)> {
+    if query == name {
+        return Some((MatchKind::Exact, 1000));
+    }
+
+    let query_lower = query.to_lowercase();
+    let name_lower = name.to_lowercase();
+    let length_penalty = (name_lower.chars().count() as i64 - query_lower.chars().count() as i64).abs();
+
+    if query_lower == name_lower {
+        return Some((MatchKind::Exact, 950));
+    }
+
+    if name_lower.starts_with(&query_lower) {
+        return Some((MatchKind::Prefix, 800 - length_penalty.min(100)));
+    }
+
+    let words = split_words(name);
+    if words.len() > 1 {
+        let query_words = split_words(query);
+        let matched = if query_words.len() > 1 {
+            match_query_words(&query_words, &words)
+        } else {
+            match_word_prefixes(&query_lower, &words)
+        };
+        if matched {
+            return Some((MatchKind::WordPart, 600 - words.len() as i64 * 5));
+        }
+    }
+
+    if let Some(pos) = name_lower.find(&query_lower) {
+        return Some((MatchKind::Substring, 400 - (pos as i64).min(100)));
+    }
+
+    // タイポ許容：名前全体、または同じ長さの先頭部分との編集距離で判定
+    let max_distance = match query_lower.chars().count() {
+        0..=3 => return None,
+        4..=6 => 1,
+        7..=10 => 2,
+        _ => 3,
+    };
+    let whole = damerau_levenshtein(&query_lower, &name_lower);
+    let prefix: String = name_lower.chars().take(query_lower.chars().count()).collect();
+    let head = damerau_levenshtein(&query_lower, &prefix);
+
+    if whole <= max_distance {
+        Some((MatchKind::Fuzzy, 200 - whole as i64 * 40))
+    } else if head <= max_distance {
+        Some((MatchKind::Fuzzy, 190 - head as i64 * 40 - length_penalty.min(50)))
+    } else {
+        None
+    }
+}
+
+/// 可視性によるスコア補正
+fn visibility_bonus(visibility: &str) -> i64 {
+    match visibility {
+        "pub" => 30,
+        "private" => 0,
+        _ => 15, // pub(crate), pub(super) など
+    }
+}
+
+/// 定義場所によるスコア補正（本番コード > テストコード > 依存クレート）
+fn origin_bonus(symbol: &SymbolInfo) -> i64 {
+    if symbol.crate_name.is_some() {
+        -30
+    } else if symbol.is_test {
+        0
+    } else {
+        20
+    }
+}
+
+/// camelCase / snake_case の名前を小文字の単語列に分割
+pub fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' || c == ':' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // "fooBar" と "HTTPServer" の "S" の位置で区切る
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+
+        current.extend(c.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// 区切りのないクエリを、単語の先頭部分の連結として解釈できるか判定
+/// 例: "fd" / "finddef" → find_definition
+fn match_word_prefixes(query: &str, words: &[String]) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+
+    for (i, word) in words.iter().enumerate() {
+        let common = word.chars()
+            .zip(query.chars())
+            .take_while(|(w, q)| w == q)
+            .count();
+        // 長い先頭部分から試す（単語をまたいで消費）
+        for len in (1..=common).rev() {
+            let rest: String = query.chars().skip(len).collect();
+            if match_word_prefixes(&rest, &words[i + 1..]) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// 区切りのあるクエリの各単語が、名前の単語の先頭部分に順番通り一致するか判定
+/// 例: "find_def" / "findDef" → find_definition
+fn match_query_words(query_words: &[String], words: &[String]) -> bool {
+    let mut remaining = words.iter();
+    query_words.iter().all(|q| remaining.any(|w| w.starts_with(q.as_str())))
+}
+
+/// 隣接文字の入れ替えを1操作とみなす編集距離
+fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut dist = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in dist[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            dist[i][j] = (dist[i - 1][j] + 1)
+                .min(dist[i][j - 1] + 1)
+                .min(dist[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                dist[i][j] = dist[i][j].min(dist[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    dist[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_match_kinds() {
+        assert_eq!(match_name("find_definition", "find_definition").unwrap().0, MatchKind::Exact);
+        assert_eq!(match_name("find_def", "find_definition").unwrap().0, MatchKind::Prefix);
+        assert_eq!(match_name("fd", "find_definition").unwrap().0, MatchKind::WordPart);
+        assert_eq!(match_name("CoIn", "CodeIndexer").unwrap().0, MatchKind::WordPart);
+        assert_eq!(match_name("dexer", "CodeIndexer").unwrap().0, MatchKind::Substring);
+        assert_eq!(match_name("fnid_definition", "find_definition").unwrap().0, MatchKind::Fuzzy);
+        assert!(match_name("xyz", "find_definition").is_none());
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("find_definition"), vec!["find", "definition"]);
+        assert_eq!(split_words("CodeIndexer"), vec!["code", "indexer"]);
+        assert_eq!(split_words("HTTPServer"), vec!["http", "server"]);
+    }
+
+    #[test]
+    fn test_search_ranking() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("lib.rs");
+
+        fs::write(&file_path, r#"
+pub fn parse_file() {}
+
+fn parse_files_internal() {}
+
+pub fn reparse_file() {}
+
+#[test]
+fn parse_file_works() {}
+"#).unwrap();
+
+        let mut parser = RustParser::new();
+        parser.parse_file(&file_path).unwrap();
+
+        let results = search_symbols(parser.get_all_symbols(), "parse_file", None, DEFAULT_SEARCH_LIMIT);
+        let names: Vec<&str> = results.iter().map(|m| m.symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["parse_file", "parse_files_internal", "parse_file_works", "reparse_file"]);
+        assert_eq!(results[0].match_kind, MatchKind::Exact);
+        assert_eq!(results[3].match_kind, MatchKind::Substring);
+    }
+}
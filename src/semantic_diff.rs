@@ -0,0 +1,479 @@
+use crate::api_surface::SourceTree;
+use crate::parser::{RustParser, qualified_name};
+use crate::protocol::{
+    CallEdge, SemanticDiffResponse, SemanticDiffSummary, SymbolChange, SymbolChangeKind, SymbolType, SymbolVersion,
+};
+use anyhow::Result;
+use proc_macro2::{Delimiter, LineColumn, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use syn::visit::{self, Visit};
+
+/// 片方のリビジョンのシンボル
+struct Entry {
+    name: String,
+    symbol_type: SymbolType,
+    version: SymbolVersion,
+    signature_key: String,  // ドキュメントコメントを除いたシグネチャのトークン列
+    body_key: String,       // ドキュメントコメントを除いた本体のトークン列
+}
+
+/// 片方のリビジョンのシンボルと呼び出し関係
+struct Snapshot {
+    entries: Vec<Entry>,
+    edges: BTreeMap<(String, String), (String, usize)>,  // (caller, callee) -> 最初の呼び出し位置
+    function_names: HashSet<String>,
+}
+
+/// 2つのリビジョン（to が None なら作業ツリー）のシンボル・呼び出し関係を比較する
+pub fn semantic_diff(dir: &Path, from: &str, to: Option<&str>) -> Result<SemanticDiffResponse> {
+    let old = snapshot(&SourceTree::new(dir, Some(from))?)?;
+    let new = snapshot(&SourceTree::new(dir, to)?)?;
+
+    let symbols = diff_symbols(&old.entries, &new.entries);
+
+    // 呼び出し先はどちらかのリビジョンで定義されている関数だけ
+    let known = |callee: &str| old.function_names.contains(callee) || new.function_names.contains(callee);
+    let edge_changes = |from: &Snapshot, to: &Snapshot| -> Vec<CallEdge> {
+        from.edges.iter()
+            .filter(|((_, callee), _)| known(callee))
+            .filter(|(key, _)| !to.edges.contains_key(*key))
+            .map(|((caller, callee), (file_path, line))| CallEdge {
+                caller: caller.clone(),
+                callee: callee.clone(),
+                file_path: file_path.clone(),
+                line: *line,
+            })
+            .collect()
+    };
+    let calls_added = edge_changes(&new, &old);
+    let calls_removed = edge_changes(&old, &new);
+
+    let mut summary = SemanticDiffSummary {
+        calls_added: calls_added.len(),
+        calls_removed: calls_removed.len(),
+        ..Default::default()
+    };
+    for change in &symbols {
+        match change.kind {
+            SymbolChangeKind::Added => summary.added += 1,
+            SymbolChangeKind::Removed => summary.removed += 1,
+            SymbolChangeKind::SignatureChanged => summary.signature_changed += 1,
+            SymbolChangeKind::BodyChanged => summary.body_changed += 1,
+            SymbolChangeKind::Moved => {}
+        }
+        if change.moved {
+            summary.moved += 1;
+        }
+    }
+
+    Ok(SemanticDiffResponse {
+        from: from.to_string(),
+        to: to.map(|t| t.to_string()),
+        summary,
+        symbols,
+        calls_added,
+        calls_removed,
+    })
+}
+
+/// ツリーの .rs ファイルをすべてパースする
+fn snapshot(source: &SourceTree) -> Result<Snapshot> {
+    let mut parser = RustParser::new();
+    let mut fingerprints: HashMap<(String, usize, usize), (String, String)> = HashMap::new();
+
+    for file in source.rust_files()? {
+        let Some(content) = source.read(&file) else {
+            continue;
+        };
+        let file_path = file.to_string_lossy().to_string();
+        parser.parse_source(&file_path, &content)?;
+        if let Ok(syntax_tree) = syn::parse_file(&content) {
+            let mut collector = FingerprintCollector { found: Vec::new() };
+            collector.visit_file(&syntax_tree);
+            for (position, signature, body) in collector.found {
+                fingerprints.insert((file_path.clone(), position.line, position.column), (signature, body));
+            }
+        }
+    }
+
+    let mut symbols: Vec<_> = parser.get_all_symbols().values().flatten().collect();
+    symbols.sort_by(|a, b| (&a.file_path, a.line, a.column).cmp(&(&b.file_path, b.line, b.column)));
+    let entries = symbols.iter()
+        .map(|s| {
+            let (signature_key, body_key) = fingerprints.remove(&(s.file_path.clone(), s.line, s.column))
+                .unwrap_or_else(|| (s.signature.clone(), String::new()));
+            Entry {
+                name: s.name.clone(),
+                symbol_type: s.symbol_type.clone(),
+                version: SymbolVersion {
+                    file_path: s.file_path.clone(),
+                    line: s.line,
+                    scope: s.scope.clone(),
+                    signature: s.signature.clone(),
+                },
+                signature_key,
+                body_key,
+            }
+        })
+        .collect();
+
+    // 呼び出し元は定義位置からスコープ付きの名前にする（ファイルを移動しても同じ辺になる）
+    let callers: HashMap<(&str, usize), String> = symbols.iter()
+        .filter(|s| s.symbol_type == SymbolType::Function)
+        .map(|s| ((s.file_path.as_str(), s.line), qualified_name(s.scope.as_deref(), &s.name)))
+        .collect();
+    let mut edges = BTreeMap::new();
+    for call in parser.get_call_graph() {
+        let caller = callers.get(&(call.caller_file.as_str(), call.caller_line))
+            .cloned()
+            .unwrap_or_else(|| call.caller.clone());
+        edges.entry((caller, call.callee.clone())).or_insert((call.caller_file.clone(), call.call_line));
+    }
+
+    let function_names = symbols.iter()
+        .filter(|s| s.symbol_type == SymbolType::Function)
+        .map(|s| s.name.clone())
+        .collect();
+
+    Ok(Snapshot { entries, edges, function_names })
+}
+
+/// 同じ位置（ファイル・スコープ）→ 別ファイルの同じスコープ → 内容が同じ別スコープ、の順に対応付ける
+fn diff_symbols(old: &[Entry], new: &[Entry]) -> Vec<SymbolChange> {
+    type Key<'a> = (&'a str, Option<&'a str>, &'a str, String);
+    let keys: [fn(&Entry) -> Key<'_>; 3] = [
+        |e| (e.version.file_path.as_str(), e.version.scope.as_deref(), e.name.as_str(), format!("{:?}", e.symbol_type)),
+        |e| ("", e.version.scope.as_deref(), e.name.as_str(), format!("{:?}", e.symbol_type)),
+        |e| ("", None, e.name.as_str(), format!("{:?}\n{}\n{}", e.symbol_type, e.signature_key, e.body_key)),
+    ];
+
+    let mut old_matched = vec![false; old.len()];
+    let mut new_matched = vec![false; new.len()];
+    let mut changes = Vec::new();
+
+    for key in keys {
+        let mut candidates: HashMap<Key<'_>, Vec<usize>> = HashMap::new();
+        for (index, entry) in new.iter().enumerate().filter(|(i, _)| !new_matched[*i]) {
+            candidates.entry(key(entry)).or_default().push(index);
+        }
+        for (old_index, entry) in old.iter().enumerate() {
+            if old_matched[old_index] {
+                continue;
+            }
+            let Some(new_index) = candidates.get_mut(&key(entry)).and_then(|list| (!list.is_empty()).then(|| list.remove(0))) else {
+                continue;
+            };
+            old_matched[old_index] = true;
+            new_matched[new_index] = true;
+            if let Some(change) = compare(entry, &new[new_index]) {
+                changes.push(change);
+            }
+        }
+    }
+
+    for (entry, _) in old.iter().zip(&old_matched).filter(|(_, matched)| !**matched) {
+        changes.push(SymbolChange {
+            name: entry.name.clone(),
+            symbol_type: entry.symbol_type.clone(),
+            kind: SymbolChangeKind::Removed,
+            moved: false,
+            old: Some(entry.version.clone()),
+            new: None,
+        });
+    }
+    for (entry, _) in new.iter().zip(&new_matched).filter(|(_, matched)| !**matched) {
+        changes.push(SymbolChange {
+            name: entry.name.clone(),
+            symbol_type: entry.symbol_type.clone(),
+            kind: SymbolChangeKind::Added,
+            moved: false,
+            old: None,
+            new: Some(entry.version.clone()),
+        });
+    }
+
+    changes.sort_by(|a, b| {
+        let location = |c: &SymbolChange| c.new.as_ref().or(c.old.as_ref()).map(|v| (v.file_path.clone(), v.line));
+        a.kind.cmp(&b.kind).then(location(a).cmp(&location(b)))
+    });
+    changes
+}
+
+/// 対応付けた2つのシンボルの違い（行がずれただけなら None）
+fn compare(old: &Entry, new: &Entry) -> Option<SymbolChange> {
+    let moved = old.version.file_path != new.version.file_path || old.version.scope != new.version.scope;
+    let kind = if old.signature_key != new.signature_key {
+        SymbolChangeKind::SignatureChanged
+    } else if old.body_key != new.body_key {
+        SymbolChangeKind::BodyChanged
+    } else if moved {
+        SymbolChangeKind::Moved
+    } else {
+        return None;
+    };
+
+    Some(SymbolChange {
+        name: new.name.clone(),
+        symbol_type: new.symbol_type.clone(),
+        kind,
+        moved,
+        old: Some(old.version.clone()),
+        new: Some(new.version.clone()),
+    })
+}
+
+/// ドキュメントコメント（#[doc = ...]）を除いたトークン列の文字列
+fn strip_docs(tokens: TokenStream) -> String {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut parts = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        if let (TokenTree::Punct(punct), Some(TokenTree::Group(group))) = (&tokens[index], tokens.get(index + 1))
+            && punct.as_char() == '#'
+            && group.delimiter() == Delimiter::Bracket
+            && matches!(group.stream().into_iter().next(), Some(TokenTree::Ident(ident)) if ident == "doc") {
+            index += 2;
+            continue;
+        }
+        // 内側の属性（#![doc = ...]）
+        if let (TokenTree::Punct(punct), Some(TokenTree::Punct(bang)), Some(TokenTree::Group(group))) = (&tokens[index], tokens.get(index + 1), tokens.get(index + 2))
+            && punct.as_char() == '#'
+            && bang.as_char() == '!'
+            && matches!(group.stream().into_iter().next(), Some(TokenTree::Ident(ident)) if ident == "doc") {
+            index += 3;
+            continue;
+        }
+        match &tokens[index] {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                parts.push(format!("{open} {} {close}", strip_docs(group.stream())));
+            }
+            token => parts.push(token.to_string()),
+        }
+        index += 1;
+    }
+    parts.join(" ")
+}
+
+/// 識別子の位置ごとに、シグネチャと本体の比較用文字列を集める
+struct FingerprintCollector {
+    found: Vec<(LineColumn, String, String)>,
+}
+
+impl FingerprintCollector {
+    fn add_fn(&mut self, ident: &syn::Ident, signature: TokenStream, body: Option<&syn::Block>) {
+        let body = body.map(|block| strip_docs(block.to_token_stream())).unwrap_or_default();
+        self.found.push((ident.span().start(), strip_docs(signature), body));
+    }
+}
+
+/// 本体の前まで（属性・可視性・シグネチャ）
+fn fn_header(attrs: &[syn::Attribute], vis: Option<&syn::Visibility>, sig: &syn::Signature) -> TokenStream {
+    let mut tokens = TokenStream::new();
+    for attr in attrs {
+        attr.to_tokens(&mut tokens);
+    }
+    if let Some(vis) = vis {
+        vis.to_tokens(&mut tokens);
+    }
+    sig.to_tokens(&mut tokens);
+    tokens
+}
+
+impl<'ast> Visit<'ast> for FingerprintCollector {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.add_fn(&item.sig.ident, fn_header(&item.attrs, Some(&item.vis), &item.sig), Some(&item.block));
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.add_fn(&item.sig.ident, fn_header(&item.attrs, Some(&item.vis), &item.sig), Some(&item.block));
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        self.add_fn(&item.sig.ident, fn_header(&item.attrs, None, &item.sig), item.default.as_ref());
+        visit::visit_trait_item_fn(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.found.push((item.ident.span().start(), strip_docs(item.to_token_stream()), String::new()));
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.found.push((item.ident.span().start(), strip_docs(item.to_token_stream()), String::new()));
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        // メソッドは別のシンボルとして比べるので、トレイト自身はヘッダーとメソッドのシグネチャだけ
+        let mut tokens = TokenStream::new();
+        for attr in &item.attrs {
+            attr.to_tokens(&mut tokens);
+        }
+        item.vis.to_tokens(&mut tokens);
+        item.unsafety.to_tokens(&mut tokens);
+        item.ident.to_tokens(&mut tokens);
+        item.generics.to_tokens(&mut tokens);
+        item.supertraits.to_tokens(&mut tokens);
+        for trait_item in &item.items {
+            match trait_item {
+                syn::TraitItem::Fn(method) => fn_header(&method.attrs, None, &method.sig).to_tokens(&mut tokens),
+                other => other.to_tokens(&mut tokens),
+            }
+        }
+        self.found.push((item.ident.span().start(), strip_docs(tokens), String::new()));
+        visit::visit_item_trait(self, item);
+    }
+}
+
+fn kind_label(kind: SymbolChangeKind) -> &'static str {
+    match kind {
+        SymbolChangeKind::Removed => "removed",
+        SymbolChangeKind::Added => "added",
+        SymbolChangeKind::SignatureChanged => "signature",
+        SymbolChangeKind::BodyChanged => "body",
+        SymbolChangeKind::Moved => "moved",
+    }
+}
+
+/// semantic_diff の結果をテキストに整形（CLI・MCP 用）
+pub fn format_diff(report: &SemanticDiffResponse) -> String {
+    let summary = &report.summary;
+    let mut result = format!(
+        "Semantic diff {}..{}: {} added, {} removed, {} signature changed, {} body-only changed, {} moved; calls +{} -{}\n",
+        report.from, report.to.as_deref().unwrap_or("working tree"),
+        summary.added, summary.removed, summary.signature_changed, summary.body_changed, summary.moved,
+        summary.calls_added, summary.calls_removed,
+    );
+
+    let mut current = None;
+    for change in &report.symbols {
+        if current != Some(change.kind) {
+            current = Some(change.kind);
+            result.push_str(&format!("\n[{}]\n", kind_label(change.kind)));
+        }
+        let version = change.new.as_ref().or(change.old.as_ref());
+        let name = version.map(|v| qualified_name(v.scope.as_deref(), &change.name)).unwrap_or_else(|| change.name.clone());
+        let kind = format!("{:?}", change.symbol_type).to_lowercase();
+        let location = match (&change.old, &change.new) {
+            (Some(old), Some(new)) if change.moved => format!("{}:{} -> {}:{}", old.file_path, old.line, new.file_path, new.line),
+            _ => version.map(|v| format!("{}:{}", v.file_path, v.line)).unwrap_or_default(),
+        };
+        result.push_str(&format!("  {kind} {name} ({location})\n"));
+        if change.kind == SymbolChangeKind::SignatureChanged
+            && let (Some(old), Some(new)) = (&change.old, &change.new)
+            && old.signature != new.signature {
+            result.push_str(&format!("    - {}\n    + {}\n", old.signature, new.signature));
+        }
+    }
+
+    for (label, edges) in [("calls added", &report.calls_added), ("calls removed", &report.calls_removed)] {
+        if edges.is_empty() {
+            continue;
+        }
+        result.push_str(&format!("\n[{label}]\n"));
+        for edge in edges {
+            result.push_str(&format!("  {} -> {} ({}:{})\n", edge.caller, edge.callee, edge.file_path, edge.line));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git").arg("-C").arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[test]
+    fn test_semantic_diff() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), r#"mod util;
+
+use util::helper;
+
+/// 設定
+pub struct Config {
+    pub name: String,
+}
+
+pub fn load(path: &str) -> Config {
+    Config { name: path.to_string() }
+}
+
+pub fn run() {
+    let config = load("a");
+    helper(&config.name);
+}
+
+fn obsolete() {}
+"#).unwrap();
+        fs::write(dir.path().join("src/util.rs"), "pub fn helper(name: &str) {\n    println!(\"{name}\");\n}\n\npub fn shared() {}\n").unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-q", "-m", "initial"]);
+
+        // ドキュメントの変更・行のずれは変更として扱わない
+        fs::write(dir.path().join("src/lib.rs"), r#"mod util;
+
+use util::helper;
+
+/// 設定（説明を変更）
+pub struct Config {
+    pub name: String,
+}
+
+pub fn load(path: &str, verbose: bool) -> Config {
+    Config { name: path.to_string() }
+}
+
+pub fn run() {
+    let config = load("a", false);
+    validate(&config);
+}
+
+fn validate(config: &Config) {
+    helper(&config.name);
+}
+
+pub fn shared() {}
+"#).unwrap();
+        fs::write(dir.path().join("src/util.rs"), "pub fn helper(name: &str) {\n    eprintln!(\"{name}\");\n}\n").unwrap();
+
+        let report = semantic_diff(dir.path(), "HEAD", None).unwrap();
+        let changes: Vec<(SymbolChangeKind, &str, bool)> = report.symbols.iter().map(|c| (c.kind, c.name.as_str(), c.moved)).collect();
+        assert_eq!(changes, vec![
+            (SymbolChangeKind::Removed, "obsolete", false),
+            (SymbolChangeKind::Added, "validate", false),
+            (SymbolChangeKind::SignatureChanged, "load", false),
+            (SymbolChangeKind::BodyChanged, "run", false),
+            (SymbolChangeKind::BodyChanged, "helper", false),
+            (SymbolChangeKind::Moved, "shared", true),
+        ]);
+        assert_eq!(report.summary.moved, 1);
+
+        let edges = |edges: &[CallEdge]| edges.iter().map(|e| format!("{} -> {}", e.caller, e.callee)).collect::<Vec<_>>();
+        assert_eq!(edges(&report.calls_added), vec!["run -> validate", "validate -> helper"]);
+        assert_eq!(edges(&report.calls_removed), vec!["run -> helper"]);
+
+        assert!(semantic_diff(dir.path(), "no-such-revision", None).is_err());
+    }
+}
@@ -0,0 +1,588 @@
+use crate::parser::{RustParser, doc_comment};
+use crate::protocol::{JsonSchemaParams, JsonSchemaResponse, SymbolType};
+use anyhow::{Result, bail};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// serde の属性のうち、JSON の形に影響するもの
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: bool,
+    transparent: bool,
+    deny_unknown_fields: bool,
+    skip: bool,
+    optional: bool,  // default・skip_serializing_if・skip_serializing・skip_deserializing（なくてもよいフィールド）
+    flatten: bool,
+}
+
+fn serde_attrs(attrs: &[syn::Attribute]) -> SerdeAttrs {
+    let mut result = SerdeAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        // 解釈できない属性は無視して、読み取れたところまでを使う
+        let _ = attr.parse_nested_meta(|meta| {
+            let key = meta.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+            match key.as_str() {
+                "rename" => result.rename = name_value(&meta)?,
+                "rename_all" => result.rename_all = name_value(&meta)?,
+                "tag" => result.tag = Some(meta.value()?.parse::<syn::LitStr>()?.value()),
+                "content" => result.content = Some(meta.value()?.parse::<syn::LitStr>()?.value()),
+                "untagged" => result.untagged = true,
+                "transparent" => result.transparent = true,
+                "deny_unknown_fields" => result.deny_unknown_fields = true,
+                "skip" => result.skip = true,
+                "flatten" => result.flatten = true,
+                "default" | "skip_serializing_if" | "skip_serializing" | "skip_deserializing" => {
+                    result.optional = true;
+                    skip_value(&meta)?;
+                }
+                _ => skip_value(&meta)?,
+            }
+            Ok(())
+        });
+    }
+    result
+}
+
+/// `rename = "x"` または `rename(serialize = "x", deserialize = "y")`（serialize 側を優先）
+fn name_value(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse::<syn::LitStr>()?.value()));
+    }
+    let (mut serialize, mut deserialize) = (None, None);
+    meta.parse_nested_meta(|inner| {
+        let value = inner.value()?.parse::<syn::LitStr>()?.value();
+        if inner.path.is_ident("serialize") {
+            serialize = Some(value);
+        } else if inner.path.is_ident("deserialize") {
+            deserialize = Some(value);
+        }
+        Ok(())
+    })?;
+    Ok(serialize.or(deserialize))
+}
+
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+        meta.input.parse::<proc_macro2::TokenTree>()?;
+    }
+    Ok(())
+}
+
+/// rename_all の規則で名前を変換（variant は PascalCase、フィールドは snake_case の名前を受け取る）
+fn apply_rename_all(name: &str, rule: &str, is_variant: bool) -> String {
+    // いったん単語に分ける
+    let words: Vec<String> = if is_variant {
+        let mut words: Vec<String> = Vec::new();
+        for c in name.chars() {
+            if c.is_uppercase() || words.is_empty() {
+                words.push(String::new());
+            }
+            words.last_mut().expect("pushed above").push(c);
+        }
+        words
+    } else {
+        name.split('_').map(str::to_string).collect()
+    };
+    let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let capitalize = |w: &String| {
+        let mut chars = w.chars();
+        chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+    };
+
+    match rule {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "PascalCase" => lower.iter().map(capitalize).collect(),
+        "camelCase" => lower.iter().enumerate()
+            .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+            .collect(),
+        "snake_case" => lower.join("_"),
+        "SCREAMING_SNAKE_CASE" => lower.join("_").to_uppercase(),
+        "kebab-case" => lower.join("-"),
+        "SCREAMING-KEBAB-CASE" => lower.join("-").to_uppercase(),
+        _ => name.to_string(),
+    }
+}
+
+/// 名前の変換（rename が最優先、次に rename_all）
+fn wire_name(ident: &syn::Ident, attrs: &SerdeAttrs, rename_all: Option<&str>, is_variant: bool) -> String {
+    let name = ident.to_string().trim_start_matches("r#").to_string();
+    match (&attrs.rename, rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(rule)) => apply_rename_all(&name, rule, is_variant),
+        (None, None) => name,
+    }
+}
+
+fn with_description(mut schema: Value, description: Option<String>) -> Value {
+    if let (Some(description), Some(object)) = (description, schema.as_object_mut()) {
+        object.insert("description".to_string(), Value::String(description));
+    }
+    schema
+}
+
+fn nullable(schema: Value) -> Value {
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+/// 構造体・列挙型の定義（ファイルから読み直したもの）
+enum TypeItem {
+    Struct(syn::ItemStruct),
+    Enum(syn::ItemEnum),
+}
+
+impl TypeItem {
+    fn attrs(&self) -> &[syn::Attribute] {
+        match self {
+            TypeItem::Struct(item) => &item.attrs,
+            TypeItem::Enum(item) => &item.attrs,
+        }
+    }
+
+    fn generics(&self) -> Vec<String> {
+        let generics = match self {
+            TypeItem::Struct(item) => &item.generics,
+            TypeItem::Enum(item) => &item.generics,
+        };
+        generics.type_params().map(|param| param.ident.to_string()).collect()
+    }
+}
+
+/// 型をたどりながら JSON Schema を組み立てる
+struct SchemaBuilder<'a> {
+    parser: &'a RustParser,
+    files: HashMap<String, Option<syn::File>>,
+    root: String,
+    defs: BTreeMap<String, Value>,
+    unresolved: BTreeSet<String>,
+}
+
+/// serde の derive を持つ構造体・列挙型の JSON Schema を生成（参照している型はファイルをまたいで $defs に入れる）
+pub fn json_schema(parser: &RustParser, params: &JsonSchemaParams) -> Result<JsonSchemaResponse> {
+    let mut builder = SchemaBuilder {
+        parser,
+        files: HashMap::new(),
+        root: params.type_name.clone(),
+        defs: BTreeMap::new(),
+        unresolved: BTreeSet::new(),
+    };
+
+    let Some((file_path, line, item)) = builder.find_type(&params.type_name, params.file_path.as_deref(), true) else {
+        bail!("Struct or enum not found: {}", params.type_name);
+    };
+    if !is_serde_type(&item) {
+        bail!("{} does not derive Serialize or Deserialize", params.type_name);
+    }
+
+    let mut schema = builder.item_schema(&item, &file_path);
+    if let Some(object) = schema.as_object_mut() {
+        let title = serde_attrs(item.attrs()).rename.unwrap_or_else(|| params.type_name.clone());
+        object.insert("$schema".to_string(), Value::String(SCHEMA_DIALECT.to_string()));
+        object.insert("title".to_string(), Value::String(title));
+        if !builder.defs.is_empty() {
+            object.insert("$defs".to_string(), Value::Object(builder.defs.into_iter().collect()));
+        }
+    }
+
+    Ok(JsonSchemaResponse {
+        type_name: params.type_name.clone(),
+        file_path,
+        line,
+        schema,
+        unresolved: builder.unresolved.into_iter().collect(),
+    })
+}
+
+fn is_serde_type(item: &TypeItem) -> bool {
+    crate::trait_impls::derived_traits(item.attrs()).iter()
+        .any(|(path, _)| path.ends_with("Serialize") || path.ends_with("Deserialize"))
+}
+
+impl SchemaBuilder<'_> {
+    /// インデックスから構造体・列挙型を探し、定義をファイルから読み直す（同名なら prefer_file を優先）
+    fn find_type(&mut self, name: &str, prefer_file: Option<&str>, exact_file: bool) -> Option<(String, usize, TypeItem)> {
+        let mut candidates: Vec<(String, usize)> = self.parser.find_symbol(name, None)?
+            .into_iter()
+            .filter(|s| matches!(s.symbol_type, SymbolType::Struct | SymbolType::Enum) && s.crate_name.is_none())
+            .map(|s| (s.file_path.clone(), s.line))
+            .collect();
+        candidates.sort();
+        if let Some(file) = prefer_file
+            && (exact_file || candidates.iter().any(|(path, _)| path == file)) {
+            candidates.retain(|(path, _)| path == file);
+        }
+        let (file_path, line) = candidates.into_iter().next()?;
+
+        let syntax_tree = self.files.entry(file_path.clone())
+            .or_insert_with(|| std::fs::read_to_string(&file_path).ok().and_then(|content| syn::parse_file(&content).ok()))
+            .as_ref()?;
+        let item = find_item(&syntax_tree.items, name, line)?;
+        Some((file_path, line, item))
+    }
+
+    /// プロジェクト内の型への $ref（初めて参照されたときに $defs へ追加する）
+    fn type_ref(&mut self, name: &str, from_file: &str) -> Option<Value> {
+        if name == self.root {
+            return Some(json!({ "$ref": "#" }));
+        }
+        let reference = json!({ "$ref": format!("#/$defs/{name}") });
+        if self.defs.contains_key(name) {
+            return Some(reference);
+        }
+
+        let (file_path, _, item) = self.find_type(name, Some(from_file), false)?;
+        if !is_serde_type(&item) {
+            self.unresolved.insert(format!("{name} (no serde derive)"));
+            return Some(json!({}));
+        }
+        // 再帰的な型のために先に登録しておく
+        self.defs.insert(name.to_string(), Value::Null);
+        let schema = self.item_schema(&item, &file_path);
+        self.defs.insert(name.to_string(), schema);
+        Some(reference)
+    }
+
+    fn item_schema(&mut self, item: &TypeItem, file: &str) -> Value {
+        let generics = item.generics();
+        let schema = match item {
+            TypeItem::Struct(item_struct) => self.struct_schema(item_struct, file, &generics),
+            TypeItem::Enum(item_enum) => self.enum_schema(item_enum, file, &generics),
+        };
+        with_description(schema, doc_comment(item.attrs()))
+    }
+
+    fn struct_schema(&mut self, item: &syn::ItemStruct, file: &str, generics: &[String]) -> Value {
+        let attrs = serde_attrs(&item.attrs);
+        if attrs.transparent
+            && let Some(field) = item.fields.iter().find(|field| !serde_attrs(&field.attrs).skip) {
+            return self.type_schema(&field.ty, file, generics);
+        }
+        match &item.fields {
+            syn::Fields::Named(fields) => self.object_schema(fields, &attrs, file, generics),
+            syn::Fields::Unnamed(fields) => self.tuple_schema(fields, file, generics),
+            syn::Fields::Unit => json!({ "type": "null" }),
+        }
+    }
+
+    /// 名前付きフィールドのオブジェクト（flatten したフィールドは allOf で合成）
+    fn object_schema(&mut self, fields: &syn::FieldsNamed, container: &SerdeAttrs, file: &str, generics: &[String]) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut flattened = Vec::new();
+
+        for field in &fields.named {
+            let attrs = serde_attrs(&field.attrs);
+            if attrs.skip {
+                continue;
+            }
+            let schema = self.type_schema(&field.ty, file, generics);
+            if attrs.flatten {
+                flattened.push(schema);
+                continue;
+            }
+            let Some(ident) = &field.ident else {
+                continue;
+            };
+            let name = wire_name(ident, &attrs, container.rename_all.as_deref(), false);
+            // Option は省略されると None になる
+            if !(attrs.optional || container.optional || is_option(&field.ty)) {
+                required.push(Value::String(name.clone()));
+            }
+            properties.insert(name, with_description(schema, doc_comment(&field.attrs)));
+        }
+
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("object"));
+        object.insert("properties".to_string(), Value::Object(properties));
+        if !required.is_empty() {
+            object.insert("required".to_string(), Value::Array(required));
+        }
+        if container.deny_unknown_fields && flattened.is_empty() {
+            object.insert("additionalProperties".to_string(), json!(false));
+        }
+
+        if flattened.is_empty() {
+            Value::Object(object)
+        } else {
+            let mut all_of = vec![Value::Object(object)];
+            all_of.extend(flattened);
+            json!({ "allOf": all_of })
+        }
+    }
+
+    /// タプル構造体・タプル variant（1要素なら中身そのもの）
+    fn tuple_schema(&mut self, fields: &syn::FieldsUnnamed, file: &str, generics: &[String]) -> Value {
+        let items: Vec<Value> = fields.unnamed.iter()
+            .filter(|field| !serde_attrs(&field.attrs).skip)
+            .map(|field| self.type_schema(&field.ty, file, generics))
+            .collect();
+        match items.len() {
+            1 => items.into_iter().next().expect("length checked"),
+            len => json!({ "type": "array", "prefixItems": items, "minItems": len, "maxItems": len }),
+        }
+    }
+
+    fn enum_schema(&mut self, item: &syn::ItemEnum, file: &str, generics: &[String]) -> Value {
+        let attrs = serde_attrs(&item.attrs);
+        let variants: Vec<&syn::Variant> = item.variants.iter().filter(|v| !serde_attrs(&v.attrs).skip).collect();
+
+        // 外部タグのユニット variant だけなら文字列の列挙
+        let externally_tagged = attrs.tag.is_none() && !attrs.untagged;
+        if externally_tagged && variants.iter().all(|v| matches!(v.fields, syn::Fields::Unit)) {
+            let names: Vec<String> = variants.iter()
+                .map(|v| wire_name(&v.ident, &serde_attrs(&v.attrs), attrs.rename_all.as_deref(), true))
+                .collect();
+            return json!({ "type": "string", "enum": names });
+        }
+
+        let mut one_of = Vec::new();
+        for variant in variants {
+            let variant_attrs = serde_attrs(&variant.attrs);
+            let name = wire_name(&variant.ident, &variant_attrs, attrs.rename_all.as_deref(), true);
+            let content = match &variant.fields {
+                syn::Fields::Unit => None,
+                syn::Fields::Unnamed(fields) => Some(self.tuple_schema(fields, file, generics)),
+                // variant の rename_all はフィールド名に効く
+                syn::Fields::Named(fields) => Some(self.object_schema(fields, &variant_attrs, file, generics)),
+            };
+
+            let schema = match (&attrs.tag, &attrs.content) {
+                _ if attrs.untagged => content.unwrap_or_else(|| json!({ "type": "null" })),
+                (Some(tag), Some(content_key)) => {
+                    let mut properties = Map::new();
+                    let mut required = vec![json!(tag)];
+                    properties.insert(tag.clone(), json!({ "const": name }));
+                    if let Some(content) = content {
+                        properties.insert(content_key.clone(), content);
+                        required.push(json!(content_key));
+                    }
+                    json!({ "type": "object", "properties": properties, "required": required })
+                }
+                (Some(tag), None) => {
+                    let tag_schema = json!({ "type": "object", "properties": { tag.as_str(): { "const": name } }, "required": [tag] });
+                    match content {
+                        // フィールドはタグと同じオブジェクトに並ぶ
+                        Some(Value::Object(mut object)) if object.contains_key("properties") => {
+                            if let Some(Value::Object(properties)) = object.get_mut("properties") {
+                                properties.insert(tag.clone(), json!({ "const": name }));
+                            }
+                            let required = object.entry("required").or_insert_with(|| json!([]));
+                            if let Value::Array(required) = required {
+                                required.insert(0, json!(tag));
+                            }
+                            Value::Object(object)
+                        }
+                        Some(content) => json!({ "allOf": [tag_schema, content] }),
+                        None => tag_schema,
+                    }
+                }
+                (None, _) => match content {
+                    Some(content) => json!({
+                        "type": "object",
+                        "properties": { name.as_str(): content },
+                        "required": [name],
+                        "additionalProperties": false,
+                    }),
+                    None => json!({ "const": name }),
+                },
+            };
+            one_of.push(with_description(schema, doc_comment(&variant.attrs)));
+        }
+
+        json!({ "oneOf": one_of })
+    }
+
+    /// Rust の型を JSON Schema にする（プロジェクト内の型は $ref、分からない型は {}）
+    fn type_schema(&mut self, ty: &syn::Type, file: &str, generics: &[String]) -> Value {
+        match ty {
+            syn::Type::Reference(reference) => self.type_schema(&reference.elem, file, generics),
+            syn::Type::Paren(paren) => self.type_schema(&paren.elem, file, generics),
+            syn::Type::Group(group) => self.type_schema(&group.elem, file, generics),
+            syn::Type::Slice(slice) => json!({ "type": "array", "items": self.type_schema(&slice.elem, file, generics) }),
+            syn::Type::Array(array) => json!({ "type": "array", "items": self.type_schema(&array.elem, file, generics) }),
+            syn::Type::Tuple(tuple) if tuple.elems.is_empty() => json!({ "type": "null" }),
+            syn::Type::Tuple(tuple) => {
+                let items: Vec<Value> = tuple.elems.iter().map(|elem| self.type_schema(elem, file, generics)).collect();
+                let len = items.len();
+                json!({ "type": "array", "prefixItems": items, "minItems": len, "maxItems": len })
+            }
+            syn::Type::Path(type_path) => self.path_schema(type_path, file, generics),
+            other => {
+                self.unresolved.insert(quote::quote!(#other).to_string());
+                json!({})
+            }
+        }
+    }
+
+    fn path_schema(&mut self, type_path: &syn::TypePath, file: &str, generics: &[String]) -> Value {
+        let Some(segment) = type_path.path.segments.last() else {
+            return json!({});
+        };
+        let name = segment.ident.to_string();
+        let args: Vec<&syn::Type> = match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => args.args.iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let arg = |index: usize, this: &mut Self| match args.get(index) {
+            Some(ty) => this.type_schema(ty, file, generics),
+            None => json!({}),
+        };
+
+        match name.as_str() {
+            _ if generics.contains(&name) => json!({}),
+            "bool" => json!({ "type": "boolean" }),
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => json!({ "type": "integer", "minimum": 0 }),
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => json!({ "type": "integer" }),
+            "f32" | "f64" => json!({ "type": "number" }),
+            "char" => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+            "String" | "str" | "PathBuf" | "Path" => json!({ "type": "string" }),
+            "DateTime" => json!({ "type": "string", "format": "date-time" }),
+            "Value" => json!({}),
+            "Option" => nullable(arg(0, self)),
+            "Vec" | "VecDeque" | "LinkedList" => json!({ "type": "array", "items": arg(0, self) }),
+            "HashSet" | "BTreeSet" => json!({ "type": "array", "items": arg(0, self), "uniqueItems": true }),
+            "HashMap" | "BTreeMap" => json!({ "type": "object", "additionalProperties": arg(1, self) }),
+            "Box" | "Arc" | "Rc" | "Cow" => arg(args.len().saturating_sub(1), self),
+            _ => match self.type_ref(&name, file) {
+                Some(reference) => reference,
+                None => {
+                    self.unresolved.insert(name);
+                    json!({})
+                }
+            },
+        }
+    }
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(type_path) if type_path.path.segments.last().is_some_and(|s| s.ident == "Option"))
+}
+
+/// ファイル内（インラインの mod も含む）から名前と行で定義を探す
+fn find_item(items: &[syn::Item], name: &str, line: usize) -> Option<TypeItem> {
+    let mut fallback = None;
+    for item in items {
+        let found = match item {
+            syn::Item::Struct(item_struct) if item_struct.ident == name => {
+                Some((item_struct.span().start().line, item_struct.ident.span().start().line, TypeItem::Struct(item_struct.clone())))
+            }
+            syn::Item::Enum(item_enum) if item_enum.ident == name => {
+                Some((item_enum.span().start().line, item_enum.ident.span().start().line, TypeItem::Enum(item_enum.clone())))
+            }
+            syn::Item::Mod(item_mod) => {
+                if let Some((_, inner)) = &item_mod.content
+                    && let Some(found) = find_item(inner, name, line) {
+                    return Some(found);
+                }
+                None
+            }
+            _ => None,
+        };
+        match found {
+            Some((start, ident_line, item)) if start == line || ident_line == line => return Some(item),
+            Some((_, _, item)) if fallback.is_none() => fallback = Some(item),
+            _ => {}
+        }
+    }
+    fallback
+}
+
+/// JSON Schema を整形（CLI 用）
+pub fn format_schema(report: &JsonSchemaResponse) -> String {
+    let mut result = serde_json::to_string_pretty(&report.schema).unwrap_or_default();
+    result.push('\n');
+    if !report.unresolved.is_empty() {
+        result.push_str(&format!("\n// Unresolved types (schema left open): {}\n", report.unresolved.join(", ")));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_json_schema() {
+        let dir = TempDir::new().unwrap();
+        let protocol = dir.path().join("protocol.rs");
+        let kinds = dir.path().join("kinds.rs");
+        fs::write(&protocol, r#"
+use serde::{Deserialize, Serialize};
+
+/// リクエスト
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ServerRequest {
+    pub request_id: u64,
+    #[serde(rename = "kind")]
+    pub symbol_kind: Kind,
+    pub file_path: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(skip)]
+    pub cache: Cache,
+    pub children: Vec<ServerRequest>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Started,
+    Progress { done: usize },
+    Failed(Detail),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Detail {
+    #[serde(flatten)]
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+pub struct Cache;
+"#).unwrap();
+        fs::write(&kinds, "#[derive(Serialize, Deserialize)]\n#[serde(rename_all = \"SCREAMING_SNAKE_CASE\")]\npub enum Kind { StructItem, EnumItem }\n").unwrap();
+
+        let mut parser = RustParser::new();
+        parser.parse_file(&protocol).unwrap();
+        parser.parse_file(&kinds).unwrap();
+
+        let report = json_schema(&parser, &JsonSchemaParams { type_name: "ServerRequest".to_string(), file_path: None }).unwrap();
+        let schema = &report.schema;
+        assert_eq!(schema["title"], "ServerRequest");
+        assert_eq!(schema["description"], "リクエスト");
+        assert_eq!(schema["additionalProperties"], false);
+        let properties: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(properties, ["children", "filePath", "kind", "requestId", "tags"]);
+        assert_eq!(schema["required"], json!(["requestId", "kind", "children"]));
+        assert_eq!(schema["properties"]["requestId"], json!({ "type": "integer", "minimum": 0 }));
+        assert_eq!(schema["properties"]["kind"], json!({ "$ref": "#/$defs/Kind" }));
+        assert_eq!(schema["properties"]["children"]["items"], json!({ "$ref": "#" }));
+        assert_eq!(schema["$defs"]["Kind"], json!({ "type": "string", "enum": ["STRUCT_ITEM", "ENUM_ITEM"] }));
+
+        let event = json_schema(&parser, &JsonSchemaParams { type_name: "Event".to_string(), file_path: None }).unwrap();
+        let variants = event.schema["oneOf"].as_array().unwrap();
+        assert_eq!(variants[0], json!({ "type": "object", "properties": { "type": { "const": "started" } }, "required": ["type"] }));
+        assert_eq!(variants[1]["required"], json!(["type", "done"]));
+        assert_eq!(variants[2]["allOf"][1], json!({ "$ref": "#/$defs/Detail" }));
+        assert_eq!(event.schema["$defs"]["Detail"]["allOf"][1]["additionalProperties"], json!({}));
+
+        assert!(json_schema(&parser, &JsonSchemaParams { type_name: "Cache".to_string(), file_path: None }).is_err());
+    }
+}
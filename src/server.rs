@@ -1,5 +1,5 @@
 use crate::indexer::CodeIndexer;
-use crate::protocol::{self, ServerRequest, ServerResponse, FindDefinitionParams, FindDefinitionResponse, FindUsagesParams, FindUsagesResponse, StatsResponse, SymbolDefinition, SymbolUsage, ChangeProjectParams, ChangeProjectResponse};
+use crate::protocol::{self, ServerRequest, ServerResponse, FindDefinitionParams, FindDefinitionResponse, FindUsagesParams, FindUsagesResponse, StatsResponse, SymbolDefinition, SymbolUsage, ChangeProjectParams, ChangeProjectResponse, SearchSymbolsParams, SearchSymbolsResponse, SymbolSearchResult, GetDiagnosticsParams, GetDiagnosticsResponse, FileDiagnostics, FindLocalReferencesParams, FindLocalReferencesResponse, FindTestsForParams, FindTestsForResponse, TestMatchResult, ListTestsParams, ListTestsResponse, UnsafeReportParams, PanicReportParams, GetMetricsParams, FindDeadCodeParams, ApiSurfaceParams, ApiDiffParams, AsyncHazardsParams, GetErrorMapParams, FindErrorOriginsParams, FindSwallowedErrorsParams, FindImplementationsParams, GetImplMatrixParams, JsonSchemaParams, GetSourceParams, ExplainSymbolParams, RenameSymbolParams, StructuralSearchParams, SemanticDiffParams, SymbolHistoryParams, GetChurnParams, ImpactAnalysisParams, SuggestImportsParams};
 use crate::web_ui::{LogSender, LogBroadcaster};
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
@@ -28,6 +28,12 @@ impl CodeIntelServer {
         }
     }
 
+    /// 依存クレートのソースもインデックスする
+    pub fn with_dependency_indexing(mut self) -> Self {
+        self.indexer = Arc::new(Mutex::new(CodeIndexer::new().with_dependencies()));
+        self
+    }
+
     pub fn with_web_ui(mut self, log_sender: LogSender) -> Self {
         self.log_broadcaster = Some(LogBroadcaster::new(log_sender));
         self
@@ -57,6 +63,8 @@ impl CodeIntelServer {
             
             // Web UIに統計情報を送信
             self.broadcast_stats(&stats);
+
+            Self::spawn_dependency_indexing(Arc::clone(&self.indexer), Arc::clone(&self.project_path), project_path);
         }
 
         // TCPリスナー開始
@@ -175,6 +183,87 @@ impl CodeIntelServer {
             protocol::methods::FIND_USAGES => {
                 Self::handle_find_usages(indexer, &request.params).await?
             }
+            protocol::methods::SEARCH_SYMBOLS => {
+                Self::handle_search_symbols(indexer, &request.params).await?
+            }
+            protocol::methods::GET_DIAGNOSTICS => {
+                Self::handle_get_diagnostics(indexer, &request.params).await?
+            }
+            protocol::methods::FIND_LOCAL_REFERENCES => {
+                Self::handle_find_local_references(indexer, project_path, &request.params).await?
+            }
+            protocol::methods::FIND_TESTS_FOR => {
+                Self::handle_find_tests_for(indexer, &request.params).await?
+            }
+            protocol::methods::LIST_TESTS => {
+                Self::handle_list_tests(indexer, &request.params).await?
+            }
+            protocol::methods::UNSAFE_REPORT => {
+                Self::handle_unsafe_report(indexer, &request.params).await?
+            }
+            protocol::methods::PANIC_REPORT => {
+                Self::handle_panic_report(indexer, &request.params).await?
+            }
+            protocol::methods::GET_METRICS => {
+                Self::handle_get_metrics(indexer, &request.params).await?
+            }
+            protocol::methods::FIND_DEAD_CODE => {
+                Self::handle_find_dead_code(indexer, &request.params).await?
+            }
+            protocol::methods::API_SURFACE => {
+                Self::handle_api_surface(project_path, &request.params).await?
+            }
+            protocol::methods::API_DIFF => {
+                Self::handle_api_diff(project_path, &request.params).await?
+            }
+            protocol::methods::ASYNC_HAZARDS => {
+                Self::handle_async_hazards(indexer, &request.params).await?
+            }
+            protocol::methods::GET_ERROR_MAP => {
+                Self::handle_get_error_map(indexer, &request.params).await?
+            }
+            protocol::methods::FIND_ERROR_ORIGINS => {
+                Self::handle_find_error_origins(indexer, &request.params).await?
+            }
+            protocol::methods::FIND_SWALLOWED_ERRORS => {
+                Self::handle_find_swallowed_errors(indexer, &request.params).await?
+            }
+            protocol::methods::FIND_IMPLEMENTATIONS => {
+                Self::handle_find_implementations(indexer, &request.params).await?
+            }
+            protocol::methods::GET_IMPL_MATRIX => {
+                Self::handle_get_impl_matrix(indexer, &request.params).await?
+            }
+            protocol::methods::GET_JSON_SCHEMA => {
+                Self::handle_get_json_schema(indexer, &request.params).await?
+            }
+            protocol::methods::GET_SOURCE => {
+                Self::handle_get_source(indexer, &request.params).await?
+            }
+            protocol::methods::EXPLAIN_SYMBOL => {
+                Self::handle_explain_symbol(indexer, &request.params).await?
+            }
+            protocol::methods::RENAME_SYMBOL => {
+                Self::handle_rename_symbol(indexer, &request.params).await?
+            }
+            protocol::methods::STRUCTURAL_SEARCH => {
+                Self::handle_structural_search(indexer, &request.params).await?
+            }
+            protocol::methods::SEMANTIC_DIFF => {
+                Self::handle_semantic_diff(project_path, &request.params).await?
+            }
+            protocol::methods::SYMBOL_HISTORY => {
+                Self::handle_symbol_history(indexer, &request.params).await?
+            }
+            protocol::methods::GET_CHURN => {
+                Self::handle_get_churn(indexer, &request.params).await?
+            }
+            protocol::methods::IMPACT_ANALYSIS => {
+                Self::handle_impact_analysis(indexer, project_path, &request.params).await?
+            }
+            protocol::methods::SUGGEST_IMPORTS => {
+                Self::handle_suggest_imports(indexer, project_path, &request.params).await?
+            }
             protocol::methods::GET_STATS => {
                 Self::handle_get_stats(indexer).await?
             }
@@ -245,6 +334,360 @@ impl CodeIntelServer {
         Ok(serde_json::to_value(response)?)
     }
 
+    async fn handle_search_symbols(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: SearchSymbolsParams = serde_json::from_value(params.clone())
+            .context("Invalid search_symbols parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let limit = params.limit.unwrap_or(crate::search::DEFAULT_SEARCH_LIMIT);
+        let matches = indexer_guard.search_symbols(&params.query, params.symbol_type, limit);
+
+        let results: Vec<SymbolSearchResult> = matches
+            .into_iter()
+            .map(|m| SymbolSearchResult {
+                symbol: m.symbol.clone().into(),
+                match_kind: m.match_kind,
+                score: m.score,
+                is_test: m.symbol.is_test,
+            })
+            .collect();
+
+        let response = SearchSymbolsResponse { results };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_get_diagnostics(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: GetDiagnosticsParams = serde_json::from_value(params.clone())
+            .context("Invalid get_diagnostics parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let mut files: Vec<FileDiagnostics> = indexer_guard.get_parser().get_recovery_status()
+            .iter()
+            .filter(|(file_path, _)| params.file_path.as_deref().is_none_or(|path| path == file_path.as_str()))
+            .map(|(file_path, recovery)| FileDiagnostics {
+                file_path: file_path.clone(),
+                recovery: *recovery,
+                diagnostics: indexer_guard.get_diagnostics(Some(file_path))
+                    .into_iter()
+                    .map(|d| d.into())
+                    .collect(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        let response = GetDiagnosticsResponse { files };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_find_local_references(indexer: &Arc<Mutex<CodeIndexer>>, project_path: &Arc<Mutex<String>>, params: &Value) -> Result<Value> {
+        let params: FindLocalReferencesParams = serde_json::from_value(params.clone())
+            .context("Invalid find_local_references parameters")?;
+
+        // 相対パスはプロジェクトルートからのパスとして扱う
+        let file_path = PathBuf::from(&params.file_path);
+        let file_path = if file_path.is_relative() && !file_path.exists() {
+            Path::new(project_path.lock().await.as_str()).join(file_path)
+        } else {
+            file_path
+        };
+
+        let indexer_guard = indexer.lock().await;
+        let response = match indexer_guard.find_local_references(&file_path, params.line, params.column)? {
+            Some(local_refs) => local_refs.into(),
+            None => FindLocalReferencesResponse { binding: None, references: vec![], is_mutated: false },
+        };
+
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_find_tests_for(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: FindTestsForParams = serde_json::from_value(params.clone())
+            .context("Invalid find_tests_for parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let max_depth = params.max_depth.unwrap_or(crate::test_map::DEFAULT_TEST_SEARCH_DEPTH);
+        let tests: Vec<TestMatchResult> = indexer_guard.find_tests_for(&params.symbol_name, max_depth)
+            .into_iter()
+            .map(|m| TestMatchResult {
+                test: m.test.into(),
+                depth: m.depth,
+                call_path: m.call_path,
+            })
+            .collect();
+
+        let response = FindTestsForResponse { tests };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_list_tests(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: ListTestsParams = serde_json::from_value(params.clone())
+            .context("Invalid list_tests parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let mut tests: Vec<_> = indexer_guard.get_tests()
+            .iter()
+            .filter(|t| params.kind.is_none_or(|kind| t.kind == kind))
+            .filter(|t| params.file_path.as_deref().is_none_or(|path| t.file_path == path))
+            .map(protocol::TestDefinition::from)
+            .collect();
+        tests.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+
+        let response = ListTestsResponse { tests };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_unsafe_report(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: UnsafeReportParams = serde_json::from_value(params.clone())
+            .context("Invalid unsafe_report parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::unsafe_audit::build_report(indexer_guard.get_unsafe_sites(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_panic_report(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: PanicReportParams = serde_json::from_value(params.clone())
+            .context("Invalid panic_report parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::panic_audit::build_report(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_get_metrics(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: GetMetricsParams = serde_json::from_value(params.clone())
+            .context("Invalid get_metrics parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::metrics::build_report(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_find_dead_code(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: FindDeadCodeParams = serde_json::from_value(params.clone())
+            .context("Invalid find_dead_code parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = indexer_guard.find_dead_code(&params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_api_surface(project_path: &Arc<Mutex<String>>, params: &Value) -> Result<Value> {
+        let params: ApiSurfaceParams = serde_json::from_value(params.clone())
+            .context("Invalid api_surface parameters")?;
+
+        // ソースの読み込み（git show / ls-tree を含む）はブロッキングなので別スレッドで実行する
+        let crate_dir = Self::crate_dir(project_path, params.crate_path.as_deref()).await;
+        let response = tokio::task::spawn_blocking(move || {
+            crate::api_surface::api_surface(&crate_dir, params.revision.as_deref())
+        }).await??;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_api_diff(project_path: &Arc<Mutex<String>>, params: &Value) -> Result<Value> {
+        let params: ApiDiffParams = serde_json::from_value(params.clone())
+            .context("Invalid api_diff parameters")?;
+
+        // git show / ls-tree はブロッキングなので、インデックスのロックは取らずに別スレッドで実行する
+        let crate_dir = Self::crate_dir(project_path, params.crate_path.as_deref()).await;
+        let response = tokio::task::spawn_blocking(move || {
+            crate::api_surface::api_diff(&crate_dir, &params.from, params.to.as_deref())
+        }).await??;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    /// クレートのディレクトリ（相対パスはプロジェクトルートからのパスとして扱う）
+    async fn crate_dir(project_path: &Arc<Mutex<String>>, crate_path: Option<&str>) -> PathBuf {
+        let project_path = PathBuf::from(project_path.lock().await.as_str());
+        match crate_path {
+            Some(crate_path) => project_path.join(crate_path),
+            None => project_path,
+        }
+    }
+
+    async fn handle_async_hazards(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: AsyncHazardsParams = serde_json::from_value(params.clone())
+            .context("Invalid async_hazards parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::async_audit::build_report(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_get_error_map(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: GetErrorMapParams = serde_json::from_value(params.clone())
+            .context("Invalid get_error_map parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::error_flow::get_error_map(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_find_error_origins(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: FindErrorOriginsParams = serde_json::from_value(params.clone())
+            .context("Invalid find_error_origins parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::error_flow::find_error_origins(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_find_swallowed_errors(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: FindSwallowedErrorsParams = serde_json::from_value(params.clone())
+            .context("Invalid find_swallowed_errors parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::error_flow::find_swallowed_errors(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_find_implementations(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: FindImplementationsParams = serde_json::from_value(params.clone())
+            .context("Invalid find_implementations parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::trait_impls::find_implementations(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_get_impl_matrix(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: GetImplMatrixParams = serde_json::from_value(params.clone())
+            .context("Invalid get_impl_matrix parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::trait_impls::impl_matrix(indexer_guard.get_parser(), &params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_get_json_schema(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: JsonSchemaParams = serde_json::from_value(params.clone())
+            .context("Invalid get_json_schema parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = crate::serde_schema::json_schema(indexer_guard.get_parser(), &params)?;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_get_source(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: GetSourceParams = serde_json::from_value(params.clone())
+            .context("Invalid get_source parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = indexer_guard.get_source(&params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_explain_symbol(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: ExplainSymbolParams = serde_json::from_value(params.clone())
+            .context("Invalid explain_symbol parameters")?;
+
+        let indexer_guard = indexer.lock().await;
+        let response = indexer_guard.explain_symbol(&params);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_rename_symbol(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: RenameSymbolParams = serde_json::from_value(params.clone())
+            .context("Invalid rename_symbol parameters")?;
+
+        // 全ファイルの構文解析と書き込みはブロッキングなので別スレッドで実行し、書き込みの間はロックを外す
+        let indexer_guard = indexer.clone().lock_owned().await;
+        let plan_params = params.clone();
+        let mut response = tokio::task::spawn_blocking(move || indexer_guard.plan_rename_symbol(&plan_params)).await??;
+        if params.apply.unwrap_or(false) {
+            let edits = response.edits.clone();
+            let updated = tokio::task::spawn_blocking(move || crate::rename::apply_edits(&edits)).await??;
+            Self::reindex_files(indexer, updated).await?;
+            response.applied = true;
+        }
+        Ok(serde_json::to_value(response)?)
+    }
+
+    /// 書き換えたファイルを別スレッドで再インデックスする
+    async fn reindex_files(indexer: &Arc<Mutex<CodeIndexer>>, files: Vec<PathBuf>) -> Result<()> {
+        let mut indexer_guard = indexer.clone().lock_owned().await;
+        tokio::task::spawn_blocking(move || files.iter().try_for_each(|file| indexer_guard.index_file(file))).await?
+    }
+
+    async fn handle_structural_search(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: StructuralSearchParams = serde_json::from_value(params.clone())
+            .context("Invalid structural_search parameters")?;
+
+        // rename_symbol と同じく、解析と書き込みは別スレッドで実行し、書き込みの間はロックを外す
+        let indexer_guard = indexer.clone().lock_owned().await;
+        let search_params = params.clone();
+        let mut response = tokio::task::spawn_blocking(move || indexer_guard.structural_matches(&search_params)).await??;
+        if params.apply.unwrap_or(false) {
+            let matches = response.matches.clone();
+            let (updated, skipped) = tokio::task::spawn_blocking(move || crate::structural_search::apply_replacements(&matches)).await??;
+            Self::reindex_files(indexer, updated).await?;
+            response.applied = true;
+            response.skipped_overlapping = skipped;
+        }
+        response.matches.truncate(params.limit.unwrap_or(crate::structural_search::DEFAULT_MATCH_LIMIT));
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_semantic_diff(project_path: &Arc<Mutex<String>>, params: &Value) -> Result<Value> {
+        let params: SemanticDiffParams = serde_json::from_value(params.clone())
+            .context("Invalid semantic_diff parameters")?;
+
+        // 2つのリビジョンのソースを git から読むのはブロッキングなので別スレッドで実行する
+        let crate_dir = Self::crate_dir(project_path, params.path.as_deref()).await;
+        let response = tokio::task::spawn_blocking(move || {
+            crate::semantic_diff::semantic_diff(&crate_dir, &params.from, params.to.as_deref())
+        }).await??;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_symbol_history(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: SymbolHistoryParams = serde_json::from_value(params.clone())
+            .context("Invalid symbol_history parameters")?;
+
+        // git log はブロッキングなので、インデックスのロックを外してから別スレッドで実行する
+        let symbols = indexer.lock().await.history_symbols(&params);
+        let response = tokio::task::spawn_blocking(move || crate::git_history::symbol_history(&symbols, &params)).await??;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_get_churn(indexer: &Arc<Mutex<CodeIndexer>>, params: &Value) -> Result<Value> {
+        let params: GetChurnParams = serde_json::from_value(params.clone())
+            .context("Invalid get_churn parameters")?;
+
+        let symbols = indexer.lock().await.churn_symbols(&params);
+        let response = tokio::task::spawn_blocking(move || crate::git_history::churn_report(&symbols, &params)).await??;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_impact_analysis(indexer: &Arc<Mutex<CodeIndexer>>, project_path: &Arc<Mutex<String>>, params: &Value) -> Result<Value> {
+        let params: ImpactAnalysisParams = serde_json::from_value(params.clone())
+            .context("Invalid impact_analysis parameters")?;
+
+        let diff = match params.diff {
+            Some(diff) => diff,
+            None => {
+                // git diff はブロッキングなので別スレッドで実行する（インデックスのロックはまだ取らない）
+                let dir = Self::crate_dir(project_path, params.path.as_deref()).await;
+                let base = params.base.clone();
+                tokio::task::spawn_blocking(move || crate::impact::git_diff(&dir, base.as_deref())).await??
+            }
+        };
+        let indexer_guard = indexer.lock().await;
+        let response = indexer_guard.impact_analysis(&diff, params.max_depth.unwrap_or(crate::impact::DEFAULT_IMPACT_DEPTH));
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn handle_suggest_imports(indexer: &Arc<Mutex<CodeIndexer>>, project_path: &Arc<Mutex<String>>, params: &Value) -> Result<Value> {
+        let mut params: SuggestImportsParams = serde_json::from_value(params.clone())
+            .context("Invalid suggest_imports parameters")?;
+
+        // 相対パスはプロジェクトルートからのパスとして扱う
+        params.from_file = Self::crate_dir(project_path, Some(&params.from_file)).await.to_string_lossy().to_string();
+        let indexer_guard = indexer.lock().await;
+        let response = indexer_guard.suggest_imports(&params)?;
+        Ok(serde_json::to_value(response)?)
+    }
+
     async fn handle_get_stats(indexer: &Arc<Mutex<CodeIndexer>>) -> Result<Value> {
         let indexer_guard = indexer.lock().await;
         let stats = indexer_guard.get_stats();
@@ -293,8 +736,12 @@ impl CodeIntelServer {
             // 既存のウォッチャーを停止
             indexer_guard.stop_watching();
             
-            // インデックスをクリア
-            *indexer_guard = CodeIndexer::new();
+            // インデックスをクリア（依存クレートのインデックス設定は引き継ぐ）
+            *indexer_guard = if indexer_guard.indexes_dependencies() {
+                CodeIndexer::new().with_dependencies()
+            } else {
+                CodeIndexer::new()
+            };
             
             // 新しいディレクトリをインデックス
             indexer_guard.index_directory(&params.project_path)
@@ -302,6 +749,7 @@ impl CodeIntelServer {
             
             indexer_guard.get_stats()
         };
+        Self::spawn_dependency_indexing(Arc::clone(indexer), Arc::clone(project_path), params.project_path.clone());
 
         let response = ChangeProjectResponse {
             success: true,
@@ -312,6 +760,39 @@ impl CodeIntelServer {
         Ok(serde_json::to_value(response)?)
     }
 
+    /// 依存クレートの公開 API を裏で読み込む（初回のインデックスを待たせず、読み込み中もロックを持たない）
+    fn spawn_dependency_indexing(indexer: Arc<Mutex<CodeIndexer>>, project_path: Arc<Mutex<String>>, scanned_path: String) {
+        tokio::spawn(async move {
+            let indexed = {
+                let indexer = indexer.lock().await;
+                if !indexer.indexes_dependencies() {
+                    return;
+                }
+                indexer.dependency_crates().to_vec()
+            };
+
+            let path = scanned_path.clone();
+            let scan = match tokio::task::spawn_blocking(move || crate::deps::scan_dependencies(Path::new(&path), &indexed)).await {
+                Ok(Ok(scan)) => scan,
+                Ok(Err(e)) => {
+                    warn!("Failed to index dependency sources: {}", e);
+                    return;
+                }
+                Err(e) => {
+                    warn!("Dependency indexing task failed: {}", e);
+                    return;
+                }
+            };
+
+            // 読み込み中にプロジェクトが切り替わっていたら捨てる
+            if *project_path.lock().await != scanned_path {
+                return;
+            }
+            let count = indexer.lock().await.add_dependency_scan(scan);
+            info!("Indexed public APIs of {} dependency crates", count);
+        });
+    }
+
     fn broadcast_log(&self, message: String) {
         if let Some(ref broadcaster) = self.log_broadcaster {
             broadcaster.log(message);
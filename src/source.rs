@@ -0,0 +1,245 @@
+use crate::parser::SymbolInfo;
+use crate::protocol::{GetSourceParams, GetSourceResponse, SymbolSource};
+use proc_macro2::{Delimiter, LineColumn, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::HashMap;
+use syn::visit::{self, Visit};
+
+/// アイテムの範囲（行は1ベース、列は0ベースの文字単位）
+struct ItemRange {
+    attributes_start: LineColumn,  // 最初の属性・ドキュメントコメント
+    code_start: LineColumn,        // 属性を除いた最初のトークン
+    signature_end: LineColumn,     // 本体の { の直前のトークンの末尾（本体がなければ end）
+    end: LineColumn,
+}
+
+/// シンボルのソーステキストを取得（ファイルは読み直し、定義行・列でアイテムを特定する）
+pub fn get_source(symbols: &[&SymbolInfo], params: &GetSourceParams) -> GetSourceResponse {
+    let mut files: HashMap<&str, Option<(String, Option<syn::File>)>> = HashMap::new();
+    let mut sources = Vec::new();
+
+    for symbol in symbols {
+        let Some((content, syntax_tree)) = files.entry(symbol.file_path.as_str())
+            .or_insert_with(|| {
+                let content = std::fs::read_to_string(&symbol.file_path).ok()?;
+                let syntax_tree = syn::parse_file(&content).ok();
+                Some((content, syntax_tree))
+            })
+            .as_ref() else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+
+        // 名前付きクロージャなど構文木から特定できないものは定義行から end_line までを返す
+        let range = syntax_tree.as_ref()
+            .and_then(|tree| find_item_range(tree, symbol.line, symbol.column))
+            .unwrap_or_else(|| {
+                let start = LineColumn { line: symbol.line, column: 0 };
+                let end = LineColumn { line: symbol.end_line, column: usize::MAX };
+                ItemRange { attributes_start: start, code_start: start, signature_end: end, end }
+            });
+        sources.push(build_source(symbol, &lines, &range, params));
+    }
+
+    GetSourceResponse { sources }
+}
+
+fn build_source(symbol: &SymbolInfo, lines: &[&str], range: &ItemRange, params: &GetSourceParams) -> SymbolSource {
+    let start = if params.include_attributes.unwrap_or(false) { range.attributes_start } else { range.code_start };
+    let item_lines = range.end.line + 1 - start.line;
+    // 大きすぎる本体はシグネチャだけにする
+    let signature_only = params.signature_only.unwrap_or(false)
+        || params.max_lines.is_some_and(|max| item_lines > max);
+    let end = if signature_only { range.signature_end } else { range.end };
+
+    let context = params.context_lines.unwrap_or(0);
+    let first_line = start.line.saturating_sub(context).max(1);
+    let last_line = (end.line + context).min(lines.len());
+
+    let mut text = String::new();
+    for line in first_line..=last_line {
+        let Some(source_line) = lines.get(line - 1) else {
+            break;
+        };
+        let chars: Vec<char> = source_line.chars().collect();
+        // 行頭・行末の空白だけならその行は丸ごと含め、同じ行の別のコードは切り落とす
+        let from = if line == start.line && !chars[..start.column.min(chars.len())].iter().all(|c| c.is_whitespace()) {
+            start.column.min(chars.len())
+        } else {
+            0
+        };
+        let to = if line == end.line && context == 0 { end.column.min(chars.len()) } else { chars.len() };
+        let to = if line == end.line && chars[to..].iter().all(|c| c.is_whitespace()) { chars.len() } else { to };
+        text.extend(&chars[from..to.max(from)]);
+        text.push('\n');
+    }
+
+    SymbolSource {
+        name: symbol.name.clone(),
+        symbol_type: symbol.symbol_type.clone(),
+        file_path: symbol.file_path.clone(),
+        scope: symbol.scope.clone(),
+        crate_name: symbol.crate_name.clone(),
+        start_line: first_line,
+        end_line: last_line,
+        item_lines,
+        signature_only,
+        text,
+    }
+}
+
+/// 識別子の位置が (line, column) のアイテムを探し、その範囲を求める
+fn find_item_range(syntax_tree: &syn::File, line: usize, column: usize) -> Option<ItemRange> {
+    let mut finder = ItemFinder { target: LineColumn { line, column }, found: None };
+    finder.visit_file(syntax_tree);
+    finder.found.and_then(token_range)
+}
+
+struct ItemFinder {
+    target: LineColumn,
+    found: Option<TokenStream>,
+}
+
+impl ItemFinder {
+    fn check(&mut self, ident: &syn::Ident, item: &dyn ToTokens) {
+        if self.found.is_none() && ident.span().start() == self.target {
+            self.found = Some(item.to_token_stream());
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ItemFinder {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.check(&item.sig.ident, item);
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.check(&item.sig.ident, item);
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        self.check(&item.sig.ident, item);
+        visit::visit_trait_item_fn(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.check(&item.ident, item);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.check(&item.ident, item);
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.check(&item.ident, item);
+        visit::visit_item_trait(self, item);
+    }
+}
+
+/// アイテムのトークン列から、属性の開始・コードの開始・シグネチャの終わり・全体の終わりを求める
+fn token_range(tokens: TokenStream) -> Option<ItemRange> {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let attributes_start = tokens.first()?.span().start();
+    let end = tokens.last()?.span().end();
+
+    // 外側の属性（`#` と `[...]` の組）を飛ばす
+    let mut index = 0;
+    while let (Some(TokenTree::Punct(punct)), Some(TokenTree::Group(group))) = (tokens.get(index), tokens.get(index + 1)) {
+        if punct.as_char() != '#' || group.delimiter() != Delimiter::Bracket {
+            break;
+        }
+        index += 2;
+    }
+    let code_start = tokens.get(index)?.span().start();
+
+    let signature_end = tokens[index..].iter()
+        .position(|token| matches!(token, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace))
+        .and_then(|body| body.checked_sub(1))
+        .map(|last| tokens[index + last].span().end())
+        .unwrap_or(end);
+
+    Some(ItemRange { attributes_start, code_start, signature_end, end })
+}
+
+/// get_source の結果をテキストに整形（CLI・MCP 用）
+pub fn format_sources(response: &GetSourceResponse) -> String {
+    let mut result = String::new();
+    for source in &response.sources {
+        let name = match &source.scope {
+            Some(scope) => format!("{scope}::{}", source.name),
+            None => source.name.clone(),
+        };
+        let note = if source.signature_only { format!(" (signature only, {} lines in full)", source.item_lines) } else { String::new() };
+        result.push_str(&format!("// {}:{}-{} {}{}\n", source.file_path, source.start_line, source.end_line, name, note));
+        result.push_str(&source.text);
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_get_source() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, r#"use std::fmt;
+
+/// 設定
+#[derive(Debug)]
+pub struct Config {
+    pub name: String,
+}
+
+impl Config {
+    /// 名前を返す
+    #[inline]
+    pub fn name(&self) -> &str
+    where
+        Self: Sized,
+    {
+        &self.name
+    }
+}
+"#).unwrap();
+        let mut parser = RustParser::new();
+        parser.parse_file(&path).unwrap();
+
+        let source = |name: &str, params: GetSourceParams| {
+            let symbols = parser.find_symbol(name, None).unwrap();
+            get_source(&symbols, &params).sources.remove(0)
+        };
+        let params = GetSourceParams {
+            symbol_name: String::new(),
+            symbol_type: None,
+            file_path: None,
+            line: None,
+            include_attributes: None,
+            context_lines: None,
+            signature_only: None,
+            max_lines: None,
+        };
+
+        let config = source("Config", params.clone());
+        assert_eq!(config.text, "pub struct Config {\n    pub name: String,\n}\n");
+        assert_eq!((config.start_line, config.end_line), (5, 7));
+
+        let documented = source("Config", GetSourceParams { include_attributes: Some(true), context_lines: Some(1), ..params.clone() });
+        assert_eq!(documented.text, "\n/// 設定\n#[derive(Debug)]\npub struct Config {\n    pub name: String,\n}\n\n");
+
+        let method = source("name", params.clone());
+        assert_eq!(method.text, "    pub fn name(&self) -> &str\n    where\n        Self: Sized,\n    {\n        &self.name\n    }\n");
+
+        let signature = source("name", GetSourceParams { max_lines: Some(3), ..params });
+        assert!(signature.signature_only);
+        assert_eq!(signature.item_lines, 6);
+        assert_eq!(signature.text, "    pub fn name(&self) -> &str\n    where\n        Self: Sized,\n");
+    }
+}
@@ -0,0 +1,548 @@
+use crate::protocol::{StructuralMatch, StructuralSearchParams, StructuralSearchResponse};
+use crate::rename::FileUpdate;
+use anyhow::{Context, Result, bail};
+use proc_macro2::LineColumn;
+use quote::ToTokens;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::Expr;
+
+/// 返す一致の件数のデフォルト
+pub const DEFAULT_MATCH_LIMIT: usize = 100;
+
+/// パターン中のメタ変数をこの接頭辞の識別子に置き換えてから構文解析する
+const PLACEHOLDER_PREFIX: &str = "__ssr_";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MetaKind {
+    Expr,   // $x: 任意の式
+    Lit,    // $x:lit: リテラル
+    Str,    // $x:str: 文字列リテラル
+    Ident,  // $x:ident: 識別子1つ
+    Rest,   // $..xs: 引数・要素の0個以上の並び
+}
+
+#[derive(Debug, Clone)]
+struct MetaVar {
+    name: Option<String>,  // $_ は None（束縛しない）
+    kind: MetaKind,
+}
+
+/// 構文解析済みのパターン
+pub struct Pattern {
+    expr: Expr,
+    metavars: HashMap<String, MetaVar>,  // プレースホルダー -> メタ変数
+}
+
+/// メタ変数の束縛（比較用のトークン列と、ソース上の範囲）
+#[derive(Clone)]
+struct Binding {
+    tokens: String,
+    range: Option<(LineColumn, LineColumn)>,
+}
+
+type Bindings = BTreeMap<String, Binding>;
+
+/// `$x` などのメタ変数を含む Rust の式をパターンとして解析する
+pub fn parse_pattern(pattern: &str) -> Result<Pattern> {
+    let mut source = String::new();
+    let mut metavars = HashMap::new();
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut index = 0;
+
+    while index < chars.len() {
+        if chars[index] != '$' {
+            source.push(chars[index]);
+            index += 1;
+            continue;
+        }
+        index += 1;
+        let rest = chars[index..].starts_with(&['.', '.']);
+        if rest {
+            index += 2;
+        }
+        let name = take_ident(&chars, &mut index);
+        if name.is_empty() {
+            bail!("Expected a metavariable name after '$' in pattern: {}", pattern);
+        }
+
+        // $x:lit のような制約（:: はパスの区切りなので制約ではない）
+        let mut kind = if rest { MetaKind::Rest } else { MetaKind::Expr };
+        if !rest && chars.get(index) == Some(&':') && chars.get(index + 1) != Some(&':') {
+            let mut after = index + 1;
+            let constraint = take_ident(&chars, &mut after);
+            kind = match constraint.as_str() {
+                "expr" => MetaKind::Expr,
+                "lit" => MetaKind::Lit,
+                "str" => MetaKind::Str,
+                "ident" => MetaKind::Ident,
+                _ => bail!("Unknown metavariable constraint '{}' (available: expr, lit, str, ident)", constraint),
+            };
+            index = after;
+        }
+
+        let placeholder = format!("{PLACEHOLDER_PREFIX}{}", metavars.len());
+        source.push_str(&placeholder);
+        metavars.insert(placeholder, MetaVar { name: (name != "_").then_some(name), kind });
+    }
+
+    let expr = syn::parse_str::<Expr>(&source)
+        .with_context(|| format!("Pattern must be a Rust expression: {}", pattern))?;
+    Ok(Pattern { expr, metavars })
+}
+
+fn take_ident(chars: &[char], index: &mut usize) -> String {
+    let start = *index;
+    while chars.get(*index).is_some_and(|c| c.is_alphanumeric() || *c == '_') {
+        *index += 1;
+    }
+    chars[start..*index].iter().collect()
+}
+
+/// 置換テンプレートの $x・$..xs を束縛したソースで置き換える
+fn render_template(template: &str, bindings: &BTreeMap<String, String>) -> String {
+    let chars: Vec<char> = template.chars().collect();
+    let mut result = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        if chars[index] != '$' {
+            result.push(chars[index]);
+            index += 1;
+            continue;
+        }
+        let mut after = index + 1;
+        if chars[after..].starts_with(&['.', '.']) {
+            after += 2;
+        }
+        let name = take_ident(&chars, &mut after);
+        match bindings.get(&name) {
+            Some(text) => {
+                result.push_str(text);
+                index = after;
+            }
+            None => {
+                result.push('$');
+                index += 1;
+            }
+        }
+    }
+    result
+}
+
+/// テンプレートが参照しているメタ変数がパターンにあるか確かめる
+fn check_template(template: &str, pattern: &Pattern) -> Result<()> {
+    let chars: Vec<char> = template.chars().collect();
+    for (index, _) in chars.iter().enumerate().filter(|(_, c)| **c == '$') {
+        let mut after = index + 1;
+        if chars[after..].starts_with(&['.', '.']) {
+            after += 2;
+        }
+        let name = take_ident(&chars, &mut after);
+        if !name.is_empty() && !pattern.metavars.values().any(|v| v.name.as_deref() == Some(name.as_str())) {
+            bail!("Rewrite template uses ${} which is not bound by the pattern", name);
+        }
+    }
+    Ok(())
+}
+
+impl Pattern {
+    fn metavar(&self, expr: &Expr) -> Option<&MetaVar> {
+        match expr {
+            Expr::Path(path) if path.qself.is_none() => path.path.get_ident()
+                .and_then(|ident| self.metavars.get(&ident.to_string())),
+            _ => None,
+        }
+    }
+
+    fn contains_metavar(&self, tokens: &dyn ToTokens) -> bool {
+        tokens.to_token_stream().to_string().contains(PLACEHOLDER_PREFIX)
+    }
+
+    fn bind(&self, var: &MetaVar, targets: &[&Expr], bindings: &mut Bindings) -> bool {
+        let accepted = match (var.kind, targets) {
+            (MetaKind::Rest, _) => true,
+            (MetaKind::Expr, [_]) => true,
+            (MetaKind::Lit, [Expr::Lit(_)]) => true,
+            (MetaKind::Str, [Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(_), .. })]) => true,
+            (MetaKind::Ident, [Expr::Path(path)]) => path.qself.is_none() && path.path.get_ident().is_some(),
+            _ => false,
+        };
+        if !accepted {
+            return false;
+        }
+        let Some(name) = &var.name else {
+            return true;
+        };
+
+        let tokens = targets.iter().map(|e| e.to_token_stream().to_string()).collect::<Vec<_>>().join(" , ");
+        let range = match (targets.first().and_then(|e| token_range(*e)), targets.last().and_then(|e| token_range(*e))) {
+            (Some((start, _)), Some((_, end))) => Some((start, end)),
+            _ => None,
+        };
+        // 同じメタ変数が複数回出てきたら同じ式でなければならない
+        match bindings.get(name) {
+            Some(existing) => existing.tokens == tokens,
+            None => {
+                bindings.insert(name.clone(), Binding { tokens, range });
+                true
+            }
+        }
+    }
+
+    fn match_expr(&self, pattern: &Expr, target: &Expr, bindings: &mut Bindings) -> bool {
+        if let Some(var) = self.metavar(pattern) {
+            return self.bind(var, &[target], bindings);
+        }
+
+        match (pattern, target) {
+            (Expr::Call(p), Expr::Call(t)) => {
+                self.match_expr(&p.func, &t.func, bindings) && self.match_list(&p.args, &t.args, bindings)
+            }
+            (Expr::MethodCall(p), Expr::MethodCall(t)) => {
+                p.method == t.method
+                    && tokens_eq(&p.turbofish, &t.turbofish)
+                    && self.match_expr(&p.receiver, &t.receiver, bindings)
+                    && self.match_list(&p.args, &t.args, bindings)
+            }
+            (Expr::Path(p), Expr::Path(t)) => p.qself.is_none() && t.qself.is_none() && path_matches(&p.path, &t.path),
+            (Expr::Field(p), Expr::Field(t)) => p.member == t.member && self.match_expr(&p.base, &t.base, bindings),
+            (Expr::Reference(p), Expr::Reference(t)) => {
+                p.mutability.is_some() == t.mutability.is_some() && self.match_expr(&p.expr, &t.expr, bindings)
+            }
+            (Expr::Unary(p), Expr::Unary(t)) => tokens_eq(&p.op, &t.op) && self.match_expr(&p.expr, &t.expr, bindings),
+            (Expr::Binary(p), Expr::Binary(t)) => {
+                tokens_eq(&p.op, &t.op)
+                    && self.match_expr(&p.left, &t.left, bindings)
+                    && self.match_expr(&p.right, &t.right, bindings)
+            }
+            (Expr::Try(p), Expr::Try(t)) => self.match_expr(&p.expr, &t.expr, bindings),
+            (Expr::Await(p), Expr::Await(t)) => self.match_expr(&p.base, &t.base, bindings),
+            (Expr::Index(p), Expr::Index(t)) => {
+                self.match_expr(&p.expr, &t.expr, bindings) && self.match_expr(&p.index, &t.index, bindings)
+            }
+            (Expr::Paren(p), Expr::Paren(t)) => self.match_expr(&p.expr, &t.expr, bindings),
+            (Expr::Tuple(p), Expr::Tuple(t)) => self.match_list(&p.elems, &t.elems, bindings),
+            (Expr::Array(p), Expr::Array(t)) => self.match_list(&p.elems, &t.elems, bindings),
+            (Expr::Cast(p), Expr::Cast(t)) => tokens_eq(&p.ty, &t.ty) && self.match_expr(&p.expr, &t.expr, bindings),
+            (Expr::Macro(p), Expr::Macro(t)) => {
+                if !path_matches(&p.mac.path, &t.mac.path) {
+                    return false;
+                }
+                // 本体がカンマ区切りの式なら式として比べる（format!("{}", $x) など）
+                let parse = |mac: &syn::Macro| mac.parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated).ok();
+                match (parse(&p.mac), parse(&t.mac)) {
+                    (Some(p_args), Some(t_args)) => self.match_list(&p_args, &t_args, bindings),
+                    _ => tokens_eq(&p.mac.tokens, &t.mac.tokens),
+                }
+            }
+            // それ以外の式はメタ変数を含まない場合にトークン列で比べる
+            _ => !self.contains_metavar(pattern) && tokens_eq(pattern, target),
+        }
+    }
+
+    fn match_list(&self, patterns: &Punctuated<Expr, syn::Token![,]>, targets: &Punctuated<Expr, syn::Token![,]>, bindings: &mut Bindings) -> bool {
+        let patterns: Vec<&Expr> = patterns.iter().collect();
+        let targets: Vec<&Expr> = targets.iter().collect();
+
+        let rest = patterns.iter().position(|p| self.metavar(p).is_some_and(|v| v.kind == MetaKind::Rest));
+        let Some(rest) = rest else {
+            return patterns.len() == targets.len()
+                && patterns.iter().zip(&targets).all(|(p, t)| self.match_expr(p, t, bindings));
+        };
+
+        let (before, after) = (&patterns[..rest], &patterns[rest + 1..]);
+        if targets.len() < before.len() + after.len() {
+            return false;
+        }
+        let middle_end = targets.len() - after.len();
+        before.iter().zip(&targets[..before.len()]).all(|(p, t)| self.match_expr(p, t, bindings))
+            && after.iter().zip(&targets[middle_end..]).all(|(p, t)| self.match_expr(p, t, bindings))
+            && self.metavar(patterns[rest]).is_some_and(|var| self.bind(var, &targets[before.len()..middle_end], bindings))
+    }
+}
+
+fn tokens_eq(a: &dyn ToTokens, b: &dyn ToTokens) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+/// パターンのパスが対象のパスの末尾に一致するか（HashMap::new は std::collections::HashMap::new にも一致）
+fn path_matches(pattern: &syn::Path, target: &syn::Path) -> bool {
+    let segments = |path: &syn::Path| path.segments.iter().map(|s| s.to_token_stream().to_string()).collect::<Vec<_>>();
+    let (pattern_segments, target_segments) = (segments(pattern), segments(target));
+    (pattern.leading_colon.is_none() || target.leading_colon.is_some()) && target_segments.ends_with(&pattern_segments)
+}
+
+/// トークン列の最初と最後の位置
+fn token_range(tokens: &dyn ToTokens) -> Option<(LineColumn, LineColumn)> {
+    let tokens: Vec<proc_macro2::TokenTree> = tokens.to_token_stream().into_iter().collect();
+    Some((tokens.first()?.span().start(), tokens.last()?.span().end()))
+}
+
+/// 式ごとにパターンとの一致を試す
+struct MatchCollector<'a> {
+    pattern: &'a Pattern,
+    found: Vec<((LineColumn, LineColumn), Bindings)>,
+}
+
+impl MatchCollector<'_> {
+    fn try_match(&mut self, expr: &Expr) {
+        let mut bindings = Bindings::new();
+        if self.pattern.match_expr(&self.pattern.expr, expr, &mut bindings)
+            && let Some(range) = token_range(expr) {
+            self.found.push((range, bindings));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for MatchCollector<'_> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.try_match(expr);
+        visit::visit_expr(self, expr);
+    }
+
+    fn visit_stmt_macro(&mut self, stmt: &'ast syn::StmtMacro) {
+        // 文として書かれたマクロ（println!(...); など）も式として比べる
+        self.try_match(&Expr::Macro(syn::ExprMacro { attrs: Vec::new(), mac: stmt.mac.clone() }));
+        visit::visit_stmt_macro(self, stmt);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// 行頭のバイト位置の一覧
+fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0).chain(content.match_indices('\n').map(|(i, _)| i + 1)).collect()
+}
+
+/// (行, 文字単位の列) をバイト位置に変換
+fn byte_offset(content: &str, line_starts: &[usize], position: LineColumn) -> Option<usize> {
+    let start = *line_starts.get(position.line.checked_sub(1)?)?;
+    let line = &content[start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    if position.column == line.chars().count() {
+        return Some(start + line.len());
+    }
+    line.char_indices().nth(position.column).map(|(i, _)| start + i)
+}
+
+fn slice(content: &str, line_starts: &[usize], (start, end): (LineColumn, LineColumn)) -> Option<String> {
+    let from = byte_offset(content, line_starts, start)?;
+    let to = byte_offset(content, line_starts, end)?;
+    content.get(from..to).map(|s| s.to_string())
+}
+
+/// ファイルからパターンに一致する式を探す（limit は適用しない）
+pub fn search(files: &[PathBuf], params: &StructuralSearchParams) -> Result<StructuralSearchResponse> {
+    let pattern = parse_pattern(&params.pattern)?;
+    if let Some(template) = &params.rewrite {
+        check_template(template, &pattern)?;
+    }
+
+    let mut matches = Vec::new();
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let Ok(syntax_tree) = syn::parse_file(&content) else {
+            continue;
+        };
+        let mut collector = MatchCollector { pattern: &pattern, found: Vec::new() };
+        collector.visit_file(&syntax_tree);
+
+        let starts = line_starts(&content);
+        let file_path = file.to_string_lossy().to_string();
+        for (range, bindings) in collector.found {
+            let Some(text) = slice(&content, &starts, range) else {
+                continue;
+            };
+            let bindings: BTreeMap<String, String> = bindings.into_iter()
+                .map(|(name, binding)| {
+                    let text = binding.range.and_then(|r| slice(&content, &starts, r)).unwrap_or_default();
+                    (name, text)
+                })
+                .collect();
+            let replacement = params.rewrite.as_ref().map(|template| render_template(template, &bindings));
+            matches.push(StructuralMatch {
+                file_path: file_path.clone(),
+                line: range.0.line,
+                column: range.0.column,
+                end_line: range.1.line,
+                end_column: range.1.column,
+                text,
+                bindings,
+                replacement,
+            });
+        }
+    }
+    matches.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)).then(a.column.cmp(&b.column)));
+    // マクロの本体を式として読むと同じ式を2回見ることがある
+    matches.dedup_by(|a, b| a.file_path == b.file_path && a.line == b.line && a.column == b.column && a.end_line == b.end_line && a.end_column == b.end_column);
+
+    Ok(StructuralSearchResponse {
+        pattern: params.pattern.clone(),
+        total_matches: matches.len(),
+        matches,
+        applied: false,
+        skipped_overlapping: 0,
+    })
+}
+
+/// 置換をファイルに書き込み、(書き換えたファイル, 重なっていて置換しなかった件数) を返す
+///
+/// 入れ子の一致は外側だけを置換する。元のテキストが変わっていたり書き込みに失敗したりしたら、
+/// どのファイルも書き換えずにエラーにする。
+pub fn apply_replacements(matches: &[StructuralMatch]) -> Result<(Vec<PathBuf>, usize)> {
+    let mut by_file: BTreeMap<&str, Vec<&StructuralMatch>> = BTreeMap::new();
+    for m in matches.iter().filter(|m| m.replacement.is_some()) {
+        by_file.entry(m.file_path.as_str()).or_default().push(m);
+    }
+
+    let mut skipped = 0;
+    let mut updated = Vec::new();
+    for (file_path, file_matches) in by_file {
+        let content = std::fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read {}", file_path))?;
+        let starts = line_starts(&content);
+
+        // 開始位置順に見て、直前に採用した一致の中にあるものは飛ばす
+        let mut ranges: Vec<(std::ops::Range<usize>, &str)> = Vec::new();
+        for m in file_matches {
+            let start = LineColumn { line: m.line, column: m.column };
+            let end = LineColumn { line: m.end_line, column: m.end_column };
+            let range = byte_offset(&content, &starts, start).zip(byte_offset(&content, &starts, end))
+                .map(|(from, to)| from..to)
+                .filter(|range| content.get(range.clone()) == Some(m.text.as_str()));
+            let Some(range) = range else {
+                bail!("{}:{}:{} no longer matches; re-run the search", file_path, m.line, m.column + 1);
+            };
+            if ranges.last().is_some_and(|(last, _)| range.start < last.end) {
+                skipped += 1;
+                continue;
+            }
+            ranges.push((range, m.replacement.as_deref().unwrap_or_default()));
+        }
+
+        let mut new_content = content.clone();
+        for (range, replacement) in ranges.into_iter().rev() {
+            new_content.replace_range(range, replacement);
+        }
+        updated.push(FileUpdate { path: PathBuf::from(file_path), original: content, content: new_content });
+    }
+
+    crate::rename::write_files(&updated)?;
+    Ok((updated.into_iter().map(|update| update.path).collect(), skipped))
+}
+
+/// structural_search の結果をテキストに整形（CLI・MCP 用）
+pub fn format_matches(response: &StructuralSearchResponse) -> String {
+    let mut result = format!("{} match(es) for `{}`", response.total_matches, response.pattern);
+    if response.matches.len() < response.total_matches {
+        result.push_str(&format!(" (showing {})", response.matches.len()));
+    }
+    if response.applied {
+        result.push_str(" (applied");
+        if response.skipped_overlapping > 0 {
+            result.push_str(&format!(", {} overlapping match(es) skipped", response.skipped_overlapping));
+        }
+        result.push(')');
+    }
+    result.push('\n');
+
+    for m in &response.matches {
+        result.push_str(&format!("\n{}:{}:{}\n", m.file_path, m.line, m.column + 1));
+        for line in m.text.lines() {
+            result.push_str(&format!("  - {}\n", line));
+        }
+        if let Some(replacement) = &m.replacement {
+            for line in replacement.lines() {
+                result.push_str(&format!("  + {}\n", line));
+            }
+        }
+        if !m.bindings.is_empty() {
+            let bindings: Vec<String> = m.bindings.iter().map(|(name, text)| format!("${name} = {text}")).collect();
+            result.push_str(&format!("  {}\n", bindings.join(", ")));
+        }
+    }
+    result
+}
+
+/// 検索対象のファイル（file_path の指定があればそれだけ）
+pub fn target_files<'a>(files: impl Iterator<Item = &'a Path>, file_path: Option<&str>) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = files
+        .filter(|path| file_path.is_none_or(|wanted| path.to_string_lossy() == wanted || path.ends_with(wanted)))
+        .map(|path| path.to_path_buf())
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_structural_search() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, r#"use std::collections::HashMap;
+
+fn run(config: Option<Config>) {
+    let map: HashMap<String, u32> = HashMap::new();
+    let other = std::collections::HashMap::<u8, u8>::new();
+    let name = config.unwrap().name.unwrap();
+    log("init", "ready");
+    log(level, "started");
+    log("x", name);
+    println!("{} {}", map.len(), lookup(&map, "key").unwrap());
+    same(a, a);
+    same(a, b);
+}
+"#).unwrap();
+        let files = vec![path.clone()];
+        let params = |pattern: &str, rewrite: Option<&str>| StructuralSearchParams {
+            pattern: pattern.to_string(),
+            rewrite: rewrite.map(|s| s.to_string()),
+            file_path: None,
+            limit: None,
+            apply: None,
+        };
+        let found = |pattern: &str| -> Vec<String> {
+            search(&files, &params(pattern, None)).unwrap().matches.into_iter().map(|m| m.text).collect()
+        };
+
+        assert_eq!(found("HashMap::new()"), vec!["HashMap::new()"]);
+        assert_eq!(found("log($level, $msg:str)"), vec![r#"log("init", "ready")"#, r#"log(level, "started")"#]);
+        assert_eq!(found("log($_:lit, $_:ident)"), vec![r#"log("x", name)"#]);
+        assert_eq!(found("same($x, $x)"), vec!["same(a, a)"]);
+        assert_eq!(found("println!($..args)").len(), 1);
+        assert!(parse_pattern("foo($x:float)").is_err());
+
+        let unwraps = search(&files, &params("$x.unwrap()", Some("$x.expect(\"TODO\")"))).unwrap();
+        let texts: Vec<(&str, Option<&str>)> = unwraps.matches.iter().map(|m| (m.text.as_str(), m.replacement.as_deref())).collect();
+        assert_eq!(texts, vec![
+            ("config.unwrap().name.unwrap()", Some("config.unwrap().name.expect(\"TODO\")")),
+            ("config.unwrap()", Some("config.expect(\"TODO\")")),
+            (r#"lookup(&map, "key").unwrap()"#, Some(r#"lookup(&map, "key").expect("TODO")"#)),
+        ]);
+        assert_eq!(unwraps.matches[2].bindings["x"], r#"lookup(&map, "key")"#);
+        assert!(search(&files, &params("$x.unwrap()", Some("$y"))).is_err());
+
+        // 入れ子の一致は外側だけ置換する
+        let (updated, skipped) = apply_replacements(&unwraps.matches).unwrap();
+        assert_eq!((updated.len(), skipped), (1, 1));
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("let name = config.unwrap().name.expect(\"TODO\");"));
+        assert!(content.contains(r#"lookup(&map, "key").expect("TODO"));"#));
+        assert!(apply_replacements(&unwraps.matches).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::parser::{RustParser, TestInfo};
+use std::collections::{HashSet, VecDeque};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+
+/// find_tests_for で max_depth 未指定時に辿る呼び出しの深さ
+pub const DEFAULT_TEST_SEARCH_DEPTH: usize = 10;
+
+/// ドキュメントコメント中の Rust コードブロック（doctest）
+#[derive(Debug, Clone)]
+pub struct DocCodeBlock {
+    pub line: usize,               // 開始フェンスの行
+    pub end_line: usize,           // 終了フェンスの行
+    pub code: String,
+    pub source_lines: Vec<usize>,  // コードの各行に対応するソースの行
+}
+
+/// テストを探した結果（test から対象シンボルまでの呼び出し経路付き）
+#[derive(Debug, Clone)]
+pub struct TestMatch<'a> {
+    pub test: &'a TestInfo,
+    pub depth: usize,            // テストから対象シンボルまでの呼び出し段数
+    pub call_path: Vec<String>,  // テスト → ... → 対象シンボル
+}
+
+/// テスト関数の属性名を返す（#[test] / #[tokio::test] / #[rstest] など）
+pub fn test_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter()
+        .map(|attr| attr.path())
+        .find(|path| path.segments.last()
+            .is_some_and(|seg| matches!(seg.ident.to_string().as_str(), "test" | "rstest" | "test_case")))
+        .map(|path| path.segments.iter()
+            .map(|seg| seg.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::"))
+}
+
+/// 読み取り中のコードブロック（開始行, Rust か, (ソース行, コード行)）
+type OpenCodeBlock = (usize, bool, Vec<(usize, String)>);
+
+/// ドキュメントコメントから doctest として実行される Rust コードブロックを取り出す
+pub fn doc_code_blocks(attrs: &[syn::Attribute]) -> Vec<DocCodeBlock> {
+    // (ソース行, テキスト) に展開（/** */ は複数行を含む）
+    let mut doc_lines: Vec<(usize, String)> = Vec::new();
+    for attr in attrs {
+        if let syn::Meta::NameValue(name_value) = &attr.meta
+            && name_value.path.is_ident("doc")
+            && let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) = &name_value.value {
+            let start_line = lit.span().start().line;
+            for (i, text) in lit.value().lines().enumerate() {
+                doc_lines.push((start_line + i, text.to_string()));
+            }
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut current: Option<OpenCodeBlock> = None;
+
+    for (line, text) in doc_lines {
+        let trimmed = text.trim();
+        let Some(info) = trimmed.strip_prefix("```") else {
+            if let Some((_, _, code_lines)) = current.as_mut() {
+                code_lines.push((line, unhide_doc_line(&text)));
+            }
+            continue;
+        };
+
+        match current.take() {
+            None => current = Some((line, is_rust_code_block(info), Vec::new())),
+            Some((start, true, code_lines)) => blocks.push(DocCodeBlock {
+                line: start,
+                end_line: line,
+                code: code_lines.iter().map(|(_, l)| l.as_str()).collect::<Vec<_>>().join("\n"),
+                source_lines: code_lines.iter().map(|(l, _)| *l).collect(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    blocks
+}
+
+/// フェンスの info string から、実行される Rust コードか判定
+fn is_rust_code_block(info: &str) -> bool {
+    info.split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .all(|token| matches!(token, "rust" | "should_panic" | "no_run" | "standalone_crate") || token.starts_with("edition"))
+}
+
+/// 先頭の空白1つと、doctest の隠し行マーカー（"# "）を取り除く
+fn unhide_doc_line(text: &str) -> String {
+    let text = text.strip_prefix(' ').unwrap_or(text);
+    let trimmed = text.trim_start();
+    if trimmed == "#" {
+        String::new()
+    } else if let Some(rest) = trimmed.strip_prefix("# ") {
+        rest.to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// doctest のコードから呼び出している関数・メソッド名を取り出す（名前, ソース行, 列）
+pub fn doctest_calls(block: &DocCodeBlock) -> Vec<(String, usize, usize, bool)> {
+    // fn main がなければ rustdoc と同様に本体として包む
+    let (parsed, line_offset) = if block.code.contains("fn main") {
+        (syn::parse_file(&block.code).map(|file| file.items.into_iter().map(syn::Stmt::Item).collect()), 0)
+    } else {
+        (syn::parse_str::<syn::Block>(&format!("{{\n{}\n}}", block.code)).map(|b| b.stmts), 1)
+    };
+    let Ok(stmts) = parsed else {
+        return Vec::new();
+    };
+
+    let mut collector = CallNameCollector { calls: Vec::new() };
+    for stmt in &stmts {
+        collector.visit_stmt(stmt);
+    }
+
+    collector.calls.into_iter()
+        .filter_map(|(name, span, method_call)| {
+            let start = span.start();
+            let source_line = *block.source_lines.get(start.line.checked_sub(line_offset + 1)?)?;
+            Some((name, source_line, start.column, method_call))
+        })
+        .collect()
+}
+
+/// 呼び出し名を集める（doctest はクレート名付きのパスで呼ぶため、パスの末尾を関数名とする）
+struct CallNameCollector {
+    calls: Vec<(String, proc_macro2::Span, bool)>,  // (名前, 位置, メソッド呼び出しか)
+}
+
+impl<'ast> Visit<'ast> for CallNameCollector {
+    fn visit_expr_call(&mut self, call_expr: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path_expr) = &*call_expr.func
+            && let Some(segment) = path_expr.path.segments.last() {
+            self.calls.push((segment.ident.to_string(), segment.ident.span(), false));
+        }
+        visit::visit_expr_call(self, call_expr);
+    }
+
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        self.calls.push((method_call.method.to_string(), method_call.method.span(), true));
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Ok(args) = mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// 逆コールグラフを辿り、指定シンボルを推移的に呼び出しているテストを探す
+pub fn find_tests_for<'a>(parser: &'a RustParser, symbol_name: &str, max_depth: usize) -> Vec<TestMatch<'a>> {
+    let mut results: Vec<TestMatch<'a>> = Vec::new();
+    let mut visited: HashSet<String> = HashSet::from([symbol_name.to_string()]);
+    let mut queue: VecDeque<Vec<String>> = VecDeque::from([vec![symbol_name.to_string()]]);
+
+    while let Some(path) = queue.pop_front() {
+        if path.len() > max_depth {
+            continue;
+        }
+
+        for call in parser.get_calls_to_function(&path[0]) {
+            let mut call_path = vec![call.caller.clone()];
+            call_path.extend(path.iter().cloned());
+
+            // 同名の関数と区別するため、定義位置まで一致するテストのみ
+            let test = parser.get_tests().iter().find(|t| {
+                t.name == call.caller && t.file_path == call.caller_file && t.line == call.caller_line
+            });
+            if let Some(test) = test {
+                if !results.iter().any(|m| std::ptr::eq(m.test, test)) {
+                    results.push(TestMatch { test, depth: path.len(), call_path });
+                }
+                continue;
+            }
+
+            if visited.insert(call.caller.clone()) {
+                queue.push_back(call_path);
+            }
+        }
+    }
+
+    results.sort_by(|a, b| {
+        a.depth.cmp(&b.depth)
+            .then(a.test.file_path.cmp(&b.test.file_path))
+            .then(a.test.line.cmp(&b.test.line))
+    });
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::TestKind;
+
+    #[test]
+    fn test_find_tests_for() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/lib.rs", r#"
+/// 2倍にする
+///
+/// ```
+/// # use my_crate::double;
+/// assert_eq!(my_crate::double(2), 4);
+/// ```
+///
+/// ```text
+/// not_code(1)
+/// ```
+pub fn double(x: i32) -> i32 {
+    x * 2
+}
+
+pub fn quadruple(x: i32) -> i32 {
+    double(double(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quadruple() {
+        assert_eq!(quadruple(1), 4);
+    }
+
+    #[tokio::test]
+    async fn test_unrelated() {}
+}
+"#).unwrap();
+        parser.parse_source("tests/integration.rs", "#[test]\nfn it_doubles() {\n    helper();\n}\n\nfn helper() {\n    double(1);\n}\n").unwrap();
+
+        let tests = parser.get_tests();
+        assert_eq!(tests.len(), 4);
+        let doctest = tests.iter().find(|t| t.kind == TestKind::Doctest).unwrap();
+        assert_eq!((doctest.line, doctest.end_line), (4, 7));
+        assert!(tests.iter().any(|t| t.name == "test_unrelated" && t.attribute.as_deref() == Some("tokio::test")));
+        assert!(tests.iter().any(|t| t.name == "it_doubles" && t.kind == TestKind::Integration));
+
+        let matches = find_tests_for(&parser, "double", DEFAULT_TEST_SEARCH_DEPTH);
+        let found: Vec<(&str, usize)> = matches.iter().map(|m| (m.test.name.as_str(), m.depth)).collect();
+        assert_eq!(found, vec![("doctest_double_4", 1), ("test_quadruple", 2), ("it_doubles", 2)]);
+        assert_eq!(matches[1].call_path, vec!["test_quadruple", "quadruple", "double"]);
+
+        assert_eq!(find_tests_for(&parser, "double", 1).len(), 1);
+    }
+}
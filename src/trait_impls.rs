@@ -0,0 +1,248 @@
+use crate::parser::{ImplInfo, RustParser};
+use crate::protocol::{
+    FindImplementationsParams, FindImplementationsResponse, GetImplMatrixParams, GetImplMatrixResponse, ImplMatrixRow,
+    ImplSource, Implementation, SymbolType,
+};
+use proc_macro2::Span;
+use std::collections::{BTreeMap, HashMap};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+
+/// #[derive(...)] と #[cfg_attr(..., derive(...))] で実装されるトレイトのパスと属性の位置
+pub fn derived_traits(attrs: &[syn::Attribute]) -> Vec<(String, Span)> {
+    let mut traits = Vec::new();
+    for attr in attrs {
+        if attr.path().is_ident("derive") {
+            if let Ok(paths) = attr.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated) {
+                traits.extend(paths.iter().map(|path| (path_string(path), attr.span())));
+            }
+        } else if attr.path().is_ident("cfg_attr")
+            && let Ok(metas) = attr.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated) {
+            // 先頭は条件なので飛ばす
+            for meta in metas.iter().skip(1) {
+                if let syn::Meta::List(list) = meta
+                    && list.path.is_ident("derive")
+                    && let Ok(paths) = list.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated) {
+                    traits.extend(paths.iter().map(|path| (path_string(path), attr.span())));
+                }
+            }
+        }
+    }
+    traits
+}
+
+pub fn path_string(path: &syn::Path) -> String {
+    path.segments.iter().map(|s| s.ident.to_string()).collect::<Vec<_>>().join("::")
+}
+
+/// トレイトがクエリに一致するか（"::" を含むクエリはパスの末尾一致、含まなければ名前で比較）
+fn trait_matches(info: &ImplInfo, query: &str) -> bool {
+    if query.contains("::") {
+        info.trait_path == query || info.trait_path.ends_with(&format!("::{query}"))
+    } else {
+        info.trait_name == query
+    }
+}
+
+/// トレイトを実装している型（derive による実装を含む）
+pub fn find_implementations(parser: &RustParser, params: &FindImplementationsParams) -> FindImplementationsResponse {
+    let include_tests = params.include_tests.unwrap_or(false);
+
+    let mut implementations: Vec<Implementation> = parser.get_impls().iter()
+        .filter(|info| include_tests || !info.is_test)
+        .filter(|info| trait_matches(info, &params.trait_name))
+        .map(|info| Implementation {
+            trait_name: info.trait_name.clone(),
+            trait_path: info.trait_path.clone(),
+            type_name: info.type_name.clone(),
+            file_path: info.file_path.clone(),
+            line: info.line,
+            source: info.source,
+        })
+        .collect();
+    implementations.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)).then(a.type_name.cmp(&b.type_name)));
+
+    FindImplementationsResponse { trait_name: params.trait_name.clone(), implementations }
+}
+
+/// 構造体・列挙型ごとに、derive と impl ブロックのどちらでトレイトを実装しているかの表
+pub fn impl_matrix(parser: &RustParser, params: &GetImplMatrixParams) -> GetImplMatrixResponse {
+    let include_tests = params.include_tests.unwrap_or(false);
+
+    let mut rows: Vec<ImplMatrixRow> = parser.get_all_symbols().values()
+        .flatten()
+        .filter(|s| matches!(s.symbol_type, SymbolType::Struct | SymbolType::Enum) && s.crate_name.is_none())
+        .filter(|s| include_tests || !s.is_test)
+        .map(|s| ImplMatrixRow {
+            type_name: s.name.clone(),
+            symbol_type: s.symbol_type.clone(),
+            file_path: s.file_path.clone(),
+            line: s.line,
+            scope: s.scope.clone(),
+            traits: BTreeMap::new(),
+        })
+        .collect();
+    rows.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+
+    let mut rows_by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, row) in rows.iter().enumerate() {
+        rows_by_name.entry(row.type_name.clone()).or_default().push(index);
+    }
+
+    // 型は名前で引き、同名の型が複数あれば実装と同じファイルのものを優先する
+    for info in parser.get_impls().iter().filter(|info| include_tests || !info.is_test) {
+        let Some(candidates) = rows_by_name.get(&info.type_name) else {
+            continue;
+        };
+        let same_file: Vec<usize> = candidates.iter().copied().filter(|&i| rows[i].file_path == info.file_path).collect();
+        let targets = if same_file.is_empty() { candidates.clone() } else { same_file };
+        for index in targets {
+            rows[index].traits.entry(info.trait_name.clone())
+                .and_modify(|source| *source = (*source).min(info.source))
+                .or_insert(info.source);
+        }
+    }
+
+    rows.retain(|row| params.file_path.as_deref().is_none_or(|path| row.file_path == path));
+
+    let traits = match &params.traits {
+        Some(traits) => traits.iter()
+            .map(|t| t.rsplit("::").next().unwrap_or(t).to_string())
+            .collect(),
+        None => {
+            // 実装している型の多い順
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for name in rows.iter().flat_map(|row| row.traits.keys()) {
+                *counts.entry(name.as_str()).or_default() += 1;
+            }
+            let mut traits: Vec<(&str, usize)> = counts.into_iter().collect();
+            traits.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            traits.into_iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>()
+        }
+    };
+    for row in &mut rows {
+        row.traits.retain(|name, _| traits.contains(name));
+    }
+
+    GetImplMatrixResponse { traits, rows }
+}
+
+pub fn source_label(source: ImplSource) -> &'static str {
+    match source {
+        ImplSource::Derive => "derive",
+        ImplSource::Manual => "impl",
+    }
+}
+
+/// find_implementations の結果をテキストに整形（CLI 用）
+pub fn format_implementations(report: &FindImplementationsResponse) -> String {
+    let derived = report.implementations.iter().filter(|i| i.source == ImplSource::Derive).count();
+    let mut result = format!(
+        "Implementations of {}: {} ({} derived, {} manual)\n",
+        report.trait_name, report.implementations.len(), derived, report.implementations.len() - derived,
+    );
+    for implementation in &report.implementations {
+        result.push_str(&format!(
+            "  {}:{} {} ({} {})\n",
+            implementation.file_path, implementation.line, implementation.type_name,
+            source_label(implementation.source), implementation.trait_path,
+        ));
+    }
+    result
+}
+
+/// get_impl_matrix の結果を表に整形（CLI 用）
+pub fn format_impl_matrix(report: &GetImplMatrixResponse) -> String {
+    let mut result = format!(
+        "Trait matrix: {} type(s) x {} trait(s) (D = derive, I = impl)\n\n",
+        report.rows.len(), report.traits.len(),
+    );
+    let name_width = report.rows.iter().map(|row| row.type_name.len()).max().unwrap_or(0).max("Type".len());
+
+    result.push_str(&format!("{:name_width$}", "Type"));
+    for name in &report.traits {
+        result.push_str(&format!("  {name}"));
+    }
+    result.push_str("  Location\n");
+
+    for row in &report.rows {
+        result.push_str(&format!("{:name_width$}", row.type_name));
+        for name in &report.traits {
+            let mark = match row.traits.get(name) {
+                Some(ImplSource::Derive) => "D",
+                Some(ImplSource::Manual) => "I",
+                None => "",
+            };
+            result.push_str(&format!("  {mark:width$}", width = name.len()));
+        }
+        result.push_str(&format!("  {}:{}\n", row.file_path, row.line));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impl_matrix() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/protocol.rs", r#"
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerRequest {
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Kind {
+    A,
+}
+
+pub struct Handle;
+
+impl Clone for Handle {
+    fn clone(&self) -> Self { Handle }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "A") }
+}
+
+impl<T> Clone for Wrapper<T> {
+    fn clone(&self) -> Self { todo!() }
+}
+"#).unwrap();
+
+        let clones = find_implementations(&parser, &FindImplementationsParams { trait_name: "Clone".to_string(), include_tests: None });
+        let found: Vec<(&str, ImplSource)> = clones.implementations.iter().map(|i| (i.type_name.as_str(), i.source)).collect();
+        assert_eq!(found, vec![
+            ("ServerRequest", ImplSource::Derive),
+            ("Kind", ImplSource::Derive),
+            ("Handle", ImplSource::Manual),
+            ("Wrapper", ImplSource::Manual),
+        ]);
+        let serialize = find_implementations(&parser, &FindImplementationsParams { trait_name: "serde::Serialize".to_string(), include_tests: None });
+        assert_eq!(serialize.implementations.len(), 1);
+        assert_eq!(serialize.implementations[0].type_name, "Kind");
+
+        let matrix = impl_matrix(&parser, &GetImplMatrixParams { file_path: None, traits: None, include_tests: None });
+        assert_eq!(&matrix.traits[..3], ["Clone", "Debug", "Serialize"]);
+        let kinds: Vec<(&str, Vec<(&str, ImplSource)>)> = matrix.rows.iter()
+            .map(|row| (row.type_name.as_str(), row.traits.iter().map(|(t, s)| (t.as_str(), *s)).collect()))
+            .collect();
+        assert_eq!(kinds, vec![
+            ("ServerRequest", vec![("Clone", ImplSource::Derive), ("Debug", ImplSource::Derive), ("Deserialize", ImplSource::Derive), ("Serialize", ImplSource::Derive)]),
+            ("Kind", vec![("Clone", ImplSource::Derive), ("Copy", ImplSource::Derive), ("Debug", ImplSource::Derive), ("Display", ImplSource::Manual), ("PartialEq", ImplSource::Derive), ("Serialize", ImplSource::Derive)]),
+            ("Handle", vec![("Clone", ImplSource::Manual)]),
+        ]);
+
+        let selected = impl_matrix(&parser, &GetImplMatrixParams { file_path: None, traits: Some(vec!["fmt::Display".to_string()]), include_tests: None });
+        assert_eq!(selected.traits, vec!["Display"]);
+        assert_eq!(selected.rows[1].traits.len(), 1);
+        assert!(selected.rows[0].traits.is_empty());
+    }
+}
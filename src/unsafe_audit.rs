@@ -0,0 +1,185 @@
+use crate::parser::UnsafeInfo;
+use crate::protocol::{UnsafeKind, UnsafeReportParams, UnsafeReportResponse, UnsafeSite, UnsafeSummary};
+
+/// unsafe キーワードの直前にある `// SAFETY:` コメントを探す（line は1ベース、column は0ベース）
+///
+/// 同じ行の `/* SAFETY: */` と、空行を挟まずに続く直前のコメント・属性行を対象とする。
+/// unsafe fn / unsafe trait のドキュメントの `# Safety` セクションも SAFETY コメントとみなす。
+pub fn find_safety_comment(lines: &[&str], line: usize, column: usize) -> Option<String> {
+    let current = lines.get(line.checked_sub(1)?)?;
+    let before: String = current.chars().take(column).collect();
+    if let Some(pos) = before.find("/*")
+        && is_safety_text(&before[pos..]) {
+        return Some(before[pos..].trim().to_string());
+    }
+
+    let mut in_block_comment = false;
+    for text in lines[..line - 1].iter().rev() {
+        let trimmed = text.trim();
+        // 上に向かって読むので、"*/" で始まり "/*" で終わるブロックコメント
+        let is_comment = if in_block_comment || trimmed.ends_with("*/") {
+            in_block_comment = !trimmed.contains("/*");
+            true
+        } else if trimmed.starts_with("//") {
+            true
+        } else if trimmed.starts_with("#[") {
+            continue;
+        } else {
+            false
+        };
+
+        if !is_comment {
+            break;
+        }
+        if is_safety_text(trimmed) {
+            return Some(trimmed.to_string());
+        }
+    }
+
+    None
+}
+
+/// "SAFETY:"（大文字小文字は問わない）か、ドキュメントの "# Safety" 見出しを含むか
+fn is_safety_text(text: &str) -> bool {
+    let upper = text.to_ascii_uppercase();
+    upper.contains("SAFETY:") || (text.starts_with("//") && upper.trim_start_matches('/').trim() == "# SAFETY")
+}
+
+/// unsafe なコードの一覧をフィルタしてレポートにまとめる（ファイル・行順）
+pub fn build_report<'a>(sites: impl IntoIterator<Item = &'a UnsafeInfo>, params: &UnsafeReportParams) -> UnsafeReportResponse {
+    let undocumented_only = params.undocumented_only.unwrap_or(false);
+    let include_tests = params.include_tests.unwrap_or(true);
+
+    let mut sites: Vec<UnsafeSite> = sites.into_iter()
+        .filter(|site| params.file_path.as_deref().is_none_or(|path| site.file_path == path))
+        .filter(|site| include_tests || !site.is_test)
+        .filter(|site| !undocumented_only || site.safety_comment.is_none())
+        .map(UnsafeSite::from)
+        .collect();
+    sites.sort_by(|a, b| {
+        a.file_path.cmp(&b.file_path)
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+
+    let mut summary = UnsafeSummary { total: sites.len(), ..Default::default() };
+    for site in &sites {
+        if site.has_safety_comment {
+            summary.documented += 1;
+        } else {
+            summary.undocumented += 1;
+        }
+        match site.kind {
+            UnsafeKind::Function => summary.functions += 1,
+            UnsafeKind::Impl => summary.impls += 1,
+            UnsafeKind::Trait => summary.traits += 1,
+            UnsafeKind::Block => summary.blocks += 1,
+        }
+    }
+
+    UnsafeReportResponse { sites, summary }
+}
+
+/// レポートをファイルごとのテキストに整形（CLI 用）
+pub fn format_text_report(report: &UnsafeReportResponse) -> String {
+    let summary = &report.summary;
+    let mut result = format!(
+        "Unsafe inventory: {} site(s), {} documented, {} missing SAFETY comment\n  functions: {}, impls: {}, traits: {}, blocks: {}\n",
+        summary.total, summary.documented, summary.undocumented,
+        summary.functions, summary.impls, summary.traits, summary.blocks,
+    );
+
+    let mut current_file: Option<&str> = None;
+    for site in &report.sites {
+        if current_file != Some(site.file_path.as_str()) {
+            result.push_str(&format!("\n{}\n", site.file_path));
+            current_file = Some(&site.file_path);
+        }
+
+        let kind = match site.kind {
+            UnsafeKind::Function => "fn",
+            UnsafeKind::Impl => "impl",
+            UnsafeKind::Trait => "trait",
+            UnsafeKind::Block => "block",
+        };
+        let status = match &site.safety_comment {
+            Some(comment) => format!("✅ {comment}"),
+            None => "❌ missing SAFETY comment".to_string(),
+        };
+        let test_marker = if site.is_test { " [test]" } else { "" };
+        result.push_str(&format!(
+            "  {:>5}:{:<4} {:<6} {}{}\n        {}\n        {}\n",
+            site.line, site.column, kind,
+            site.enclosing_symbol.as_deref().unwrap_or("<top level>"), test_marker,
+            site.signature, status,
+        ));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+
+    #[test]
+    fn test_unsafe_inventory() {
+        let mut parser = RustParser::new();
+        parser.parse_source("src/lib.rs", r#"
+/// 生ポインタから読む
+///
+/// # Safety
+///
+/// ptr は有効であること
+pub unsafe fn read(ptr: *const u8) -> u8 {
+    // SAFETY: 呼び出し側が有効性を保証する
+    unsafe { *ptr }
+}
+
+pub struct Handle(*mut u8);
+
+unsafe impl Send for Handle {}
+
+pub unsafe trait Zeroable {}
+
+impl Handle {
+    pub fn first(&self) -> u8 {
+        let value = unsafe { *self.0 };
+        value
+    }
+
+    pub fn second(&self) -> u8 {
+        /* SAFETY: 1バイト以上確保済み */ unsafe { *self.0.add(1) }
+    }
+}
+"#).unwrap();
+
+        let report = build_report(parser.get_unsafe_sites(), &UnsafeReportParams {
+            file_path: None,
+            undocumented_only: None,
+            include_tests: None,
+        });
+        let found: Vec<(UnsafeKind, usize, Option<&str>, bool)> = report.sites.iter()
+            .map(|s| (s.kind, s.line, s.enclosing_symbol.as_deref(), s.has_safety_comment))
+            .collect();
+        assert_eq!(found, vec![
+            (UnsafeKind::Function, 7, Some("read"), true),
+            (UnsafeKind::Block, 9, Some("read"), true),
+            (UnsafeKind::Impl, 14, Some("Handle"), false),
+            (UnsafeKind::Trait, 16, Some("Zeroable"), false),
+            (UnsafeKind::Block, 20, Some("Handle::first"), false),
+            (UnsafeKind::Block, 25, Some("Handle::second"), true),
+        ]);
+        assert_eq!(report.sites[2].signature, "unsafe impl Send for Handle");
+        assert_eq!((report.summary.documented, report.summary.undocumented, report.summary.blocks), (3, 3, 3));
+
+        let undocumented = build_report(parser.get_unsafe_sites(), &UnsafeReportParams {
+            file_path: Some("src/lib.rs".to_string()),
+            undocumented_only: Some(true),
+            include_tests: None,
+        });
+        assert_eq!(undocumented.summary.total, 3);
+        assert!(format_text_report(&undocumented).contains("❌ missing SAFETY comment"));
+    }
+}
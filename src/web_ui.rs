@@ -9,21 +9,16 @@ use axum::{
 };
 use serde_json::json;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 use tower_http::cors::CorsLayer;
 use tracing::{debug, info, warn};
 
 pub type LogSender = broadcast::Sender<String>;
-pub type LogReceiver = broadcast::Receiver<String>;
 
 #[derive(Clone)]
 struct StatsData {
     indexed_files_count: usize,
     total_symbols: usize,
-    total_functions: usize,
-    total_structs: usize,
-    total_enums: usize,
-    total_traits: usize,
     unique_symbol_names: usize,
     is_watching: bool,
 }
@@ -189,6 +184,58 @@ async fn dashboard() -> impl IntoResponse {
             font-size: 14px;
             margin-bottom: 10px;
         }
+        .panel {
+            background: #2d2d30;
+            border: 1px solid #3e3e42;
+            padding: 20px;
+            border-radius: 10px;
+            margin-bottom: 20px;
+        }
+        .panel h3 {
+            margin-top: 0;
+            color: #569cd6;
+        }
+        .panel input, .panel select {
+            padding: 8px 12px;
+            background: #1e1e1e;
+            border: 1px solid #444;
+            color: #d4d4d4;
+            border-radius: 5px;
+            margin-right: 10px;
+        }
+        .panel input {
+            width: 40%;
+        }
+        .result-table {
+            width: 100%;
+            border-collapse: collapse;
+            margin-top: 10px;
+            font-size: 13px;
+        }
+        .result-table th, .result-table td {
+            text-align: left;
+            padding: 4px 8px;
+            border-bottom: 1px solid #3e3e42;
+        }
+        .result-table th {
+            color: #569cd6;
+        }
+        .result-table td.mono {
+            font-family: 'Consolas', 'Monaco', monospace;
+        }
+        .badge {
+            display: inline-block;
+            padding: 1px 6px;
+            border-radius: 8px;
+            font-size: 11px;
+            background: #3e3e42;
+        }
+        .badge.test {
+            background: #6b21a8;
+        }
+        .badge.warn {
+            background: #92400e;
+        }
     </style>
 </head>
 <body>
@@ -208,6 +255,52 @@ async fn dashboard() -> impl IntoResponse {
         </div>
     </div>
     
+    <div class="panel">
+        <h3>🔎 Symbol Search</h3>
+        <input type="text" id="search-query" placeholder="Search symbols (e.g. find_def, CoIn)" onkeydown="if (event.key === 'Enter') searchSymbols()">
+        <select id="search-type">
+            <option value="">All types</option>
+            <option value="Function">Function</option>
+            <option value="Struct">Struct</option>
+            <option value="Enum">Enum</option>
+            <option value="Trait">Trait</option>
+        </select>
+        <button class="btn" onclick="searchSymbols()">Search</button>
+        <div id="search-results"></div>
+    </div>
+    
+    <div class="panel">
+        <h3>🔥 Hotspots</h3>
+        <select id="metrics-sort" onchange="requestMetrics()">
+            <option value="Complexity">Cyclomatic complexity</option>
+            <option value="Lines">Lines of code</option>
+            <option value="Nesting">Nesting depth</option>
+            <option value="Params">Parameters</option>
+            <option value="EarlyReturns">Early returns</option>
+            <option value="FanIn">Fan-in</option>
+            <option value="FanOut">Fan-out</option>
+        </select>
+        <button class="btn" onclick="requestMetrics()">Refresh</button>
+        <div id="metrics"></div>
+    </div>
+    
+    <div class="panel">
+        <h3>🕒 Git Churn</h3>
+        <select id="churn-sort" onchange="requestChurn()">
+            <option value="Churn">Most changed</option>
+            <option value="Recent">Recently changed</option>
+            <option value="Oldest">Longest unchanged</option>
+        </select>
+        <button class="btn" onclick="requestChurn()">Load</button>
+        <div id="churn"><p>Runs git log for every indexed file, so it is loaded on demand.</p></div>
+    </div>
+    
+    <div class="panel">
+        <h3>⚠️ Parse Diagnostics</h3>
+        <button class="btn" onclick="requestDiagnostics()">Refresh</button>
+        <div id="diagnostics"></div>
+    </div>
+    
     <div class="stats">
         <div class="stat-card">
             <h3>📁 Indexed Files</h3>
@@ -251,6 +344,8 @@ async fn dashboard() -> impl IntoResponse {
                 document.getElementById('status').className = 'status connected';
                 document.getElementById('status').textContent = 'Connected';
                 console.log('WebSocket connected');
+                requestDiagnostics();
+                requestMetrics();
             };
             
             ws.onmessage = function(event) {
@@ -263,6 +358,14 @@ async fn dashboard() -> impl IntoResponse {
                     } else if (data.type === 'stats') {
                         console.log('Updating stats with:', data);
                         updateStats(data);
+                    } else if (data.type === 'search_symbols_response') {
+                        renderSearchResults(data);
+                    } else if (data.type === 'get_diagnostics_response') {
+                        renderDiagnostics(data);
+                    } else if (data.type === 'get_metrics_response') {
+                        renderMetrics(data);
+                    } else if (data.type === 'get_churn_response') {
+                        renderChurn(data);
                     } else if (data.type === 'change_project_response') {
                         if (data.success) {
                             addLogEntry(`✅ ${data.message}`);
@@ -378,6 +481,180 @@ async fn dashboard() -> impl IntoResponse {
             addLogEntry(`📤 Requesting project change to: ${newPath}`);
         }
         
+        function searchSymbols() {
+            const query = document.getElementById('search-query').value.trim();
+            if (!query) {
+                return;
+            }
+            
+            if (!ws || ws.readyState !== WebSocket.OPEN) {
+                addLogEntry('❌ WebSocket is not connected');
+                return;
+            }
+            
+            const symbolType = document.getElementById('search-type').value;
+            ws.send(JSON.stringify({
+                type: 'search_symbols',
+                query: query,
+                symbol_type: symbolType || null
+            }));
+        }
+        
+        function escapeHtml(text) {
+            const div = document.createElement('div');
+            div.textContent = text;
+            return div.innerHTML;
+        }
+        
+        function renderSearchResults(data) {
+            const container = document.getElementById('search-results');
+            if (!data.success) {
+                container.textContent = `❌ ${data.message}`;
+                return;
+            }
+            
+            const results = data.result.results || [];
+            if (results.length === 0) {
+                container.textContent = 'No matching symbols';
+                return;
+            }
+            
+            let html = '<table class="result-table"><tr><th>Name</th><th>Type</th><th>Match</th><th>Location</th><th>Signature</th></tr>';
+            for (const r of results) {
+                const testBadge = r.is_test ? ' <span class="badge test">test</span>' : '';
+                const crateBadge = r.crate_name ? ` <span class="badge">${escapeHtml(r.crate_name)}</span>` : '';
+                html += `<tr>
+                    <td class="mono">${escapeHtml(r.scope ? `${r.scope}::${r.name}` : r.name)}${testBadge}${crateBadge}</td>
+                    <td><span class="badge">${escapeHtml(r.visibility)}</span> ${r.symbol_type}</td>
+                    <td>${r.match_kind}</td>
+                    <td class="mono">${escapeHtml(r.file_path)}:${r.line}</td>
+                    <td class="mono">${escapeHtml(r.signature)}</td>
+                </tr>`;
+            }
+            html += '</table>';
+            container.innerHTML = html;
+        }
+        
+        function requestDiagnostics() {
+            if (!ws || ws.readyState !== WebSocket.OPEN) {
+                return;
+            }
+            ws.send(JSON.stringify({ type: 'get_diagnostics' }));
+        }
+        
+        function renderDiagnostics(data) {
+            const container = document.getElementById('diagnostics');
+            if (!data.success) {
+                container.textContent = `❌ ${data.message}`;
+                return;
+            }
+            
+            const files = data.result.files || [];
+            if (files.length === 0) {
+                container.textContent = '✅ No parse errors';
+                return;
+            }
+            
+            let html = '<table class="result-table"><tr><th>Location</th><th>Recovery</th><th>Message</th></tr>';
+            for (const file of files) {
+                for (const d of file.diagnostics) {
+                    html += `<tr>
+                        <td class="mono">${escapeHtml(file.file_path)}:${d.line}:${d.column}</td>
+                        <td><span class="badge warn">${file.recovery}</span></td>
+                        <td class="mono">${escapeHtml(d.message)}</td>
+                    </tr>`;
+                }
+            }
+            html += '</table>';
+            container.innerHTML = html;
+        }
+        
+        function requestMetrics() {
+            if (!ws || ws.readyState !== WebSocket.OPEN) {
+                return;
+            }
+            ws.send(JSON.stringify({
+                type: 'get_metrics',
+                sort_by: document.getElementById('metrics-sort').value,
+                limit: 20
+            }));
+        }
+        
+        function renderMetrics(data) {
+            const container = document.getElementById('metrics');
+            if (!data.success) {
+                container.textContent = `❌ ${data.message}`;
+                return;
+            }
+            
+            const functions = data.result.functions || [];
+            if (functions.length === 0) {
+                container.textContent = 'No functions indexed';
+                return;
+            }
+            
+            let html = '<table class="result-table"><tr><th>Function</th><th>LOC</th><th>CC</th><th>Nesting</th><th>Params</th><th>Early returns</th><th>Fan-in</th><th>Fan-out</th><th>Location</th></tr>';
+            for (const m of functions) {
+                const ccBadge = m.cyclomatic_complexity >= 10 ? 'badge warn' : 'badge';
+                html += `<tr>
+                    <td class="mono">${escapeHtml(m.scope ? `${m.scope}::${m.name}` : m.name)}</td>
+                    <td>${m.lines_of_code}</td>
+                    <td><span class="${ccBadge}">${m.cyclomatic_complexity}</span></td>
+                    <td>${m.max_nesting}</td>
+                    <td>${m.params}</td>
+                    <td>${m.early_returns}</td>
+                    <td>${m.fan_in}</td>
+                    <td>${m.fan_out}</td>
+                    <td class="mono">${escapeHtml(m.file_path)}:${m.line}</td>
+                </tr>`;
+            }
+            html += `</table><div class="current-path">${functions.length} of ${data.result.total_functions} functions</div>`;
+            container.innerHTML = html;
+        }
+        
+        function requestChurn() {
+            if (!ws || ws.readyState !== WebSocket.OPEN) {
+                return;
+            }
+            ws.send(JSON.stringify({
+                type: 'get_churn',
+                sort_by: document.getElementById('churn-sort').value,
+                limit: 20
+            }));
+        }
+        
+        function renderChurn(data) {
+            const container = document.getElementById('churn');
+            if (!data.success) {
+                container.textContent = `❌ ${data.message}`;
+                return;
+            }
+            
+            const symbols = data.result.symbols || [];
+            if (symbols.length === 0) {
+                container.textContent = 'No committed symbols';
+                return;
+            }
+            
+            let html = '<table class="result-table"><tr><th>Symbol</th><th>Churn</th><th>Authors</th><th>Last change</th><th>Age</th><th>Last commit</th><th>Location</th></tr>';
+            for (const s of symbols) {
+                const churnBadge = s.stats.churn >= 10 ? 'badge warn' : 'badge';
+                const modified = `${s.stats.last_modified_days}d ago${s.stats.uncommitted ? ' (modified)' : ''}`;
+                html += `<tr>
+                    <td class="mono">${escapeHtml(s.scope ? `${s.scope}::${s.name}` : s.name)}</td>
+                    <td><span class="${churnBadge}">${s.stats.churn}</span></td>
+                    <td>${s.stats.authors}</td>
+                    <td>${modified}</td>
+                    <td>${s.stats.age_days}d</td>
+                    <td class="mono" title="${escapeHtml(s.stats.last_summary)}">${escapeHtml(s.stats.last_commit.substring(0, 8))} ${escapeHtml(s.stats.last_author)}</td>
+                    <td class="mono">${escapeHtml(s.file_path)}:${s.line}</td>
+                </tr>`;
+            }
+            const skipped = data.result.skipped_files.length > 0 ? `, skipped ${data.result.skipped_files.length} file(s) without git history` : '';
+            html += `</table><div class="current-path">${symbols.length} of ${data.result.total_symbols} symbols${skipped}</div>`;
+            container.innerHTML = html;
+        }
+        
         async function selectLocalDirectory() {
             // File System Access APIのサポートチェック
             if (!('showDirectoryPicker' in window)) {
@@ -511,9 +788,19 @@ async fn websocket_connection(socket: WebSocket, state: WebUIState) {
         }
     }
 
-    // ログメッセージをクライアントに転送
+    // 検索などの応答はこの接続にだけ返す
+    let (reply_sender, mut reply_receiver) = mpsc::unbounded_channel::<String>();
+
+    // ログメッセージと応答をクライアントに転送
     let send_task = tokio::spawn(async move {
-        while let Ok(log_message) = log_receiver.recv().await {
+        loop {
+            let log_message = tokio::select! {
+                received = log_receiver.recv() => match received {
+                    Ok(message) => message,
+                    Err(_) => break,
+                },
+                Some(reply) = reply_receiver.recv() => reply,
+            };
             // メッセージがすでにJSONかどうかチェック
             let message = if log_message.starts_with("{") && log_message.contains("\"type\"") {
                 // すでに整形されたJSONメッセージ（統計情報など）
@@ -541,8 +828,11 @@ async fn websocket_connection(socket: WebSocket, state: WebUIState) {
             match msg {
                 Ok(Message::Text(text)) => {
                     // クライアントからのメッセージを処理
-                    if let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) {
-                        if data["type"] == "change_project" {
+                    let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) else {
+                        continue;
+                    };
+                    match data["type"].as_str() {
+                        Some("change_project") => {
                             if let Some(project_path) = data["project_path"].as_str() {
                                 // TCPクライアントを使用してサーバーにリクエストを送信
                                 tokio::spawn(handle_change_project_request(
@@ -552,6 +842,56 @@ async fn websocket_connection(socket: WebSocket, state: WebUIState) {
                                 ));
                             }
                         }
+                        Some("get_diagnostics") => {
+                            tokio::spawn(forward_request(
+                                tcp_port,
+                                methods::GET_DIAGNOSTICS,
+                                json!({ "file_path": data["file_path"] }),
+                                "get_diagnostics_response",
+                                reply_sender.clone(),
+                            ));
+                        }
+                        Some("get_metrics") => {
+                            let params = json!({
+                                "sort_by": data["sort_by"],
+                                "limit": data["limit"]
+                            });
+                            tokio::spawn(forward_request(
+                                tcp_port,
+                                methods::GET_METRICS,
+                                params,
+                                "get_metrics_response",
+                                reply_sender.clone(),
+                            ));
+                        }
+                        Some("get_churn") => {
+                            let params = json!({
+                                "sort_by": data["sort_by"],
+                                "limit": data["limit"]
+                            });
+                            tokio::spawn(forward_request(
+                                tcp_port,
+                                methods::GET_CHURN,
+                                params,
+                                "get_churn_response",
+                                reply_sender.clone(),
+                            ));
+                        }
+                        Some("search_symbols") => {
+                            let params = json!({
+                                "query": data["query"],
+                                "symbol_type": data["symbol_type"],
+                                "limit": data["limit"]
+                            });
+                            tokio::spawn(forward_request(
+                                tcp_port,
+                                methods::SEARCH_SYMBOLS,
+                                params,
+                                "search_symbols_response",
+                                reply_sender.clone(),
+                            ));
+                        }
+                        _ => {}
                     }
                 }
                 Ok(Message::Close(_)) => {
@@ -597,10 +937,6 @@ impl LogBroadcaster {
         let stats_data = StatsData {
             indexed_files_count: indexed_files,
             total_symbols,
-            total_functions: 0,  // TODO: 個別の統計を受け取るように改善
-            total_structs: 0,
-            total_enums: 0,
-            total_traits: 0,
             unique_symbol_names: unique_names,
             is_watching,
         };
@@ -622,7 +958,7 @@ impl LogBroadcaster {
 }
 
 use crate::client::CodeIntelClient;
-use crate::protocol::{ServerRequest, ChangeProjectParams};
+use crate::protocol::{methods, ServerRequest, ServerResponse, ChangeProjectParams};
 use futures_util::{SinkExt, StreamExt};
 
 async fn handle_change_project_request(tcp_port: u16, project_path: String, log_sender: LogSender) {
@@ -667,4 +1003,36 @@ async fn handle_change_project_request(tcp_port: u16, project_path: String, log_
             let _ = log_sender.send(message.to_string());
         }
     }
-}
\ No newline at end of file
+}
+
+/// TCPサーバーへリクエストを転送し、結果を指定タイプのメッセージとしてWebSocketに送信
+/// リクエストをサーバーに中継し、結果を要求元の WebSocket にだけ返す
+async fn forward_request(tcp_port: u16, method: &'static str, params: serde_json::Value, response_type: &'static str, reply_sender: mpsc::UnboundedSender<String>) {
+    let client = CodeIntelClient::new(tcp_port);
+    
+    let request = ServerRequest {
+        id: 1,
+        method: method.to_string(),
+        params,
+    };
+    
+    let message = match client.send_request(request).await {
+        Ok(ServerResponse { result: Some(result), error: None, .. }) => json!({
+            "type": response_type,
+            "success": true,
+            "result": result
+        }),
+        Ok(response) => json!({
+            "type": response_type,
+            "success": false,
+            "message": response.error.unwrap_or_else(|| "Empty response from server".to_string())
+        }),
+        Err(e) => json!({
+            "type": response_type,
+            "success": false,
+            "message": format!("Request failed: {}", e)
+        }),
+    };
+    
+    let _ = reply_sender.send(message.to_string());
+}